@@ -0,0 +1,64 @@
+//! Runs the busy beaver champions to completion on a blank tape.
+//!
+//! Run with `cargo bench --bench busy_beaver`.
+use std::{
+    hint::black_box,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use turing::{
+    busy_beaver::{Champion, CHAMPIONS},
+    macro_machine::MacroMachine,
+    TuringMachine, VecTape,
+};
+
+/// Time the fastest of three runs of `run`, which returns the final tape.
+fn bench(name: &str, champion: &Champion, run: impl Fn() -> VecTape) {
+    let mut best = Duration::MAX;
+
+    for _ in 0..3 {
+        let start = Instant::now();
+        let tape = run();
+        best = best.min(start.elapsed());
+
+        assert_eq!(tape.to_string().matches('1').count(), champion.ones);
+    }
+
+    let per_step = best.as_secs_f64() * 1e9 / champion.steps as f64;
+    println!(
+        "BB({}) {name:<10} {:>10} steps {best:>12.2?} {per_step:>8.2} ns/step",
+        champion.states, champion.steps
+    );
+}
+
+fn main() {
+    for champion in CHAMPIONS {
+        let program = champion.program();
+        let compiled = program.compile();
+        let profiled = program.compile_profiled(&VecTape::from_str("_").unwrap(), 100_000);
+
+        bench("machine", &champion, || {
+            let mut machine = TuringMachine::from_tape(VecTape::from_str("_").unwrap());
+            let steps = machine.run(black_box(&program)).count();
+            assert_eq!(steps as u64, champion.steps);
+            machine.tape().clone()
+        });
+        bench("compiled", &champion, || {
+            let mut tape = VecTape::from_str("_").unwrap();
+            black_box(&compiled).execute(&mut tape).unwrap();
+            tape
+        });
+        bench("profiled", &champion, || {
+            let mut tape = VecTape::from_str("_").unwrap();
+            black_box(&profiled).execute(&mut tape).unwrap();
+            tape
+        });
+        bench("macro", &champion, || {
+            let mut machine = MacroMachine::new(&VecTape::from_str("_").unwrap(), 3);
+            let (_, steps) = machine.execute(black_box(&program), None).unwrap();
+            assert_eq!(steps, champion.steps);
+            machine.to_tape()
+        });
+    }
+}
@@ -0,0 +1,37 @@
+//! Compares the tape backends on the standard workloads of the `bench`
+//! module.
+//!
+//! Run with `cargo bench --bench tape`.
+use std::{fmt::Debug, str::FromStr};
+
+use turing::{
+    bench::{self, Workload},
+    PackedTape, PagedTape, RleTape, Segment, SparseTape, Tape, VecDequeTape, VecTape,
+};
+
+fn report<T>(name: &str, workload: &Workload)
+where
+    T: Tape<Symbol = Segment> + FromStr,
+    T::Err: Debug,
+{
+    let measurement = bench::measure::<T>(workload, 3);
+    println!(
+        "{name:<24} {:>12.2?} {:>12} steps/s",
+        measurement.elapsed,
+        measurement.steps_per_second()
+    );
+}
+
+fn main() {
+    for workload in bench::workloads() {
+        println!("{} ({} steps)", workload.name, workload.steps);
+
+        report::<VecTape>("VecTape", &workload);
+        report::<VecDequeTape>("VecDequeTape", &workload);
+        report::<SparseTape>("SparseTape", &workload);
+        report::<PagedTape>("PagedTape", &workload);
+        report::<PackedTape>("PackedTape", &workload);
+        report::<RleTape>("RleTape", &workload);
+        println!();
+    }
+}
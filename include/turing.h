@@ -0,0 +1,96 @@
+#ifndef TURING_H
+#define TURING_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// The result of `turing_machine_step`.
+typedef enum TuringStatus {
+  // The step was performed and the program has not halted yet.
+  TuringStatus_Running = 0,
+  // The program reached a final state.
+  TuringStatus_Halted = 1,
+  // The program reached an error state or no transition was defined.
+  TuringStatus_Failed = 2,
+  // A null pointer was passed.
+  TuringStatus_InvalidArgument = -1,
+} TuringStatus;
+
+// A `machine::TuringMachine` running a program on a `VecTape`.
+typedef struct TuringMachine TuringMachine;
+
+// A parsed `Program` over `crate::Segment`s.
+typedef struct TuringProgram TuringProgram;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Parse a program. Returns null if `source` is null, not valid UTF-8 or not
+// a valid program.
+//
+// # Safety
+//
+// `source` has to be null or point to a NUL-terminated string.
+TuringProgram *turing_program_parse(const char *source);
+
+// Release a program returned by [`turing_program_parse`].
+//
+// # Safety
+//
+// `program` has to be null or a pointer returned by
+// [`turing_program_parse`] that was not released yet.
+void turing_program_free(TuringProgram *program);
+
+// Create a machine that runs `program` on `tape`, starting in the initial
+// state of the program. The program is copied, so it may be released
+// afterwards. Returns null if an argument is null or the tape is invalid.
+//
+// # Safety
+//
+// `program` has to be null or a live pointer returned by
+// [`turing_program_parse`], and `tape` has to be null or point to a
+// NUL-terminated string.
+TuringMachine *turing_machine_new(const TuringProgram *program, const char *tape);
+
+// Release a machine returned by [`turing_machine_new`].
+//
+// # Safety
+//
+// `machine` has to be null or a pointer returned by [`turing_machine_new`]
+// that was not released yet.
+void turing_machine_free(TuringMachine *machine);
+
+// Perform a single step. Once the program has halted or failed, the same
+// status is returned again without performing any step.
+//
+// # Safety
+//
+// `machine` has to be null or a live pointer returned by
+// [`turing_machine_new`].
+TuringStatus turing_machine_step(TuringMachine *machine);
+
+// Returns the contents of the tape, or null if `machine` is null. The string
+// has to be released with [`turing_string_free`].
+//
+// # Safety
+//
+// `machine` has to be null or a live pointer returned by
+// [`turing_machine_new`].
+char *turing_tape_to_string(const TuringMachine *machine);
+
+// Release a string returned by [`turing_tape_to_string`].
+//
+// # Safety
+//
+// `s` has to be null or a pointer returned by [`turing_tape_to_string`]
+// that was not released yet.
+void turing_string_free(char *s);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* TURING_H */
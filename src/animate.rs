@@ -0,0 +1,262 @@
+//! Export of runs as animated SVG images.
+//!
+//! [`svg`] replays a [`Trace`] recorded with
+//! [`crate::TuringMachine::execute_traced`] on the tape the run started
+//! with and draws one frame per step, showing the tape, the cursor and the
+//! current state. The frames are switched with SMIL animations, so the image
+//! plays and loops in browsers without any scripts:
+//!
+//! ```
+//! use std::{str::FromStr, time::Duration};
+//!
+//! use turing::{animate, trace::Trace, Program, TuringMachine, VecTape};
+//!
+//! let program = Program::from_str(include_str!("../examples/next_integer.tng")).unwrap();
+//! let tape = VecTape::from_str("_1011_").unwrap();
+//! let mut trace = Trace::new();
+//!
+//! let mut machine = TuringMachine::from_tape(tape.clone());
+//! machine.execute_traced(&program, &mut trace).unwrap();
+//!
+//! let image = animate::svg(&program, &tape, &trace, Duration::from_millis(250));
+//! assert!(image.starts_with("<svg"));
+//! ```
+//!
+//! Every frame contains the whole visited part of the tape, so the image
+//! grows with the number of steps times the width of the tape.
+use std::{
+    fmt::{self, Write},
+    time::Duration,
+};
+
+use crate::{tape::Symbol, trace::Trace, Program, State, Tape, VecTape};
+
+/// The width and height of a cell of the tape.
+const CELL: i64 = 32;
+/// The space around the drawing.
+const MARGIN: i64 = 8;
+/// The height of the line with the step and state above the tape.
+const HEADER: i64 = 24;
+/// The height of the cursor marker below the tape.
+const MARKER: i64 = 12;
+
+/// Returns an SVG image that shows the run of `program` on `tape` recorded
+/// in `trace`, showing every step for `delay` and then starting over.
+///
+/// States are labelled with their names if they have one.
+#[must_use]
+pub fn svg<S>(program: &Program<S>, tape: &VecTape<S>, trace: &Trace<S>, delay: Duration) -> String
+where
+    S: Symbol + fmt::Display,
+{
+    let frames = frames(program, tape, trace);
+
+    // The part of the tape that is written or visited at any point
+    let mut first = tape.position();
+    let mut last = first;
+    for (_, tape) in &frames {
+        first = first.min(tape.position());
+        last = last.max(tape.position());
+
+        if let Some((leftmost, rightmost)) = extent(tape) {
+            first = first.min(leftmost);
+            last = last.max(rightmost);
+        }
+    }
+
+    let width = 2 * MARGIN + (last - first + 1) * CELL;
+    let height = 2 * MARGIN + HEADER + CELL + MARKER;
+    let count = frames.len();
+    let duration = delay.as_millis().max(1) * count as u128;
+
+    let mut out = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+         viewBox=\"0 0 {width} {height}\">\n"
+    );
+    out.push_str(
+        "<style>text{font-family:monospace;font-size:16px}\
+         .cell{fill:#fff;stroke:#444}.head{fill:none;stroke:#d33;stroke-width:3}\
+         .marker{fill:#d33}</style>\n",
+    );
+
+    for (index, (state, tape)) in frames.iter().enumerate() {
+        if count == 1 {
+            out.push_str("<g>\n");
+        } else {
+            let (values, key_times) = if index == 0 {
+                (String::from("1;0"), format!("0;{}", fraction(1, count)))
+            } else if index + 1 == count {
+                (String::from("0;1"), format!("0;{}", fraction(index, count)))
+            } else {
+                (
+                    String::from("0;1;0"),
+                    format!(
+                        "0;{};{}",
+                        fraction(index, count),
+                        fraction(index + 1, count)
+                    ),
+                )
+            };
+            let _ = writeln!(
+                out,
+                "<g opacity=\"{}\"><animate attributeName=\"opacity\" calcMode=\"discrete\" \
+                 dur=\"{duration}ms\" repeatCount=\"indefinite\" values=\"{values}\" \
+                 keyTimes=\"{key_times}\"/>",
+                u8::from(index == 0)
+            );
+        }
+
+        let label = program
+            .state_name(*state)
+            .map_or_else(|| state.to_string(), str::to_owned);
+        let _ = writeln!(
+            out,
+            "<text x=\"{MARGIN}\" y=\"{}\">Step {index}, state {}</text>",
+            MARGIN + 16,
+            escape(&label)
+        );
+
+        for cell in first..=last {
+            let x = MARGIN + (cell - first) * CELL;
+            let y = MARGIN + HEADER;
+            let _ = writeln!(
+                out,
+                "<rect class=\"cell\" x=\"{x}\" y=\"{y}\" width=\"{CELL}\" height=\"{CELL}\"/>"
+            );
+
+            let segment = tape.get(cell);
+            if segment != S::BLANK {
+                let _ = writeln!(
+                    out,
+                    "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\">{}</text>",
+                    x + CELL / 2,
+                    y + CELL / 2 + 6,
+                    escape(&segment.to_string())
+                );
+            }
+        }
+
+        let x = MARGIN + (tape.position() - first) * CELL;
+        let y = MARGIN + HEADER;
+        let _ = writeln!(
+            out,
+            "<rect class=\"head\" x=\"{x}\" y=\"{y}\" width=\"{CELL}\" height=\"{CELL}\"/>\n\
+             <path class=\"marker\" d=\"M{} {} l{} {MARKER} h{} z\"/>\n</g>",
+            x + CELL / 2,
+            y + CELL,
+            -MARKER / 2,
+            MARKER
+        );
+    }
+
+    out.push_str("</svg>\n");
+    out
+}
+
+/// Returns the state and the tape before the run and after every step.
+fn frames<S>(program: &Program<S>, tape: &VecTape<S>, trace: &Trace<S>) -> Vec<(State, VecTape<S>)>
+where
+    S: Symbol,
+{
+    let origin = tape.position();
+    let mut replay = tape.clone();
+    let mut frames = vec![(
+        trace
+            .steps()
+            .first()
+            .map_or(program.initial_state, |step| step.from),
+        replay.clone(),
+    )];
+
+    for step in trace.steps() {
+        let position = origin + step.position;
+        replay.seek(position);
+        replay.put(step.written);
+        replay.seek(position + step.action.offset());
+
+        frames.push((step.to, replay.clone()));
+    }
+
+    frames
+}
+
+/// Returns the positions of the leftmost and rightmost non-empty segment of
+/// `tape`, if it has any.
+fn extent<S>(tape: &VecTape<S>) -> Option<(i64, i64)>
+where
+    S: Symbol,
+{
+    let logical = |index: usize| {
+        tape.position() + i64::try_from(index).expect("tape position exceeds i64")
+            - i64::try_from(tape.position).expect("tape position exceeds i64")
+    };
+    let leftmost = tape.inner.iter().position(|segment| *segment != S::BLANK)?;
+    let rightmost = tape
+        .inner
+        .iter()
+        .rposition(|segment| *segment != S::BLANK)?;
+
+    Some((logical(leftmost), logical(rightmost)))
+}
+
+/// Returns `numerator / denominator` as a decimal between 0 and 1.
+fn fraction(numerator: usize, denominator: usize) -> String {
+    let millionths = numerator as u128 * 1_000_000 / denominator as u128;
+    format!("{}.{:06}", millionths / 1_000_000, millionths % 1_000_000)
+}
+
+/// Escape `s` for use in the text of an SVG element.
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{str::FromStr, time::Duration};
+
+    use crate::{animate::*, *};
+
+    #[test]
+    fn test_svg_frames() {
+        let program = Program::from_str("+start\n-1\nstart,start,a,<,r\nstart,1,_,_,l").unwrap();
+        let tape = VecTape::from_str("_aa").unwrap();
+        let mut trace = Trace::new();
+        TuringMachine::from_tape(tape.clone())
+            .execute_traced(&program, &mut trace)
+            .unwrap();
+
+        let image = svg(&program, &tape, &trace, Duration::from_millis(100));
+
+        // One frame before the run and one after each of the three steps
+        assert_eq!(image.matches("<animate ").count(), 4);
+        assert!(image.contains("dur=\"400ms\""));
+        assert!(image.contains("values=\"0;1;0\" keyTimes=\"0;0.250000;0.500000\""));
+        assert!(image.contains("values=\"0;1\" keyTimes=\"0;0.750000\""));
+        assert!(image.contains("Step 0, state start"));
+        assert!(image.contains("Step 3, state 1"));
+        assert!(image.contains("&lt;"));
+        // The cells of the input and the blank right of it
+        assert!(image.contains("width=\"112\""));
+    }
+
+    #[test]
+    fn test_svg_single_frame() {
+        let program = Program::from_str("+0\n-0").unwrap();
+        let tape = VecTape::from_str("_").unwrap();
+
+        let image = svg(&program, &tape, &Trace::new(), Duration::from_secs(1));
+        assert!(!image.contains("<animate"));
+        assert!(image.contains("Step 0, state 0"));
+    }
+}
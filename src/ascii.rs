@@ -0,0 +1,159 @@
+//! A tape alphabet of bytes, for machines that process text.
+//!
+//! [`encode`] loads a string onto a tape of [`Byte`]s and [`decode_str`] reads
+//! the result back, so text-processing machines can be run on real strings:
+//!
+//! ```
+//! use turing::{
+//!     ascii::{self, Byte},
+//!     Program, TuringMachine,
+//! };
+//!
+//! // Replaces every comma with a semicolon
+//! let program = Program::<Byte>::parse("+0\n-1\n0,0,\\x2c,;,r\n0,0,*,=,r\n0,1,_,_,n").unwrap();
+//! let mut machine = TuringMachine::from_tape(ascii::encode("a, b, c"));
+//! machine.execute(&program).unwrap();
+//!
+//! assert_eq!(ascii::decode_str(machine.tape()).unwrap(), "a; b; c");
+//! ```
+use std::{fmt, str::FromStr};
+
+use crate::{error::InvalidProgram, tape::Symbol, VecTape};
+
+/// A segment of a tape of bytes.
+///
+/// In programs, "_" is the blank segment, a single ASCII character is the
+/// byte of that character and any other byte is written as "\x" followed by
+/// two hexadecimal digits, like "\x2c" for a comma. Unlike with
+/// [`crate::Segment`]s, " " is the byte of a space.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Byte {
+    /// A blank segment.
+    Blank,
+    /// A segment holding a byte.
+    Value(u8),
+}
+
+impl Symbol for Byte {
+    const BLANK: Self = Self::Blank;
+}
+
+impl FromStr for Byte {
+    type Err = InvalidProgram;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "_" {
+            return Ok(Self::Blank);
+        }
+
+        if let Some(hex) = s.strip_prefix("\\x").filter(|hex| hex.len() == 2) {
+            return u8::from_str_radix(hex, 16)
+                .map(Self::Value)
+                .map_err(|_| InvalidProgram::InvalidSegment);
+        }
+
+        match s.as_bytes() {
+            [byte] if byte.is_ascii() => Ok(Self::Value(*byte)),
+            [] => Err(InvalidProgram::InvalidSegment),
+            _ => Err(InvalidProgram::MultiCharacterSymbol),
+        }
+    }
+}
+
+impl fmt::Display for Byte {
+    /// Writes the segment the way it is written in programs.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Blank => f.write_str("_"),
+            Self::Value(byte) if (byte.is_ascii_graphic() && *byte != b'_') || *byte == b' ' => {
+                write!(f, "{}", char::from(*byte))
+            }
+            Self::Value(byte) => write!(f, "\\x{byte:02x}"),
+        }
+    }
+}
+
+/// Returns a tape holding the bytes of `text`, with the cursor on the first
+/// of them.
+///
+/// The text is surrounded by blanks, so a tape for the empty string is blank.
+#[must_use]
+pub fn encode(text: impl AsRef<[u8]>) -> VecTape<Byte> {
+    let mut inner = vec![Byte::Blank];
+    inner.extend(text.as_ref().iter().copied().map(Byte::Value));
+    inner.push(Byte::Blank);
+
+    VecTape::new(inner, 1)
+}
+
+/// Returns the bytes from the leftmost to the rightmost non-blank segment of
+/// a tape, or [`None`] if there is a blank between them.
+#[must_use]
+pub fn decode(tape: &VecTape<Byte>) -> Option<Vec<u8>> {
+    tape.trim()
+        .iter()
+        .map(|segment| match segment {
+            Byte::Blank => None,
+            Byte::Value(byte) => Some(*byte),
+        })
+        .collect()
+}
+
+/// Returns the text on a tape like [`decode`], or [`None`] if it is not valid
+/// UTF-8.
+#[must_use]
+pub fn decode_str(tape: &VecTape<Byte>) -> Option<String> {
+    String::from_utf8(decode(tape)?).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::{ascii::*, *};
+
+    #[test]
+    fn test_byte_syntax() {
+        for (text, byte) in [
+            ("_", Byte::Blank),
+            ("a", Byte::Value(b'a')),
+            (" ", Byte::Value(b' ')),
+            ("\\x2c", Byte::Value(b',')),
+            ("\\x5f", Byte::Value(b'_')),
+            ("\\xff", Byte::Value(0xff)),
+        ] {
+            assert_eq!(Byte::from_str(text), Ok(byte));
+            assert_eq!(Byte::from_str(&byte.to_string()), Ok(byte));
+        }
+
+        assert_eq!(Byte::from_str(""), Err(InvalidProgram::InvalidSegment));
+        assert_eq!(Byte::from_str("\\xzz"), Err(InvalidProgram::InvalidSegment));
+        assert_eq!(
+            Byte::from_str("ab"),
+            Err(InvalidProgram::MultiCharacterSymbol)
+        );
+        assert_eq!(
+            Byte::from_str("ä"),
+            Err(InvalidProgram::MultiCharacterSymbol)
+        );
+    }
+
+    #[test]
+    fn test_text_roundtrip() {
+        // Capitalizes "a"s and "b"s and appends a "!"
+        let program =
+            Program::<Byte>::parse("+0\n-1\n0,0,a,A,r\n0,0,b,B,r\n0,0,*,=,r\n0,1,_,!,n").unwrap();
+        let mut machine = TuringMachine::from_tape(encode("a b.c"));
+        machine.execute(&program).unwrap();
+        assert_eq!(decode_str(machine.tape()).as_deref(), Some("A B.c!"));
+
+        assert_eq!(decode(&encode("")), Some(Vec::new()));
+        assert_eq!(decode_str(&encode("grüße")).as_deref(), Some("grüße"));
+        assert_eq!(decode_str(&encode([0xff])), None);
+
+        let mut tape = encode("abc");
+        tape.right();
+        tape.put(Byte::Blank);
+        assert_eq!(decode(&tape), None);
+    }
+}
@@ -0,0 +1,408 @@
+//! Machines, seed databases and run results in the formats of
+//! [bbchallenge.org](https://bbchallenge.org).
+//!
+//! The seed database stores each machine in 6 bytes per state, 3 for each of
+//! the two symbols: the symbol to write, the direction to move in, where 0 is
+//! right and 1 is left, and the next state, where 1 is "A", 2 is "B" and so
+//! on. A next state of 0 marks a transition that is undefined, which is how
+//! the database represents the transition to the halting state. Index files
+//! list the positions of machines in the database as big-endian 32-bit
+//! integers.
+//!
+//! Configurations are written in the notation of the community, with the
+//! state in front of the cell under the cursor:
+//!
+//! ```text
+//! 0^inf 1 1 A> 0 1 0^inf
+//! ```
+//!
+//! States are lettered like in [`crate::busy_beaver::encode`], so that
+//! results can be compared with those of other deciders:
+//!
+//! ```
+//! use std::str::FromStr;
+//!
+//! use turing::{bbchallenge, busy_beaver, TuringMachine, VecTape};
+//!
+//! let program = busy_beaver::BB2.program();
+//! let bytes = bbchallenge::encode(&program).unwrap();
+//! assert_eq!(bytes, [1, 0, 2, 1, 1, 2, 1, 1, 1, 0, 0, 0]);
+//!
+//! let mut machine = TuringMachine::from_tape(VecTape::from_str("_").unwrap());
+//! let state = machine.execute(&program).unwrap();
+//! assert_eq!(
+//!     bbchallenge::configuration(&program, state, machine.tape()).unwrap(),
+//!     "0^inf 1 1 Z> 1 1 0^inf"
+//! );
+//! ```
+use std::fmt::Write;
+
+use crate::{
+    busy_beaver::{self, digit, letter, state_order},
+    error::InvalidMachineCode,
+    trace::Trace,
+    Program, Segment, State, Tape, VecTape,
+};
+
+/// The number of bytes before the first machine of a seed database.
+pub const HEADER_BYTES: usize = 30;
+
+/// The number of bytes of a machine with five states.
+pub const MACHINE_BYTES: usize = 30;
+
+/// Returns the program in the seed database format, or [`None`] if it cannot
+/// be expressed in it.
+///
+/// Only programs that can be written in the standard text format of
+/// [`crate::busy_beaver`] and use at most two symbols can be expressed.
+/// Transitions to a final state are stored as undefined transitions.
+#[must_use]
+pub fn encode(program: &Program) -> Option<Vec<u8>> {
+    let code = busy_beaver::encode(program)?;
+    let rows: Vec<&str> = code.split('_').collect();
+
+    let mut bytes = Vec::new();
+    for row in &rows {
+        if row.len() > 6 {
+            return None;
+        }
+
+        for cell in row.as_bytes().chunks(3) {
+            let &[write, action, to] = cell else {
+                return None;
+            };
+            let to = to.wrapping_sub(b'A');
+            if cell == b"---" || usize::from(to) >= rows.len() {
+                bytes.extend([0, 0, 0]);
+            } else {
+                bytes.extend([write - b'0', u8::from(action == b'L'), to + 1]);
+            }
+        }
+        // Programs that only read the blank have one transition per state
+        bytes.resize(bytes.len().next_multiple_of(6), 0);
+    }
+
+    Some(bytes)
+}
+
+/// Parse a machine in the seed database format, with 6 bytes per state.
+///
+/// Undefined transitions stay undefined, so the program does not have any
+/// final states and runs until it reaches an undefined transition.
+///
+/// # Errors
+///
+/// This method will error if there are no bytes, the number of bytes is not a
+/// multiple of 6 or a transition is out of range.
+pub fn decode(bytes: &[u8]) -> Result<Program, InvalidMachineCode> {
+    if bytes.is_empty() {
+        return Err(InvalidMachineCode::Empty);
+    }
+    if !bytes.len().is_multiple_of(6) {
+        return Err(InvalidMachineCode::SymbolCount);
+    }
+
+    let states = bytes.len() / 6;
+    let mut code = String::new();
+    for (index, cell) in bytes.chunks(3).enumerate() {
+        if index > 0 && index % 2 == 0 {
+            code.push('_');
+        }
+
+        let &[write, action, to] = cell else {
+            unreachable!("the length is a multiple of 3");
+        };
+        if to == 0 {
+            code.push_str("---");
+            continue;
+        }
+
+        let action = match action {
+            0 => Some('R'),
+            1 => Some('L'),
+            _ => None,
+        };
+        let Some(action) = action.filter(|_| write <= 1 && usize::from(to) <= states) else {
+            return Err(InvalidMachineCode::InvalidTransition(format!(
+                "{write},{},{to}",
+                cell[1]
+            )));
+        };
+        let _ = write!(code, "{write}{action}{}", letter(usize::from(to - 1)));
+    }
+
+    busy_beaver::parse(&code)
+}
+
+/// A seed database of machines with five states.
+///
+/// The database starts with a header of [`HEADER_BYTES`] bytes, followed by
+/// [`MACHINE_BYTES`] bytes for every machine. The machines are only decoded
+/// when they are accessed, so the bytes can be a memory map of the whole
+/// database.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SeedDatabase<'a> {
+    /// The number of machines that were undecided after the time limit of the
+    /// enumeration.
+    pub undecided_time: u32,
+    /// The number of machines that were undecided after the space limit of
+    /// the enumeration.
+    pub undecided_space: u32,
+    /// The total number of machines, as given by the header.
+    pub total: u32,
+    /// Whether the machines are sorted.
+    pub sorted: bool,
+    machines: &'a [u8],
+}
+
+impl<'a> SeedDatabase<'a> {
+    /// Read the header of a database.
+    ///
+    /// Returns [`None`] if the bytes are shorter than the header or the
+    /// machines do not all have [`MACHINE_BYTES`] bytes.
+    #[must_use]
+    pub fn new(bytes: &'a [u8]) -> Option<Self> {
+        if bytes.len() < HEADER_BYTES || !(bytes.len() - HEADER_BYTES).is_multiple_of(MACHINE_BYTES)
+        {
+            return None;
+        }
+
+        let count = |offset: usize| {
+            u32::from_be_bytes([
+                bytes[offset],
+                bytes[offset + 1],
+                bytes[offset + 2],
+                bytes[offset + 3],
+            ])
+        };
+
+        Some(Self {
+            undecided_time: count(0),
+            undecided_space: count(4),
+            total: count(8),
+            sorted: bytes[12] == 1,
+            machines: &bytes[HEADER_BYTES..],
+        })
+    }
+
+    /// Returns the number of machines in the database.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.machines.len() / MACHINE_BYTES
+    }
+
+    /// Whether the database does not contain any machines.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.machines.is_empty()
+    }
+
+    /// Returns the bytes of the machine at `index`.
+    #[must_use]
+    pub fn machine(&self, index: usize) -> Option<&'a [u8]> {
+        self.machines
+            .get(index * MACHINE_BYTES..(index + 1) * MACHINE_BYTES)
+    }
+
+    /// Returns the machine at `index` as a [`Program`], or [`None`] if there
+    /// is no machine at `index` or it is invalid.
+    #[must_use]
+    pub fn program(&self, index: usize) -> Option<Program> {
+        decode(self.machine(index)?).ok()
+    }
+}
+
+/// Parse an index file, or return [`None`] if its length is not a multiple
+/// of 4.
+#[must_use]
+pub fn read_index(bytes: &[u8]) -> Option<Vec<u32>> {
+    if !bytes.len().is_multiple_of(4) {
+        return None;
+    }
+
+    Some(
+        bytes
+            .chunks(4)
+            .map(|id| u32::from_be_bytes([id[0], id[1], id[2], id[3]]))
+            .collect(),
+    )
+}
+
+/// Returns `ids` as an index file.
+#[must_use]
+pub fn write_index(ids: &[u32]) -> Vec<u8> {
+    ids.iter().flat_map(|id| id.to_be_bytes()).collect()
+}
+
+/// Returns the configuration of `program` in `state` on `tape`, or [`None`]
+/// if the program cannot be expressed in the standard text format or the
+/// tape contains segments other than the blank and digits.
+///
+/// Only the part of the tape between the non-blank segments and the cursor is
+/// written out. Final states are written as "Z".
+#[must_use]
+pub fn configuration(program: &Program, state: State, tape: &VecTape) -> Option<String> {
+    let name = if program.final_states.contains(&state) {
+        'Z'
+    } else {
+        letter(
+            state_order(program)
+                .iter()
+                .position(|other| *other == state)?,
+        )
+    };
+
+    let mut filled = tape
+        .inner
+        .iter()
+        .enumerate()
+        .filter(|(_, segment)| **segment != Segment::Empty)
+        .map(|(index, _)| index);
+    let first = filled
+        .clone()
+        .next()
+        .map_or(tape.position, |index| index.min(tape.position));
+    let last = filled
+        .next_back()
+        .map_or(tape.position, |index| index.max(tape.position));
+
+    let mut line = String::from("0^inf");
+    for index in first..=last {
+        if index == tape.position {
+            let _ = write!(line, " {name}>");
+        }
+        let segment = tape.inner.get(index).copied().unwrap_or(Segment::Empty);
+        let _ = write!(line, " {}", digit(segment)?);
+    }
+    line.push_str(" 0^inf");
+
+    Some(line)
+}
+
+/// Returns the configuration before every step of `trace` and after the
+/// last one, for a run of `program` that started on `tape`.
+///
+/// Returns [`None`] under the same conditions as [`configuration`].
+#[must_use]
+pub fn configurations(program: &Program, tape: &VecTape, trace: &Trace) -> Option<Vec<String>> {
+    let mut tape = tape.clone();
+    let start = tape.position();
+    let mut state = trace
+        .steps()
+        .first()
+        .map_or(program.initial_state, |step| step.from);
+
+    let mut lines = vec![configuration(program, state, &tape)?];
+    for step in trace.steps() {
+        tape.seek(start + step.position);
+        tape.put(step.written);
+        tape.seek(start + step.position + step.action.offset());
+        state = step.to;
+        lines.push(configuration(program, state, &tape)?);
+    }
+
+    Some(lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::{bbchallenge::*, error::InvalidMachineCode, *};
+
+    #[test]
+    fn test_encode_decode() {
+        let code = "1RB1LC_1RC1RB_1RD0LE_1LA1LD_---0LA";
+        let bytes = encode(&busy_beaver::parse(code).unwrap()).unwrap();
+        assert_eq!(bytes.len(), MACHINE_BYTES);
+        assert_eq!(&bytes[24..], [0, 0, 0, 0, 1, 1]);
+        assert_eq!(busy_beaver::encode(&decode(&bytes).unwrap()).unwrap(), code);
+
+        // Halting transitions become undefined
+        let bytes = encode(&busy_beaver::BB5.program()).unwrap();
+        assert_eq!(busy_beaver::encode(&decode(&bytes).unwrap()).unwrap(), code);
+
+        // Three symbols do not fit
+        assert_eq!(
+            encode(&busy_beaver::parse("1RB2LA0RZ_2LA1RB1LA").unwrap()),
+            None
+        );
+
+        assert_eq!(decode(&[]).unwrap_err(), InvalidMachineCode::Empty);
+        assert_eq!(
+            decode(&[1, 0, 1]).unwrap_err(),
+            InvalidMachineCode::SymbolCount
+        );
+        assert_eq!(
+            decode(&[1, 2, 1, 0, 0, 0]).unwrap_err(),
+            InvalidMachineCode::InvalidTransition("1,2,1".to_string())
+        );
+        assert_eq!(
+            decode(&[1, 0, 2, 0, 0, 0]).unwrap_err(),
+            InvalidMachineCode::InvalidTransition("1,0,2".to_string())
+        );
+    }
+
+    #[test]
+    fn test_seed_database() {
+        let machine = encode(&busy_beaver::BB5.program()).unwrap();
+        let mut bytes = vec![0; HEADER_BYTES];
+        bytes[3] = 2;
+        bytes[7] = 1;
+        bytes[11] = 3;
+        bytes[12] = 1;
+        bytes.extend(&machine);
+        bytes.extend(&machine);
+
+        let database = SeedDatabase::new(&bytes).unwrap();
+        assert_eq!(
+            (
+                database.undecided_time,
+                database.undecided_space,
+                database.total,
+                database.sorted
+            ),
+            (2, 1, 3, true)
+        );
+        assert_eq!(database.len(), 2);
+        assert_eq!(database.machine(1), Some(&machine[..]));
+        assert_eq!(database.machine(2), None);
+        assert!(database.program(0).is_some());
+
+        assert_eq!(SeedDatabase::new(&bytes[..40]), None);
+        assert!(SeedDatabase::new(&bytes[..HEADER_BYTES])
+            .unwrap()
+            .is_empty());
+
+        let ids = [0, 7, 88_664_064];
+        assert_eq!(read_index(&write_index(&ids)).unwrap(), ids);
+        assert_eq!(read_index(&[0, 0, 1]), None);
+    }
+
+    #[test]
+    fn test_configurations() {
+        let program = busy_beaver::BB2.program();
+        let tape = VecTape::from_str("_").unwrap();
+        let mut trace = Trace::new();
+        TuringMachine::from_tape(tape.clone())
+            .execute_traced(&program, &mut trace)
+            .unwrap();
+
+        assert_eq!(
+            configurations(&program, &tape, &trace).unwrap(),
+            [
+                "0^inf A> 0 0^inf",
+                "0^inf 1 B> 0 0^inf",
+                "0^inf A> 1 1 0^inf",
+                "0^inf B> 0 1 1 0^inf",
+                "0^inf A> 0 1 1 1 0^inf",
+                "0^inf 1 B> 1 1 1 0^inf",
+                "0^inf 1 1 Z> 1 1 0^inf",
+            ]
+        );
+
+        // Segments other than digits have no notation
+        let letters = VecTape::from_str("_a").unwrap();
+        assert_eq!(configuration(&program, State(0), &letters), None);
+    }
+}
@@ -0,0 +1,209 @@
+//! Measuring the speed of tape backends.
+//!
+//! [`workloads`] is a standard set of programs with different access
+//! patterns, and [`measure`] runs one of them on any [`Tape`] backend. This
+//! is what `cargo bench --bench tape` reports, and it works the same way for
+//! custom backends:
+//!
+//! ```
+//! use turing::{bench, VecDequeTape, VecTape};
+//!
+//! for workload in bench::workloads() {
+//!     let workload = workload.with_steps(1000);
+//!     let vec = bench::measure::<VecTape>(&workload, 1);
+//!     let deque = bench::measure::<VecDequeTape>(&workload, 1);
+//!
+//!     assert_eq!(vec.steps, deque.steps);
+//! }
+//! ```
+use std::{
+    fmt,
+    hint::black_box,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use crate::{busy_beaver::BB5, ExecutionError, Program, Segment, Tape, TuringMachine};
+
+/// Counts up in binary forever, walking to the right end of the number and
+/// carrying back to the left.
+const INCREMENT_LOOP: &str = "+0\n-2\n\
+    0,0,0,0,r\n0,0,1,1,r\n0,1,_,_,l\n\
+    1,1,1,0,l\n1,0,0,1,r\n1,0,_,1,r";
+
+/// Walks back and forth over a growing block of ones, extending it by one on
+/// the left end every time.
+const LEFT_BOUNCER: &str = "+0\n-2\n\
+    0,1,_,1,r\n0,0,1,1,l\n\
+    1,1,1,1,r\n1,0,_,_,l";
+
+/// The number of ones the copy workload duplicates.
+const COPY_LENGTH: usize = 1000;
+
+/// The number of steps the copy workload takes to duplicate
+/// [`COPY_LENGTH`] ones.
+const COPY_STEPS: u64 = 2_004_002;
+
+/// The default number of steps of the workloads that do not halt.
+const STEPS: u64 = 5_000_000;
+
+/// A program and input to measure tape backends with.
+#[derive(Debug, Clone)]
+pub struct Workload {
+    /// A short description of the workload.
+    pub name: &'static str,
+    /// The program to run.
+    pub program: Program,
+    /// The tape to run it on, as accepted by [`FromStr`].
+    pub input: String,
+    /// The number of steps to run for, which is exactly the number of steps
+    /// it takes the program to halt if it halts within the limit.
+    pub steps: u64,
+}
+
+impl Workload {
+    /// Returns the workload limited to at most `steps` steps.
+    #[must_use]
+    pub fn with_steps(mut self, steps: u64) -> Self {
+        self.steps = self.steps.min(steps);
+        self
+    }
+}
+
+/// Returns the standard workloads: a binary counter, duplicating a block of
+/// ones, the fifth busy beaver champion and a machine that keeps growing the
+/// tape to the left.
+///
+/// # Panics
+///
+/// This function never panics, the programs are known to be valid.
+#[must_use]
+pub fn workloads() -> Vec<Workload> {
+    vec![
+        Workload {
+            name: "increment loop",
+            program: Program::from_str(INCREMENT_LOOP).expect("program is valid"),
+            input: String::from("_0_"),
+            steps: STEPS,
+        },
+        Workload {
+            name: "copy",
+            program: Program::from_str(include_str!("../examples/copy.tng"))
+                .expect("program is valid"),
+            input: format!("_{}_", "1".repeat(COPY_LENGTH)),
+            steps: COPY_STEPS,
+        },
+        Workload {
+            name: "busy beaver 5",
+            program: BB5.program(),
+            input: String::from("_"),
+            steps: STEPS,
+        },
+        Workload {
+            name: "left bouncer",
+            program: Program::from_str(LEFT_BOUNCER).expect("program is valid"),
+            input: String::from("_"),
+            steps: STEPS,
+        },
+    ]
+}
+
+/// The speed of a tape backend on a [`Workload`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Measurement {
+    /// The number of steps performed.
+    pub steps: u64,
+    /// The time the fastest run took.
+    pub elapsed: Duration,
+}
+
+impl Measurement {
+    /// Returns the number of steps performed per second.
+    #[must_use]
+    pub fn steps_per_second(&self) -> u64 {
+        let rate = u128::from(self.steps) * 1_000_000_000 / self.elapsed.as_nanos().max(1);
+        u64::try_from(rate).unwrap_or(u64::MAX)
+    }
+}
+
+impl fmt::Display for Measurement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} steps in {:.2?} ({} steps/s)",
+            self.steps,
+            self.elapsed,
+            self.steps_per_second()
+        )
+    }
+}
+
+/// Run `workload` on a `T` tape `runs` times and return the fastest run.
+///
+/// # Panics
+///
+/// This function panics if the input of the workload is not a valid `T` or
+/// if the program fails with an error other than running out of steps.
+#[must_use]
+pub fn measure<T>(workload: &Workload, runs: u32) -> Measurement
+where
+    T: Tape<Symbol = Segment> + FromStr,
+    T::Err: fmt::Debug,
+{
+    let mut elapsed = Duration::MAX;
+
+    for _ in 0..runs.max(1) {
+        let mut machine = TuringMachine::from_tape(T::from_str(&workload.input).unwrap());
+        let start = Instant::now();
+        let result = machine.execute_bounded(black_box(&workload.program), workload.steps);
+        elapsed = elapsed.min(start.elapsed());
+
+        assert!(matches!(
+            result,
+            Ok(_) | Err(ExecutionError::StepLimitExceeded(_))
+        ));
+        black_box(machine.tape());
+    }
+
+    Measurement {
+        steps: workload.steps,
+        elapsed,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::{bench::*, *};
+
+    #[test]
+    fn test_workload_steps() {
+        for workload in workloads() {
+            let workload = workload.with_steps(COPY_STEPS);
+            let mut machine = TuringMachine::from_tape(VecTape::from_str(&workload.input).unwrap());
+            let mut run = machine.run(&workload.program);
+            let steps = run
+                .by_ref()
+                .take(usize::try_from(COPY_STEPS).unwrap())
+                .count();
+
+            assert_eq!(u64::try_from(steps).unwrap(), workload.steps);
+            // Only the copy workload halts
+            assert_eq!(run.state().is_none(), workload.name == "copy");
+        }
+    }
+
+    #[test]
+    fn test_measurement() {
+        let measurement = Measurement {
+            steps: 3_000,
+            elapsed: Duration::from_millis(2),
+        };
+        assert_eq!(measurement.steps_per_second(), 1_500_000);
+        assert_eq!(
+            measurement.to_string(),
+            "3000 steps in 2.00ms (1500000 steps/s)"
+        );
+    }
+}
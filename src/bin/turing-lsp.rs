@@ -0,0 +1,18 @@
+use std::{
+    io::{stdin, stdout},
+    process::exit,
+};
+
+use turing::lsp::Server;
+
+fn main() {
+    match Server::new().run(stdin().lock(), stdout().lock()) {
+        Ok(true) => {}
+        // The client exited without shutting the server down first
+        Ok(false) => exit(1),
+        Err(e) => {
+            eprintln!("Connection error: {e}.");
+            exit(1);
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use std::{
+    env::args,
+    fs::read_to_string,
+    io::{stdin, stdout},
+    process::exit,
+    str::FromStr,
+};
+
+use turing::{tui::Visualizer, Program, VecTape};
+
+fn main() {
+    let mut args = args();
+    // Skip the executable
+    args.next();
+
+    let (Some(program_path), Some(tape)) = (args.next(), args.next()) else {
+        eprintln!("Usage: turing-tui <program> <tape>");
+        exit(1);
+    };
+
+    let tape = VecTape::from_str(&tape).unwrap_or_else(|_| {
+        eprintln!("Tape buffer is invalid.");
+        exit(1);
+    });
+
+    let code = read_to_string(program_path).unwrap_or_else(|e| {
+        eprintln!("Could not read program: {e}.");
+        exit(1);
+    });
+
+    let program = Program::from_str(&code).unwrap_or_else(|e| {
+        eprintln!("Failed to parse program: {e}");
+        exit(1);
+    });
+
+    if let Err(e) = Visualizer::new(&program, tape).run(stdin().lock(), stdout().lock()) {
+        eprintln!("Terminal error: {e}.");
+        exit(1);
+    }
+}
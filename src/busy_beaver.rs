@@ -0,0 +1,340 @@
+//! Busy beaver machines and their standard text format.
+//!
+//! The format lists the transitions of every state, with states separated by
+//! underscores. Each transition is the symbol to write, the direction to move
+//! in and the next state, or "---" if the transition is undefined. States are
+//! named "A", "B", "C" and so on, and the symbol "0" is the blank. Any letter
+//! after the last state stands for a halting state, usually "Z" or "H":
+//!
+//! ```text
+//! 1RB1LB_1LA1RZ
+//! ```
+//!
+//! This is the two-state busy beaver, which writes four ones in six steps
+//! before halting.
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    fmt::Write,
+};
+
+use crate::{
+    error::InvalidMachineCode,
+    program::{Comments, EntryPoints, StateNames, Transition},
+    Direction, Program, Segment, State,
+};
+
+/// A busy beaver champion: the machine that runs the longest before halting
+/// among all machines with the same number of states and two symbols.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Champion {
+    /// The number of states, not counting the halting state.
+    pub states: usize,
+    /// The machine in the standard text format.
+    pub code: &'static str,
+    /// The number of steps it takes to halt on a blank tape.
+    pub steps: u64,
+    /// The number of ones on the tape after it halted.
+    pub ones: usize,
+}
+
+impl Champion {
+    /// Returns the machine as a [`Program`].
+    ///
+    /// # Panics
+    ///
+    /// This method never panics, the codes of the champions are valid.
+    #[must_use]
+    pub fn program(&self) -> Program {
+        parse(self.code).expect("champion codes are valid")
+    }
+}
+
+/// The two-state busy beaver.
+pub const BB2: Champion = Champion {
+    states: 2,
+    code: "1RB1LB_1LA1RZ",
+    steps: 6,
+    ones: 4,
+};
+
+/// The three-state busy beaver.
+pub const BB3: Champion = Champion {
+    states: 3,
+    code: "1RB1RZ_1LB0RC_1LC1LA",
+    steps: 21,
+    ones: 5,
+};
+
+/// The four-state busy beaver.
+pub const BB4: Champion = Champion {
+    states: 4,
+    code: "1RB1LB_1LA0LC_1RZ1LD_1RD0RA",
+    steps: 107,
+    ones: 13,
+};
+
+/// The five-state busy beaver.
+pub const BB5: Champion = Champion {
+    states: 5,
+    code: "1RB1LC_1RC1RB_1RD0LE_1LA1LD_1RZ0LA",
+    steps: 47_176_870,
+    ones: 4098,
+};
+
+/// All known busy beaver champions with two symbols, by number of states.
+pub const CHAMPIONS: [Champion; 4] = [BB2, BB3, BB4, BB5];
+
+/// Parse a machine in the standard text format.
+///
+/// The program starts in state "A" and names every state after its letter.
+/// The symbol "0" becomes [`Segment::Empty`], every other digit a
+/// [`Segment::Symbol`].
+///
+/// # Errors
+///
+/// This method will error if the code is not in the standard text format.
+pub fn parse(code: &str) -> Result<Program, InvalidMachineCode> {
+    let rows: Vec<&str> = code.trim().split('_').collect();
+    let count = rows.len();
+    let symbols = rows[0].len() / 3;
+
+    if rows[0].is_empty() {
+        return Err(InvalidMachineCode::Empty);
+    }
+    if symbols > 10 || rows.iter().any(|row| row.len() != symbols * 3) {
+        return Err(InvalidMachineCode::SymbolCount);
+    }
+
+    let mut names = StateNames::new();
+    let mut final_states = HashSet::new();
+    let mut transitions = HashMap::new();
+
+    for (index, row) in rows.iter().enumerate() {
+        let from = State(index);
+        names.insert(from, letter(index).to_string());
+
+        for (read, cell) in row.as_bytes().chunks(3).enumerate() {
+            let invalid = || {
+                InvalidMachineCode::InvalidTransition(String::from_utf8_lossy(cell).into_owned())
+            };
+
+            let &[write, action, to] = cell else {
+                return Err(invalid());
+            };
+            if cell == b"---" {
+                continue;
+            }
+
+            let write = char::from(write)
+                .to_digit(10)
+                .filter(|write| (*write as usize) < symbols)
+                .ok_or_else(invalid)?;
+            let action = match action {
+                b'L' => Direction::Left,
+                b'R' => Direction::Right,
+                _ => return Err(invalid()),
+            };
+            if !to.is_ascii_uppercase() {
+                return Err(invalid());
+            }
+
+            let to = usize::from(to - b'A');
+            let to = if to < count {
+                State(to)
+            } else {
+                // Every halting letter gets its own final state after the
+                // regular states
+                let name = letter(to).to_string();
+                let state = names
+                    .iter()
+                    .find(|(_, other)| **other == name)
+                    .map_or(State(count + final_states.len()), |(state, _)| *state);
+                names.insert(state, name);
+                final_states.insert(state);
+                state
+            };
+
+            let condition = segment(read);
+            transitions.insert(
+                (from, condition),
+                Transition {
+                    from,
+                    to,
+                    condition,
+                    write: segment(write as usize),
+                    action,
+                },
+            );
+        }
+    }
+
+    Ok(Program {
+        initial_state: State(0),
+        final_states,
+        rejecting_states: HashSet::new(),
+        error_states: HashSet::new(),
+        transitions,
+        wildcards: HashMap::new(),
+        names,
+        entry_points: EntryPoints::new(),
+        comments: Comments::new(),
+    })
+}
+
+/// Returns the program in the standard text format, or [`None`] if it cannot
+/// be expressed in it.
+///
+/// The initial state becomes "A", the other states follow in ascending order
+/// and final states become "Z". Programs that have error states, keep the
+/// cursor in place or use symbols other than the blank and digits cannot be
+/// expressed in the format.
+#[must_use]
+pub fn encode(program: &Program) -> Option<String> {
+    if !program.error_states.is_empty() {
+        return None;
+    }
+
+    let mut symbols = 1;
+    for transition in program.transitions.values() {
+        symbols = symbols
+            .max(digit(transition.condition)? + 1)
+            .max(digit(transition.write)? + 1);
+    }
+    for wildcard in program.wildcards.values() {
+        if let Some(write) = wildcard.write {
+            symbols = symbols.max(digit(write)? + 1);
+        }
+    }
+
+    let order = state_order(program);
+    if order.len() > 25 {
+        return None;
+    }
+
+    let mut code = String::new();
+    for (index, state) in order.iter().enumerate() {
+        if index > 0 {
+            code.push('_');
+        }
+
+        for read in 0..symbols {
+            let Some(transition) = program.transition(*state, segment(read)) else {
+                code.push_str("---");
+                continue;
+            };
+
+            let action = match transition.action {
+                Direction::Left => 'L',
+                Direction::Right => 'R',
+                Direction::Stay => return None,
+            };
+            let to = if program.final_states.contains(&transition.to) {
+                'Z'
+            } else {
+                letter(order.iter().position(|other| *other == transition.to)?)
+            };
+
+            let _ = write!(code, "{}{action}{to}", digit(transition.write)?);
+        }
+    }
+
+    Some(code)
+}
+
+/// Returns the initial state followed by the other states with transitions
+/// that are not final, in the order they are lettered in the text format.
+pub(crate) fn state_order(program: &Program) -> Vec<State> {
+    let mut states = BTreeSet::new();
+    for transition in program.transitions.values() {
+        states.extend([transition.from, transition.to]);
+    }
+    for wildcard in program.wildcards.values() {
+        states.extend([wildcard.from, wildcard.to]);
+    }
+
+    std::iter::once(program.initial_state)
+        .chain(states.into_iter().filter(|state| {
+            *state != program.initial_state && !program.final_states.contains(state)
+        }))
+        .collect()
+}
+
+pub(crate) fn letter(index: usize) -> char {
+    char::from(b'A' + u8::try_from(index).unwrap_or(u8::MAX - b'A'))
+}
+
+fn segment(symbol: usize) -> Segment {
+    match symbol {
+        0 => Segment::Empty,
+        symbol => Segment::Symbol(char::from(b'0' + u8::try_from(symbol).unwrap_or(0))),
+    }
+}
+
+pub(crate) fn digit(segment: Segment) -> Option<usize> {
+    match segment {
+        Segment::Empty => Some(0),
+        Segment::Symbol(c) => c.to_digit(10).map(|digit| digit as usize),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::{busy_beaver::*, error::InvalidMachineCode, *};
+
+    #[test]
+    fn test_champions() {
+        for champion in [BB2, BB3, BB4] {
+            let program = champion.program();
+            let mut machine = TuringMachine::from_tape(VecTape::from_str("_").unwrap());
+
+            assert_eq!(machine.run(&program).count() as u64, champion.steps);
+            assert_eq!(
+                machine.tape().to_string().matches('1').count(),
+                champion.ones
+            );
+        }
+
+        let program = BB5.program();
+        assert_eq!(program.state("E").unwrap().to_string(), "4");
+        assert_eq!(program.state("Z").unwrap().to_string(), "5");
+    }
+
+    #[test]
+    fn test_encode() {
+        for champion in CHAMPIONS {
+            assert_eq!(encode(&champion.program()).unwrap(), champion.code);
+        }
+
+        assert_eq!(
+            encode(&parse("1RB---_0LA1RH").unwrap()).unwrap(),
+            "1RB---_0LA1RZ"
+        );
+        assert_eq!(
+            encode(&Program::from_str(include_str!("../examples/append.tng")).unwrap()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse("").unwrap_err(), InvalidMachineCode::Empty);
+        assert_eq!(
+            parse("1RB1LB_1LA").unwrap_err(),
+            InvalidMachineCode::SymbolCount
+        );
+        assert_eq!(
+            parse("1RB2LB_1LA1RZ").unwrap_err(),
+            InvalidMachineCode::InvalidTransition(String::from("2LB"))
+        );
+        assert_eq!(
+            parse("1NB1LB_1LA1RZ").unwrap_err(),
+            InvalidMachineCode::InvalidTransition(String::from("1NB"))
+        );
+        assert_eq!(
+            parse("1NB1LB_1LA1RZ").unwrap_err().to_string(),
+            "invalid transition \"1NB\", expected \"---\" or a symbol, a direction and a state"
+        );
+    }
+}
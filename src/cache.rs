@@ -0,0 +1,569 @@
+//! An on-disk cache for the results of running [`Program`]s.
+//!
+//! Entries are content-addressed: the file name of an entry is derived from a
+//! fingerprint of the program, the input tape and the configuration used to
+//! run it. Entries are never modified after they have been written, which
+//! makes it safe to share a cache directory between parallel processes.
+use std::{
+    fmt::Write as _,
+    fs::{self, OpenOptions},
+    io::{self, Write as _},
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::{ExecutionConfig, ExecutionError, Program, State, TuringMachine, VecTape};
+
+/// The part of the crate version that can change the behaviour of a run.
+///
+/// Entries written by a different version are never served.
+const CACHE_VERSION: &str = concat!(
+    env!("CARGO_PKG_VERSION_MAJOR"),
+    ".",
+    env!("CARGO_PKG_VERSION_MINOR")
+);
+
+/// The first line of every entry file.
+const ENTRY_HEADER: &str = "turing-run-cache 1";
+
+/// Name of the index file inside the cache directory.
+const INDEX_FILE: &str = "index";
+
+/// Counter to make temporary file names unique within this process.
+static TEMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// A value that can be part of a [`RunCache`] key.
+///
+/// Implementations must write a canonical representation that only depends on
+/// the behaviour-relevant contents of the value, so that equal inputs map to
+/// the same entry across processes.
+pub trait Fingerprint {
+    /// Append the canonical representation of `self` to `out`.
+    fn fingerprint(&self, out: &mut String);
+}
+
+impl Fingerprint for Program {
+    fn fingerprint(&self, out: &mut String) {
+        let _ = writeln!(out, "+{}", self.initial_state);
+
+        let mut final_states: Vec<_> = self.final_states.iter().collect();
+        final_states.sort();
+        for state in final_states {
+            if self.rejecting_states.contains(state) {
+                let _ = writeln!(out, "-R{state}");
+            } else {
+                let _ = writeln!(out, "-{state}");
+            }
+        }
+
+        let mut error_states: Vec<_> = self.error_states.iter().collect();
+        error_states.sort();
+        for state in error_states {
+            let _ = writeln!(out, "!{state}");
+        }
+
+        let mut names: Vec<_> = self.names.iter().collect();
+        names.sort();
+        for (state, name) in names {
+            let _ = writeln!(out, "={state} {name}");
+        }
+
+        // Entry points are already sorted by name
+        for (name, state) in &self.entry_points {
+            let _ = writeln!(out, "@entry {name} {state}");
+        }
+
+        let mut transitions: Vec<_> = self.transitions.values().collect();
+        transitions.sort_by_key(|transition| (transition.from, transition.condition));
+        for transition in transitions {
+            let _ = writeln!(
+                out,
+                "{},{},{},{},{}",
+                transition.from,
+                transition.to,
+                transition.condition,
+                transition.write,
+                transition.action
+            );
+        }
+
+        let mut wildcards: Vec<_> = self.wildcards.values().collect();
+        wildcards.sort_by_key(|wildcard| wildcard.from);
+        for wildcard in wildcards {
+            let write = wildcard
+                .write
+                .map_or_else(|| String::from("="), |write| write.to_string());
+            let _ = writeln!(
+                out,
+                "{},{},*,{write},{}",
+                wildcard.from, wildcard.to, wildcard.action
+            );
+        }
+    }
+}
+
+impl Fingerprint for VecTape {
+    fn fingerprint(&self, out: &mut String) {
+        let _ = writeln!(out, "{} {} {self}", self.position, self.origin);
+    }
+}
+
+impl Fingerprint for ExecutionConfig {
+    fn fingerprint(&self, out: &mut String) {
+        // Destructured so that new fields cannot be left out of the key
+        let Self {
+            max_steps,
+            max_tape_len,
+            detect_loops,
+            record_trace,
+        } = self;
+
+        max_steps.fingerprint(out);
+        max_tape_len.fingerprint(out);
+        detect_loops.fingerprint(out);
+        record_trace.fingerprint(out);
+    }
+}
+
+impl Fingerprint for () {
+    fn fingerprint(&self, _out: &mut String) {}
+}
+
+impl Fingerprint for bool {
+    fn fingerprint(&self, out: &mut String) {
+        let _ = writeln!(out, "{self}");
+    }
+}
+
+impl Fingerprint for u64 {
+    fn fingerprint(&self, out: &mut String) {
+        let _ = writeln!(out, "{self}");
+    }
+}
+
+impl Fingerprint for usize {
+    fn fingerprint(&self, out: &mut String) {
+        let _ = writeln!(out, "{self}");
+    }
+}
+
+impl<T> Fingerprint for Option<T>
+where
+    T: Fingerprint,
+{
+    fn fingerprint(&self, out: &mut String) {
+        match self {
+            Some(value) => {
+                out.push_str("some\n");
+                value.fingerprint(out);
+            }
+            None => out.push_str("none\n"),
+        }
+    }
+}
+
+/// The outcome of a run, either computed or served from a [`RunCache`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachedRunReport {
+    /// The result of executing the program.
+    pub result: Result<State, ExecutionError>,
+    /// The tape after the program finished.
+    pub tape: VecTape,
+    /// Whether the report was served from the cache instead of being computed.
+    pub cached: bool,
+}
+
+impl CachedRunReport {
+    /// Serialize the report into the entry format, prefixed by the key
+    /// material it belongs to.
+    fn encode(&self, material: &str) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "{ENTRY_HEADER}");
+        let _ = writeln!(out, "{}", material.len());
+        out.push_str(material);
+
+        let _ = match &self.result {
+            Ok(state) => writeln!(out, "ok {state}"),
+            Err(ExecutionError::UndefinedBehavior(state, segment)) => {
+                writeln!(out, "undefined {state} {segment}")
+            }
+            Err(ExecutionError::ReachedError(state)) => writeln!(out, "error {state}"),
+            Err(ExecutionError::StepLimitExceeded(steps)) => writeln!(out, "limit {steps}"),
+            Err(ExecutionError::InfiniteLoopDetected(state)) => writeln!(out, "loop {state}"),
+            Err(ExecutionError::TapeLimitExceeded(cells)) => writeln!(out, "tape {cells}"),
+            Err(ExecutionError::Cancelled(state)) => writeln!(out, "cancelled {state}"),
+            Err(ExecutionError::FellOffTape(state)) => writeln!(out, "edge {state}"),
+        };
+        let _ = writeln!(
+            out,
+            "{} {} {}",
+            self.tape.position, self.tape.origin, self.tape
+        );
+
+        out
+    }
+
+    /// Parse an entry, returning [`None`] if it is corrupt or belongs to
+    /// different key material.
+    fn decode(entry: &str, material: &str) -> Option<Self> {
+        let entry = entry.strip_prefix(ENTRY_HEADER)?.strip_prefix('\n')?;
+        let (length, entry) = entry.split_once('\n')?;
+        let length: usize = length.parse().ok()?;
+
+        if entry.get(..length)? != material {
+            return None;
+        }
+
+        let mut lines = entry[length..].lines();
+        let mut result = lines.next()?.split(' ');
+        let result = match (result.next()?, result.next(), result.next()) {
+            ("ok", Some(state), None) => Ok(state.parse().ok()?),
+            ("undefined", Some(state), Some(segment)) => Err(ExecutionError::UndefinedBehavior(
+                state.parse().ok()?,
+                segment.parse().ok()?,
+            )),
+            ("error", Some(state), None) => Err(ExecutionError::ReachedError(state.parse().ok()?)),
+            ("limit", Some(steps), None) => {
+                Err(ExecutionError::StepLimitExceeded(steps.parse().ok()?))
+            }
+            ("loop", Some(state), None) => {
+                Err(ExecutionError::InfiniteLoopDetected(state.parse().ok()?))
+            }
+            ("tape", Some(cells), None) => {
+                Err(ExecutionError::TapeLimitExceeded(cells.parse().ok()?))
+            }
+            ("cancelled", Some(state), None) => Err(ExecutionError::Cancelled(state.parse().ok()?)),
+            ("edge", Some(state), None) => Err(ExecutionError::FellOffTape(state.parse().ok()?)),
+            _ => return None,
+        };
+
+        let mut tape = lines.next()?.splitn(3, ' ');
+        let position: usize = tape.next()?.parse().ok()?;
+        let origin: usize = tape.next()?.parse().ok()?;
+        let tape: VecTape = tape.next()?.parse().ok()?;
+
+        if position >= tape.inner.len() || lines.next().is_some() {
+            return None;
+        }
+
+        Some(Self {
+            result,
+            tape: VecTape {
+                inner: tape.inner,
+                position,
+                origin,
+            },
+            cached: true,
+        })
+    }
+}
+
+/// A content-addressed, on-disk cache of [`CachedRunReport`]s.
+///
+/// The cache is safe to share between processes: entries are written to a
+/// temporary file and atomically renamed into place, and entries that cannot
+/// be read back are treated as misses.
+#[derive(Debug)]
+pub struct RunCache {
+    dir: PathBuf,
+}
+
+impl RunCache {
+    /// Open the cache stored in `dir`, creating the directory if needed.
+    ///
+    /// # Errors
+    ///
+    /// This method will error if the directory cannot be created.
+    pub fn open(dir: impl AsRef<Path>) -> io::Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+
+        Ok(Self { dir })
+    }
+
+    /// Returns the report for running `program` on `tape` with `config`,
+    /// invoking `runner` only if no valid entry exists yet.
+    ///
+    /// The runner is given a [`TuringMachine`] loaded with a copy of `tape`.
+    /// Failing to store a fresh report is not an error, the report is simply
+    /// not cached.
+    pub fn get_or_run<C, F>(
+        &self,
+        program: &Program,
+        tape: &VecTape,
+        config: &C,
+        runner: F,
+    ) -> CachedRunReport
+    where
+        C: Fingerprint,
+        F: FnOnce(&mut TuringMachine<VecTape>, &Program, &C) -> Result<State, ExecutionError>,
+    {
+        let mut material = format!("turing {CACHE_VERSION}\n");
+        program.fingerprint(&mut material);
+        material.push_str("--\n");
+        tape.fingerprint(&mut material);
+        material.push_str("--\n");
+        config.fingerprint(&mut material);
+
+        let key = format!("{:016x}", fnv1a(material.as_bytes()));
+        let path = self.entry_path(&key);
+
+        if let Some(report) = fs::read_to_string(&path)
+            .ok()
+            .and_then(|entry| CachedRunReport::decode(&entry, &material))
+        {
+            return report;
+        }
+
+        let mut machine = TuringMachine::from_tape(tape.clone());
+        let result = runner(&mut machine, program, config);
+        let report = CachedRunReport {
+            result,
+            tape: machine.tape().clone(),
+            cached: false,
+        };
+
+        let _ = self.store(&key, &path, &report.encode(&material));
+
+        report
+    }
+
+    /// Remove all entries that were written by a different version of this
+    /// crate and can therefore never be served again.
+    ///
+    /// Returns the number of removed entries.
+    ///
+    /// # Errors
+    ///
+    /// This method will error if the index cannot be read or rewritten.
+    pub fn prune(&self) -> io::Result<usize> {
+        let index_path = self.dir.join(INDEX_FILE);
+        let index = match fs::read_to_string(&index_path) {
+            Ok(index) => index,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e),
+        };
+
+        let mut kept = String::new();
+        let mut removed = 0;
+
+        for line in index.lines() {
+            match line.split_once(' ') {
+                Some((CACHE_VERSION, _)) => {
+                    kept.push_str(line);
+                    kept.push('\n');
+                }
+                Some((_, key)) => match fs::remove_file(self.entry_path(key)) {
+                    Ok(()) => removed += 1,
+                    Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                    Err(e) => return Err(e),
+                },
+                None => {}
+            }
+        }
+
+        let temp = self.temp_path(INDEX_FILE);
+        fs::write(&temp, kept)?;
+        fs::rename(temp, index_path)?;
+
+        Ok(removed)
+    }
+
+    fn entry_path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{key}.run"))
+    }
+
+    fn temp_path(&self, name: &str) -> PathBuf {
+        let counter = TEMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+        self.dir
+            .join(format!("{name}.{}.{counter}.tmp", process::id()))
+    }
+
+    fn store(&self, key: &str, path: &Path, entry: &str) -> io::Result<()> {
+        let temp = self.temp_path(key);
+        fs::write(&temp, entry)?;
+
+        if let Err(e) = fs::rename(&temp, path) {
+            let _ = fs::remove_file(&temp);
+            return Err(e);
+        }
+
+        // A single small append is atomic, so concurrent writers never
+        // interleave lines.
+        let mut index = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.dir.join(INDEX_FILE))?;
+        index.write_all(format!("{CACHE_VERSION} {key}\n").as_bytes())
+    }
+}
+
+/// The 64-bit FNV-1a hash, which is stable across platforms and releases.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, env, fs, path::PathBuf, process, str::FromStr};
+
+    use crate::{cache::RunCache, *};
+
+    fn cache_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("turing-cache-{}-{name}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_cache_hit_and_miss() {
+        let dir = cache_dir("hit");
+        let cache = RunCache::open(&dir).unwrap();
+        let program = Program::from_str(include_str!("../examples/next_integer.tng")).unwrap();
+        let runs = Cell::new(0);
+        let runner = |machine: &mut TuringMachine<VecTape>, program: &Program, (): &()| {
+            runs.set(runs.get() + 1);
+            machine.execute(program)
+        };
+
+        let tape = VecTape::from_str("_111_").unwrap();
+        let first = cache.get_or_run(&program, &tape, &(), runner);
+        let second = cache.get_or_run(&program, &tape, &(), runner);
+
+        assert!(!first.cached);
+        assert!(second.cached);
+        assert_eq!(first.result, second.result);
+        assert_eq!(first.tape, second.tape);
+        assert_eq!(runs.get(), 1);
+
+        let tape = VecTape::from_str("_101_").unwrap();
+        let third = cache.get_or_run(&program, &tape, &(), runner);
+
+        assert!(!third.cached);
+        assert_eq!(third.tape.to_string(), "_110_");
+        assert_eq!(runs.get(), 2);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_cache_key_covers_program_and_tape() {
+        let dir = cache_dir("key");
+        let cache = RunCache::open(&dir).unwrap();
+        let runs = Cell::new(0);
+        let runner = |machine: &mut TuringMachine<VecTape>, program: &Program, (): &()| {
+            runs.set(runs.get() + 1);
+            machine.execute(program)
+        };
+        let tape = VecTape::from_str("_1_").unwrap();
+
+        let program = Program::from_str("+0\n-1\n0,1,*,=,n").unwrap();
+        let rejecting = Program::from_str("+0\n-R1\n0,1,*,=,n").unwrap();
+        let entry = Program::from_str("+0\n-1\n@entry start 0\n0,1,*,=,n").unwrap();
+        let mut named = program.clone();
+        named.names.insert(State(1), String::from("done"));
+
+        for (runs_after, program) in [(1, &program), (2, &rejecting), (3, &entry), (4, &named)] {
+            assert!(!cache.get_or_run(program, &tape, &(), runner).cached);
+            assert!(cache.get_or_run(program, &tape, &(), runner).cached);
+            assert_eq!(runs.get(), runs_after);
+        }
+
+        // The same segments and cursor, but at a different logical position
+        let shifted = VecTape {
+            origin: tape.origin + 1,
+            ..tape.clone()
+        };
+        let report = cache.get_or_run(&program, &shifted, &(), runner);
+        assert!(!report.cached);
+        assert_eq!(report.tape.origin, shifted.origin);
+        assert_eq!(runs.get(), 5);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_cache_key_covers_config() {
+        let dir = cache_dir("config");
+        let cache = RunCache::open(&dir).unwrap();
+        let program = Program::from_str("+0\n-1\n0,0,_,1,r").unwrap();
+        let tape = VecTape::from_str("_").unwrap();
+        let runs = Cell::new(0);
+        let runner =
+            |machine: &mut TuringMachine<VecTape>, program: &Program, config: &ExecutionConfig| {
+                runs.set(runs.get() + 1);
+                machine.execute_with(program, config, &mut ExecutionReport::default())
+            };
+
+        let default = ExecutionConfig {
+            max_steps: Some(10),
+            ..ExecutionConfig::default()
+        };
+        let configs = [
+            default.clone(),
+            ExecutionConfig {
+                max_steps: Some(20),
+                ..default.clone()
+            },
+            ExecutionConfig {
+                max_tape_len: Some(5),
+                ..default.clone()
+            },
+            ExecutionConfig {
+                detect_loops: true,
+                ..default.clone()
+            },
+            ExecutionConfig {
+                record_trace: true,
+                ..default
+            },
+        ];
+
+        for (runs_after, config) in (1..).zip(&configs) {
+            let report = cache.get_or_run(&program, &tape, config, runner);
+            assert!(!report.cached);
+            assert!(cache.get_or_run(&program, &tape, config, runner).cached);
+            assert_eq!(runs.get(), runs_after);
+        }
+        assert_eq!(
+            cache
+                .get_or_run(&program, &tape, &configs[2], runner)
+                .result,
+            Err(ExecutionError::TapeLimitExceeded(5))
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_cache_corrupt_entry() {
+        let dir = cache_dir("corrupt");
+        let cache = RunCache::open(&dir).unwrap();
+        let program = Program::from_str(include_str!("../examples/append.tng")).unwrap();
+        let tape = VecTape::from_str("_111_").unwrap();
+        let runs = Cell::new(0);
+        let runner = |machine: &mut TuringMachine<VecTape>, program: &Program, (): &()| {
+            runs.set(runs.get() + 1);
+            machine.execute(program)
+        };
+
+        cache.get_or_run(&program, &tape, &(), runner);
+
+        for entry in fs::read_dir(&dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().is_some_and(|extension| extension == "run") {
+                fs::write(path, "garbage").unwrap();
+            }
+        }
+
+        let report = cache.get_or_run(&program, &tape, &(), runner);
+        assert!(!report.cached);
+        assert_eq!(runs.get(), 2);
+        assert!(cache.get_or_run(&program, &tape, &(), runner).cached);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,636 @@
+//! Compilation of [`Program`]s into dense transition tables.
+//!
+//! A [`Program`] looks up every step in a hash map keyed by state and
+//! segment. A [`CompiledProgram`] numbers states and symbols densely instead,
+//! so that each step is a single array access, which makes long runs a lot
+//! faster.
+//!
+//! Programs with at most four symbols, like most busy beaver candidates, get
+//! a second table without any special cases: every entry has the symbol to
+//! write and the cursor offset resolved, and missing transitions lead to a
+//! sentinel state, so steps only branch to halt, to grow the tape and to
+//! check the step limit. [`Program::compile_profiled`] additionally lays out
+//! the table so that the rows of the states a sample run spends most of its
+//! time in are next to each other.
+use std::{
+    cmp::Reverse,
+    collections::{BTreeSet, HashMap},
+};
+
+use crate::{tape::Symbol, Direction, ExecutionError, Program, Segment, State, VecTape};
+
+/// A transition of a [`CompiledProgram`], with dense state and symbol
+/// numbers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct CompiledTransition {
+    to: u32,
+    /// The symbol to write, or [`WRITE_BACK`].
+    write: u32,
+    action: Direction,
+}
+
+/// The symbol number of wildcard transitions that write back the segment
+/// they read.
+const WRITE_BACK: u32 = u32::MAX;
+
+/// The number of symbols a program may use at most to get a [`SmallTable`],
+/// which is also the length of its rows.
+const SMALL_WIDTH: usize = 4;
+
+/// What happens when the machine enters a state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Running,
+    Final,
+    Error,
+    /// The sentinel state of a [`SmallTable`] that missing transitions lead
+    /// to.
+    Undefined,
+}
+
+/// A transition of a [`SmallTable`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct SmallTransition {
+    to: u32,
+    write: u8,
+    offset: i8,
+}
+
+/// The transitions of a program with at most [`SMALL_WIDTH`] symbols, with
+/// one row of [`SMALL_WIDTH`] entries per state.
+///
+/// Missing transitions write back what they read, stay in place and lead to
+/// the sentinel state after the last one, so a step never has to check
+/// whether there is a transition.
+#[derive(Debug, Clone)]
+struct SmallTable {
+    table: Vec<SmallTransition>,
+    /// The kinds of the states, followed by [`Kind::Undefined`].
+    kinds: Vec<Kind>,
+}
+
+/// A [`Program`] compiled into a dense transition table with
+/// [`Program::compile`].
+#[derive(Debug, Clone)]
+pub struct CompiledProgram<S = Segment> {
+    /// The symbols by their number, starting with the blank.
+    symbols: Vec<S>,
+    /// The original states by their dense number.
+    states: Vec<State>,
+    kinds: Vec<Kind>,
+    initial: u32,
+    /// The transitions, with one row of `symbols.len()` entries per state.
+    table: Vec<Option<CompiledTransition>>,
+    /// The wildcard of each state, for symbols that only appear on the tape.
+    wildcards: Vec<Option<CompiledTransition>>,
+    small: Option<SmallTable>,
+}
+
+impl<S> Program<S>
+where
+    S: Symbol,
+{
+    /// Compile the program into a dense transition table for fast execution.
+    ///
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use turing::{Program, VecTape};
+    /// let program = Program::from_str(include_str!("../examples/next_integer.tng")).unwrap();
+    /// let compiled = program.compile();
+    ///
+    /// let mut tape = VecTape::from_str("_1011_").unwrap();
+    /// compiled.execute(&mut tape).unwrap();
+    /// assert_eq!(tape.to_string(), "_1100_");
+    /// ```
+    #[must_use]
+    pub fn compile(&self) -> CompiledProgram<S> {
+        self.compile_ordered(&HashMap::new())
+    }
+
+    /// Compile the program like [`Program::compile`], after running it on
+    /// `sample` for at most `max_steps` steps to find out which states are
+    /// entered most often.
+    ///
+    /// The transitions of these states are laid out next to each other, which
+    /// keeps the hot part of large programs in the cache during long runs on
+    /// similar tapes:
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use turing::{busy_beaver::BB4, VecTape};
+    /// let compiled = BB4
+    ///     .program()
+    ///     .compile_profiled(&VecTape::from_str("_").unwrap(), 1000);
+    ///
+    /// let mut tape = VecTape::from_str("_").unwrap();
+    /// compiled.execute(&mut tape).unwrap();
+    /// assert_eq!(tape.to_string().matches('1').count(), 13);
+    /// ```
+    #[must_use]
+    pub fn compile_profiled(&self, sample: &VecTape<S>, max_steps: u64) -> CompiledProgram<S> {
+        let compiled = self.compile();
+        let mut visits = vec![0_u64; compiled.states.len()];
+        let mut tape = sample.clone();
+        let _ = compiled.run_general(&mut tape, Some(max_steps), |state| visits[state] += 1);
+
+        let visits = compiled.states.iter().copied().zip(visits).collect();
+        self.compile_ordered(&visits)
+    }
+
+    /// Compile the program, numbering the states that were visited most
+    /// often according to `visits` first.
+    fn compile_ordered(&self, visits: &HashMap<State, u64>) -> CompiledProgram<S> {
+        let mut symbols = BTreeSet::new();
+        let mut states = BTreeSet::from([self.initial_state]);
+        states.extend(self.final_states.iter().chain(&self.error_states).copied());
+
+        for transition in self.transitions.values() {
+            symbols.extend([transition.condition, transition.write]);
+            states.extend([transition.from, transition.to]);
+        }
+        for wildcard in self.wildcards.values() {
+            symbols.extend(wildcard.write);
+            states.extend([wildcard.from, wildcard.to]);
+        }
+
+        symbols.remove(&S::BLANK);
+        let symbols: Vec<S> = std::iter::once(S::BLANK).chain(symbols).collect();
+        let mut states: Vec<State> = states.into_iter().collect();
+        states.sort_by_key(|state| Reverse(visits.get(state).copied().unwrap_or(0)));
+
+        let symbol_numbers: HashMap<S, u32> = (0..)
+            .zip(symbols.iter().copied())
+            .map(|(i, s)| (s, i))
+            .collect();
+        let state_numbers: HashMap<State, u32> = (0..)
+            .zip(states.iter().copied())
+            .map(|(i, s)| (s, i))
+            .collect();
+
+        let compile = |to: State, write: Option<S>, action| CompiledTransition {
+            to: state_numbers[&to],
+            write: write.map_or(WRITE_BACK, |write| symbol_numbers[&write]),
+            action,
+        };
+
+        let wildcards: Vec<_> = states
+            .iter()
+            .map(|state| {
+                self.wildcards
+                    .get(state)
+                    .map(|wildcard| compile(wildcard.to, wildcard.write, wildcard.action))
+            })
+            .collect();
+
+        let mut table = Vec::with_capacity(states.len() * symbols.len());
+        for (state, wildcard) in states.iter().zip(&wildcards) {
+            for (number, symbol) in (0..).zip(&symbols) {
+                let transition = match self.transitions.get(&(*state, *symbol)) {
+                    Some(transition) => Some(compile(
+                        transition.to,
+                        Some(transition.write),
+                        transition.action,
+                    )),
+                    None => wildcard.map(|wildcard| CompiledTransition {
+                        write: if wildcard.write == WRITE_BACK {
+                            number
+                        } else {
+                            wildcard.write
+                        },
+                        ..wildcard
+                    }),
+                };
+
+                table.push(transition);
+            }
+        }
+
+        let kinds: Vec<Kind> = states
+            .iter()
+            .map(|state| {
+                if self.final_states.contains(state) {
+                    Kind::Final
+                } else if self.error_states.contains(state) {
+                    Kind::Error
+                } else {
+                    Kind::Running
+                }
+            })
+            .collect();
+
+        let small =
+            (symbols.len() <= SMALL_WIDTH).then(|| small_table(&table, &kinds, symbols.len()));
+
+        CompiledProgram {
+            initial: state_numbers[&self.initial_state],
+            symbols,
+            states,
+            kinds,
+            table,
+            wildcards,
+            small,
+        }
+    }
+}
+
+/// Returns the [`SmallTable`] of a table with rows of `width` entries.
+fn small_table(table: &[Option<CompiledTransition>], kinds: &[Kind], width: usize) -> SmallTable {
+    let undefined = u32::try_from(kinds.len()).unwrap_or(u32::MAX);
+    let mut small = Vec::with_capacity(kinds.len() * SMALL_WIDTH);
+
+    for row in table.chunks(width.max(1)) {
+        for read in 0..SMALL_WIDTH {
+            let read_number = u8::try_from(read).unwrap_or(u8::MAX);
+            small.push(match row.get(read).copied().flatten() {
+                Some(transition) => SmallTransition {
+                    to: transition.to,
+                    write: u8::try_from(transition.write).unwrap_or(u8::MAX),
+                    offset: match transition.action {
+                        Direction::Left => -1,
+                        Direction::Right => 1,
+                        Direction::Stay => 0,
+                    },
+                },
+                None => SmallTransition {
+                    to: undefined,
+                    write: read_number,
+                    offset: 0,
+                },
+            });
+        }
+    }
+
+    let mut kinds = kinds.to_vec();
+    kinds.push(Kind::Undefined);
+
+    SmallTable {
+        table: small,
+        kinds,
+    }
+}
+
+/// The tape of a run of a [`CompiledProgram`], as symbol numbers.
+struct NumberedTape<S> {
+    cells: Vec<u32>,
+    /// The symbols by their number, including those that only appear on the
+    /// tape.
+    symbols: Vec<S>,
+    position: usize,
+    /// The index of the first cell of the tape, which is only moved to the
+    /// left once the cursor actually goes there.
+    start: usize,
+    /// The number of cells added to the left of the tape.
+    padding: usize,
+}
+
+impl<S> NumberedTape<S>
+where
+    S: Symbol,
+{
+    /// Number the segments of `tape`, with the symbols that only appear on
+    /// the tape numbered after `symbols`.
+    fn new(symbols: &[S], tape: &VecTape<S>) -> Self {
+        let mut symbols = symbols.to_vec();
+        let mut numbers: HashMap<S, u32> = (0..)
+            .zip(symbols.iter().copied())
+            .map(|(i, s)| (s, i))
+            .collect();
+        let cells = tape
+            .inner
+            .iter()
+            .map(|symbol| {
+                *numbers.entry(*symbol).or_insert_with(|| {
+                    symbols.push(*symbol);
+                    u32::try_from(symbols.len() - 1).unwrap_or(u32::MAX)
+                })
+            })
+            .collect();
+
+        Self {
+            cells,
+            symbols,
+            position: tape.position,
+            start: 0,
+            padding: 0,
+        }
+    }
+
+    /// Grow the tape to the left in bulk instead of one cell at a time,
+    /// keeping the cursor on the same cell.
+    fn grow_left(&mut self) {
+        let grow = self.cells.len();
+        self.cells.splice(0..0, std::iter::repeat_n(0, grow));
+        self.position += grow;
+        self.start += grow;
+        self.padding += grow;
+    }
+
+    /// Write the cells back to `tape`.
+    fn store(self, tape: &mut VecTape<S>) {
+        tape.inner.clear();
+        tape.inner.extend(
+            self.cells[self.start..]
+                .iter()
+                .map(|number| self.symbols[*number as usize]),
+        );
+        tape.position = self.position - self.start;
+        tape.origin = tape.origin + self.padding - self.start;
+    }
+}
+
+impl<S> CompiledProgram<S>
+where
+    S: Symbol,
+{
+    /// Run the program on `tape`, like [`crate::TuringMachine::execute`].
+    ///
+    /// # Errors
+    ///
+    /// This method will error if it encounters undefined behaviour or reaches
+    /// an error state.
+    pub fn execute(&self, tape: &mut VecTape<S>) -> Result<State, ExecutionError<S>> {
+        self.run(tape, None)
+    }
+
+    /// Run the program on `tape` for at most `max_steps` steps, like
+    /// [`crate::TuringMachine::execute_bounded`].
+    ///
+    /// # Errors
+    ///
+    /// This method will error if it encounters undefined behaviour, reaches
+    /// an error state or does not halt within `max_steps` steps.
+    pub fn execute_bounded(
+        &self,
+        tape: &mut VecTape<S>,
+        max_steps: u64,
+    ) -> Result<State, ExecutionError<S>> {
+        self.run(tape, Some(max_steps))
+    }
+
+    fn run(
+        &self,
+        tape: &mut VecTape<S>,
+        max_steps: Option<u64>,
+    ) -> Result<State, ExecutionError<S>> {
+        match &self.small {
+            // Symbols that only appear on the tape need the wildcards
+            Some(small)
+                if tape
+                    .inner
+                    .iter()
+                    .all(|symbol| self.symbols.contains(symbol)) =>
+            {
+                self.run_small(small, tape, max_steps)
+            }
+            _ => self.run_general(tape, max_steps, |_| {}),
+        }
+    }
+
+    /// Run the program with the full table, calling `visit` with the dense
+    /// number of every state a step starts in.
+    fn run_general(
+        &self,
+        tape: &mut VecTape<S>,
+        max_steps: Option<u64>,
+        mut visit: impl FnMut(usize),
+    ) -> Result<State, ExecutionError<S>> {
+        // Symbols that only appear on the tape are numbered after the ones of
+        // the program and can only be matched by wildcards
+        let width = self.symbols.len();
+        let mut cells = NumberedTape::new(&self.symbols, tape);
+        let mut state = self.initial as usize;
+        let mut steps = 0;
+
+        let result = loop {
+            if max_steps == Some(steps) {
+                break Err(ExecutionError::StepLimitExceeded(steps));
+            }
+
+            visit(state);
+
+            let read = cells.cells[cells.position];
+            let transition = match usize::try_from(read) {
+                Ok(read) if read < width => self.table[state * width + read],
+                _ => self.wildcards[state],
+            };
+            let Some(transition) = transition else {
+                break Err(ExecutionError::UndefinedBehavior(
+                    self.states[state],
+                    cells.symbols[read as usize],
+                ));
+            };
+
+            cells.cells[cells.position] = if transition.write == WRITE_BACK {
+                read
+            } else {
+                transition.write
+            };
+
+            match transition.action {
+                Direction::Left => {
+                    if cells.position == 0 {
+                        cells.grow_left();
+                    }
+
+                    cells.position -= 1;
+                    cells.start = cells.start.min(cells.position);
+                }
+                Direction::Right => {
+                    cells.position += 1;
+
+                    if cells.position == cells.cells.len() {
+                        cells.cells.push(0);
+                    }
+                }
+                Direction::Stay => {}
+            }
+
+            steps += 1;
+            state = transition.to as usize;
+
+            match self.kinds[state] {
+                Kind::Running => {}
+                Kind::Final => break Ok(self.states[state]),
+                Kind::Error => break Err(ExecutionError::ReachedError(self.states[state])),
+                Kind::Undefined => unreachable!("the full table has no sentinel state"),
+            }
+        };
+
+        cells.store(tape);
+        result
+    }
+
+    /// Run the program with its [`SmallTable`], on a tape that only holds
+    /// symbols of the program.
+    fn run_small(
+        &self,
+        small: &SmallTable,
+        tape: &mut VecTape<S>,
+        max_steps: Option<u64>,
+    ) -> Result<State, ExecutionError<S>> {
+        let NumberedTape {
+            mut cells,
+            symbols,
+            mut position,
+            mut start,
+            mut padding,
+        } = NumberedTape::new(&self.symbols, tape);
+        let max_steps = max_steps.unwrap_or(u64::MAX);
+        let mut state = self.initial as usize;
+        let mut steps = 0;
+
+        let result = loop {
+            if steps == max_steps {
+                break Err(ExecutionError::StepLimitExceeded(steps));
+            }
+
+            let read = cells[position];
+            let transition = small.table[state * SMALL_WIDTH + read as usize];
+
+            cells[position] = u32::from(transition.write);
+            position = position.wrapping_add_signed(isize::from(transition.offset));
+            start = start.min(position);
+
+            // Either moved left off the start or right off the end
+            if position >= cells.len() {
+                if position == usize::MAX {
+                    let grow = cells.len();
+                    cells.splice(0..0, std::iter::repeat_n(0, grow));
+                    position = grow - 1;
+                    start = position;
+                    padding += grow;
+                } else {
+                    cells.push(0);
+                }
+            }
+
+            let from = state;
+            state = transition.to as usize;
+
+            match small.kinds[state] {
+                Kind::Running => steps += 1,
+                Kind::Final => break Ok(self.states[state]),
+                Kind::Error => break Err(ExecutionError::ReachedError(self.states[state])),
+                Kind::Undefined => {
+                    break Err(ExecutionError::UndefinedBehavior(
+                        self.states[from],
+                        symbols[read as usize],
+                    ))
+                }
+            }
+        };
+
+        let cells = NumberedTape {
+            cells,
+            symbols,
+            position,
+            start,
+            padding,
+        };
+        cells.store(tape);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::{busy_beaver, trace::Trace, *};
+
+    fn assert_same(program: &Program, input: &str) {
+        let mut machine = TuringMachine::from_tape(VecTape::from_str(input).unwrap());
+        let expected = machine.execute_bounded(program, 10_000);
+
+        let mut tape = VecTape::from_str(input).unwrap();
+        let result = program.compile().execute_bounded(&mut tape, 10_000);
+
+        assert_eq!(result, expected);
+        assert_eq!(&tape, machine.tape());
+        assert_eq!(tape.position(), machine.tape().position());
+    }
+
+    #[test]
+    fn test_compiled_matches_machine() {
+        for (code, inputs) in [
+            (
+                include_str!("../examples/next_integer.tng"),
+                ["_1011_", "_111_", "_0_"],
+            ),
+            (include_str!("../examples/copy.tng"), ["_1_", "_111_", "_"]),
+            (
+                include_str!("../examples/palindrome.tng"),
+                ["_101_", "_10_", "_1_"],
+            ),
+            ("+0\n-1\n0,0,*,x,l\n0,1,_,=,n", ["_ab_", "_a_", "_"]),
+            ("+0\n-1\n0,0,*,=,r\n0,1,_,y,l", ["_abc_", "_zz_", "_"]),
+            ("+0\n-1\n!2\n0,0,a,b,r\n0,2,c,c,l", ["_aa_", "_ac_", "_b_"]),
+            ("+0\n-1\n0,0,a,b,l\n0,1,_,c,n", ["_aaa_", "_a_", "_"]),
+        ] {
+            let program = Program::from_str(code).unwrap();
+
+            for input in inputs {
+                assert_same(&program, input);
+            }
+        }
+
+        for champion in [busy_beaver::BB2, busy_beaver::BB3, busy_beaver::BB4] {
+            assert_same(&champion.program(), "_");
+        }
+    }
+
+    #[test]
+    fn test_compiled_errors() {
+        let program = Program::from_str("+0\n-1\n0,0,_,_,r").unwrap();
+        let mut tape = VecTape::from_str("_").unwrap();
+        assert_eq!(
+            program.compile().execute_bounded(&mut tape, 5),
+            Err(ExecutionError::StepLimitExceeded(5))
+        );
+
+        let program = Program::from_str("+0\n0,0,_,_,r").unwrap();
+        let mut tape = VecTape::from_str("__a").unwrap();
+        assert_eq!(
+            program.compile().execute(&mut tape),
+            Err(ExecutionError::UndefinedBehavior(
+                program.initial_state(),
+                Segment::Symbol('a')
+            ))
+        );
+    }
+
+    #[test]
+    fn test_compile_profiled() {
+        let program = Program::from_str(include_str!("../examples/copy.tng")).unwrap();
+        let sample = VecTape::from_str("_1111_").unwrap();
+        let compiled = program.compile_profiled(&sample, 1000);
+
+        // States are numbered by how many steps of the sample run start in
+        // them
+        let mut trace = Trace::new();
+        TuringMachine::from_tape(sample)
+            .execute_traced(&program, &mut trace)
+            .unwrap();
+        let visits: Vec<usize> = compiled
+            .states
+            .iter()
+            .map(|state| {
+                trace
+                    .steps()
+                    .iter()
+                    .filter(|step| step.from == *state)
+                    .count()
+            })
+            .collect();
+        assert!(visits.windows(2).all(|pair| pair[0] >= pair[1]));
+        assert!(visits[0] > visits[visits.len() - 1]);
+
+        for input in ["_1_", "_111_", "_"] {
+            let mut machine = TuringMachine::from_tape(VecTape::from_str(input).unwrap());
+            let expected = machine.execute(&program);
+            let mut tape = VecTape::from_str(input).unwrap();
+
+            assert_eq!(compiled.execute(&mut tape), expected);
+            assert_eq!(&tape, machine.tape());
+        }
+    }
+}
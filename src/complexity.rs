@@ -0,0 +1,236 @@
+//! Empirical estimation of the running time of programs.
+//!
+//! [`analyze`] runs a program on inputs of increasing size, records the
+//! number of steps it takes on each of them and fits the worst case of every
+//! size to a polynomial and an exponential function to classify how the
+//! running time grows:
+//!
+//! ```
+//! use std::str::FromStr;
+//!
+//! use turing::{
+//!     complexity::{self, Growth},
+//!     Program, VecTape,
+//! };
+//!
+//! let program = Program::from_str(include_str!("../examples/copy.tng")).unwrap();
+//! let input = |size: usize| [VecTape::from_str(&format!("_{}_", "1".repeat(size))).unwrap()];
+//!
+//! let complexity = complexity::analyze(&program, 1..=16, input, 100_000).unwrap();
+//! assert_eq!(complexity.growth, Some(Growth::Quadratic));
+//! assert_eq!(complexity.samples.len(), 16);
+//! ```
+//!
+//! The classification is only as good as the inputs: a handful of small
+//! sizes cannot tell apart growth rates that only differ for large inputs,
+//! and random inputs may miss the worst case entirely.
+use std::fmt;
+
+use crate::{tape::Symbol, ExecutionError, Program, TuringMachine, VecTape};
+
+/// How the running time of a program grows with the size of its input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Growth {
+    /// The program takes the same number of steps on inputs of every size.
+    Constant,
+    /// The running time grows proportionally to the size of the input.
+    Linear,
+    /// The running time grows with the square of the size of the input.
+    Quadratic,
+    /// The running time grows with a higher power of the size of the input,
+    /// the degree of the polynomial.
+    Polynomial(u32),
+    /// The running time grows exponentially with the size of the input.
+    Exponential,
+}
+
+impl fmt::Display for Growth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Constant => f.write_str("constant"),
+            Self::Linear => f.write_str("linear"),
+            Self::Quadratic => f.write_str("quadratic"),
+            Self::Polynomial(degree) => write!(f, "polynomial of degree {degree}"),
+            Self::Exponential => f.write_str("exponential"),
+        }
+    }
+}
+
+/// The number of steps a program took on the inputs of one size.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sample {
+    /// The size the inputs were generated for.
+    pub size: usize,
+    /// The number of steps taken on every input, in the order they were
+    /// generated.
+    pub steps: Vec<u64>,
+}
+
+impl Sample {
+    /// Returns the largest number of steps taken on an input of this size,
+    /// or 0 if no input was generated for it.
+    #[must_use]
+    pub fn worst(&self) -> u64 {
+        self.steps.iter().copied().max().unwrap_or_default()
+    }
+}
+
+/// The result of [`analyze`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Complexity {
+    /// The steps taken for every size, in the order the sizes were given.
+    pub samples: Vec<Sample>,
+    /// The growth rate that fits the worst cases best, or [`None`] if there
+    /// were fewer than two different sizes above 0 with inputs.
+    pub growth: Option<Growth>,
+}
+
+/// Run `program` on the inputs `inputs` generates for each of `sizes` for
+/// at most `max_steps` steps each and estimate how its running time grows.
+///
+/// # Errors
+///
+/// This function will error if the program fails or does not halt within
+/// `max_steps` steps on any of the inputs.
+pub fn analyze<S, I>(
+    program: &Program<S>,
+    sizes: impl IntoIterator<Item = usize>,
+    mut inputs: impl FnMut(usize) -> I,
+    max_steps: u64,
+) -> Result<Complexity, ExecutionError<S>>
+where
+    S: Symbol,
+    I: IntoIterator<Item = VecTape<S>>,
+{
+    let mut samples = Vec::new();
+
+    for size in sizes {
+        let mut steps = Vec::new();
+
+        for tape in inputs(size) {
+            let mut machine = TuringMachine::from_tape(tape);
+            let mut run = machine.run(program);
+            for result in run
+                .by_ref()
+                .take(usize::try_from(max_steps).unwrap_or(usize::MAX))
+            {
+                result?;
+            }
+
+            if run.state().is_some() {
+                return Err(ExecutionError::StepLimitExceeded(max_steps));
+            }
+            steps.push(run.steps());
+        }
+
+        samples.push(Sample { size, steps });
+    }
+
+    let growth = fit(&samples);
+    Ok(Complexity { samples, growth })
+}
+
+/// Fit the worst cases of `samples` to `steps = c * size^k` and to
+/// `steps = c * b^size` by least squares on their logarithms and classify
+/// the one with the smaller error.
+fn fit(samples: &[Sample]) -> Option<Growth> {
+    let points: Vec<(f64, f64)> = samples
+        .iter()
+        .filter(|sample| sample.size > 0 && !sample.steps.is_empty())
+        .map(|sample| (float(sample.size as u64), float(sample.worst().max(1)).ln()))
+        .collect();
+
+    let (degree, polynomial) = regression(points.iter().map(|(size, steps)| (size.ln(), *steps)))?;
+    let (_, exponential) = regression(points.iter().copied())?;
+
+    if exponential < polynomial {
+        return Some(Growth::Exponential);
+    }
+
+    // Rounded to the nearest degree, so that n log n counts as linear
+    let degree = degree.round();
+    Some(if degree < 0.5 {
+        Growth::Constant
+    } else if degree < 1.5 {
+        Growth::Linear
+    } else if degree < 2.5 {
+        Growth::Quadratic
+    } else {
+        let degree = (3..u32::MAX).find(|other| f64::from(*other) >= degree);
+        Growth::Polynomial(degree.unwrap_or(u32::MAX))
+    })
+}
+
+/// Returns the slope and the sum of squared residuals of the line that fits
+/// `points` best, or [`None`] if there are fewer than two different x
+/// coordinates.
+fn regression(points: impl Iterator<Item = (f64, f64)> + Clone) -> Option<(f64, f64)> {
+    let count = float(points.clone().count() as u64);
+    let mean_x = points.clone().map(|(x, _)| x).sum::<f64>() / count;
+    let mean_y = points.clone().map(|(_, y)| y).sum::<f64>() / count;
+
+    let variance: f64 = points.clone().map(|(x, _)| (x - mean_x).powi(2)).sum();
+    if variance < f64::EPSILON {
+        return None;
+    }
+
+    let covariance: f64 = points
+        .clone()
+        .map(|(x, y)| (x - mean_x) * (y - mean_y))
+        .sum();
+    let slope = covariance / variance;
+    let residuals = points
+        .map(|(x, y)| (y - mean_y - slope * (x - mean_x)).powi(2))
+        .sum();
+
+    Some((slope, residuals))
+}
+
+/// Converts `value` to a float. Step counts and sizes large enough to lose
+/// precision are far beyond what can be measured, and the fit works on
+/// logarithms anyway.
+#[allow(clippy::cast_precision_loss)]
+fn float(value: u64) -> f64 {
+    value as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::{complexity::*, *};
+
+    #[test]
+    fn test_analyze() {
+        let ones = |size: usize| [VecTape::from_str(&format!("_{}_", "1".repeat(size))).unwrap()];
+
+        // Walks to the end of the input and halts
+        let program = Program::from_str("+0\n-1\n0,0,1,1,r\n0,1,_,_,n").unwrap();
+        let complexity = analyze(&program, [0, 2, 4, 8, 16, 32], ones, 1000).unwrap();
+        assert_eq!(complexity.growth, Some(Growth::Linear));
+        assert_eq!(complexity.samples[0].steps, [1]);
+        assert_eq!(complexity.samples[5].worst(), 33);
+
+        let program = Program::from_str("+0\n-1\n0,1,*,=,n").unwrap();
+        let complexity = analyze(&program, 1..10, ones, 1000).unwrap();
+        assert_eq!(complexity.growth, Some(Growth::Constant));
+
+        // Counts down to zero from a binary number of ones
+        let program = Program::from_str(
+            "+0\n-3\n0,0,0,0,r\n0,0,1,1,r\n0,1,_,_,l\n1,1,0,1,l\n1,0,1,0,r\n1,3,_,_,r",
+        )
+        .unwrap();
+        let complexity = analyze(&program, 1..=10, ones, 1_000_000).unwrap();
+        assert_eq!(complexity.growth, Some(Growth::Exponential));
+        assert_eq!(complexity.growth.unwrap().to_string(), "exponential");
+
+        // A single size is not enough to fit anything
+        let complexity = analyze(&program, [4], ones, 1_000_000).unwrap();
+        assert_eq!(complexity.growth, None);
+
+        assert_eq!(
+            analyze(&program, [64], ones, 1000),
+            Err(ExecutionError::StepLimitExceeded(1000))
+        );
+    }
+}
@@ -0,0 +1,210 @@
+//! Combination of [`Program`]s into larger ones.
+use crate::{program::Transition, tape::Symbol, Program, State};
+
+impl<S> Program<S>
+where
+    S: Symbol,
+{
+    /// Returns one more than the highest state number used by the program.
+    pub(crate) fn state_bound(&self) -> usize {
+        let transitions = self
+            .transitions
+            .values()
+            .flat_map(|transition| [transition.from, transition.to]);
+        let wildcards = self
+            .wildcards
+            .values()
+            .flat_map(|wildcard| [wildcard.from, wildcard.to]);
+
+        std::iter::once(self.initial_state)
+            .chain(self.final_states.iter().copied())
+            .chain(self.error_states.iter().copied())
+            .chain(self.entry_points.values().copied())
+            .chain(self.names.keys().copied())
+            .chain(transitions)
+            .chain(wildcards)
+            .map(|state| state.0 + 1)
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Returns the program with every state number increased by `offset`.
+    pub(crate) fn shifted(&self, offset: usize) -> Self {
+        self.map_states(|state| State(state.0 + offset))
+    }
+
+    /// Returns the program with every state replaced by `f(state)`.
+    pub(crate) fn map_states(&self, f: impl Fn(State) -> State) -> Self {
+        Self {
+            initial_state: f(self.initial_state),
+            final_states: self.final_states.iter().map(|state| f(*state)).collect(),
+            rejecting_states: self
+                .rejecting_states
+                .iter()
+                .map(|state| f(*state))
+                .collect(),
+            error_states: self.error_states.iter().map(|state| f(*state)).collect(),
+            transitions: self
+                .transitions
+                .values()
+                .map(|transition| {
+                    let transition = Transition {
+                        from: f(transition.from),
+                        to: f(transition.to),
+                        ..*transition
+                    };
+                    ((transition.from, transition.condition), transition)
+                })
+                .collect(),
+            wildcards: self
+                .wildcards
+                .values()
+                .map(|wildcard| {
+                    let mut wildcard = *wildcard;
+                    wildcard.from = f(wildcard.from);
+                    wildcard.to = f(wildcard.to);
+                    (wildcard.from, wildcard)
+                })
+                .collect(),
+            names: self
+                .names
+                .iter()
+                .map(|(state, name)| (f(*state), name.clone()))
+                .collect(),
+            entry_points: self
+                .entry_points
+                .iter()
+                .map(|(name, state)| (name.clone(), f(*state)))
+                .collect(),
+            comments: self
+                .comments
+                .iter()
+                .map(|(target, comment)| (target.map_states(&f), comment.clone()))
+                .collect(),
+        }
+    }
+
+    /// Returns a program that runs this program and continues with `other`
+    /// once it reaches a final state.
+    ///
+    /// The states of `other` are renumbered to follow the states of this
+    /// program, and every transition into a final state of this program leads
+    /// to the initial state of `other` instead. The final states of the
+    /// result are the ones of `other`, while the error states of both
+    /// programs are kept. Names and entry points of `other` that are already
+    /// used by this program are dropped.
+    ///
+    /// Incrementing a binary number twice:
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use turing::{Program, TuringMachine, VecTape};
+    /// let increment = Program::from_str(include_str!("../examples/next_integer.tng")).unwrap();
+    /// let program = increment.then(&increment);
+    ///
+    /// let mut machine = TuringMachine::from_tape(VecTape::from_str("_1011_").unwrap());
+    /// machine.execute(&program).unwrap();
+    /// assert_eq!(machine.output(), "1101");
+    /// ```
+    #[must_use]
+    pub fn then(&self, other: &Self) -> Self {
+        let other = other.shifted(self.state_bound());
+        let link = |state| {
+            if self.final_states.contains(&state) {
+                other.initial_state
+            } else {
+                state
+            }
+        };
+        let first = self.map_states(link);
+
+        let mut names = self.names.clone();
+        names.retain(|state, _| !self.final_states.contains(state));
+        for (state, name) in other.names {
+            if !names.values().any(|other| *other == name) {
+                names.insert(state, name);
+            }
+        }
+        let mut entry_points = other.entry_points;
+        entry_points.extend(first.entry_points);
+        let mut comments = other.comments;
+        comments.extend(first.comments);
+
+        let mut transitions = other.transitions;
+        transitions.extend(
+            first
+                .transitions
+                .into_iter()
+                .filter(|((from, _), _)| !self.final_states.contains(from)),
+        );
+        let mut wildcards = other.wildcards;
+        wildcards.extend(
+            first
+                .wildcards
+                .into_iter()
+                .filter(|(from, _)| !self.final_states.contains(from)),
+        );
+
+        Self {
+            initial_state: first.initial_state,
+            final_states: other.final_states,
+            rejecting_states: other.rejecting_states,
+            error_states: first
+                .error_states
+                .union(&other.error_states)
+                .copied()
+                .collect(),
+            transitions,
+            wildcards,
+            names,
+            entry_points,
+            comments,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::*;
+
+    #[test]
+    fn test_then() {
+        let increment = Program::from_str(include_str!("../examples/next_integer.tng")).unwrap();
+        let program = increment.then(&increment).then(&increment);
+
+        for input in ["_0_", "_1_", "_1011_", "_111_"] {
+            let mut expected = TuringMachine::from_tape(VecTape::from_str(input).unwrap());
+            for _ in 0..3 {
+                expected.execute(&increment).unwrap();
+            }
+
+            let mut machine = TuringMachine::from_tape(VecTape::from_str(input).unwrap());
+            let state = machine.execute(&program).unwrap();
+
+            assert_eq!(
+                machine.tape().to_string().trim_matches('_'),
+                expected.tape().to_string().trim_matches('_')
+            );
+            assert!(program.final_states.contains(&state));
+        }
+
+        assert_eq!(program.final_states.len(), 1);
+        assert_eq!(program.state_bound(), 3 * increment.state_bound());
+    }
+
+    #[test]
+    fn test_then_names() {
+        let first = Program::from_str("+start\n-done\nstart,done,_,1,r").unwrap();
+        let second = Program::from_str("+start\n-end\nstart,end,_,2,n").unwrap();
+        let program = first.then(&second);
+
+        assert_eq!(program.initial_state(), first.initial_state());
+        assert_eq!(program.state("done"), None);
+        assert!(program.state("end").is_some());
+        assert_eq!(
+            program.to_string(),
+            "+start\n-end\nstart,2,_,1,r\n2,end,_,2,n\n"
+        );
+    }
+}
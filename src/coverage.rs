@@ -0,0 +1,223 @@
+//! Transition coverage of [`Program`]s.
+//!
+//! A [`Coverage`] counts how often each transition of a program was taken in
+//! one or more recorded [`Trace`]s, so the transitions that none of the test
+//! tapes exercise can be found:
+//!
+//! ```
+//! use std::str::FromStr;
+//!
+//! use turing::{coverage::Coverage, trace::Trace, Program, TuringMachine, VecTape};
+//!
+//! let program = Program::from_str(include_str!("../examples/next_integer.tng")).unwrap();
+//! let mut coverage = Coverage::new(&program);
+//!
+//! for input in ["_10_", "_11_"] {
+//!     let mut machine = TuringMachine::from_tape(VecTape::from_str(input).unwrap());
+//!     let mut trace = Trace::new();
+//!     machine.execute_traced(&program, &mut trace).unwrap();
+//!     coverage.record(&trace);
+//! }
+//!
+//! assert!(coverage.taken() < coverage.len());
+//! println!("{coverage}");
+//! ```
+use std::{collections::HashMap, fmt};
+
+use crate::{tape::Symbol, trace::Trace, Program, Segment, State};
+
+/// A transition of a [`Program`], identified by what it is taken for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Branch<S = Segment> {
+    /// The transition of a state for a specific segment.
+    Transition {
+        /// The "from" state of the transition.
+        from: State,
+        /// The condition of the transition.
+        condition: S,
+    },
+    /// The wildcard transition of a state.
+    Wildcard(State),
+}
+
+/// How often each transition of a [`Program`] was taken.
+#[derive(Debug, Clone)]
+pub struct Coverage<'p, S = Segment> {
+    program: &'p Program<S>,
+    hits: HashMap<Branch<S>, u64>,
+}
+
+impl<'p, S> Coverage<'p, S>
+where
+    S: Symbol,
+{
+    /// Create a coverage of `program` in which no transition was taken yet.
+    #[must_use]
+    pub fn new(program: &'p Program<S>) -> Self {
+        let hits = program
+            .transitions
+            .values()
+            .map(|transition| Branch::Transition {
+                from: transition.from,
+                condition: transition.condition,
+            })
+            .chain(program.wildcards.keys().copied().map(Branch::Wildcard))
+            .map(|branch| (branch, 0))
+            .collect();
+
+        Self { program, hits }
+    }
+
+    /// Count the transitions taken in `trace`, which has to be a trace of
+    /// the same program.
+    ///
+    /// Steps that match no transition of the program are ignored.
+    pub fn record(&mut self, trace: &Trace<S>) {
+        for step in trace.steps() {
+            let branch = if self
+                .program
+                .transitions
+                .contains_key(&(step.from, step.read))
+            {
+                Branch::Transition {
+                    from: step.from,
+                    condition: step.read,
+                }
+            } else {
+                Branch::Wildcard(step.from)
+            };
+
+            if let Some(hits) = self.hits.get_mut(&branch) {
+                *hits += 1;
+            }
+        }
+    }
+
+    /// Returns how often a transition was taken, or [`None`] if the program
+    /// has no such transition.
+    #[must_use]
+    pub fn hits(&self, branch: &Branch<S>) -> Option<u64> {
+        self.hits.get(branch).copied()
+    }
+
+    /// Returns the number of transitions of the program.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.hits.len()
+    }
+
+    /// Whether the program has no transitions.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.hits.is_empty()
+    }
+
+    /// Returns the number of transitions that were taken at least once.
+    #[must_use]
+    pub fn taken(&self) -> usize {
+        self.hits.values().filter(|hits| **hits != 0).count()
+    }
+
+    /// Returns the transitions that were never taken, sorted by state.
+    #[must_use]
+    pub fn untaken(&self) -> Vec<Branch<S>> {
+        let mut untaken: Vec<_> = self
+            .hits
+            .iter()
+            .filter(|(_, hits)| **hits == 0)
+            .map(|(branch, _)| *branch)
+            .collect();
+        untaken.sort();
+
+        untaken
+    }
+}
+
+impl<S> fmt::Display for Branch<S>
+where
+    S: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Transition { from, condition } => write!(f, "{from},{condition}"),
+            Self::Wildcard(from) => write!(f, "{from},*"),
+        }
+    }
+}
+
+impl<S> fmt::Display for Coverage<'_, S>
+where
+    S: Symbol + fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} of {} transitions taken", self.taken(), self.len())?;
+
+        for branch in self.untaken() {
+            writeln!(f, "untaken: {branch}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::{coverage::*, *};
+
+    fn record(coverage: &mut Coverage<'_>, program: &Program, input: &str) {
+        let mut machine = TuringMachine::from_tape(VecTape::from_str(input).unwrap());
+        let mut trace = Trace::new();
+        let _ = machine.execute_traced(program, &mut trace);
+        coverage.record(&trace);
+    }
+
+    #[test]
+    fn test_coverage() {
+        let program = Program::from_str(include_str!("../examples/next_integer.tng")).unwrap();
+        let mut coverage = Coverage::new(&program);
+        assert_eq!(coverage.taken(), 0);
+        assert_eq!(coverage.untaken().len(), coverage.len());
+
+        record(&mut coverage, &program, "_1_");
+        let untaken = coverage.untaken();
+        assert!(!untaken.is_empty());
+        assert_eq!(coverage.taken() + untaken.len(), coverage.len());
+
+        record(&mut coverage, &program, "_1011_");
+        record(&mut coverage, &program, "_0_");
+        assert!(coverage.untaken().len() < untaken.len());
+
+        let first = Branch::Transition {
+            from: program.initial_state(),
+            condition: Segment::Symbol('1'),
+        };
+        assert_eq!(coverage.hits(&first), Some(4));
+        assert_eq!(
+            coverage.hits(&Branch::Wildcard(program.initial_state())),
+            None
+        );
+    }
+
+    #[test]
+    fn test_coverage_wildcards() {
+        let program = Program::from_str("+0\n-1\n!2\n0,1,1,1,n\n0,2,*,=,r").unwrap();
+        let mut coverage = Coverage::new(&program);
+        let wildcard = Branch::Wildcard(program.initial_state());
+
+        record(&mut coverage, &program, "_0_");
+        assert_eq!(coverage.hits(&wildcard), Some(1));
+        assert_eq!(
+            coverage.untaken(),
+            [Branch::Transition {
+                from: program.initial_state(),
+                condition: Segment::Symbol('1'),
+            }]
+        );
+        assert_eq!(
+            coverage.to_string(),
+            "1 of 2 transitions taken\nuntaken: 0,1\n"
+        );
+    }
+}
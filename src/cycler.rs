@@ -0,0 +1,281 @@
+//! Detection of translated cyclers.
+//!
+//! Loop detection in [`crate::TuringMachine::execute_with`] only finds
+//! machines that return to the exact same configuration. Many machines that
+//! never halt instead repeat the same pattern while moving along the tape,
+//! like a machine that walks right forever. These translated cyclers are
+//! found with Lin's recurrence criterion: whenever the cursor visits a new
+//! cell beyond everything it visited before, it is compared to earlier
+//! visits of a new cell on the same side in the same state. If the segments
+//! between the cursor and the furthest point it went back to in between are
+//! the same both times, the machine repeats the steps between the two visits
+//! forever, shifted by the distance between them:
+//!
+//! ```
+//! use std::str::FromStr;
+//!
+//! use turing::{Program, VecTape};
+//!
+//! // Walks right, leaving "ab" behind
+//! let program = Program::from_str("+0\n-2\n0,1,_,a,r\n1,0,_,b,r").unwrap();
+//!
+//! let cycle = program
+//!     .translated_cycle(&VecTape::from_str("_").unwrap(), 100)
+//!     .unwrap();
+//! assert_eq!((cycle.period, cycle.shift), (2, 2));
+//! ```
+use crate::{dispatch::StateTable, tape::Symbol, Program, State, Tape, VecTape};
+
+/// A translated cycle found by [`Program::translated_cycle`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TranslatedCycle {
+    /// The state the machine is in at the start of every repetition.
+    pub state: State,
+    /// The number of steps before the first repetition starts.
+    pub steps: u64,
+    /// The number of steps of a repetition.
+    pub period: u64,
+    /// The distance the cursor moves by in every repetition, to the right if
+    /// positive.
+    pub shift: i64,
+}
+
+/// A recurrence found by a [`CycleDetector`], with states of type `Q`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Recurrence<Q> {
+    pub(crate) state: Q,
+    pub(crate) steps: u64,
+    pub(crate) period: u64,
+    pub(crate) shift: i64,
+}
+
+/// A configuration in which the cursor visited a new cell.
+#[derive(Debug, Clone)]
+struct Record<Q, S> {
+    step: u64,
+    state: Q,
+    position: i64,
+    /// The furthest point the cursor went back to before the next record on
+    /// the same side, as a position multiplied by the sign of the side, so
+    /// that smaller is further back.
+    inner: i64,
+    /// The segments from the cursor back to the other end of the used part
+    /// of the tape.
+    cells: Vec<S>,
+}
+
+/// The records on one side of the tape.
+#[derive(Debug, Clone)]
+struct Side<Q, S> {
+    /// 1 for the right side, -1 for the left side.
+    sign: i64,
+    /// The furthest position the cursor visited on this side.
+    edge: i64,
+    /// The position of the last segment of the input on this side. Only
+    /// cells beyond it are blank until they are visited.
+    input: i64,
+    records: Vec<Record<Q, S>>,
+}
+
+/// Finds translated cycles in a run by following its steps.
+///
+/// States and symbols are generic, so that the detector can follow the
+/// interpreter as well as the compact machines of [`crate::search`].
+#[derive(Debug, Clone)]
+pub(crate) struct CycleDetector<Q, S> {
+    blank: S,
+    left: Side<Q, S>,
+    right: Side<Q, S>,
+}
+
+impl<Q, S> CycleDetector<Q, S>
+where
+    Q: Copy + Eq,
+    S: Copy + Eq,
+{
+    /// Create a detector for a run that starts at `position` on a tape whose
+    /// non-blank segments lie between `leftmost` and `rightmost`.
+    pub(crate) fn new(blank: S, position: i64, leftmost: i64, rightmost: i64) -> Self {
+        Self {
+            blank,
+            left: Side {
+                sign: -1,
+                edge: position,
+                input: leftmost,
+                records: Vec::new(),
+            },
+            right: Side {
+                sign: 1,
+                edge: position,
+                input: rightmost,
+                records: Vec::new(),
+            },
+        }
+    }
+
+    /// Follow the run to the configuration after `step` steps, in which the
+    /// machine is in `state` at `position`, with the tape given by `get`.
+    pub(crate) fn observe(
+        &mut self,
+        step: u64,
+        state: Q,
+        position: i64,
+        get: impl Fn(i64) -> S,
+    ) -> Option<Recurrence<Q>> {
+        for side in [&mut self.left, &mut self.right] {
+            if let Some(record) = side.records.last_mut() {
+                record.inner = record.inner.min(side.sign * position);
+            }
+        }
+
+        let (side, other) = if position > self.right.edge {
+            (&mut self.right, &self.left)
+        } else if position < self.left.edge {
+            (&mut self.left, &self.right)
+        } else {
+            return None;
+        };
+        side.edge = position;
+
+        // Cells beyond the input are only blank once the cursor left it
+        if side.sign * position <= side.sign * side.input {
+            return None;
+        }
+
+        let sign = side.sign;
+        let mut inner = sign * position;
+        for record in side.records.iter().rev() {
+            inner = inner.min(record.inner);
+            if record.state != state {
+                continue;
+            }
+
+            let depth = sign * record.position - inner;
+            let same = (0..=depth).all(|offset| {
+                let before = usize::try_from(offset)
+                    .ok()
+                    .and_then(|offset| record.cells.get(offset))
+                    .copied()
+                    .unwrap_or(self.blank);
+                before == get(position - sign * offset)
+            });
+
+            if same {
+                return Some(Recurrence {
+                    state,
+                    steps: record.step,
+                    period: step - record.step,
+                    shift: position - record.position,
+                });
+            }
+        }
+
+        // The other end of the part of the tape that may not be blank
+        let end = -sign * (-sign * other.edge).max(-sign * other.input);
+        let cells = (0..=sign * (position - end))
+            .map(|offset| get(position - sign * offset))
+            .collect();
+        side.records.push(Record {
+            step,
+            state,
+            position,
+            inner: sign * position,
+            cells,
+        });
+
+        None
+    }
+}
+
+impl<S> Program<S>
+where
+    S: Symbol,
+{
+    /// Run the program on a copy of `tape` for at most `max_steps` steps and
+    /// return the translated cycle it enters, if it enters one within the
+    /// limit.
+    ///
+    /// Returns [`None`] if the program halts, fails or does not enter a
+    /// translated cycle within the limit. Machines that return to the exact
+    /// same configuration without moving along the tape are not reported,
+    /// [`crate::ExecutionConfig::detect_loops`] finds those.
+    #[must_use]
+    pub fn translated_cycle(&self, tape: &VecTape<S>, max_steps: u64) -> Option<TranslatedCycle> {
+        let (table, mut index) = StateTable::new(self, self.initial_state);
+        let mut tape = tape.clone();
+
+        let start = tape.position();
+        let leftmost = start - i64::try_from(tape.position).ok()?;
+        let rightmost = leftmost + i64::try_from(tape.inner.len()).ok()? - 1;
+        let mut detector = CycleDetector::new(S::BLANK, start, leftmost, rightmost);
+
+        for step in 1..=max_steps {
+            let (to, transition) = table.transition(index, *tape.current())?;
+            tape.put(transition.write);
+            tape.seek(tape.position() + transition.action.offset());
+            index = to;
+
+            if table.is_halting(index) {
+                return None;
+            }
+
+            if let Some(recurrence) =
+                detector.observe(step, index, tape.position(), |position| tape.get(position))
+            {
+                return Some(TranslatedCycle {
+                    state: table.state(recurrence.state),
+                    steps: recurrence.steps,
+                    period: recurrence.period,
+                    shift: recurrence.shift,
+                });
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::{cycler::*, *};
+
+    #[test]
+    fn test_translated_cycle() {
+        // A binary counter that counts up forever grows, but never repeats
+        let counter = Program::from_str(
+            "+0\n-2\n0,0,0,0,r\n0,0,1,1,r\n0,1,_,_,l\n1,1,1,0,l\n1,0,0,1,r\n1,0,_,1,r",
+        )
+        .unwrap();
+        let tape = VecTape::from_str("_0_").unwrap();
+        assert_eq!(counter.translated_cycle(&tape, 10_000), None);
+
+        // Walks back and forth over a block of ones that grows to the left
+        let bouncer =
+            Program::from_str("+0\n-2\n0,1,_,1,r\n0,0,1,1,l\n1,1,1,1,r\n1,0,_,_,l").unwrap();
+        assert_eq!(bouncer.translated_cycle(&tape, 10_000), None);
+
+        // Moves right over the input and then keeps writing to the right
+        let walker = Program::from_str("+0\n-2\n0,0,1,1,r\n0,1,_,0,r\n1,0,_,1,r").unwrap();
+        let cycle = walker
+            .translated_cycle(&VecTape::from_str("_111").unwrap(), 100)
+            .unwrap();
+        assert_eq!((cycle.steps, cycle.period, cycle.shift), (3, 2, 2));
+
+        // The input is in the way the first time
+        let walker = Program::from_str("+0\n-2\n0,0,*,=,l").unwrap();
+        let cycle = walker
+            .translated_cycle(&VecTape::from_str("_1_1").unwrap(), 100)
+            .unwrap();
+        assert_eq!(
+            cycle,
+            TranslatedCycle {
+                state: State(0),
+                steps: 2,
+                period: 1,
+                shift: -1,
+            }
+        );
+    }
+}
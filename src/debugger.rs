@@ -0,0 +1,322 @@
+//! Interactive debugging of [`Program`]s.
+//!
+//! A [`Debugger`] runs a program on a [`TuringMachine`] and pauses whenever a
+//! breakpoint or watchpoint is hit, returning control to the caller.
+use std::collections::HashSet;
+
+use crate::{tape::Tape, ExecutionError, Program, Segment, State, TuringMachine};
+
+/// The reason a [`Debugger`] paused execution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stop<S = Segment> {
+    /// The program reached a final state and cannot continue.
+    Halted(State),
+    /// The machine entered a state with a breakpoint.
+    StateBreakpoint(State),
+    /// The cursor moved to a position with a breakpoint.
+    PositionBreakpoint(i64),
+    /// A watched position was overwritten with a different segment.
+    Watchpoint {
+        /// The position that was written to.
+        position: i64,
+        /// The segment before the write.
+        old: S,
+        /// The segment after the write.
+        new: S,
+    },
+}
+
+/// A wrapper around a [`TuringMachine`] that supports breakpoints and
+/// watchpoints.
+///
+/// Positions are relative to the cursor position at the start of the run.
+#[derive(Debug)]
+pub struct Debugger<'p, T>
+where
+    T: Tape,
+{
+    machine: TuringMachine<T>,
+    program: &'p Program<T::Symbol>,
+    state: State,
+    origin: i64,
+    steps: u64,
+    finished: Option<Result<State, ExecutionError<T::Symbol>>>,
+    state_breakpoints: HashSet<State>,
+    position_breakpoints: HashSet<i64>,
+    watchpoints: HashSet<i64>,
+}
+
+impl<'p, T> Debugger<'p, T>
+where
+    T: Tape,
+{
+    /// Create a new [`Debugger`] that runs `program` on `machine`, starting
+    /// in the initial state of the program.
+    #[must_use]
+    pub fn new(machine: TuringMachine<T>, program: &'p Program<T::Symbol>) -> Self {
+        Self {
+            origin: machine.tape().position(),
+            machine,
+            program,
+            state: program.initial_state,
+            steps: 0,
+            finished: None,
+            state_breakpoints: HashSet::new(),
+            position_breakpoints: HashSet::new(),
+            watchpoints: HashSet::new(),
+        }
+    }
+
+    /// Returns a reference to the debugged machine.
+    #[must_use]
+    pub fn machine(&self) -> &TuringMachine<T> {
+        &self.machine
+    }
+
+    /// Consumes the debugger and returns the debugged machine.
+    #[must_use]
+    pub fn into_machine(self) -> TuringMachine<T> {
+        self.machine
+    }
+
+    /// Returns the state the machine is in.
+    #[must_use]
+    pub fn state(&self) -> State {
+        self.state
+    }
+
+    /// Returns the cursor position.
+    #[must_use]
+    pub fn position(&self) -> i64 {
+        self.machine.tape().position() - self.origin
+    }
+
+    /// Returns the number of steps performed so far.
+    #[must_use]
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    /// Pause whenever the machine enters `state`.
+    pub fn add_breakpoint(&mut self, state: State) {
+        self.state_breakpoints.insert(state);
+    }
+
+    /// Remove a breakpoint added with [`Debugger::add_breakpoint`].
+    pub fn remove_breakpoint(&mut self, state: State) {
+        self.state_breakpoints.remove(&state);
+    }
+
+    /// Pause whenever the cursor moves to `position`.
+    pub fn add_position_breakpoint(&mut self, position: i64) {
+        self.position_breakpoints.insert(position);
+    }
+
+    /// Remove a breakpoint added with [`Debugger::add_position_breakpoint`].
+    pub fn remove_position_breakpoint(&mut self, position: i64) {
+        self.position_breakpoints.remove(&position);
+    }
+
+    /// Pause whenever the segment at `position` is overwritten with a
+    /// different segment.
+    pub fn add_watchpoint(&mut self, position: i64) {
+        self.watchpoints.insert(position);
+    }
+
+    /// Remove a watchpoint added with [`Debugger::add_watchpoint`].
+    pub fn remove_watchpoint(&mut self, position: i64) {
+        self.watchpoints.remove(&position);
+    }
+
+    /// Remember the last `limit` steps, so they can be undone with
+    /// [`Debugger::step_back`].
+    pub fn set_history_limit(&mut self, limit: usize) {
+        self.machine.set_history_limit(limit);
+    }
+
+    /// Undo the last step that is still in the history.
+    ///
+    /// Returns whether a step was undone. Breakpoints and watchpoints are not
+    /// checked while stepping backwards.
+    pub fn step_back(&mut self) -> bool {
+        let Some(state) = self.machine.step_back() else {
+            return false;
+        };
+
+        self.state = state;
+        self.steps = self.steps.saturating_sub(1);
+        self.finished = None;
+
+        true
+    }
+
+    /// Perform a single step and report whether it hit a breakpoint or
+    /// watchpoint.
+    ///
+    /// If multiple apply, halting takes precedence over watchpoints, which
+    /// take precedence over state breakpoints, which take precedence over
+    /// position breakpoints. Once the program has halted or failed, the same
+    /// result is returned again without performing any step.
+    ///
+    /// # Errors
+    ///
+    /// This method will error if the machine encounters undefined behaviour
+    /// or reaches an error state.
+    pub fn step(&mut self) -> Result<Option<Stop<T::Symbol>>, ExecutionError<T::Symbol>> {
+        match &self.finished {
+            Some(Ok(state)) => return Ok(Some(Stop::Halted(*state))),
+            Some(Err(e)) => return Err(e.clone()),
+            None => {}
+        }
+
+        let old = *self.machine.tape().current();
+        let written_at = self.position();
+        let outcome = match self.machine.step(self.program, self.state) {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                self.finished = Some(Err(e.clone()));
+                return Err(e);
+            }
+        };
+
+        self.steps += 1;
+        self.state = outcome.state;
+
+        if outcome.halted {
+            self.finished = Some(Ok(outcome.state));
+            return Ok(Some(Stop::Halted(outcome.state)));
+        }
+
+        if old != outcome.written && self.watchpoints.contains(&written_at) {
+            return Ok(Some(Stop::Watchpoint {
+                position: written_at,
+                old,
+                new: outcome.written,
+            }));
+        }
+
+        if self.state_breakpoints.contains(&self.state) {
+            return Ok(Some(Stop::StateBreakpoint(self.state)));
+        }
+
+        let position = self.position();
+        if self.position_breakpoints.contains(&position) {
+            return Ok(Some(Stop::PositionBreakpoint(position)));
+        }
+
+        Ok(None)
+    }
+
+    /// Run until a breakpoint or watchpoint is hit or the program halts.
+    ///
+    /// # Errors
+    ///
+    /// This method will error if the machine encounters undefined behaviour
+    /// or reaches an error state.
+    pub fn resume(&mut self) -> Result<Stop<T::Symbol>, ExecutionError<T::Symbol>> {
+        loop {
+            if let Some(stop) = self.step()? {
+                return Ok(stop);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::{debugger::*, *};
+
+    fn debugger<'p>(program: &'p Program, tape: &str) -> Debugger<'p, VecTape> {
+        let machine = TuringMachine::from_tape(VecTape::from_str(tape).unwrap());
+        Debugger::new(machine, program)
+    }
+
+    #[test]
+    fn test_state_breakpoint() {
+        let program = Program::from_str(include_str!("../examples/copy.tng")).unwrap();
+        let mut debugger = debugger(&program, "_11_");
+        let state = State::from_str("2").unwrap();
+        debugger.add_breakpoint(state);
+
+        assert_eq!(debugger.resume(), Ok(Stop::StateBreakpoint(state)));
+        assert_eq!(debugger.state(), state);
+        assert_eq!(debugger.position(), 3);
+
+        debugger.remove_breakpoint(state);
+        let Ok(Stop::Halted(_)) = debugger.resume() else {
+            panic!("expected program to halt");
+        };
+        assert!(matches!(debugger.resume(), Ok(Stop::Halted(_))));
+        assert_eq!(debugger.machine().tape().to_string(), "_11_11");
+    }
+
+    #[test]
+    fn test_position_breakpoint_and_watchpoint() {
+        let program = Program::from_str(include_str!("../examples/next_integer.tng")).unwrap();
+        let mut debugger = debugger(&program, "_101_");
+        debugger.add_position_breakpoint(3);
+        debugger.add_watchpoint(2);
+
+        assert_eq!(debugger.resume(), Ok(Stop::PositionBreakpoint(3)));
+        assert_eq!(debugger.steps(), 3);
+
+        assert_eq!(
+            debugger.resume(),
+            Ok(Stop::Watchpoint {
+                position: 2,
+                old: Segment::Symbol('1'),
+                new: Segment::Symbol('0'),
+            })
+        );
+        assert_eq!(debugger.position(), 1);
+    }
+
+    #[test]
+    fn test_step_back() {
+        let program = Program::from_str(include_str!("../examples/next_integer.tng")).unwrap();
+        let mut debugger = debugger(&program, "_101_");
+        debugger.set_history_limit(2);
+
+        let Ok(Stop::Halted(_)) = debugger.resume() else {
+            panic!("expected program to halt");
+        };
+        let steps = debugger.steps();
+
+        assert!(debugger.step_back());
+        assert!(debugger.step_back());
+        assert!(!debugger.step_back());
+        assert_eq!(debugger.steps(), steps - 2);
+        assert_eq!(debugger.machine().tape().to_string(), "_110_");
+
+        assert_eq!(debugger.step(), Ok(None));
+        assert!(matches!(debugger.step(), Ok(Some(Stop::Halted(_)))));
+        assert_eq!(debugger.steps(), steps);
+    }
+
+    #[test]
+    fn test_circular_tape_position() {
+        let program = Program::from_str("+0\n-1\n0,0,_,a,r\n0,1,a,a,n").unwrap();
+        let machine = TuringMachine::from_tape(CircularTape::from_str("___").unwrap());
+        let mut debugger = Debugger::new(machine, &program);
+        debugger.set_history_limit(3);
+        debugger.add_position_breakpoint(0);
+
+        assert_eq!(debugger.resume(), Ok(Stop::PositionBreakpoint(0)));
+        assert_eq!(debugger.steps(), 3);
+
+        assert!(debugger.step_back());
+        assert_eq!(debugger.position(), 2);
+        assert_eq!(debugger.step(), Ok(Some(Stop::PositionBreakpoint(0))));
+    }
+
+    #[test]
+    fn test_debugger_error() {
+        let program = Program::from_str(include_str!("../examples/palindrome.tng")).unwrap();
+        let mut debugger = debugger(&program, "_10_");
+
+        let error = debugger.resume().unwrap_err();
+        assert_eq!(debugger.step(), Err(error));
+    }
+}
@@ -0,0 +1,234 @@
+//! Comparison of two [`Program`]s line by line.
+//!
+//! [`Program::diff`] lists the transitions and state markers that were added,
+//! removed or changed between two programs. States are compared by number,
+//! so the programs should use the same numbering, and names and comments are
+//! ignored. The diff is printed like a unified diff of the programs in the
+//! text format:
+//!
+//! ```
+//! use std::str::FromStr;
+//!
+//! use turing::Program;
+//!
+//! let submission = Program::from_str("+0\n-1\n0,0,a,a,r\n0,1,_,_,n").unwrap();
+//! let solution = Program::from_str("+0\n-1\n!2\n0,0,a,b,r\n0,2,*,=,n").unwrap();
+//!
+//! assert_eq!(
+//!     submission.diff(&solution).to_string(),
+//!     "+ !2\n- 0,1,_,_,n\n- 0,0,a,a,r\n+ 0,0,a,b,r\n+ 0,2,*,=,n\n"
+//! );
+//! ```
+use std::{collections::BTreeSet, fmt};
+
+use crate::{
+    program::{Transition, Wildcard},
+    tape::Symbol,
+    Program, Segment, State, Verdict,
+};
+
+/// A line of a program that can differ between two programs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Item<S = Segment> {
+    /// The initial state.
+    Initial(State),
+    /// A final state and the verdict it halts with.
+    Final(State, Verdict),
+    /// An error state.
+    Error(State),
+    /// A transition.
+    Transition(Transition<S>),
+    /// A wildcard transition.
+    Wildcard(Wildcard<S>),
+}
+
+impl<S> fmt::Display for Item<S>
+where
+    S: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Initial(state) => write!(f, "+{state}"),
+            Self::Final(state, Verdict::Accepted) => write!(f, "-{state}"),
+            Self::Final(state, Verdict::Rejected) => write!(f, "-R{state}"),
+            Self::Error(state) => write!(f, "!{state}"),
+            Self::Transition(transition) => write!(
+                f,
+                "{},{},{},{},{}",
+                transition.from,
+                transition.to,
+                transition.condition,
+                transition.write,
+                transition.action
+            ),
+            Self::Wildcard(wildcard) => {
+                write!(f, "{},{},*,", wildcard.from, wildcard.to)?;
+                match &wildcard.write {
+                    Some(write) => write!(f, "{write},{}", wildcard.action),
+                    None => write!(f, "=,{}", wildcard.action),
+                }
+            }
+        }
+    }
+}
+
+/// A difference between two programs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change<S = Segment> {
+    /// The line only occurs in the other program.
+    Added(Item<S>),
+    /// The line only occurs in this program.
+    Removed(Item<S>),
+    /// The line was replaced: the initial state differs, a final state halts
+    /// with another verdict or a transition for the same state and condition
+    /// behaves differently.
+    Changed(Item<S>, Item<S>),
+}
+
+/// The differences between two programs, as returned by [`Program::diff`].
+///
+/// Changes are sorted like the lines of a printed program: the initial
+/// state, the final states, the error states, the transitions and then the
+/// wildcards.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProgramDiff<S = Segment> {
+    changes: Vec<Change<S>>,
+}
+
+impl<S> ProgramDiff<S> {
+    /// Returns the changes between the programs.
+    #[must_use]
+    pub fn changes(&self) -> &[Change<S>] {
+        &self.changes
+    }
+
+    /// Whether the programs have the same lines, apart from names and
+    /// comments.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+impl<S> fmt::Display for ProgramDiff<S>
+where
+    S: fmt::Display,
+{
+    /// Writes every removed line prefixed with "- " and every added line
+    /// prefixed with "+ ". Changed lines are written as a removal followed by
+    /// an addition.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for change in &self.changes {
+            match change {
+                Change::Added(item) => writeln!(f, "+ {item}")?,
+                Change::Removed(item) => writeln!(f, "- {item}")?,
+                Change::Changed(old, new) => writeln!(f, "- {old}\n+ {new}")?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<S> Program<S>
+where
+    S: Symbol,
+{
+    /// Returns the lines that differ between this program and `other`.
+    #[must_use]
+    pub fn diff(&self, other: &Self) -> ProgramDiff<S> {
+        let mut changes = Vec::new();
+        let mut compare = |old: Option<Item<S>>, new: Option<Item<S>>| match (old, new) {
+            (Some(old), Some(new)) if old != new => changes.push(Change::Changed(old, new)),
+            (Some(old), None) => changes.push(Change::Removed(old)),
+            (None, Some(new)) => changes.push(Change::Added(new)),
+            _ => {}
+        };
+
+        compare(
+            Some(Item::Initial(self.initial_state)),
+            Some(Item::Initial(other.initial_state)),
+        );
+
+        let finals: BTreeSet<_> = self.final_states.union(&other.final_states).collect();
+        for state in finals {
+            let item = |program: &Self| {
+                program
+                    .verdict(*state)
+                    .map(|verdict| Item::Final(*state, verdict))
+            };
+            compare(item(self), item(other));
+        }
+
+        let errors: BTreeSet<_> = self.error_states.union(&other.error_states).collect();
+        for state in errors {
+            let item = |program: &Self| {
+                program
+                    .error_states
+                    .contains(state)
+                    .then_some(Item::Error(*state))
+            };
+            compare(item(self), item(other));
+        }
+
+        let keys: BTreeSet<_> = self
+            .transitions
+            .keys()
+            .chain(other.transitions.keys())
+            .collect();
+        for key in keys {
+            let item = |program: &Self| program.transitions.get(key).copied().map(Item::Transition);
+            compare(item(self), item(other));
+        }
+
+        let states: BTreeSet<_> = self
+            .wildcards
+            .keys()
+            .chain(other.wildcards.keys())
+            .collect();
+        for state in states {
+            let item = |program: &Self| program.wildcards.get(state).copied().map(Item::Wildcard);
+            compare(item(self), item(other));
+        }
+
+        ProgramDiff { changes }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::{diff::*, *};
+
+    #[test]
+    fn test_diff() {
+        let program = Program::from_str("+0\n-1\n-2\n!3\n0,1,a,a,r\n0,2,*,=,n").unwrap();
+        assert!(program.diff(&program).is_empty());
+        assert!(program
+            .diff(&Program::from_str(&program.to_string()).unwrap())
+            .is_empty());
+
+        let other = Program::from_str("+1\n-1\n-R2\n0,1,a,a,l\n0,2,*,b,n").unwrap();
+        let state = |s| State::from_str(s).unwrap();
+        assert_eq!(
+            program.diff(&other).changes()[..3],
+            [
+                Change::Changed(Item::Initial(state("0")), Item::Initial(state("1"))),
+                Change::Changed(
+                    Item::Final(state("2"), Verdict::Accepted),
+                    Item::Final(state("2"), Verdict::Rejected)
+                ),
+                Change::Removed(Item::Error(state("3"))),
+            ]
+        );
+        assert_eq!(
+            program.diff(&other).to_string(),
+            "- +0\n+ +1\n- -2\n+ -R2\n- !3\n- 0,1,a,a,r\n+ 0,1,a,a,l\n- 0,2,*,=,n\n+ 0,2,*,b,n\n"
+        );
+        assert_eq!(
+            other.diff(&program).to_string(),
+            "- +1\n+ +0\n- -R2\n+ -2\n+ !3\n- 0,1,a,a,l\n+ 0,1,a,a,r\n- 0,2,*,b,n\n+ 0,2,*,=,n\n"
+        );
+    }
+}
@@ -0,0 +1,150 @@
+//! The transitions of a [`Program`] grouped by state, for the interpreter.
+//!
+//! [`Program`] stores its transitions in a hash map keyed by state and
+//! segment, which is convenient for building and transforming programs but
+//! needs a hash for every step. A [`StateTable`] is built once when an
+//! execution starts: every state gets an entry with its few transitions, and
+//! transitions refer to the entry of the state they lead to by its index.
+use std::collections::{BTreeSet, HashMap};
+
+use crate::{
+    program::{Transition, Wildcard},
+    tape::Symbol,
+    ExecutionError, Program, State,
+};
+
+/// What happens when the machine enters a state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Running,
+    Final,
+    Error,
+}
+
+/// A transition of a [`StateEntry`], with the index of the entry it leads
+/// to.
+#[derive(Debug, Clone, Copy)]
+struct Edge<S> {
+    to: usize,
+    transition: Transition<S>,
+}
+
+/// The wildcard transition of a [`StateEntry`], with the index of the entry
+/// it leads to.
+#[derive(Debug, Clone, Copy)]
+struct WildcardEdge<S> {
+    to: usize,
+    wildcard: Wildcard<S>,
+}
+
+/// A state and the transitions that leave it.
+#[derive(Debug, Clone)]
+struct StateEntry<S> {
+    state: State,
+    kind: Kind,
+    edges: Vec<Edge<S>>,
+    wildcard: Option<WildcardEdge<S>>,
+}
+
+/// The states of a [`Program`] by index, each with its transitions.
+#[derive(Debug, Clone)]
+pub(crate) struct StateTable<S> {
+    entries: Vec<StateEntry<S>>,
+}
+
+impl<S> StateTable<S>
+where
+    S: Symbol,
+{
+    /// Group the transitions of `program`, returning the table and the index
+    /// of `start`, which does not have to be a state of the program.
+    pub(crate) fn new(program: &Program<S>, start: State) -> (Self, usize) {
+        let mut states = BTreeSet::from([program.initial_state, start]);
+        states.extend(program.final_states.iter().chain(&program.error_states));
+        for transition in program.transitions.values() {
+            states.extend([transition.from, transition.to]);
+        }
+        for wildcard in program.wildcards.values() {
+            states.extend([wildcard.from, wildcard.to]);
+        }
+
+        let indices: HashMap<State, usize> = states
+            .iter()
+            .enumerate()
+            .map(|(index, state)| (*state, index))
+            .collect();
+
+        let mut entries: Vec<StateEntry<S>> = states
+            .iter()
+            .map(|state| StateEntry {
+                state: *state,
+                // Final states take precedence over error states
+                kind: if program.final_states.contains(state) {
+                    Kind::Final
+                } else if program.error_states.contains(state) {
+                    Kind::Error
+                } else {
+                    Kind::Running
+                },
+                edges: Vec::new(),
+                wildcard: program.wildcards.get(state).map(|wildcard| WildcardEdge {
+                    to: indices[&wildcard.to],
+                    wildcard: *wildcard,
+                }),
+            })
+            .collect();
+
+        for transition in program.transitions.values() {
+            entries[indices[&transition.from]].edges.push(Edge {
+                to: indices[&transition.to],
+                transition: *transition,
+            });
+        }
+        for entry in &mut entries {
+            entry.edges.sort_by_key(|edge| edge.transition.condition);
+        }
+
+        (Self { entries }, indices[&start])
+    }
+
+    /// Returns the state at `index`.
+    pub(crate) fn state(&self, index: usize) -> State {
+        self.entries[index].state
+    }
+
+    /// Returns the transition of the state at `index` for `read`, together
+    /// with the index of the state it leads to, like
+    /// [`Program::transition`].
+    pub(crate) fn transition(&self, index: usize, read: S) -> Option<(usize, Transition<S>)> {
+        let entry = &self.entries[index];
+
+        if let Some(edge) = entry
+            .edges
+            .iter()
+            .find(|edge| edge.transition.condition == read)
+        {
+            return Some((edge.to, edge.transition));
+        }
+
+        entry
+            .wildcard
+            .map(|edge| (edge.to, edge.wildcard.resolve(read)))
+    }
+
+    /// Whether execution halts after entering the state at `index`, like
+    /// [`Program::halts_in`].
+    pub(crate) fn halts_in(&self, index: usize) -> Result<bool, ExecutionError<S>> {
+        let entry = &self.entries[index];
+
+        match entry.kind {
+            Kind::Running => Ok(false),
+            Kind::Final => Ok(true),
+            Kind::Error => Err(ExecutionError::ReachedError(entry.state)),
+        }
+    }
+
+    /// Whether the state at `index` is a final or error state.
+    pub(crate) fn is_halting(&self, index: usize) -> bool {
+        self.entries[index].kind != Kind::Running
+    }
+}
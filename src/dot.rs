@@ -0,0 +1,140 @@
+//! Export of [`Program`]s as [Graphviz](https://graphviz.org) graphs.
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::{self, Write},
+};
+
+use crate::{tape::Symbol, Program, State};
+
+impl<S> Program<S>
+where
+    S: Symbol + fmt::Display,
+{
+    /// Returns the state graph of the program in the DOT language.
+    ///
+    /// Every state is a node and every transition an edge labelled
+    /// `read/write,move`, where wildcard transitions read `*` and transitions
+    /// that write back what they read write `=`. Transitions between the
+    /// same states share one edge. The initial state has an incoming arrow,
+    /// final states are drawn as double circles and error states as octagons.
+    #[must_use]
+    pub fn to_dot(&self) -> String {
+        let mut states = BTreeSet::from([self.initial_state]);
+        states.extend(self.final_states.iter().copied());
+        states.extend(self.error_states.iter().copied());
+
+        let mut edges: BTreeMap<(State, State), Vec<String>> = BTreeMap::new();
+        let mut transitions: Vec<_> = self.transitions.values().collect();
+        transitions.sort_by_key(|transition| (transition.from, transition.condition));
+        for transition in transitions {
+            edges
+                .entry((transition.from, transition.to))
+                .or_default()
+                .push(format!(
+                    "{}/{},{}",
+                    transition.condition, transition.write, transition.action
+                ));
+        }
+
+        let mut wildcards: Vec<_> = self.wildcards.values().collect();
+        wildcards.sort_by_key(|wildcard| wildcard.from);
+        for wildcard in wildcards {
+            let write = wildcard
+                .write
+                .map_or_else(|| String::from("="), |write| write.to_string());
+            edges
+                .entry((wildcard.from, wildcard.to))
+                .or_default()
+                .push(format!("*/{write},{}", wildcard.action));
+        }
+
+        for (from, to) in edges.keys() {
+            states.insert(*from);
+            states.insert(*to);
+        }
+
+        let mut out = String::from("digraph program {\n    rankdir=LR;\n");
+        let _ = writeln!(out, "    start [shape=point];");
+
+        for state in &states {
+            let shape = if self.final_states.contains(state) {
+                "doublecircle"
+            } else if self.error_states.contains(state) {
+                "octagon"
+            } else {
+                "circle"
+            };
+            let _ = writeln!(out, "    {} [shape={shape}];", self.dot_id(*state));
+        }
+
+        let _ = writeln!(out, "    start -> {};", self.dot_id(self.initial_state));
+
+        for ((from, to), labels) in edges {
+            let _ = writeln!(
+                out,
+                "    {} -> {} [label=\"{}\"];",
+                self.dot_id(from),
+                self.dot_id(to),
+                escape(&labels.join("\n"))
+            );
+        }
+
+        out.push_str("}\n");
+        out
+    }
+
+    /// Returns the quoted node identifier of `state`.
+    fn dot_id(&self, state: State) -> String {
+        let name = self
+            .state_name(state)
+            .map_or_else(|| state.to_string(), str::to_owned);
+        format!("\"{}\"", escape(&name))
+    }
+}
+
+/// Escape `s` for use in a quoted DOT string.
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::*;
+
+    #[test]
+    fn test_to_dot() {
+        let program = Program::from_str(
+            "+scan\n-2\n!3\nscan,scan,1,1,r\nscan,scan,0,0,r\nscan,2,_,_,n\nscan,3,*,\",l",
+        )
+        .unwrap();
+
+        assert_eq!(
+            program.to_dot(),
+            r#"digraph program {
+    rankdir=LR;
+    start [shape=point];
+    "2" [shape=doublecircle];
+    "3" [shape=octagon];
+    "scan" [shape=circle];
+    start -> "scan";
+    "scan" -> "2" [label="_/_,n"];
+    "scan" -> "3" [label="*/\",l"];
+    "scan" -> "scan" [label="0/0,r\n1/1,r"];
+}
+"#
+        );
+    }
+}
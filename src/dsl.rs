@@ -0,0 +1,304 @@
+//! Construction of [`Program`]s from structured instructions.
+//!
+//! Instead of numbering states and listing transitions by hand, a program
+//! can be written as a sequence of [`Instruction`]s with loops and
+//! conditions, and [`compile`] turns it into a program. The instructions
+//! work with any [`Symbol`] type, and the functions that create them accept
+//! anything that converts into it, like a [`char`] for [`Segment`]:
+//!
+//! ```
+//! use std::str::FromStr;
+//!
+//! use turing::{dsl::*, Program, TuringMachine, VecTape};
+//!
+//! // Adds 1 to a binary number
+//! let program: Program = compile([
+//!     move_right_until('_'),
+//!     move_left(),
+//!     while_read('1', [write('0'), move_left()]),
+//!     write('1'),
+//! ]);
+//!
+//! let mut machine = TuringMachine::from_tape(VecTape::from_str("_1011_").unwrap());
+//! machine.execute(&program).unwrap();
+//! assert_eq!(machine.tape().to_string(), "_1100_");
+//! ```
+//!
+//! Every instruction is compiled into transitions that match any segment
+//! with the wildcard "*", so the compiled program does not need to know the
+//! alphabet of its input.
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    program::{Transition, Transitions, Wildcard, Wildcards},
+    tape::Symbol,
+    Direction, Program, Segment, State,
+};
+
+/// A step of a program written with the [`crate::dsl`] module.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Instruction<S = Segment> {
+    /// Write the segment under the cursor.
+    Write(S),
+    /// Move the cursor.
+    Move(Direction),
+    /// Move the cursor in the direction until it is on the segment. Does not
+    /// move if it already is.
+    MoveUntil(Direction, S),
+    /// Run the first instructions if the cursor is on the segment and the
+    /// second ones otherwise.
+    IfRead(S, Vec<Instruction<S>>, Vec<Instruction<S>>),
+    /// Run the instructions as long as the cursor is on the segment when
+    /// they start.
+    WhileRead(S, Vec<Instruction<S>>),
+    /// Halt and accept the input.
+    Accept,
+    /// Halt and reject the input.
+    Reject,
+}
+
+/// Write `segment` under the cursor.
+pub fn write<S>(segment: impl Into<S>) -> Instruction<S> {
+    Instruction::Write(segment.into())
+}
+
+/// Move the cursor to the left by one.
+#[must_use]
+pub fn move_left<S>() -> Instruction<S> {
+    Instruction::Move(Direction::Left)
+}
+
+/// Move the cursor to the right by one.
+#[must_use]
+pub fn move_right<S>() -> Instruction<S> {
+    Instruction::Move(Direction::Right)
+}
+
+/// Move the cursor to the left until it is on `segment`.
+pub fn move_left_until<S>(segment: impl Into<S>) -> Instruction<S> {
+    Instruction::MoveUntil(Direction::Left, segment.into())
+}
+
+/// Move the cursor to the right until it is on `segment`.
+pub fn move_right_until<S>(segment: impl Into<S>) -> Instruction<S> {
+    Instruction::MoveUntil(Direction::Right, segment.into())
+}
+
+/// Run `then` if the cursor is on `segment` and `otherwise` if it is not.
+pub fn if_read<S>(
+    segment: impl Into<S>,
+    then: impl IntoIterator<Item = Instruction<S>>,
+    otherwise: impl IntoIterator<Item = Instruction<S>>,
+) -> Instruction<S> {
+    Instruction::IfRead(
+        segment.into(),
+        then.into_iter().collect(),
+        otherwise.into_iter().collect(),
+    )
+}
+
+/// Run `body` for as long as the cursor is on `segment` before it starts.
+pub fn while_read<S>(
+    segment: impl Into<S>,
+    body: impl IntoIterator<Item = Instruction<S>>,
+) -> Instruction<S> {
+    Instruction::WhileRead(segment.into(), body.into_iter().collect())
+}
+
+/// Halt and accept the input.
+#[must_use]
+pub fn accept<S>() -> Instruction<S> {
+    Instruction::Accept
+}
+
+/// Halt and reject the input.
+#[must_use]
+pub fn reject<S>() -> Instruction<S> {
+    Instruction::Reject
+}
+
+/// Compile `instructions` into a program that accepts its input after the
+/// last one.
+///
+/// The states are numbered in the order they are first reached with
+/// [`Program::renumber`], so the initial state is 0.
+pub fn compile<S>(instructions: impl IntoIterator<Item = Instruction<S>>) -> Program<S>
+where
+    S: Symbol,
+{
+    let instructions: Vec<_> = instructions.into_iter().collect();
+    let mut compiler = Compiler {
+        next: 2,
+        transitions: Transitions::new(),
+        wildcards: Wildcards::new(),
+    };
+    let initial_state = compiler.block(&instructions, ACCEPT);
+
+    // The rejecting state is only kept if it is used
+    let rejects = initial_state == REJECT
+        || compiler
+            .transitions
+            .values()
+            .any(|transition| transition.to == REJECT)
+        || compiler
+            .wildcards
+            .values()
+            .any(|wildcard| wildcard.to == REJECT);
+    let final_states = if rejects {
+        HashSet::from([ACCEPT, REJECT])
+    } else {
+        HashSet::from([ACCEPT])
+    };
+
+    let mut program = Program::from_parts(
+        initial_state,
+        final_states,
+        HashSet::new(),
+        compiler.transitions,
+        compiler.wildcards,
+        HashMap::new(),
+    );
+    if rejects {
+        program.rejecting_states.insert(REJECT);
+    }
+
+    program.renumber().0
+}
+
+/// The state compiled programs accept their input in.
+const ACCEPT: State = State(0);
+
+/// The state compiled programs reject their input in.
+const REJECT: State = State(1);
+
+/// The transitions compiled so far and the next free state.
+struct Compiler<S> {
+    next: usize,
+    transitions: Transitions<S>,
+    wildcards: Wildcards<S>,
+}
+
+impl<S> Compiler<S>
+where
+    S: Symbol,
+{
+    /// Returns a state that is not used yet.
+    fn state(&mut self) -> State {
+        self.next += 1;
+        State(self.next - 1)
+    }
+
+    /// Add a wildcard from `from` to `to`.
+    fn wildcard(&mut self, from: State, to: State, write: Option<S>, action: Direction) {
+        let wildcard = Wildcard {
+            from,
+            to,
+            write,
+            action,
+        };
+        self.wildcards.insert(from, wildcard);
+    }
+
+    /// Add a transition from `from` to `to` that reads `condition`, leaves it
+    /// unchanged and keeps the cursor in place.
+    fn branch(&mut self, from: State, to: State, condition: S) {
+        let transition = Transition {
+            from,
+            to,
+            condition,
+            write: condition,
+            action: Direction::Stay,
+        };
+        self.transitions.insert((from, condition), transition);
+    }
+
+    /// Compile `instructions` to continue in `next` after the last one and
+    /// return the state to start them in.
+    fn block(&mut self, instructions: &[Instruction<S>], next: State) -> State {
+        instructions.iter().rev().fold(next, |next, instruction| {
+            self.instruction(instruction, next)
+        })
+    }
+
+    /// Compile `instruction` to continue in `next` and return the state to
+    /// start it in.
+    fn instruction(&mut self, instruction: &Instruction<S>, next: State) -> State {
+        let start = match instruction {
+            Instruction::Accept => return ACCEPT,
+            Instruction::Reject => return REJECT,
+            _ => self.state(),
+        };
+
+        match instruction {
+            Instruction::Write(segment) => {
+                self.wildcard(start, next, Some(*segment), Direction::Stay);
+            }
+            Instruction::Move(action) => self.wildcard(start, next, None, *action),
+            Instruction::MoveUntil(action, segment) => {
+                self.branch(start, next, *segment);
+                self.wildcard(start, start, None, *action);
+            }
+            Instruction::IfRead(segment, then, otherwise) => {
+                let then = self.block(then, next);
+                let otherwise = self.block(otherwise, next);
+                self.branch(start, then, *segment);
+                self.wildcard(start, otherwise, None, Direction::Stay);
+            }
+            Instruction::WhileRead(segment, body) => {
+                let body = self.block(body, start);
+                self.branch(start, body, *segment);
+                self.wildcard(start, next, None, Direction::Stay);
+            }
+            Instruction::Accept | Instruction::Reject => {}
+        }
+
+        start
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::{dsl::*, *};
+
+    #[test]
+    fn test_compile() {
+        // Accepts inputs that start and end with the same symbol
+        let program: Program = compile([
+            if_read(
+                'a',
+                [
+                    move_right_until('_'),
+                    move_left(),
+                    if_read('a', [], [reject()]),
+                ],
+                [
+                    move_right_until('_'),
+                    move_left(),
+                    if_read('b', [], [reject()]),
+                ],
+            ),
+            accept(),
+        ]);
+
+        for (input, verdict) in [
+            ("_aba_", Verdict::Accepted),
+            ("_abb_", Verdict::Rejected),
+            ("_bab_", Verdict::Accepted),
+            ("_ba_", Verdict::Rejected),
+        ] {
+            let mut machine = TuringMachine::from_tape(VecTape::from_str(input).unwrap());
+            assert_eq!(machine.decide(&program).unwrap().1, verdict, "{input}");
+        }
+
+        let program: Program = compile([]);
+        assert_eq!(program.to_tng_string(), "+0\n-0\n");
+
+        let program: Program = compile([write('x'), move_right(), write('y')]);
+        assert_eq!(
+            program.to_tng_string(),
+            "+0\n-3\n0,1,*,x,n\n1,2,*,=,r\n2,3,*,y,n\n"
+        );
+    }
+}
@@ -0,0 +1,219 @@
+//! Modification of [`Program`]s in place.
+//!
+//! Editors and optimizers can change a program without printing and parsing
+//! it again. The methods check the program stays valid and leave it
+//! unchanged if it would not:
+//!
+//! ```
+//! use std::str::FromStr;
+//!
+//! use turing::{Direction, InvalidProgram, Program, Segment, State, Verdict};
+//!
+//! let mut program = Program::from_str("+0\n-1\n0,1,_,_,n").unwrap();
+//! let state = |s| State::from_str(s).unwrap();
+//!
+//! program
+//!     .add_transition(
+//!         state("0"),
+//!         state("0"),
+//!         Segment::Symbol('a'),
+//!         Segment::Symbol('b'),
+//!         Direction::Right,
+//!     )
+//!     .unwrap();
+//! assert_eq!(
+//!     program.add_transition(
+//!         state("0"),
+//!         state("1"),
+//!         Segment::Empty,
+//!         Segment::Empty,
+//!         Direction::Left
+//!     ),
+//!     Err(InvalidProgram::DuplicateTransition)
+//! );
+//!
+//! program.rename_state(state("0"), "start").unwrap();
+//! assert_eq!(
+//!     program.to_string(),
+//!     "+start\n-1\nstart,1,_,_,n\nstart,start,a,b,r\n"
+//! );
+//! ```
+use crate::{
+    error::InvalidProgram,
+    program::{is_state_name, CommentTarget, Transition},
+    tape::Symbol,
+    Direction, Program, State, Verdict,
+};
+
+impl<S> Program<S>
+where
+    S: Symbol,
+{
+    /// Add a transition from `from` to `to` that matches `condition`, writes
+    /// `write` and then performs `action`.
+    ///
+    /// # Errors
+    ///
+    /// This method will error with [`InvalidProgram::DuplicateTransition`] if
+    /// `from` already has a transition for `condition`.
+    pub fn add_transition(
+        &mut self,
+        from: State,
+        to: State,
+        condition: S,
+        write: S,
+        action: Direction,
+    ) -> Result<(), InvalidProgram> {
+        if self.transitions.contains_key(&(from, condition)) {
+            return Err(InvalidProgram::DuplicateTransition);
+        }
+
+        self.transitions.insert(
+            (from, condition),
+            Transition {
+                from,
+                to,
+                condition,
+                write,
+                action,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Remove the transition of `from` for `condition` together with its
+    /// comment and return it, or [`None`] if there is no such transition.
+    ///
+    /// Wildcards are not removed, so `from` may still have a transition for
+    /// `condition` afterwards.
+    pub fn remove_transition(&mut self, from: State, condition: S) -> Option<Transition<S>> {
+        self.comments
+            .remove(&CommentTarget::Transition(from, condition));
+        self.transitions.remove(&(from, condition))
+    }
+
+    /// Set the state execution starts in and return the previous one.
+    ///
+    /// # Errors
+    ///
+    /// This method will error with [`InvalidProgram::ConflictingStateRole`]
+    /// if `state` is an error state.
+    pub fn set_initial(&mut self, state: State) -> Result<State, InvalidProgram> {
+        if self.error_states.contains(&state) {
+            return Err(InvalidProgram::ConflictingStateRole(state));
+        }
+
+        Ok(std::mem::replace(&mut self.initial_state, state))
+    }
+
+    /// Make `state` a final state that accepts or rejects its input as given
+    /// by `verdict`, replacing its previous verdict.
+    ///
+    /// Returns whether `state` was not a final state before.
+    ///
+    /// # Errors
+    ///
+    /// This method will error with [`InvalidProgram::ConflictingStateRole`]
+    /// if `state` is an error state.
+    pub fn add_final(&mut self, state: State, verdict: Verdict) -> Result<bool, InvalidProgram> {
+        if self.error_states.contains(&state) {
+            return Err(InvalidProgram::ConflictingStateRole(state));
+        }
+
+        match verdict {
+            Verdict::Accepted => self.rejecting_states.remove(&state),
+            Verdict::Rejected => self.rejecting_states.insert(state),
+        };
+
+        Ok(self.final_states.insert(state))
+    }
+
+    /// Give `state` the name `name`, replacing its previous name, if any.
+    ///
+    /// # Errors
+    ///
+    /// This method will error with [`InvalidProgram::InvalidState`] if `name`
+    /// is not a valid state name, another state already has that name or
+    /// `state` does not occur in the program.
+    pub fn rename_state(&mut self, state: State, name: &str) -> Result<(), InvalidProgram> {
+        let taken = self.state(name).is_some_and(|other| other != state);
+        if !is_state_name(name) || taken || !self.states().contains(&state) {
+            return Err(InvalidProgram::InvalidState);
+        }
+
+        self.names.insert(state, name.to_owned());
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::*;
+
+    #[test]
+    fn test_edit() {
+        let mut program = Program::from_str("+0\n-1\n0,1,a,a,r # done\n0,0,*,=,r").unwrap();
+        let state = |s| State::from_str(s).unwrap();
+
+        let removed = program
+            .remove_transition(state("0"), Segment::Symbol('a'))
+            .unwrap();
+        assert_eq!(removed.to(), state("1"));
+        assert!(program
+            .remove_transition(state("0"), Segment::Symbol('a'))
+            .is_none());
+        assert_eq!(program.to_string(), "+0\n-1\n0,0,*,=,r\n");
+
+        // Comments of removed transitions are not restored by adding them back
+        program
+            .add_transition(
+                state("0"),
+                state("2"),
+                Segment::Symbol('a'),
+                Segment::Empty,
+                Direction::Stay,
+            )
+            .unwrap();
+        assert_eq!(program.set_initial(state("2")), Ok(state("0")));
+        assert_eq!(program.add_final(state("2"), Verdict::Rejected), Ok(true));
+        assert_eq!(program.add_final(state("1"), Verdict::Accepted), Ok(false));
+        assert_eq!(program.verdict(state("2")), Some(Verdict::Rejected));
+        assert_eq!(program.to_string(), "+2\n-1\n-R2\n0,2,a,_,n\n0,0,*,=,r\n");
+
+        assert_eq!(program.rename_state(state("1"), "end"), Ok(()));
+        assert_eq!(program.rename_state(state("1"), "end"), Ok(()));
+        for name in ["end", "9", "_x", "", "R1", "A23"] {
+            assert_eq!(
+                program.rename_state(state("0"), name),
+                Err(InvalidProgram::InvalidState)
+            );
+        }
+        assert_eq!(
+            program.rename_state(state("5"), "other"),
+            Err(InvalidProgram::InvalidState)
+        );
+        assert_eq!(program.state("end"), Some(state("1")));
+    }
+
+    #[test]
+    fn test_edit_error_states() {
+        let mut program = Program::from_str("+0\n-1\n!2\n0,1,a,a,r\n0,2,b,b,r").unwrap();
+        let state = |s| State::from_str(s).unwrap();
+
+        assert_eq!(
+            program.set_initial(state("2")),
+            Err(InvalidProgram::ConflictingStateRole(state("2")))
+        );
+        for verdict in [Verdict::Accepted, Verdict::Rejected] {
+            assert_eq!(
+                program.add_final(state("2"), verdict),
+                Err(InvalidProgram::ConflictingStateRole(state("2")))
+            );
+        }
+        assert_eq!(program.to_string(), "+0\n-1\n!2\n0,1,a,a,r\n0,2,b,b,r\n");
+    }
+}
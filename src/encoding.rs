@@ -0,0 +1,275 @@
+//! A self-delimiting encoding of structured data on tapes.
+//!
+//! [`encode`] writes a [`Value`] onto a tape and [`decode`] reads it back, so
+//! programs can be run on numbers, byte strings, pairs and lists without a
+//! custom input format for every experiment:
+//!
+//! ```
+//! use turing::encoding::{self, Value};
+//!
+//! let value = Value::from(vec![
+//!     Value::from(5),
+//!     Value::from((Value::from("hi"), Value::from(0))),
+//! ]);
+//! let tape = encoding::encode(&value);
+//!
+//! assert_eq!(tape.to_string(), "_ln101.pb0110100001101001.n.._");
+//! assert_eq!(encoding::decode(&tape), Some(value));
+//! ```
+//!
+//! Every value starts with a letter for its kind:
+//! - A number is "n", its binary digits with the most significant one first and
+//!   no digits for zero, and ".".
+//! - A byte string is "b", the eight binary digits of every byte with the most
+//!   significant one first, and ".".
+//! - A pair is "p" and its two values.
+//! - A list is "l", its values and ".".
+//!
+//! No encoding is the start of another one, so a program can tell where a
+//! value ends without looking further, and values can be put next to each
+//! other on a tape without separators. There are no blanks inside of a value,
+//! so a program can also find the end of its input by looking for the first
+//! blank.
+use std::fmt;
+
+use crate::{Segment, VecTape};
+
+/// A piece of data that can be encoded on a tape.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    /// A natural number.
+    Number(u64),
+    /// A string of bytes.
+    Bytes(Vec<u8>),
+    /// A pair of values.
+    Pair(Box<Value>, Box<Value>),
+    /// A list of values.
+    List(Vec<Value>),
+}
+
+impl From<u64> for Value {
+    fn from(number: u64) -> Self {
+        Self::Number(number)
+    }
+}
+
+impl From<&[u8]> for Value {
+    fn from(bytes: &[u8]) -> Self {
+        Self::Bytes(bytes.to_vec())
+    }
+}
+
+impl From<&str> for Value {
+    fn from(text: &str) -> Self {
+        Self::Bytes(text.as_bytes().to_vec())
+    }
+}
+
+impl From<(Value, Value)> for Value {
+    fn from((first, second): (Value, Value)) -> Self {
+        Self::Pair(Box::new(first), Box::new(second))
+    }
+}
+
+impl From<Vec<Value>> for Value {
+    fn from(values: Vec<Value>) -> Self {
+        Self::List(values)
+    }
+}
+
+impl fmt::Display for Value {
+    /// Writes the encoding of the value, as described in the [module
+    /// documentation](self).
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Number(0) => f.write_str("n."),
+            Self::Number(number) => write!(f, "n{number:b}."),
+            Self::Bytes(bytes) => {
+                f.write_str("b")?;
+                for byte in bytes {
+                    write!(f, "{byte:08b}")?;
+                }
+                f.write_str(".")
+            }
+            Self::Pair(first, second) => write!(f, "p{first}{second}"),
+            Self::List(values) => {
+                f.write_str("l")?;
+                for value in values {
+                    write!(f, "{value}")?;
+                }
+                f.write_str(".")
+            }
+        }
+    }
+}
+
+/// Returns a tape holding the encoding of `value`, surrounded by blanks, with
+/// the cursor on its first segment.
+#[must_use]
+pub fn encode(value: &Value) -> VecTape {
+    encode_all(std::slice::from_ref(value))
+}
+
+/// Returns a tape holding the encodings of `values` next to each other,
+/// surrounded by blanks, with the cursor on the first segment of the first
+/// value.
+#[must_use]
+pub fn encode_all(values: &[Value]) -> VecTape {
+    let mut inner = vec![Segment::Empty];
+    for value in values {
+        inner.extend(value.to_string().chars().map(Segment::Symbol));
+    }
+    inner.push(Segment::Empty);
+
+    VecTape::new(inner, 1)
+}
+
+/// Returns the value encoded between the blanks on a tape, or [`None`] if it
+/// holds anything else.
+#[must_use]
+pub fn decode(tape: &VecTape) -> Option<Value> {
+    match decode_all(tape)?.as_slice() {
+        [value] => Some(value.clone()),
+        _ => None,
+    }
+}
+
+/// Returns the values encoded next to each other between the blanks on a
+/// tape, or [`None`] if it holds anything else.
+#[must_use]
+pub fn decode_all(tape: &VecTape) -> Option<Vec<Value>> {
+    let mut segments = tape.trim();
+    let mut values = Vec::new();
+
+    while !segments.is_empty() {
+        let (value, rest) = decode_prefix(segments)?;
+        values.push(value);
+        segments = rest;
+    }
+
+    Some(values)
+}
+
+/// Returns the value encoded at the start of `segments` and the segments
+/// after it, or [`None`] if they do not start with an encoded value.
+#[must_use]
+pub fn decode_prefix(segments: &[Segment]) -> Option<(Value, &[Segment])> {
+    let (kind, mut rest) = segments.split_first()?;
+
+    let value = match kind {
+        Segment::Symbol('n') => {
+            let (digits, after) = digits(rest)?;
+            rest = after;
+            if digits.starts_with('0') {
+                return None;
+            }
+            Value::Number(match digits.as_str() {
+                "" => 0,
+                digits => u64::from_str_radix(digits, 2).ok()?,
+            })
+        }
+        Segment::Symbol('b') => {
+            let (digits, after) = digits(rest)?;
+            rest = after;
+            if digits.len() % 8 != 0 {
+                return None;
+            }
+            let bytes = (0..digits.len())
+                .step_by(8)
+                .map(|start| u8::from_str_radix(&digits[start..start + 8], 2).ok())
+                .collect::<Option<_>>()?;
+            Value::Bytes(bytes)
+        }
+        Segment::Symbol('p') => {
+            let (first, after) = decode_prefix(rest)?;
+            let (second, after) = decode_prefix(after)?;
+            rest = after;
+            Value::from((first, second))
+        }
+        Segment::Symbol('l') => {
+            let mut values = Vec::new();
+            while rest.first()? != &Segment::Symbol('.') {
+                let (value, after) = decode_prefix(rest)?;
+                values.push(value);
+                rest = after;
+            }
+            rest = &rest[1..];
+            Value::List(values)
+        }
+        _ => return None,
+    };
+
+    Some((value, rest))
+}
+
+/// Returns the binary digits at the start of `segments` and the segments
+/// after the "." that ends them, or [`None`] if there is anything else
+/// before it.
+fn digits(segments: &[Segment]) -> Option<(String, &[Segment])> {
+    let end = segments
+        .iter()
+        .position(|segment| *segment == Segment::Symbol('.'))?;
+    let digits = segments[..end]
+        .iter()
+        .map(|segment| match segment {
+            Segment::Symbol(digit @ ('0' | '1')) => Some(*digit),
+            _ => None,
+        })
+        .collect::<Option<_>>()?;
+
+    Some((digits, &segments[end + 1..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::{encoding::*, *};
+
+    #[test]
+    fn test_encoding() {
+        let values = [
+            Value::from(0),
+            Value::from(u64::MAX),
+            Value::from(""),
+            Value::from(&[0, 255][..]),
+            Value::from(Vec::new()),
+            Value::from((Value::from(vec![Value::from(1)]), Value::from("ab"))),
+        ];
+
+        for value in &values {
+            assert_eq!(decode(&encode(value)).as_ref(), Some(value));
+        }
+        assert_eq!(
+            decode_all(&encode_all(&values)).as_deref(),
+            Some(&values[..])
+        );
+        assert_eq!(decode_all(&encode_all(&[])), Some(Vec::new()));
+
+        assert_eq!(
+            Value::from((Value::from(2), Value::from(vec![Value::from("a")]))).to_string(),
+            "pn10.lb01100001.."
+        );
+
+        for invalid in [
+            "_n12._",
+            "_n01._",
+            "_n1_",
+            "_b0101._",
+            "_pn1._",
+            "_ln1.",
+            "_n1._n1._",
+            "_x_",
+            &format!("_n1{}._", "0".repeat(64)),
+        ] {
+            let tape = VecTape::from_str(invalid).unwrap();
+            assert_eq!(decode(&tape), None, "{invalid}");
+        }
+        let tape = VecTape::from_str("_n1.n1._").unwrap();
+        assert_eq!(decode(&tape), None);
+        assert_eq!(
+            decode_all(&tape),
+            Some(vec![Value::from(1), Value::from(1)])
+        );
+    }
+}
@@ -0,0 +1,167 @@
+//! Testing whether two programs compute the same thing.
+//!
+//! Transformations like [`Program::minimize`] and composition are supposed to
+//! keep what a program computes. [`Program::equivalent_on`] checks this for a
+//! set of inputs by running both programs on each of them and comparing
+//! whether they halt, accept and leave the same segments on the tape:
+//!
+//! ```
+//! use std::str::FromStr;
+//!
+//! use turing::{testing, Program};
+//!
+//! let program = Program::from_str(include_str!("../examples/next_integer.tng")).unwrap();
+//! let (minimized, _) = program.minimize();
+//!
+//! let mut rng = testing::SplitMix64::new(42);
+//! let inputs = (0..100).map(|_| testing::random_tape(&mut rng, &['0', '1'], 8));
+//!
+//! assert_eq!(program.equivalent_on(&minimized, inputs, 1000), None);
+//! ```
+//!
+//! State numbers are not compared, since they usually change with the
+//! transformation. Programs that both run out of steps on an input are
+//! considered to agree on it.
+use crate::{tape::Symbol, ExecutionError, Program, Segment, TuringMachine, VecTape, Verdict};
+
+/// What a program does on an input, as compared by
+/// [`Program::equivalent_on`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Behaviour<S = Segment> {
+    /// The program halted in a final state with the given verdict and
+    /// non-empty segments on the tape.
+    Halts(Verdict, Vec<S>),
+    /// The program encountered undefined behaviour or reached an error state.
+    Fails(ExecutionError<S>),
+    /// The program did not halt within the step limit.
+    Runs,
+}
+
+impl<S> Behaviour<S>
+where
+    S: Symbol,
+{
+    /// Run `program` on `tape` for at most `max_steps` steps.
+    fn of(program: &Program<S>, tape: VecTape<S>, max_steps: u64) -> Self {
+        let mut machine = TuringMachine::from_tape(tape);
+
+        match machine.execute_bounded(program, max_steps) {
+            Ok(state) => Self::Halts(
+                program.verdict(state).unwrap_or(Verdict::Accepted),
+                machine.tape().trim().to_vec(),
+            ),
+            Err(ExecutionError::StepLimitExceeded(_)) => Self::Runs,
+            Err(error) => Self::Fails(error),
+        }
+    }
+
+    /// Whether the behaviour is the same as `other`, ignoring the states in
+    /// errors.
+    fn agrees_with(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Halts(verdict, tape), Self::Halts(other_verdict, other_tape)) => {
+                verdict == other_verdict && tape == other_tape
+            }
+            (Self::Fails(_), Self::Fails(_)) | (Self::Runs, Self::Runs) => true,
+            _ => false,
+        }
+    }
+}
+
+/// An input two programs behave differently on, found by
+/// [`Program::equivalent_on`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Counterexample<S = Segment> {
+    /// The input tape.
+    pub input: VecTape<S>,
+    /// What the program [`Program::equivalent_on`] was called on did.
+    pub left: Behaviour<S>,
+    /// What the other program did.
+    pub right: Behaviour<S>,
+}
+
+impl<S> Program<S>
+where
+    S: Symbol,
+{
+    /// Run this program and `other` on every tape of `inputs` for at most
+    /// `max_steps` steps each and return the first input they behave
+    /// differently on, or [`None`] if they agree on all of them.
+    ///
+    /// `inputs` can be any iterator, so it may generate tapes lazily, for
+    /// example with [`crate::testing::random_tape`], and stops at the first
+    /// counterexample.
+    #[must_use]
+    pub fn equivalent_on<I>(
+        &self,
+        other: &Self,
+        inputs: I,
+        max_steps: u64,
+    ) -> Option<Counterexample<S>>
+    where
+        I: IntoIterator<Item = VecTape<S>>,
+    {
+        inputs.into_iter().find_map(|input| {
+            let left = Behaviour::of(self, input.clone(), max_steps);
+            let right = Behaviour::of(other, input.clone(), max_steps);
+
+            (!left.agrees_with(&right)).then_some(Counterexample { input, left, right })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::{equivalence::*, *};
+
+    #[test]
+    fn test_equivalent_on() {
+        let program = Program::from_str(include_str!("../examples/next_integer.tng")).unwrap();
+        let inputs =
+            ["_0_", "_1_", "_1011_", "_111_"].map(|input| VecTape::from_str(input).unwrap());
+
+        assert_eq!(
+            program.equivalent_on(&program.minimize().0, inputs.clone(), 100),
+            None
+        );
+
+        // Forgets to carry into a new digit on the left
+        let broken = Program::from_str(
+            &include_str!("../examples/next_integer.tng").replace("1,3,_,1,n", "1,3,_,_,n"),
+        )
+        .unwrap();
+        let counterexample = program.equivalent_on(&broken, inputs, 100).unwrap();
+        assert_eq!(counterexample.input, VecTape::from_str("_1_").unwrap());
+        assert_eq!(
+            counterexample.left,
+            Behaviour::Halts(
+                Verdict::Accepted,
+                vec![Segment::Symbol('1'), Segment::Symbol('0')]
+            )
+        );
+        assert_eq!(
+            counterexample.right,
+            Behaviour::Halts(Verdict::Accepted, vec![Segment::Symbol('0')])
+        );
+    }
+
+    #[test]
+    fn test_equivalent_on_errors_and_limits() {
+        let tape = VecTape::from_str("_a").unwrap();
+        let undefined = Program::from_str("+0\n-2").unwrap();
+        let error = Program::from_str("+0\n-2\n!1\n0,1,a,a,n").unwrap();
+        let forever = Program::from_str("+0\n-2\n0,0,*,=,n").unwrap();
+
+        assert_eq!(undefined.equivalent_on(&error, [tape.clone()], 10), None);
+        assert_eq!(forever.equivalent_on(&forever, [tape.clone()], 10), None);
+        assert_eq!(
+            forever
+                .equivalent_on(&error, [tape.clone()], 10)
+                .unwrap()
+                .left,
+            Behaviour::Runs
+        );
+    }
+}
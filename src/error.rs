@@ -1,8 +1,14 @@
-use crate::{program::State, tape::Segment};
+use std::{error::Error, fmt};
+
+use crate::{
+    program::State,
+    tape::{Segment, Symbol, Tape},
+    trace::TraceStep,
+};
 
 /// Error returned when parsing a [`crate::Program`] fails or a check
 /// is violated.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum InvalidProgram {
     /// A transition was missing a from state.
     MissingFrom,
@@ -16,20 +22,651 @@ pub enum InvalidProgram {
     MissingAction,
     /// A state could not be parsed, because it is not a valid integer.
     InvalidState,
-    /// A segment could not be parsed, because it is not "1", "0", "_" or " ".
+    /// A segment could not be parsed, because it is empty.
     InvalidSegment,
+    /// A segment in a transition consisted of more than one character.
+    MultiCharacterSymbol,
     /// An action could not be parsed, because it is not "r", "l", "n" in upper-
-    /// or lowercase.
+    /// or lowercase, optionally followed by "*" for "r" and "l".
     InvalidAction,
     /// The program is missing an initial state.
     MissingInitialState,
+    /// A tape used more distinct symbols than a [`crate::PackedTape`] can
+    /// store.
+    TooManySymbols,
+    /// A [`crate::CircularTape`] was parsed from an empty string, but needs
+    /// at least one segment.
+    EmptyTape,
+    /// A transition has the same "from" state and condition as an earlier
+    /// one, or a state has more than one wildcard transition.
+    DuplicateTransition,
+    /// A seeking transition has the wildcard "*" as its condition, so it would
+    /// never stop.
+    WildcardSeek,
+    /// An include directive is not of the form `@include "path" start end`.
+    InvalidInclude,
+    /// An included program could not be read, or includes are nested too
+    /// deeply, which usually means a program includes itself.
+    IncludeFailed,
+    /// An entry point is not of the form `@entry name state`.
+    InvalidEntry,
+    /// An entry point has the same name as an earlier one.
+    DuplicateEntry,
+    /// A transition of a [`crate::multihead::MultiHeadProgram`] does not have
+    /// a condition, write and action for every head.
+    HeadCount,
+    /// A state is declared as an error state and as the initial state or a
+    /// final state, so it is unclear whether reaching it is an error.
+    ConflictingStateRole(State),
+    /// A line starts with a marker other than "+", "-", "!", "@include",
+    /// "@entry", "#" and "//", which is only rejected by strict parsing.
+    UnknownMarker,
+    /// A transition has more than five fields, which is only rejected by
+    /// strict parsing.
+    TrailingField,
+    /// A line starts or ends with whitespace, contains a tab or has
+    /// whitespace next to a comma, which is only rejected by strict parsing.
+    UnexpectedWhitespace,
+    /// A [`crate::library::ProgramLibrary`] has a line outside of its
+    /// programs that is not a comment, a program without a valid name or
+    /// `@end`, or an `@end` without a program.
+    InvalidLibrary,
+    /// A program in a [`crate::library::ProgramLibrary`] has the same name as
+    /// an earlier one.
+    DuplicateProgram,
+}
+
+impl fmt::Display for InvalidProgram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            Self::MissingFrom => "transition is missing the \"from\" state",
+            Self::MissingTo => "transition is missing the \"to\" state",
+            Self::MissingCondition => "transition is missing the condition",
+            Self::MissingWrite => "transition is missing the segment to write",
+            Self::MissingAction => "transition is missing the movement action",
+            Self::InvalidState => "invalid state, expected a number or a name",
+            Self::InvalidSegment => "invalid segment, expected a single character",
+            Self::MultiCharacterSymbol => "segment consists of more than one character",
+            Self::InvalidAction => {
+                "invalid movement action, expected \"l\", \"r\", \"n\", \"l*\" or \"r*\""
+            }
+            Self::MissingInitialState => "program is missing an initial state",
+            Self::TooManySymbols => "tape uses too many distinct symbols",
+            Self::EmptyTape => "circular tape needs at least one segment",
+            Self::DuplicateTransition => {
+                "transition has the same state and condition as an earlier one"
+            }
+            Self::WildcardSeek => "cannot seek for the wildcard \"*\"",
+            Self::InvalidInclude => {
+                "invalid include, expected \"@include\", a quoted path and two states"
+            }
+            Self::IncludeFailed => "included program could not be read or includes itself",
+            Self::InvalidEntry => "invalid entry point, expected \"@entry\", a name and a state",
+            Self::DuplicateEntry => "entry point has the same name as an earlier one",
+            Self::HeadCount => "transition does not have the same number of heads as the first one",
+            Self::UnknownMarker => "unknown marker at the start of the line",
+            Self::TrailingField => "transition has more than five fields",
+            Self::UnexpectedWhitespace => "unexpected whitespace",
+            Self::InvalidLibrary => {
+                "invalid library, expected programs between \"@program\" and a name, and \"@end\""
+            }
+            Self::DuplicateProgram => "program has the same name as an earlier one",
+            Self::ConflictingStateRole(state) => {
+                return write!(
+                    f,
+                    "state {state} is an error state and the initial or a final state"
+                );
+            }
+        };
+
+        f.write_str(message)
+    }
+}
+
+impl Error for InvalidProgram {}
+
+/// Error returned when parsing a [`crate::Program`] fails, with the location
+/// of the problem in the source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// What went wrong.
+    pub kind: InvalidProgram,
+    /// The line the error occurred on, counting from 1, or 0 if the error is
+    /// about the program as a whole.
+    pub line: usize,
+    /// The column the offending text starts at, in characters and counting
+    /// from 1.
+    pub column: usize,
+    /// The offending text, which is empty if something is missing.
+    pub text: String,
+    /// The full line the error occurred on.
+    pub line_text: String,
+}
+
+impl From<InvalidProgram> for ParseError {
+    fn from(kind: InvalidProgram) -> Self {
+        Self {
+            kind,
+            line: 0,
+            column: 0,
+            text: String::new(),
+            line_text: String::new(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    /// Writes the error, followed by the offending line with the offending
+    /// text underlined:
+    ///
+    /// ```text
+    /// invalid state, expected a number or a name (line 3, column 3)
+    ///   |
+    /// 3 | 0,1a,_,_,n
+    ///   |   ^^
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.line == 0 {
+            return self.kind.fmt(f);
+        }
+
+        writeln!(
+            f,
+            "{} (line {}, column {})",
+            self.kind, self.line, self.column
+        )?;
+        write_snippet(f, self.line, self.column, &self.text, &self.line_text)
+    }
+}
+
+/// Write `line_text`, the text of line `line`, with `text` underlined, which
+/// starts at `column`.
+pub(crate) fn write_snippet(
+    f: &mut fmt::Formatter<'_>,
+    line: usize,
+    column: usize,
+    text: &str,
+    line_text: &str,
+) -> fmt::Result {
+    let number = line.to_string();
+    let gutter = " ".repeat(number.len());
+    let indent = " ".repeat(column - 1);
+    let underline = "^".repeat(text.chars().count().max(1));
+
+    writeln!(f, "{gutter} |")?;
+    writeln!(f, "{number} | {line_text}")?;
+    write!(f, "{gutter} | {indent}{underline}")
+}
+
+impl Error for ParseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.kind)
+    }
 }
 
 /// An error returned by executing a program with a [`crate::TuringMachine`].
-#[derive(Debug)]
-pub enum ExecutionError {
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExecutionError<S = Segment> {
     /// No transition is defined for the current state and segment.
-    UndefinedBehavior(State, Segment),
+    UndefinedBehavior(State, S),
     /// Error state was reached.
     ReachedError(State),
+    /// The program did not halt within the given number of steps.
+    StepLimitExceeded(u64),
+    /// The machine entered the same configuration of state, cursor position
+    /// and tape contents twice, so it will never halt.
+    InfiniteLoopDetected(State),
+    /// The cursor visited more cells than the given tape length limit
+    /// allows.
+    TapeLimitExceeded(u64),
+    /// Execution was stopped with a [`crate::machine::CancellationToken`]
+    /// while the machine was in the given state.
+    Cancelled(State),
+    /// The machine tried to move left of position 0 on a tape that is
+    /// bounded on the left, while in the given state. See
+    /// [`crate::LeftEdge`].
+    FellOffTape(State),
+}
+
+impl<S> fmt::Display for ExecutionError<S>
+where
+    S: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UndefinedBehavior(state, segment) => {
+                write!(f, "no transition for \"{segment}\" in state {state}")
+            }
+            Self::ReachedError(state) => write!(f, "reached error state {state}"),
+            Self::StepLimitExceeded(steps) => write!(f, "did not halt within {steps} steps"),
+            Self::InfiniteLoopDetected(state) => {
+                write!(f, "entered an infinite loop in state {state}")
+            }
+            Self::TapeLimitExceeded(cells) => write!(f, "visited more than {cells} cells"),
+            Self::Cancelled(state) => write!(f, "cancelled in state {state}"),
+            Self::FellOffTape(state) => {
+                write!(f, "moved left of the start of the tape in state {state}")
+            }
+        }
+    }
+}
+
+impl<S> Error for ExecutionError<S> where S: fmt::Debug + fmt::Display {}
+
+/// An [`ExecutionError`] together with where on the tape and after how many
+/// steps it occurred, returned by [`crate::TuringMachine::execute_detailed`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecutionFailure<S = Segment> {
+    /// The error the program ran into.
+    pub error: ExecutionError<S>,
+    /// The number of steps performed before the error.
+    pub steps: u64,
+    /// The position of the cursor when the error occurred.
+    pub position: i64,
+    /// The segments around the cursor when the error occurred, with the one
+    /// under the cursor in the middle.
+    pub window: Vec<S>,
+}
+
+impl<S> ExecutionFailure<S>
+where
+    S: Symbol,
+{
+    /// The number of segments on either side of the cursor in
+    /// [`ExecutionFailure::window`].
+    pub const RADIUS: usize = 5;
+
+    /// Create a failure of `error` after `steps` steps on `tape`.
+    pub fn new(error: ExecutionError<S>, steps: u64, tape: &impl Tape<Symbol = S>) -> Self {
+        let position = tape.position();
+        let radius = i64::try_from(Self::RADIUS).unwrap_or(i64::MAX);
+        let window = (position - radius..=position + radius)
+            .map(|position| tape.get(position))
+            .collect();
+
+        Self {
+            error,
+            steps,
+            position,
+            window,
+        }
+    }
+}
+
+impl<S> fmt::Display for ExecutionFailure<S>
+where
+    S: fmt::Display,
+{
+    /// Writes the error, followed by the segments around the cursor with the
+    /// one under it in brackets:
+    ///
+    /// ```text
+    /// no transition for "1" in state 7 (after 11 steps, at position -3)
+    ///   _ _ _ 0 0 [1] 0 1 _ _ _
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} (after {} step{}, at position {})",
+            self.error,
+            self.steps,
+            if self.steps == 1 { "" } else { "s" },
+            self.position
+        )?;
+        f.write_str(" ")?;
+
+        let middle = self.window.len() / 2;
+        for (index, segment) in self.window.iter().enumerate() {
+            if index == middle {
+                write!(f, " [{segment}]")?;
+            } else {
+                write!(f, " {segment}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<S> Error for ExecutionFailure<S>
+where
+    S: fmt::Debug + fmt::Display + 'static,
+{
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// An error returned by [`crate::TuringMachine::replay`] when a trace does not
+/// match the program.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplayError<S = Segment> {
+    /// The program performed a different step than the one recorded.
+    Diverged {
+        /// The index of the step in the trace.
+        step: usize,
+        /// The recorded step.
+        expected: TraceStep<S>,
+        /// The step the program performed instead.
+        actual: TraceStep<S>,
+    },
+    /// The program could not perform the recorded step.
+    Failed {
+        /// The index of the step in the trace.
+        step: usize,
+        /// The error the program ran into.
+        error: ExecutionError<S>,
+    },
+    /// The program halted, but the trace has more steps.
+    Halted {
+        /// The index of the first step after halting.
+        step: usize,
+    },
+}
+
+impl<S> fmt::Display for ReplayError<S>
+where
+    S: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Diverged {
+                step,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "step {step} does not match the trace: expected {expected}, performed {actual}"
+            ),
+            Self::Failed { step, error } => write!(f, "step {step} of the trace failed: {error}"),
+            Self::Halted { step } => write!(f, "halted before step {step} of the trace"),
+        }
+    }
+}
+
+impl<S> Error for ReplayError<S>
+where
+    S: fmt::Debug + fmt::Display + 'static,
+{
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Failed { error, .. } => Some(error),
+            Self::Diverged { .. } | Self::Halted { .. } => None,
+        }
+    }
+}
+
+/// Error returned by [`crate::Program::remap_symbols`] when the mapping would
+/// change the behaviour of the program.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InvalidRemap<S = Segment> {
+    /// The blank segment is not mapped to the blank segment of the new
+    /// alphabet.
+    BlankChanged,
+    /// Both segments used by the program are mapped to the same segment.
+    Collision(S, S),
+}
+
+/// Error returned by [`crate::Program::invert`] when a program is not
+/// reversible, so its computations cannot be run backwards.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InvalidInversion {
+    /// The program does not have exactly one final state to start the
+    /// inverted program in.
+    FinalStateCount,
+    /// A transition leads back to the initial state, so the inverted program
+    /// could not tell when to halt.
+    EntersInitial,
+    /// Two transitions lead to the state and write the same segment, or a
+    /// wildcard leads to it together with other transitions.
+    AmbiguousWrite(State),
+    /// Transitions that lead to the state move the cursor in different
+    /// directions.
+    AmbiguousMove(State),
+    /// The wildcard of the state writes a fixed segment, so the segment it
+    /// read cannot be restored.
+    Wildcard(State),
+}
+
+impl fmt::Display for InvalidInversion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::FinalStateCount => f.write_str("program does not have exactly one final state"),
+            Self::EntersInitial => f.write_str("transition leads back to the initial state"),
+            Self::AmbiguousWrite(state) => write!(
+                f,
+                "transitions that lead to state {state} do not write distinct segments"
+            ),
+            Self::AmbiguousMove(state) => write!(
+                f,
+                "transitions that lead to state {state} move in different directions"
+            ),
+            Self::Wildcard(state) => {
+                write!(f, "wildcard of state {state} writes a fixed segment")
+            }
+        }
+    }
+}
+
+impl Error for InvalidInversion {}
+
+/// Error returned when constructing one of the models of computation in
+/// [`crate::models`] fails.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InvalidModel {
+    /// A tag system does not delete any symbols in a step.
+    DeletionNumber,
+    /// A tag system uses "_" or " " as a symbol, which stand for blank
+    /// segments on a tape.
+    BlankSymbol,
+    /// A counter machine has more counters than there are letters to
+    /// represent them on a tape.
+    TooManyCounters,
+    /// An instruction of a counter machine uses a counter it does not have.
+    UndefinedCounter(usize),
+    /// An instruction of a counter machine continues with an instruction
+    /// that does not exist.
+    UndefinedInstruction(usize),
+}
+
+impl fmt::Display for InvalidModel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DeletionNumber => f.write_str("tag system does not delete any symbols"),
+            Self::BlankSymbol => f.write_str("tag system uses \"_\" or \" \" as a symbol"),
+            Self::TooManyCounters => f.write_str("counter machine has too many counters"),
+            Self::UndefinedCounter(counter) => {
+                write!(f, "instruction uses undefined counter {counter}")
+            }
+            Self::UndefinedInstruction(instruction) => {
+                write!(f, "instruction {instruction} does not exist")
+            }
+        }
+    }
+}
+
+impl Error for InvalidModel {}
+
+/// Error returned when parsing a [`crate::trace::Trace`] fails.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InvalidTrace {
+    /// A step did not consist of exactly six comma-separated values.
+    FieldCount,
+    /// A position could not be parsed, because it is not a valid integer.
+    InvalidPosition,
+    /// A state, segment or action of a step could not be parsed.
+    InvalidStep(InvalidProgram),
+}
+
+impl fmt::Display for InvalidTrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::FieldCount => f.write_str("step does not consist of six comma-separated values"),
+            Self::InvalidPosition => f.write_str("invalid position, expected an integer"),
+            Self::InvalidStep(error) => write!(f, "invalid step: {error}"),
+        }
+    }
+}
+
+impl Error for InvalidTrace {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::InvalidStep(error) => Some(error),
+            Self::FieldCount | Self::InvalidPosition => None,
+        }
+    }
+}
+
+/// Error returned when parsing a machine in the standard text format of
+/// [`crate::busy_beaver`] fails.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InvalidMachineCode {
+    /// The machine has no states.
+    Empty,
+    /// A state does not have one transition for every symbol, or the machine
+    /// uses more than ten symbols.
+    SymbolCount,
+    /// A transition is neither "---" nor a symbol, a direction and a state.
+    InvalidTransition(String),
+}
+
+impl fmt::Display for InvalidMachineCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => f.write_str("machine has no states"),
+            Self::SymbolCount => f.write_str(
+                "state does not have one transition per symbol, or there are more than ten symbols",
+            ),
+            Self::InvalidTransition(transition) => write!(
+                f,
+                "invalid transition \"{transition}\", expected \"---\" or a symbol, a direction and a state"
+            ),
+        }
+    }
+}
+
+impl Error for InvalidMachineCode {}
+
+/// Error returned when importing a JFLAP file fails.
+#[cfg(feature = "jflap")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InvalidJff {
+    /// The file is not well-formed XML.
+    Xml,
+    /// The file does not describe a single-tape turing machine.
+    UnsupportedType(String),
+    /// A state or transition is missing a required element or attribute.
+    MissingElement(&'static str),
+    /// A state id or transition endpoint is not a valid integer.
+    InvalidState(String),
+    /// A transition reads or writes more than one character.
+    InvalidSymbol(String),
+    /// A transition moves in a direction other than "L", "R" or "S".
+    InvalidMove(String),
+    /// The machine has no initial state.
+    MissingInitialState,
+    /// The machine is non-deterministic.
+    DuplicateTransition,
+}
+
+#[cfg(feature = "jflap")]
+impl fmt::Display for InvalidJff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Xml => f.write_str("file is not well-formed XML"),
+            Self::UnsupportedType(kind) => {
+                write!(
+                    f,
+                    "unsupported machine type \"{kind}\", expected \"turing\""
+                )
+            }
+            Self::MissingElement(name) => write!(f, "state or transition is missing \"{name}\""),
+            Self::InvalidState(id) => write!(f, "invalid state \"{id}\", expected an integer"),
+            Self::InvalidSymbol(symbol) => {
+                write!(
+                    f,
+                    "invalid symbol \"{symbol}\", expected a single character"
+                )
+            }
+            Self::InvalidMove(action) => write!(
+                f,
+                "invalid move \"{action}\", expected \"L\", \"R\" or \"S\""
+            ),
+            Self::MissingInitialState => f.write_str("machine is missing an initial state"),
+            Self::DuplicateTransition => {
+                f.write_str("transition has the same state and symbol as an earlier one")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "jflap")]
+impl Error for InvalidJff {}
+
+/// Error returned when parsing a program in the format of [`crate::json`]
+/// fails.
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InvalidJson {
+    /// The document is not valid JSON or misses a required key, with the
+    /// message of the JSON parser.
+    Syntax(String),
+    /// A state is neither a number nor a valid state name.
+    InvalidState(String),
+    /// A segment is not a single character.
+    InvalidSymbol(String),
+    /// A move is not "l", "r" or "n".
+    InvalidMove(String),
+    /// The name of an entry point is not a valid state name.
+    InvalidEntry(String),
+    /// A state has more than one transition for the same segment.
+    DuplicateTransition,
+}
+
+#[cfg(feature = "serde")]
+impl fmt::Display for InvalidJson {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Syntax(message) => write!(f, "invalid JSON: {message}"),
+            Self::InvalidState(state) => {
+                write!(f, "invalid state \"{state}\", expected a number or a name")
+            }
+            Self::InvalidSymbol(symbol) => {
+                write!(
+                    f,
+                    "invalid segment \"{symbol}\", expected a single character"
+                )
+            }
+            Self::InvalidMove(action) => write!(
+                f,
+                "invalid move \"{action}\", expected \"l\", \"r\" or \"n\""
+            ),
+            Self::InvalidEntry(name) => write!(f, "invalid entry point name \"{name}\""),
+            Self::DuplicateTransition => {
+                f.write_str("transition has the same state and segment as an earlier one")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Error for InvalidJson {}
+
+/// Error returned when importing a turingmachine.io YAML file fails.
+#[cfg(feature = "yaml")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InvalidYaml {
+    /// The file uses YAML syntax outside of the supported subset, with the
+    /// line it occurred on, counting from 1.
+    Syntax(usize),
+    /// A required top-level key is missing.
+    MissingKey(&'static str),
+    /// A symbol is not a single character.
+    InvalidSymbol(String),
+    /// The instruction for a symbol is not understood.
+    InvalidInstruction(String),
+    /// A transition leads to a state that is not in the table.
+    UndefinedState(String),
+    /// A state has more than one instruction for the same symbol.
+    DuplicateTransition,
 }
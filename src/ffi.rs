@@ -0,0 +1,260 @@
+//! A C interface for embedding the simulator into other tools.
+//!
+//! Programs and machines are handed out as opaque pointers that have to be
+//! released with [`turing_program_free`] and [`turing_machine_free`]. All
+//! strings are NUL-terminated UTF-8, and strings returned by the library have
+//! to be released with [`turing_string_free`]. The matching declarations are
+//! in `include/turing.h`, which can be regenerated with `cbindgen`.
+//!
+//! The crate is only built as a Rust library by default. A shared or static
+//! library to link C code against is built with
+//! `cargo rustc --lib --features ffi --crate-type cdylib` or
+//! `--crate-type staticlib`.
+use std::{
+    ffi::{c_char, CStr, CString},
+    ptr,
+    str::FromStr,
+};
+
+use crate::{machine, Program, State, VecTape};
+
+/// A parsed [`Program`] over [`crate::Segment`]s.
+#[derive(Debug)]
+pub struct TuringProgram {
+    program: Program,
+}
+
+/// A [`machine::TuringMachine`] running a program on a [`VecTape`].
+#[derive(Debug)]
+pub struct TuringMachine {
+    machine: machine::TuringMachine<VecTape>,
+    program: Program,
+    state: State,
+    status: TuringStatus,
+}
+
+/// The result of [`turing_machine_step`].
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TuringStatus {
+    /// The step was performed and the program has not halted yet.
+    Running = 0,
+    /// The program reached a final state.
+    Halted = 1,
+    /// The program reached an error state or no transition was defined.
+    Failed = 2,
+    /// A null pointer was passed.
+    InvalidArgument = -1,
+}
+
+/// Reads a NUL-terminated UTF-8 string.
+///
+/// # Safety
+///
+/// `s` has to be null or point to a NUL-terminated string.
+unsafe fn read_str<'a>(s: *const c_char) -> Option<&'a str> {
+    if s.is_null() {
+        return None;
+    }
+
+    unsafe { CStr::from_ptr(s) }.to_str().ok()
+}
+
+/// Parse a program. Returns null if `source` is null, not valid UTF-8 or not
+/// a valid program.
+///
+/// # Safety
+///
+/// `source` has to be null or point to a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn turing_program_parse(source: *const c_char) -> *mut TuringProgram {
+    let Some(source) = (unsafe { read_str(source) }) else {
+        return ptr::null_mut();
+    };
+
+    match Program::from_str(source) {
+        Ok(program) => Box::into_raw(Box::new(TuringProgram { program })),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// Release a program returned by [`turing_program_parse`].
+///
+/// # Safety
+///
+/// `program` has to be null or a pointer returned by
+/// [`turing_program_parse`] that was not released yet.
+#[no_mangle]
+pub unsafe extern "C" fn turing_program_free(program: *mut TuringProgram) {
+    if !program.is_null() {
+        drop(unsafe { Box::from_raw(program) });
+    }
+}
+
+/// Create a machine that runs `program` on `tape`, starting in the initial
+/// state of the program. The program is copied, so it may be released
+/// afterwards. Returns null if an argument is null or the tape is invalid.
+///
+/// # Safety
+///
+/// `program` has to be null or a live pointer returned by
+/// [`turing_program_parse`], and `tape` has to be null or point to a
+/// NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn turing_machine_new(
+    program: *const TuringProgram,
+    tape: *const c_char,
+) -> *mut TuringMachine {
+    let (Some(program), Some(tape)) = (unsafe { program.as_ref() }, unsafe { read_str(tape) })
+    else {
+        return ptr::null_mut();
+    };
+    let Ok(tape) = VecTape::from_str(tape) else {
+        return ptr::null_mut();
+    };
+
+    Box::into_raw(Box::new(TuringMachine {
+        machine: machine::TuringMachine::from_tape(tape),
+        program: program.program.clone(),
+        state: program.program.initial_state(),
+        status: TuringStatus::Running,
+    }))
+}
+
+/// Release a machine returned by [`turing_machine_new`].
+///
+/// # Safety
+///
+/// `machine` has to be null or a pointer returned by [`turing_machine_new`]
+/// that was not released yet.
+#[no_mangle]
+pub unsafe extern "C" fn turing_machine_free(machine: *mut TuringMachine) {
+    if !machine.is_null() {
+        drop(unsafe { Box::from_raw(machine) });
+    }
+}
+
+/// Perform a single step. Once the program has halted or failed, the same
+/// status is returned again without performing any step.
+///
+/// # Safety
+///
+/// `machine` has to be null or a live pointer returned by
+/// [`turing_machine_new`].
+#[no_mangle]
+pub unsafe extern "C" fn turing_machine_step(machine: *mut TuringMachine) -> TuringStatus {
+    let Some(machine) = (unsafe { machine.as_mut() }) else {
+        return TuringStatus::InvalidArgument;
+    };
+
+    if machine.status != TuringStatus::Running {
+        return machine.status;
+    }
+
+    machine.status = match machine.machine.step(&machine.program, machine.state) {
+        Ok(outcome) => {
+            machine.state = outcome.state;
+
+            if outcome.halted {
+                TuringStatus::Halted
+            } else {
+                TuringStatus::Running
+            }
+        }
+        Err(_) => TuringStatus::Failed,
+    };
+
+    machine.status
+}
+
+/// Returns the contents of the tape, or null if `machine` is null. The string
+/// has to be released with [`turing_string_free`].
+///
+/// # Safety
+///
+/// `machine` has to be null or a live pointer returned by
+/// [`turing_machine_new`].
+#[no_mangle]
+pub unsafe extern "C" fn turing_tape_to_string(machine: *const TuringMachine) -> *mut c_char {
+    let Some(machine) = (unsafe { machine.as_ref() }) else {
+        return ptr::null_mut();
+    };
+
+    // Tapes and programs are read from C strings, so they cannot contain NUL
+    CString::new(machine.machine.tape().to_string()).map_or(ptr::null_mut(), CString::into_raw)
+}
+
+/// Release a string returned by [`turing_tape_to_string`].
+///
+/// # Safety
+///
+/// `s` has to be null or a pointer returned by [`turing_tape_to_string`]
+/// that was not released yet.
+#[no_mangle]
+pub unsafe extern "C" fn turing_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(unsafe { CString::from_raw(s) });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::{CStr, CString};
+
+    use crate::ffi::*;
+
+    #[test]
+    fn test_ffi_run() {
+        let source = CString::new(include_str!("../examples/next_integer.tng")).unwrap();
+        let input = CString::new("_1011_").unwrap();
+
+        unsafe {
+            let program = turing_program_parse(source.as_ptr());
+            assert!(!program.is_null());
+            let machine = turing_machine_new(program, input.as_ptr());
+            turing_program_free(program);
+            assert!(!machine.is_null());
+
+            let mut status = TuringStatus::Running;
+            while status == TuringStatus::Running {
+                status = turing_machine_step(machine);
+            }
+            assert_eq!(status, TuringStatus::Halted);
+            assert_eq!(turing_machine_step(machine), TuringStatus::Halted);
+
+            let tape = turing_tape_to_string(machine);
+            assert_eq!(CStr::from_ptr(tape).to_str(), Ok("_1100_"));
+            turing_string_free(tape);
+            turing_machine_free(machine);
+        }
+    }
+
+    #[test]
+    fn test_ffi_errors() {
+        let invalid = CString::new("0,1,_,_,r").unwrap();
+        let source = CString::new(include_str!("../examples/palindrome.tng")).unwrap();
+        let input = CString::new("_10_").unwrap();
+
+        unsafe {
+            assert!(turing_program_parse(ptr::null()).is_null());
+            assert!(turing_program_parse(invalid.as_ptr()).is_null());
+            assert!(turing_machine_new(ptr::null(), input.as_ptr()).is_null());
+            assert!(turing_tape_to_string(ptr::null()).is_null());
+            assert_eq!(
+                turing_machine_step(ptr::null_mut()),
+                TuringStatus::InvalidArgument
+            );
+
+            let program = turing_program_parse(source.as_ptr());
+            let machine = turing_machine_new(program, input.as_ptr());
+            let mut status = TuringStatus::Running;
+            while status == TuringStatus::Running {
+                status = turing_machine_step(machine);
+            }
+            assert_eq!(status, TuringStatus::Failed);
+
+            turing_machine_free(machine);
+            turing_program_free(program);
+        }
+    }
+}
@@ -0,0 +1,565 @@
+//! Turing machines on a two-dimensional tape.
+//!
+//! A [`GridProgram`] is written in the same format as a [`crate::Program`],
+//! except that its actions may also be "u" and "d" to move the cursor up and
+//! down. This is enough for Langton's ant, where the state is the direction
+//! the ant is facing:
+//!
+//! ```
+//! use std::str::FromStr;
+//!
+//! use turing::grid::{GridMachine, GridProgram, GridTape, Tape2D};
+//!
+//! let program = GridProgram::from_str(include_str!("../examples/langtons_ant.tng")).unwrap();
+//! let mut machine = GridMachine::from_tape(GridTape::new());
+//! assert!(machine.execute_bounded(&program, 4).is_err());
+//!
+//! assert_eq!(machine.tape().position(), (0, 0));
+//! assert_eq!(machine.tape().to_string(), "##\n##\n");
+//! ```
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    str::FromStr,
+};
+
+use crate::{
+    error::{ExecutionError, InvalidProgram, ParseError},
+    program::{Line, StateNames},
+    tape::{Segment, Symbol},
+    State,
+};
+
+/// A movement action in a [`GridProgram`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Move2D {
+    /// Move the cursor to the left by one.
+    Left,
+    /// Move the cursor to the right by one.
+    Right,
+    /// Move the cursor up by one.
+    Up,
+    /// Move the cursor down by one.
+    Down,
+    /// Keep the cursor where it is.
+    Nothing,
+}
+
+impl Move2D {
+    /// Returns the change of the cursor position caused by this move, as
+    /// `(x, y)` where `y` grows downwards.
+    #[must_use]
+    pub fn offset(self) -> (i64, i64) {
+        match self {
+            Self::Left => (-1, 0),
+            Self::Right => (1, 0),
+            Self::Up => (0, -1),
+            Self::Down => (0, 1),
+            Self::Nothing => (0, 0),
+        }
+    }
+}
+
+impl FromStr for Move2D {
+    type Err = InvalidProgram;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "r" | "R" => Ok(Self::Right),
+            "l" | "L" => Ok(Self::Left),
+            "u" | "U" => Ok(Self::Up),
+            "d" | "D" => Ok(Self::Down),
+            "n" | "N" | "" | "_" | " " => Ok(Self::Nothing),
+            _ => Err(InvalidProgram::InvalidAction),
+        }
+    }
+}
+
+impl fmt::Display for Move2D {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Left => "l",
+            Self::Right => "r",
+            Self::Up => "u",
+            Self::Down => "d",
+            Self::Nothing => "n",
+        })
+    }
+}
+
+/// An infinite two-dimensional working buffer for the [`GridMachine`].
+///
+/// Positions are `(x, y)` pairs relative to the cursor position the tape was
+/// created with, where `y` grows downwards.
+pub trait Tape2D {
+    /// The alphabet of the tape.
+    type Symbol: Symbol;
+
+    /// Move the cursor to the left by one.
+    fn left(&mut self);
+    /// Move the cursor to the right by one.
+    fn right(&mut self);
+    /// Move the cursor up by one.
+    fn up(&mut self);
+    /// Move the cursor down by one.
+    fn down(&mut self);
+    /// Write a segment at the cursor position.
+    fn put(&mut self, segment: Self::Symbol);
+    /// Returns the segment at the cursor position.
+    fn current(&self) -> &Self::Symbol;
+    /// Returns the cursor position.
+    fn position(&self) -> (i64, i64);
+
+    /// Perform a movement action.
+    fn apply(&mut self, action: Move2D) {
+        match action {
+            Move2D::Left => self.left(),
+            Move2D::Right => self.right(),
+            Move2D::Up => self.up(),
+            Move2D::Down => self.down(),
+            Move2D::Nothing => {}
+        }
+    }
+}
+
+/// A [`Tape2D`] that only stores the segments that are not blank.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GridTape<S = Segment> {
+    cells: HashMap<(i64, i64), S>,
+    position: (i64, i64),
+    /// Returned by reference for cells that were never written to.
+    blank: S,
+}
+
+impl<S> GridTape<S>
+where
+    S: Symbol,
+{
+    /// Create an empty tape.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            cells: HashMap::new(),
+            position: (0, 0),
+            blank: S::BLANK,
+        }
+    }
+
+    /// Returns the segment at `position`.
+    #[must_use]
+    pub fn get(&self, position: (i64, i64)) -> S {
+        self.cells.get(&position).copied().unwrap_or(S::BLANK)
+    }
+
+    /// Returns the smallest and largest position of all segments that are
+    /// not blank and the cursor, as `((min_x, min_y), (max_x, max_y))`.
+    #[must_use]
+    pub fn bounds(&self) -> ((i64, i64), (i64, i64)) {
+        self.cells.keys().fold(
+            (self.position, self.position),
+            |((min_x, min_y), (max_x, max_y)), &(x, y)| {
+                ((min_x.min(x), min_y.min(y)), (max_x.max(x), max_y.max(y)))
+            },
+        )
+    }
+}
+
+impl<S> Default for GridTape<S>
+where
+    S: Symbol,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> Tape2D for GridTape<S>
+where
+    S: Symbol,
+{
+    type Symbol = S;
+
+    fn left(&mut self) {
+        self.position.0 -= 1;
+    }
+
+    fn right(&mut self) {
+        self.position.0 += 1;
+    }
+
+    fn up(&mut self) {
+        self.position.1 -= 1;
+    }
+
+    fn down(&mut self) {
+        self.position.1 += 1;
+    }
+
+    fn put(&mut self, segment: S) {
+        if segment == S::BLANK {
+            self.cells.remove(&self.position);
+        } else {
+            self.cells.insert(self.position, segment);
+        }
+    }
+
+    fn current(&self) -> &S {
+        self.cells.get(&self.position).unwrap_or(&self.blank)
+    }
+
+    fn position(&self) -> (i64, i64) {
+        self.position
+    }
+}
+
+impl FromStr for GridTape {
+    type Err = InvalidProgram;
+
+    /// Read a tape with one row per line. The cursor starts on the first
+    /// segment of the first line.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut tape = Self::new();
+
+        for (y, line) in (0..).zip(s.lines()) {
+            for (x, c) in (0..).zip(line.chars()) {
+                let segment = Segment::from(c);
+
+                if segment != Segment::Empty {
+                    tape.cells.insert((x, y), segment);
+                }
+            }
+        }
+
+        Ok(tape)
+    }
+}
+
+impl<S> fmt::Display for GridTape<S>
+where
+    S: Symbol + fmt::Display,
+{
+    /// Writes the rectangle given by [`GridTape::bounds`], one row per line.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ((min_x, min_y), (max_x, max_y)) = self.bounds();
+
+        for y in min_y..=max_y {
+            for x in min_x..=max_x {
+                fmt::Display::fmt(&self.get((x, y)), f)?;
+            }
+
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A transition of a [`GridProgram`], keyed by its "from" state and
+/// condition.
+#[derive(Debug, Clone, Copy)]
+struct GridTransition<S> {
+    to: State,
+    /// The segment to write, or [`None`] to write back the segment that was
+    /// read.
+    write: Option<S>,
+    action: Move2D,
+}
+
+/// A program for the [`GridMachine`].
+///
+/// The format is the same as the one of a [`crate::Program`], with the
+/// additional actions "u" and "d" to move up and down. Includes and seeking
+/// actions are not supported.
+#[derive(Debug, Clone)]
+pub struct GridProgram<S = Segment> {
+    initial_state: State,
+    final_states: HashSet<State>,
+    error_states: HashSet<State>,
+    transitions: HashMap<(State, S), GridTransition<S>>,
+    wildcards: HashMap<State, GridTransition<S>>,
+    names: StateNames,
+}
+
+impl<S> GridProgram<S>
+where
+    S: Symbol,
+{
+    /// Returns the state execution starts in.
+    #[must_use]
+    pub fn initial_state(&self) -> State {
+        self.initial_state
+    }
+
+    /// Returns the state with the given name, if the program has one.
+    #[must_use]
+    pub fn state(&self, name: &str) -> Option<State> {
+        self.names
+            .iter()
+            .find(|(_, other)| *other == name)
+            .map(|(state, _)| *state)
+    }
+}
+
+impl<S> GridProgram<S>
+where
+    S: Symbol + FromStr<Err = InvalidProgram>,
+{
+    /// Parse a program over a custom alphabet, using the [`FromStr`]
+    /// implementation of the symbols for the segments of each transition.
+    ///
+    /// # Errors
+    ///
+    /// This method will error if the program is invalid.
+    pub fn parse(s: &str) -> Result<Self, ParseError> {
+        // The actions are parsed here and replaced with "n", so that the rest
+        // of each line can be parsed like the line of a one-dimensional program
+        let mut actions = Vec::new();
+        let mut lines = Vec::new();
+
+        for (number, line) in s.lines().enumerate() {
+            let fields: Vec<&str> = line.splitn(5, ',').collect();
+            let is_transition = !line.starts_with(['#', '/', '+', '-', '!', '@']);
+
+            if let (true, [from, to, condition, write, action]) = (is_transition, &fields[..]) {
+                let parsed = Move2D::from_str(action).map_err(|kind| ParseError {
+                    kind,
+                    line: number + 1,
+                    column: line[..line.len() - action.len()].chars().count() + 1,
+                    text: (*action).to_owned(),
+                    line_text: line.to_owned(),
+                })?;
+
+                actions.push(parsed);
+                lines.push(format!("{from},{to},{condition},{write},n"));
+            } else {
+                actions.push(Move2D::Nothing);
+                lines.push(line.to_owned());
+            }
+        }
+
+        let (lines, names) = Line::<S>::parse_all(&lines.join("\n"))?;
+        let mut initial_state = None;
+        let mut final_states = HashSet::with_capacity(1);
+        let mut error_states = HashSet::new();
+        let mut transitions = HashMap::new();
+        let mut wildcards = HashMap::new();
+
+        for (number, line) in lines {
+            match line {
+                Line::Initial(state) => initial_state = Some(state),
+                Line::Final(state, _) => {
+                    final_states.insert(state);
+                }
+                Line::Error(state) => {
+                    error_states.insert(state);
+                }
+                Line::Transition(transition) => {
+                    let key = (transition.from, transition.condition);
+                    let value = GridTransition {
+                        to: transition.to,
+                        write: Some(transition.write),
+                        action: actions[number],
+                    };
+
+                    if transitions.insert(key, value).is_some() {
+                        return Err(ParseError::duplicate_transition(s, number));
+                    }
+                }
+                Line::Wildcard(wildcard) => {
+                    let value = GridTransition {
+                        to: wildcard.to,
+                        write: wildcard.write,
+                        action: actions[number],
+                    };
+
+                    if wildcards.insert(wildcard.from, value).is_some() {
+                        return Err(ParseError::duplicate_transition(s, number));
+                    }
+                }
+                Line::Include(_) => {
+                    return Err(ParseError::unsupported_include(s, number));
+                }
+                // Execution always starts in the initial state
+                Line::Entry(..) => {}
+                Line::Seek(..) => unreachable!("actions were replaced with \"n\""),
+            }
+        }
+
+        Ok(Self {
+            initial_state: initial_state
+                .ok_or_else(|| ParseError::from(InvalidProgram::MissingInitialState))?,
+            final_states,
+            error_states,
+            transitions,
+            wildcards,
+            names,
+        })
+    }
+}
+
+impl FromStr for GridProgram {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+/// A turing machine that can execute [`GridProgram`]s on a [`Tape2D`].
+#[derive(Debug)]
+pub struct GridMachine<T> {
+    tape: T,
+}
+
+impl<T> GridMachine<T>
+where
+    T: Tape2D,
+{
+    /// Create a new [`GridMachine`] from a [`Tape2D`].
+    #[must_use]
+    pub fn from_tape(tape: T) -> Self {
+        Self { tape }
+    }
+
+    /// Returns a reference to the [`Tape2D`] of the machine.
+    #[must_use]
+    pub fn tape(&self) -> &T {
+        &self.tape
+    }
+
+    /// Perform the transition for `state` and the current segment, and
+    /// return the state it leads to.
+    ///
+    /// # Errors
+    ///
+    /// This method will error if no transition is defined.
+    pub fn step(
+        &mut self,
+        program: &GridProgram<T::Symbol>,
+        state: State,
+    ) -> Result<State, ExecutionError<T::Symbol>> {
+        let current = *self.tape.current();
+        let transition = program
+            .transitions
+            .get(&(state, current))
+            .or_else(|| program.wildcards.get(&state))
+            .ok_or(ExecutionError::UndefinedBehavior(state, current))?;
+
+        self.tape.put(transition.write.unwrap_or(current));
+        self.tape.apply(transition.action);
+
+        Ok(transition.to)
+    }
+
+    /// Run a [`GridProgram`] with this turing machine until it halts.
+    ///
+    /// # Errors
+    ///
+    /// This method will error if it encounters undefined behaviour or reaches
+    /// an error state.
+    pub fn execute(
+        &mut self,
+        program: &GridProgram<T::Symbol>,
+    ) -> Result<State, ExecutionError<T::Symbol>> {
+        self.execute_inner(program, None)
+    }
+
+    /// Run a [`GridProgram`] with this turing machine for at most
+    /// `max_steps` steps.
+    ///
+    /// # Errors
+    ///
+    /// This method will error if it encounters undefined behaviour, reaches
+    /// an error state or does not halt within `max_steps` steps.
+    pub fn execute_bounded(
+        &mut self,
+        program: &GridProgram<T::Symbol>,
+        max_steps: u64,
+    ) -> Result<State, ExecutionError<T::Symbol>> {
+        self.execute_inner(program, Some(max_steps))
+    }
+
+    fn execute_inner(
+        &mut self,
+        program: &GridProgram<T::Symbol>,
+        max_steps: Option<u64>,
+    ) -> Result<State, ExecutionError<T::Symbol>> {
+        let mut state = program.initial_state;
+        let mut steps = 0;
+
+        loop {
+            if max_steps == Some(steps) {
+                return Err(ExecutionError::StepLimitExceeded(steps));
+            }
+
+            state = self.step(program, state)?;
+            steps += 1;
+
+            if program.final_states.contains(&state) {
+                return Ok(state);
+            } else if program.error_states.contains(&state) {
+                return Err(ExecutionError::ReachedError(state));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::{grid::*, *};
+
+    #[test]
+    fn test_grid_tape() {
+        let mut tape = GridTape::from_str("ab\n_c").unwrap();
+        assert_eq!(tape.current(), &Segment::Symbol('a'));
+
+        tape.down();
+        tape.right();
+        assert_eq!(tape.current(), &Segment::Symbol('c'));
+        tape.put(Segment::Empty);
+        tape.down();
+        tape.put(Segment::Symbol('d'));
+
+        assert_eq!(tape.position(), (1, 2));
+        assert_eq!(tape.bounds(), ((0, 0), (1, 2)));
+        assert_eq!(tape.to_string(), "ab\n__\n_d\n");
+    }
+
+    #[test]
+    fn test_langtons_ant() {
+        let program = GridProgram::from_str(include_str!("../examples/langtons_ant.tng")).unwrap();
+        let position = |steps| {
+            let mut machine = GridMachine::from_tape(GridTape::new());
+            assert_eq!(
+                machine.execute_bounded(&program, steps),
+                Err(ExecutionError::StepLimitExceeded(steps))
+            );
+            machine.tape().position()
+        };
+
+        // After about 10000 steps the ant leaves the chaotic phase and builds
+        // a highway that repeats every 104 steps, moving two cells diagonally
+        assert_eq!(position(11_000), (-34, 14));
+        assert_eq!(position(11_104), (-36, 16));
+        assert_eq!(position(11_208), (-38, 18));
+    }
+
+    #[test]
+    fn test_grid_program() {
+        let program = GridProgram::from_str("+0\n-done\n0,0,_,x,d\n0,done,x,=,u").unwrap();
+        let mut machine = GridMachine::from_tape(GridTape::from_str("_\n_\nx").unwrap());
+
+        assert_eq!(
+            machine.execute(&program),
+            Ok(program.state("done").unwrap())
+        );
+        assert_eq!(machine.tape().position(), (0, 1));
+        assert_eq!(machine.tape().to_string(), "x\nx\nx\n");
+
+        let error = GridProgram::from_str("+0\n0,0,_,_,q").unwrap_err();
+        assert_eq!(error.kind, InvalidProgram::InvalidAction);
+        assert_eq!((error.line, error.column), (2, 9));
+    }
+}
@@ -0,0 +1,321 @@
+//! Deciding whether a program halts while it stays on a bounded part of the
+//! tape.
+//!
+//! Halting is undecidable in general, but a machine whose cursor never
+//! leaves a part of the tape of a fixed length only has finitely many
+//! configurations of state, cursor position and tape contents. It either
+//! halts or enters a configuration for the second time, after which it
+//! repeats the same steps forever. [`Program::halts_on`] runs the program
+//! until one of these happens or the cursor leaves the bound:
+//!
+//! ```
+//! use std::str::FromStr;
+//!
+//! use turing::{halting::Halting, Program, VecTape};
+//!
+//! // Bounces between the two ends of its input forever
+//! let program = Program::from_str("+0\n-2\n0,0,1,1,r\n0,1,_,_,l\n1,1,1,1,l\n1,0,_,_,r").unwrap();
+//!
+//! let Halting::Loops(witness) = program.halts_on(&VecTape::from_str("_111_").unwrap(), 5) else {
+//!     panic!("the program loops");
+//! };
+//! assert_eq!(witness.period, 8);
+//! ```
+use crate::{dispatch::StateTable, tape::Symbol, ExecutionError, Program, Segment, State, Tape};
+
+/// The outcome of [`Program::halts_on`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Halting<S = Segment> {
+    /// The program halts in a final state after the given number of steps.
+    Halts {
+        /// The final state.
+        state: State,
+        /// The number of steps until the program halted.
+        steps: u64,
+    },
+    /// The program stops with an error after the given number of steps,
+    /// because it reached an error state or no transition applies.
+    Fails {
+        /// The error the program stopped with.
+        error: ExecutionError<S>,
+        /// The number of steps until the program failed.
+        steps: u64,
+    },
+    /// The program runs forever without leaving the bound.
+    Loops(LoopWitness<S>),
+    /// The cursor visited more cells than the bound allows after the given
+    /// number of steps, so the analysis is inconclusive.
+    ExceedsBound {
+        /// The number of steps until the cursor left the bound.
+        steps: u64,
+    },
+}
+
+/// A configuration that a program returns to forever, found by
+/// [`Program::halts_on`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoopWitness<S = Segment> {
+    /// The number of steps before the program first enters the
+    /// configuration.
+    pub steps: u64,
+    /// The number of steps after which the program is back in the
+    /// configuration.
+    pub period: u64,
+    /// The state of the configuration.
+    pub state: State,
+    /// The cursor position of the configuration, relative to the cursor
+    /// position the run started at.
+    pub position: i64,
+    /// The segments the cursor can reach within the bound, starting at
+    /// [`LoopWitness::start`].
+    pub segments: Vec<S>,
+    /// The position of the first of the [`LoopWitness::segments`].
+    pub start: i64,
+}
+
+/// A configuration of a run within the bound.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Configuration<S> {
+    /// The index of the state in the [`StateTable`].
+    state: usize,
+    /// The index of the cursor in `cells`.
+    cursor: usize,
+    cells: Vec<S>,
+}
+
+/// What happened when a [`Configuration`] was advanced by a step.
+enum Advance<S> {
+    Running,
+    Stopped(Halting<S>),
+}
+
+/// A run of a program within the bound, counting its steps and the part of
+/// the tape it visited.
+struct BoundedRun<'t, S> {
+    table: &'t StateTable<S>,
+    bound: usize,
+    steps: u64,
+    leftmost: usize,
+    rightmost: usize,
+}
+
+impl<S> BoundedRun<'_, S>
+where
+    S: Symbol,
+{
+    /// Perform one step on `configuration`.
+    fn advance(&mut self, configuration: &mut Configuration<S>) -> Advance<S> {
+        let read = configuration.cells[configuration.cursor];
+        let Some((to, transition)) = self.table.transition(configuration.state, read) else {
+            return Advance::Stopped(Halting::Fails {
+                error: ExecutionError::UndefinedBehavior(
+                    self.table.state(configuration.state),
+                    read,
+                ),
+                steps: self.steps,
+            });
+        };
+
+        configuration.cells[configuration.cursor] = transition.write;
+        configuration.state = to;
+        self.steps += 1;
+
+        let cursor = configuration
+            .cursor
+            .checked_add_signed(isize::try_from(transition.action.offset()).unwrap_or(0));
+        match cursor {
+            Some(cursor) if cursor < configuration.cells.len() => {
+                configuration.cursor = cursor;
+                self.leftmost = self.leftmost.min(cursor);
+                self.rightmost = self.rightmost.max(cursor);
+            }
+            _ => {
+                return Advance::Stopped(Halting::ExceedsBound { steps: self.steps });
+            }
+        }
+
+        if self.rightmost - self.leftmost >= self.bound {
+            return Advance::Stopped(Halting::ExceedsBound { steps: self.steps });
+        }
+
+        match self.table.halts_in(to) {
+            Ok(false) => Advance::Running,
+            Ok(true) => Advance::Stopped(Halting::Halts {
+                state: transition.to,
+                steps: self.steps,
+            }),
+            Err(error) => Advance::Stopped(Halting::Fails {
+                error,
+                steps: self.steps,
+            }),
+        }
+    }
+}
+
+impl<S> Program<S>
+where
+    S: Symbol,
+{
+    /// Decide whether the program halts on `tape` as long as its cursor
+    /// visits at most `bound` cells.
+    ///
+    /// The result is exact within the bound: if the program neither halts,
+    /// fails nor leaves the bound, it loops forever and the returned
+    /// [`LoopWitness`] tells where. The analysis needs memory proportional to
+    /// the bound and time proportional to the bound times the number of
+    /// steps until the program halts or repeats, which can be exponential in
+    /// the bound.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `bound` is so large that the cells the cursor
+    /// can reach do not fit into memory.
+    #[must_use]
+    pub fn halts_on<T>(&self, tape: &T, bound: usize) -> Halting<S>
+    where
+        T: Tape<Symbol = S>,
+    {
+        let bound = bound.max(1);
+        let (table, initial) = StateTable::new(self, self.initial_state);
+
+        // Every cell the cursor can reach without visiting more than `bound`
+        // cells
+        let reach = i64::try_from(bound - 1).expect("bound exceeds i64");
+        let origin = tape.position();
+        let cells = (-reach..=reach)
+            .map(|offset| tape.get(origin + offset))
+            .collect();
+        let start = Configuration {
+            state: initial,
+            cursor: bound - 1,
+            cells,
+        };
+
+        let mut run = BoundedRun {
+            table: &table,
+            bound,
+            steps: 0,
+            leftmost: bound - 1,
+            rightmost: bound - 1,
+        };
+
+        // Brent's cycle detection, which only keeps one earlier configuration
+        let mut checkpoint = start.clone();
+        let mut current = start.clone();
+        let mut power = 1_u64;
+        let mut period = 0_u64;
+
+        loop {
+            if let Advance::Stopped(halting) = run.advance(&mut current) {
+                return halting;
+            }
+            period += 1;
+
+            if current == checkpoint {
+                break;
+            }
+
+            if period == power {
+                checkpoint.clone_from(&current);
+                power *= 2;
+                period = 0;
+            }
+        }
+
+        // Find the first configuration of the loop by running two copies of
+        // the program `period` steps apart until they meet
+        let mut ahead = start.clone();
+        let mut behind = start;
+        let mut replay = BoundedRun {
+            steps: 0,
+            leftmost: bound - 1,
+            rightmost: bound - 1,
+            ..run
+        };
+        for _ in 0..period {
+            replay.advance(&mut ahead);
+        }
+
+        let mut steps = 0;
+        while ahead != behind {
+            replay.advance(&mut ahead);
+            replay.advance(&mut behind);
+            steps += 1;
+        }
+
+        Halting::Loops(LoopWitness {
+            steps,
+            period,
+            state: table.state(behind.state),
+            position: i64::try_from(behind.cursor).expect("bound exceeds i64") - reach,
+            segments: behind.cells,
+            start: -reach,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::{halting::*, *};
+
+    #[test]
+    fn test_halts_on() {
+        let program = Program::from_str(include_str!("../examples/next_integer.tng")).unwrap();
+        let tape = VecTape::from_str("_1011_").unwrap();
+
+        let mut machine = TuringMachine::from_tape(tape.clone());
+        let steps = machine.run(&program).count();
+        assert_eq!(
+            program.halts_on(&tape, 6),
+            Halting::Halts {
+                state: State(3),
+                steps: u64::try_from(steps).unwrap(),
+            }
+        );
+        assert_eq!(
+            program.halts_on(&tape, 3),
+            Halting::ExceedsBound { steps: 3 }
+        );
+
+        let program = Program::from_str("+0\n-1\n0,0,1,1,r").unwrap();
+        assert!(matches!(
+            program.halts_on(&tape, 10),
+            Halting::Fails {
+                error: ExecutionError::UndefinedBehavior(State(0), Segment::Symbol('0')),
+                steps: 1,
+            }
+        ));
+    }
+
+    #[test]
+    fn test_loop_witness() {
+        // Walks right over the input and then toggles the blank behind it
+        // forever
+        let program = Program::from_str("+0\n-2\n0,0,1,1,r\n0,1,_,x,n\n1,0,x,_,n").unwrap();
+        let tape = VecTape::from_str("_11").unwrap();
+
+        let Halting::Loops(witness) = program.halts_on(&tape, 4) else {
+            panic!("the program loops");
+        };
+        assert_eq!((witness.steps, witness.period), (2, 2));
+        assert_eq!((witness.state, witness.position), (State(0), 2));
+        assert_eq!(witness.start, -3);
+        assert_eq!(
+            witness.segments[3..],
+            [
+                Segment::Symbol('1'),
+                Segment::Symbol('1'),
+                Segment::Empty,
+                Segment::Empty
+            ]
+        );
+
+        // The loop is out of reach with a smaller bound
+        assert_eq!(
+            program.halts_on(&tape, 2),
+            Halting::ExceedsBound { steps: 2 }
+        );
+    }
+}
@@ -0,0 +1,227 @@
+//! Inversion of reversible [`Program`]s.
+//!
+//! A program is reversible if the state and tape it was in before every step
+//! can be told from the state and tape after it: the transitions that lead to
+//! a state all move the cursor in the same direction and write different
+//! segments. [`Program::invert`] turns such a program into one that runs its
+//! computations backwards, restoring the input from the output:
+//!
+//! ```
+//! use std::str::FromStr;
+//!
+//! use turing::{Program, Tape, TuringMachine, VecTape};
+//!
+//! // Replaces the first "a" with "B" and the others with "b"
+//! let program = Program::from_str("+0\n-2\n0,1,a,B,r\n1,1,a,b,r\n1,2,_,_,n").unwrap();
+//! let inverted = program.invert().unwrap();
+//!
+//! let mut machine = TuringMachine::from_tape(VecTape::from_str("_aaa").unwrap());
+//! machine.execute(&program).unwrap();
+//! assert_eq!(machine.tape().to_string(), "_Bbb_");
+//!
+//! machine.execute(&inverted).unwrap();
+//! assert_eq!(machine.tape().to_string(), "_aaa_");
+//! assert_eq!(machine.tape().position(), 0);
+//! ```
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    error::InvalidInversion,
+    program::{Transition, Transitions, Wildcard, Wildcards},
+    tape::Symbol,
+    Direction, Program, State,
+};
+
+/// A transition of the original program as its "from" state, condition,
+/// write, action and "to" state. The condition and write of a wildcard that
+/// writes back what it read are [`None`].
+type Step<S> = (State, Option<S>, Option<S>, Direction, State);
+
+impl<S> Program<S>
+where
+    S: Symbol,
+{
+    /// Returns a program that runs the computations of this one backwards.
+    ///
+    /// The inverted program starts in the final state and halts in the
+    /// initial state. Started on a tape this program halted with, with the
+    /// cursor where it halted, it restores the tape this program was started
+    /// on and halts with the cursor where it started. On other tapes it may
+    /// fail or halt with any tape.
+    ///
+    /// Transitions of final and error states are never taken and ignored.
+    /// Names of states are kept, but entry points and comments are not.
+    ///
+    /// # Errors
+    ///
+    /// This method will error if the program does not have exactly one final
+    /// state, a transition leads to the initial state or the program is not
+    /// reversible, as described in [`InvalidInversion`].
+    pub fn invert(&self) -> Result<Self, InvalidInversion> {
+        let mut finals = self.final_states.iter().copied();
+        let (Some(last), None) = (finals.next(), finals.next()) else {
+            return Err(InvalidInversion::FinalStateCount);
+        };
+
+        let mut steps: Vec<Step<S>> = Vec::new();
+        for transition in self.transitions.values() {
+            steps.push((
+                transition.from,
+                Some(transition.condition),
+                Some(transition.write),
+                transition.action,
+                transition.to,
+            ));
+        }
+        for wildcard in self.wildcards.values() {
+            if wildcard.write.is_some() {
+                return Err(InvalidInversion::Wildcard(wildcard.from));
+            }
+            steps.push((wildcard.from, None, None, wildcard.action, wildcard.to));
+        }
+        steps.retain(|(from, ..)| {
+            !self.final_states.contains(from) && !self.error_states.contains(from)
+        });
+        // Sorted, so that the same error is reported every time
+        steps.sort_by_key(|(from, condition, ..)| (*from, *condition));
+
+        let mut moves: HashMap<State, Direction> = HashMap::new();
+        let mut writes: HashMap<State, HashSet<Option<S>>> = HashMap::new();
+        for (_, _, write, action, to) in &steps {
+            if *to == self.initial_state {
+                return Err(InvalidInversion::EntersInitial);
+            }
+            if *moves.entry(*to).or_insert(*action) != *action {
+                return Err(InvalidInversion::AmbiguousMove(*to));
+            }
+
+            let written = writes.entry(*to).or_default();
+            if written.contains(&None) || (write.is_none() && !written.is_empty()) {
+                return Err(InvalidInversion::AmbiguousWrite(*to));
+            }
+            if !written.insert(*write) {
+                return Err(InvalidInversion::AmbiguousWrite(*to));
+            }
+        }
+
+        // In the inverted program, a state is entered with the cursor on the
+        // segment the original program wrote when entering it, and moves it
+        // back to where the original program read that segment
+        let back = |state: &State| {
+            moves
+                .get(state)
+                .map_or(Direction::Stay, |action| action.opposite())
+        };
+
+        let mut transitions = Transitions::new();
+        let mut wildcards = Wildcards::new();
+        for (from, condition, write, _, to) in &steps {
+            if let (Some(condition), Some(write)) = (condition, write) {
+                let transition = Transition {
+                    from: *to,
+                    to: *from,
+                    condition: *write,
+                    write: *condition,
+                    action: back(from),
+                };
+                transitions.insert((transition.from, transition.condition), transition);
+            } else {
+                let wildcard = Wildcard {
+                    from: *to,
+                    to: *from,
+                    write: None,
+                    action: back(from),
+                };
+                wildcards.insert(wildcard.from, wildcard);
+            }
+        }
+
+        // The original program wrote the segment under the cursor before
+        // moving away from it, so the inverted program first moves back
+        let initial_state = if back(&last) == Direction::Stay {
+            last
+        } else {
+            let start = State(self.state_bound());
+            let wildcard = Wildcard {
+                from: start,
+                to: last,
+                write: None,
+                action: back(&last),
+            };
+            wildcards.insert(start, wildcard);
+            start
+        };
+
+        Ok(Self::from_parts(
+            initial_state,
+            HashSet::from([self.initial_state]),
+            HashSet::new(),
+            transitions,
+            wildcards,
+            self.names.clone(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::*;
+
+    #[test]
+    fn test_invert() {
+        let program = Program::from_str("+0\n-2\n0,1,a,B,r\n1,1,a,b,r\n1,2,_,_,n").unwrap();
+        assert_eq!(
+            program.invert().unwrap().to_tng_string(),
+            "+2\n-0\n1,0,B,a,n\n1,1,b,a,l\n2,1,_,_,l\n"
+        );
+
+        // Moves right into the final state, so the inverted program starts by
+        // moving left
+        let program = Program::from_str("+0\n-2\n0,1,*,=,r\n1,2,a,x,r\n1,2,b,y,r").unwrap();
+        let inverted = program.invert().unwrap();
+        assert_eq!(
+            inverted.to_tng_string(),
+            "+3\n-0\n2,1,x,a,l\n2,1,y,b,l\n1,0,*,=,n\n3,2,*,=,l\n"
+        );
+        for input in ["_aa", "_ab", "_ba", "_bb"] {
+            let mut machine = TuringMachine::from_tape(VecTape::from_str(input).unwrap());
+            machine.execute(&program).unwrap();
+            machine.execute(&inverted).unwrap();
+            assert_eq!(machine.tape().to_string().trim_end_matches('_'), input);
+            assert_eq!(machine.tape().position(), 0);
+        }
+
+        let state = |s| State::from_str(s).unwrap();
+        for (source, error) in [
+            ("+0\n-1\n-2", InvalidInversion::FinalStateCount),
+            ("+0\n0,0,a,a,r", InvalidInversion::FinalStateCount),
+            (
+                "+0\n-2\n0,1,a,a,r\n1,0,a,a,r",
+                InvalidInversion::EntersInitial,
+            ),
+            (
+                "+0\n-2\n0,1,a,a,r\n0,1,b,a,r",
+                InvalidInversion::AmbiguousWrite(state("1")),
+            ),
+            (
+                "+0\n-2\n0,1,a,a,r\n0,1,*,=,r",
+                InvalidInversion::AmbiguousWrite(state("1")),
+            ),
+            (
+                "+0\n-2\n0,1,a,a,r\n0,1,b,b,l",
+                InvalidInversion::AmbiguousMove(state("1")),
+            ),
+            ("+0\n-2\n0,1,*,a,r", InvalidInversion::Wildcard(state("0"))),
+        ] {
+            let program = Program::from_str(source).unwrap();
+            assert_eq!(program.invert().unwrap_err(), error, "{source}");
+        }
+
+        assert_eq!(
+            InvalidInversion::AmbiguousMove(state("1")).to_string(),
+            "transitions that lead to state 1 move in different directions"
+        );
+    }
+}
@@ -0,0 +1,394 @@
+//! Import of turing machines from [JFLAP](https://www.jflap.org) `.jff` files.
+//!
+//! Only single-tape turing machines are supported. Empty `<read/>` and
+//! `<write/>` elements stand for the blank symbol, and the "S" move keeps the
+//! cursor where it is.
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    error::InvalidJff,
+    program::{is_state_name, StateNames, Transition},
+    Direction, Program, Segment, State,
+};
+
+impl Program {
+    /// Parse a JFLAP `.jff` file containing a single-tape turing machine.
+    ///
+    /// State ids become the state numbers of the program, and state names that
+    /// are valid in the `.tng` format are kept as names.
+    ///
+    /// # Errors
+    ///
+    /// This method will error if the file is not valid XML, does not contain
+    /// a single-tape turing machine or the machine is non-deterministic.
+    pub fn from_jff(s: &str) -> Result<Self, InvalidJff> {
+        let root = xml::parse(s).ok_or(InvalidJff::Xml)?;
+
+        let kind = root
+            .child("type")
+            .map(xml::Element::text)
+            .unwrap_or_default();
+        if kind != "turing" || root.find("tapes").is_some() || root.find("block").is_some() {
+            return Err(InvalidJff::UnsupportedType(kind));
+        }
+
+        let automaton = root.child("automaton").unwrap_or(&root);
+        let mut initial_state = None;
+        let mut final_states = HashSet::new();
+        let mut names = StateNames::new();
+        let mut transitions = HashMap::new();
+
+        for state in automaton.children("state") {
+            let id = state
+                .attribute("id")
+                .ok_or(InvalidJff::MissingElement("id"))?;
+            let id = parse_state(id)?;
+
+            if state.child("initial").is_some() {
+                initial_state = Some(id);
+            }
+            if state.child("final").is_some() {
+                final_states.insert(id);
+            }
+            if let Some(name) = state.attribute("name") {
+                names.insert(id, name.to_owned());
+            }
+        }
+
+        // Only keep names that can be written back to a `.tng` file unambiguously.
+        let mut seen = HashMap::new();
+        for name in names.values() {
+            *seen.entry(name.clone()).or_insert(0) += 1;
+        }
+        names.retain(|_, name| seen[name.as_str()] == 1 && is_state_name(name));
+
+        for element in automaton.children("transition") {
+            let field = |name| {
+                element
+                    .child(name)
+                    .map(xml::Element::text)
+                    .ok_or(InvalidJff::MissingElement(name))
+            };
+
+            let transition = Transition {
+                from: parse_state(&field("from")?)?,
+                to: parse_state(&field("to")?)?,
+                condition: parse_symbol(&field("read")?)?,
+                write: parse_symbol(&field("write")?)?,
+                action: parse_move(&field("move")?)?,
+            };
+
+            if transitions
+                .insert((transition.from, transition.condition), transition)
+                .is_some()
+            {
+                return Err(InvalidJff::DuplicateTransition);
+            }
+        }
+
+        Ok(Self::from_parts(
+            initial_state.ok_or(InvalidJff::MissingInitialState)?,
+            final_states,
+            HashSet::new(),
+            transitions,
+            HashMap::new(),
+            names,
+        ))
+    }
+}
+
+fn parse_state(s: &str) -> Result<State, InvalidJff> {
+    s.trim()
+        .parse()
+        .map_err(|_| InvalidJff::InvalidState(s.to_owned()))
+}
+
+fn parse_symbol(s: &str) -> Result<Segment, InvalidJff> {
+    let mut chars = s.chars();
+
+    match (chars.next(), chars.next()) {
+        (None, _) => Ok(Segment::Empty),
+        (Some(c), None) => Ok(Segment::from(c)),
+        (Some(_), Some(_)) => Err(InvalidJff::InvalidSymbol(s.to_owned())),
+    }
+}
+
+fn parse_move(s: &str) -> Result<Direction, InvalidJff> {
+    match s.trim() {
+        "L" => Ok(Direction::Left),
+        "R" => Ok(Direction::Right),
+        "S" => Ok(Direction::Stay),
+        _ => Err(InvalidJff::InvalidMove(s.to_owned())),
+    }
+}
+
+/// Just enough of an XML parser to read JFLAP files.
+mod xml {
+    /// An element with its attributes, children and text content.
+    #[derive(Debug, Default)]
+    pub(super) struct Element {
+        name: String,
+        attributes: Vec<(String, String)>,
+        children: Vec<Element>,
+        text: String,
+    }
+
+    impl Element {
+        pub(super) fn attribute(&self, name: &str) -> Option<&str> {
+            self.attributes
+                .iter()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.as_str())
+        }
+
+        pub(super) fn children<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Self> {
+            self.children.iter().filter(move |child| child.name == name)
+        }
+
+        pub(super) fn child(&self, name: &str) -> Option<&Self> {
+            self.children.iter().find(|child| child.name == name)
+        }
+
+        /// Find a descendant with the given name.
+        pub(super) fn find(&self, name: &str) -> Option<&Self> {
+            self.children.iter().find_map(|child| {
+                (child.name == name)
+                    .then_some(child)
+                    .or_else(|| child.find(name))
+            })
+        }
+
+        /// Returns the text content of the element.
+        pub(super) fn text(&self) -> String {
+            self.text.clone()
+        }
+    }
+
+    /// Parse a document, returning its root element.
+    pub(super) fn parse(s: &str) -> Option<Element> {
+        let mut stack = vec![Element::default()];
+        let mut rest = s;
+
+        while !rest.is_empty() {
+            let Some(start) = rest.find('<') else {
+                stack.last_mut()?.text.push_str(&unescape(rest)?);
+                break;
+            };
+            stack.last_mut()?.text.push_str(&unescape(&rest[..start])?);
+            rest = &rest[start..];
+
+            if let Some(comment) = rest.strip_prefix("<!--") {
+                rest = &comment[comment.find("-->")? + 3..];
+            } else if rest.starts_with("<?") || rest.starts_with("<!") {
+                rest = &rest[rest.find('>')? + 1..];
+            } else if let Some(tag) = rest.strip_prefix("</") {
+                let end = tag.find('>')?;
+                let element = stack.pop()?;
+
+                if element.name != tag[..end].trim() {
+                    return None;
+                }
+
+                stack.last_mut()?.children.push(element);
+                rest = &tag[end + 1..];
+            } else {
+                let end = rest.find('>')?;
+                let tag = &rest[1..end];
+                let (tag, closed) = match tag.strip_suffix('/') {
+                    Some(tag) => (tag, true),
+                    None => (tag, false),
+                };
+                let element = parse_tag(tag)?;
+                rest = &rest[end + 1..];
+
+                if closed {
+                    stack.last_mut()?.children.push(element);
+                } else {
+                    stack.push(element);
+                }
+            }
+        }
+
+        let mut document = stack.pop()?;
+        if !stack.is_empty() || document.children.len() != 1 {
+            return None;
+        }
+        document.children.pop().map(trim)
+    }
+
+    /// Trim the text content of an element and its children, since JFLAP
+    /// indents its files.
+    fn trim(mut element: Element) -> Element {
+        element.text = element.text.trim().to_owned();
+        element.children = element.children.into_iter().map(trim).collect();
+        element
+    }
+
+    /// Parse the name and attributes of an opening tag.
+    fn parse_tag(tag: &str) -> Option<Element> {
+        let tag = tag.trim();
+        let name_end = tag.find(char::is_whitespace).unwrap_or(tag.len());
+        let mut element = Element {
+            name: tag[..name_end].to_owned(),
+            ..Element::default()
+        };
+        let mut rest = tag[name_end..].trim_start();
+
+        while !rest.is_empty() {
+            let equals = rest.find('=')?;
+            let key = rest[..equals].trim();
+            let value = rest[equals + 1..].trim_start();
+            let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+            let end = value[1..].find(quote)? + 1;
+
+            element
+                .attributes
+                .push((key.to_owned(), unescape(&value[1..end])?));
+            rest = value[end + 1..].trim_start();
+        }
+
+        Some(element)
+    }
+
+    /// Replace the predefined entities and character references.
+    fn unescape(s: &str) -> Option<String> {
+        let mut out = String::with_capacity(s.len());
+        let mut rest = s;
+
+        while let Some(start) = rest.find('&') {
+            out.push_str(&rest[..start]);
+            let end = rest[start..].find(';')? + start;
+            let entity = &rest[start + 1..end];
+
+            out.push(match entity {
+                "lt" => '<',
+                "gt" => '>',
+                "amp" => '&',
+                "quot" => '"',
+                "apos" => '\'',
+                _ => {
+                    let code = match entity.strip_prefix("#x") {
+                        Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                        None => entity.strip_prefix('#')?.parse().ok()?,
+                    };
+                    char::from_u32(code)?
+                }
+            });
+            rest = &rest[end + 1..];
+        }
+
+        out.push_str(rest);
+        Some(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::{error::InvalidJff, *};
+
+    const BINARY_INCREMENT: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="no"?><!--Created with JFLAP 7.1.--><structure>
+	<type>turing</type>
+	<automaton>
+		<!--The list of states.-->
+		<state id="0" name="right">
+			<x>60.0</x>
+			<y>90.0</y>
+			<initial/>
+		</state>
+		<state id="1" name="carry">
+			<x>200.0</x>
+			<y>90.0</y>
+		</state>
+		<state id="2" name="q2">
+			<x>340.0</x>
+			<y>90.0</y>
+			<final/>
+		</state>
+		<!--The list of transitions.-->
+		<transition>
+			<from>0</from>
+			<to>0</to>
+			<read>0</read>
+			<write>0</write>
+			<move>R</move>
+		</transition>
+		<transition>
+			<from>0</from>
+			<to>0</to>
+			<read>1</read>
+			<write>1</write>
+			<move>R</move>
+		</transition>
+		<transition>
+			<from>0</from>
+			<to>1</to>
+			<read/>
+			<write/>
+			<move>L</move>
+		</transition>
+		<transition>
+			<from>1</from>
+			<to>1</to>
+			<read>1</read>
+			<write>0</write>
+			<move>L</move>
+		</transition>
+		<transition>
+			<from>1</from>
+			<to>2</to>
+			<read>0</read>
+			<write>1</write>
+			<move>S</move>
+		</transition>
+		<transition>
+			<from>1</from>
+			<to>2</to>
+			<read/>
+			<write>1</write>
+			<move>S</move>
+		</transition>
+	</automaton>
+</structure>"#;
+
+    #[test]
+    fn test_from_jff() {
+        let program = Program::from_jff(BINARY_INCREMENT).unwrap();
+        assert_eq!(program.state("carry"), Some(State::from_str("1").unwrap()));
+
+        let mut machine = TuringMachine::from_tape(VecTape::from_str("_1011_").unwrap());
+        assert_eq!(machine.execute(&program), Ok(State::from_str("2").unwrap()));
+        assert_eq!(machine.tape().to_string(), "_1100_");
+    }
+
+    #[test]
+    fn test_from_jff_errors() {
+        assert_eq!(
+            Program::from_jff("<structure><type>fa</type></structure>").unwrap_err(),
+            InvalidJff::UnsupportedType(String::from("fa"))
+        );
+        assert_eq!(
+            Program::from_jff("<structure><type>turing</type>").unwrap_err(),
+            InvalidJff::Xml
+        );
+        assert_eq!(
+            Program::from_jff(&BINARY_INCREMENT.replace("<move>S</move>", "<move>X</move>"))
+                .unwrap_err(),
+            InvalidJff::InvalidMove(String::from("X"))
+        );
+        assert_eq!(
+            InvalidJff::InvalidMove(String::from("X")).to_string(),
+            "invalid move \"X\", expected \"L\", \"R\" or \"S\""
+        );
+        assert_eq!(
+            Program::from_jff(&BINARY_INCREMENT.replace("<read>1</read>", "<read>0</read>"))
+                .unwrap_err(),
+            InvalidJff::DuplicateTransition
+        );
+        assert_eq!(
+            Program::from_jff(&BINARY_INCREMENT.replace("<initial/>", "")).unwrap_err(),
+            InvalidJff::MissingInitialState
+        );
+    }
+}
@@ -0,0 +1,378 @@
+//! A JSON format for [`Program`]s, for tools that generate or edit programs
+//! without writing the `.tng` format.
+//!
+//! A program is an object with these keys:
+//!
+//! - `initial`: the state execution starts in.
+//! - `final`: the final states, defaults to none.
+//! - `reject`: the final states that reject the input, defaults to none. They
+//!   do not need to be listed in `final` as well.
+//! - `error`: the error states, defaults to none.
+//! - `transitions`: the transitions, defaults to none. Each transition is an
+//!   object with the keys `from`, `to`, `read`, `write` and `move`.
+//! - `entries`: the entry points, defaults to none. An object that maps the
+//!   name of each entry point to its state.
+//!
+//! States are strings holding either a number or a name, like in the `.tng`
+//! format. Segments are strings of a single character, where `_` and ` ` are
+//! the blank segment. A `read` of `*` makes the transition a wildcard, and a
+//! `write` of `=` writes back the segment that was read. The `move` is one of
+//! `l`, `r` and `n`. Other keys are ignored, so editors can store additional
+//! data like the positions of states in a diagram.
+//!
+//! ```
+//! use turing::Program;
+//!
+//! let program = Program::from_json(
+//!     r#"{
+//!         "initial": "scan",
+//!         "final": ["done"],
+//!         "transitions": [
+//!             {"from": "scan", "to": "scan", "read": "*", "write": "=", "move": "r"},
+//!             {"from": "scan", "to": "done", "read": "_", "write": "1", "move": "n"}
+//!         ]
+//!     }"#,
+//! )
+//! .unwrap();
+//!
+//! assert_eq!(
+//!     program.to_string(),
+//!     "+scan\n-done\nscan,done,_,1,n\nscan,scan,*,=,r\n"
+//! );
+//! ```
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    str::FromStr,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::InvalidJson,
+    program::{is_state_name, Comments, StateNames, Transition, Wildcard},
+    Direction, Program, Segment, State,
+};
+
+/// A program in the JSON format.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct Document {
+    initial: String,
+    #[serde(default, rename = "final")]
+    final_states: Vec<String>,
+    #[serde(default, rename = "reject", skip_serializing_if = "Vec::is_empty")]
+    rejecting_states: Vec<String>,
+    #[serde(default, rename = "error")]
+    error_states: Vec<String>,
+    #[serde(default)]
+    transitions: Vec<Entry>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    entries: BTreeMap<String, String>,
+}
+
+/// A transition in the JSON format.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+struct Entry {
+    from: String,
+    to: String,
+    read: String,
+    write: String,
+    #[serde(rename = "move")]
+    action: String,
+}
+
+impl Program {
+    /// Parse a program in the JSON format described in the [`crate::json`]
+    /// module.
+    ///
+    /// Named states are numbered after the highest numbered state, in the
+    /// order they first appear in the document.
+    ///
+    /// # Errors
+    ///
+    /// This method will error if the document is not valid JSON, does not
+    /// follow the format or does not describe a valid program.
+    pub fn from_json(s: &str) -> Result<Self, InvalidJson> {
+        let document: Document =
+            serde_json::from_str(s).map_err(|e| InvalidJson::Syntax(e.to_string()))?;
+        let names = number_names(&document)?;
+
+        let state = |token: &str| State::from_str(token).unwrap_or_else(|_| names[token]);
+        let segment = |symbol: &str| {
+            Segment::from_str(symbol).map_err(|_| InvalidJson::InvalidSymbol(symbol.to_owned()))
+        };
+
+        if let Some(name) = document.entries.keys().find(|name| !is_state_name(name)) {
+            return Err(InvalidJson::InvalidEntry(name.clone()));
+        }
+
+        let mut transitions = HashMap::new();
+        let mut wildcards = HashMap::new();
+
+        for entry in &document.transitions {
+            let from = state(&entry.from);
+            let to = state(&entry.to);
+            let write = match entry.write.as_str() {
+                "=" => None,
+                write => Some(segment(write)?),
+            };
+            let action = match entry.action.as_str() {
+                "l" | "L" => Direction::Left,
+                "r" | "R" => Direction::Right,
+                "n" | "N" => Direction::Stay,
+                action => return Err(InvalidJson::InvalidMove(action.to_owned())),
+            };
+
+            let duplicate = if entry.read == "*" {
+                let wildcard = Wildcard {
+                    from,
+                    to,
+                    write,
+                    action,
+                };
+                wildcards.insert(from, wildcard).is_some()
+            } else {
+                let condition = segment(&entry.read)?;
+                let transition = Transition {
+                    from,
+                    to,
+                    condition,
+                    write: write.unwrap_or(condition),
+                    action,
+                };
+                transitions.insert((from, condition), transition).is_some()
+            };
+
+            if duplicate {
+                return Err(InvalidJson::DuplicateTransition);
+            }
+        }
+
+        Ok(Self {
+            initial_state: state(&document.initial),
+            final_states: document
+                .final_states
+                .iter()
+                .chain(&document.rejecting_states)
+                .map(|token| state(token))
+                .collect::<HashSet<_>>(),
+            rejecting_states: document
+                .rejecting_states
+                .iter()
+                .map(|token| state(token))
+                .collect::<HashSet<_>>(),
+            error_states: document
+                .error_states
+                .iter()
+                .map(|token| state(token))
+                .collect::<HashSet<_>>(),
+            transitions,
+            wildcards,
+            entry_points: document
+                .entries
+                .iter()
+                .map(|(name, token)| (name.clone(), state(token)))
+                .collect(),
+            names: names
+                .into_iter()
+                .map(|(name, state)| (state, name.to_owned()))
+                .collect::<StateNames>(),
+            comments: Comments::new(),
+        })
+    }
+
+    /// Returns the program in the JSON format described in the
+    /// [`crate::json`] module.
+    ///
+    /// States are written with their names if they have one. Transitions are
+    /// sorted by their "from" state and condition, with the wildcard of a
+    /// state last.
+    ///
+    /// # Panics
+    ///
+    /// This method never panics, every program can be written as JSON.
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        let name = |state: State| {
+            self.state_name(state)
+                .map_or_else(|| state.to_string(), str::to_owned)
+        };
+        let sorted = |states: &HashSet<State>| {
+            let mut states: Vec<State> = states.iter().copied().collect();
+            states.sort_unstable();
+            states.into_iter().map(name).collect()
+        };
+
+        let mut transitions: Vec<&Transition> = self.transitions.values().collect();
+        transitions.sort_unstable_by_key(|transition| (transition.from, transition.condition));
+        let mut wildcards: Vec<&Wildcard> = self.wildcards.values().collect();
+        wildcards.sort_unstable_by_key(|wildcard| wildcard.from);
+
+        let mut entries = Vec::with_capacity(transitions.len() + wildcards.len());
+        let mut wildcards = wildcards.into_iter().peekable();
+        for transition in transitions {
+            while let Some(wildcard) = wildcards.next_if(|wildcard| wildcard.from < transition.from)
+            {
+                entries.push(wildcard_entry(wildcard, name));
+            }
+
+            entries.push(Entry {
+                from: name(transition.from),
+                to: name(transition.to),
+                read: transition.condition.to_string(),
+                write: transition.write.to_string(),
+                action: transition.action.to_string(),
+            });
+        }
+        entries.extend(wildcards.map(|wildcard| wildcard_entry(wildcard, name)));
+
+        let document = Document {
+            initial: name(self.initial_state),
+            final_states: sorted(
+                &self
+                    .final_states
+                    .difference(&self.rejecting_states)
+                    .copied()
+                    .collect(),
+            ),
+            rejecting_states: sorted(&self.rejecting_states),
+            error_states: sorted(&self.error_states),
+            transitions: entries,
+            entries: self
+                .entry_points
+                .iter()
+                .map(|(entry, state)| (entry.clone(), name(*state)))
+                .collect(),
+        };
+
+        serde_json::to_string_pretty(&document).expect("programs are valid JSON")
+    }
+}
+
+/// Returns the numbers of the named states of a document.
+fn number_names(document: &Document) -> Result<BTreeMap<&str, State>, InvalidJson> {
+    let tokens = std::iter::once(&document.initial)
+        .chain(&document.final_states)
+        .chain(&document.rejecting_states)
+        .chain(&document.error_states)
+        .chain(
+            document
+                .transitions
+                .iter()
+                .flat_map(|entry| [&entry.from, &entry.to]),
+        )
+        .chain(document.entries.values());
+
+    let mut next = 0;
+    let mut names: BTreeMap<&str, State> = BTreeMap::new();
+    let mut order = Vec::new();
+    for token in tokens {
+        match State::from_str(token) {
+            Ok(state) => next = next.max(state.0.saturating_add(1)),
+            Err(_) if is_state_name(token) => order.push(token.as_str()),
+            Err(_) => return Err(InvalidJson::InvalidState(token.clone())),
+        }
+    }
+    for name in order {
+        names.entry(name).or_insert_with(|| {
+            next += 1;
+            State(next - 1)
+        });
+    }
+
+    Ok(names)
+}
+
+/// Returns the entry for a wildcard transition.
+fn wildcard_entry(wildcard: &Wildcard, name: impl Fn(State) -> String) -> Entry {
+    Entry {
+        from: name(wildcard.from),
+        to: name(wildcard.to),
+        read: String::from("*"),
+        write: wildcard
+            .write
+            .map_or_else(|| String::from("="), |write| write.to_string()),
+        action: wildcard.action.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::{json::*, *};
+
+    /// Parse `json` with its states and transitions sorted, so that documents
+    /// can be compared regardless of how their states are numbered.
+    fn document(json: &str) -> Document {
+        let mut document: Document = serde_json::from_str(json).unwrap();
+        document.final_states.sort();
+        document.rejecting_states.sort();
+        document.error_states.sort();
+        document.transitions.sort();
+        document
+    }
+
+    #[test]
+    fn test_json_roundtrip() {
+        for code in [
+            include_str!("../examples/next_integer.tng"),
+            include_str!("../examples/copy.tng"),
+            include_str!("../examples/universal.tng"),
+            "+0\n-1\n!oops\n@entry skip oops\n0,0,*,=,r\n0,1,_,x,n\n1,oops,*,y,l",
+            "+0\n-A1\n-R2\n-R no\n0,1,a,a,n\n0,2,b,b,n\n0,no,_,_,n",
+        ] {
+            let program = Program::from_str(code).unwrap();
+            let json = program.to_json();
+            let imported = Program::from_json(&json).unwrap();
+
+            assert_eq!(document(&imported.to_json()), document(&json));
+            assert_eq!(imported.names.len(), program.names.len());
+            assert_eq!(imported.entry_points.len(), program.entry_points.len());
+        }
+
+        // Numbered states keep their numbers
+        let program = Program::from_str(include_str!("../examples/next_integer.tng")).unwrap();
+        let imported = Program::from_json(&program.to_json()).unwrap();
+        assert_eq!(imported.to_string(), program.to_string());
+    }
+
+    #[test]
+    fn test_from_json_errors() {
+        let transition = |read: &str, write: &str, action: &str| {
+            format!(
+                r#"{{"initial": "0", "transitions": [
+                    {{"from": "0", "to": "1", "read": "a", "write": "b", "move": "r"}},
+                    {{"from": "0", "to": "1", "read": "{read}", "write": "{write}", "move": "{action}"}}
+                ]}}"#
+            )
+        };
+
+        assert!(Program::from_json(&transition("b", "b", "n")).is_ok());
+        assert_eq!(
+            Program::from_json(&transition("a", "b", "n")).unwrap_err(),
+            InvalidJson::DuplicateTransition
+        );
+        assert_eq!(
+            Program::from_json(&transition("ab", "b", "n")).unwrap_err(),
+            InvalidJson::InvalidSymbol(String::from("ab"))
+        );
+        assert_eq!(
+            Program::from_json(&transition("b", "b", "up")).unwrap_err(),
+            InvalidJson::InvalidMove(String::from("up"))
+        );
+        assert_eq!(
+            Program::from_json(&transition("b", "b", "up"))
+                .unwrap_err()
+                .to_string(),
+            "invalid move \"up\", expected \"l\", \"r\" or \"n\""
+        );
+        assert_eq!(
+            Program::from_json(r#"{"initial": "no way"}"#).unwrap_err(),
+            InvalidJson::InvalidState(String::from("no way"))
+        );
+        assert!(matches!(
+            Program::from_json(r#"{"final": ["0"]}"#),
+            Err(InvalidJson::Syntax(_))
+        ));
+    }
+}
@@ -3,14 +3,69 @@
 #![doc = include_str!("../README.md")]
 pub use crate::{error::*, machine::*, program::*, tape::*};
 
+#[cfg(feature = "animate")]
+pub mod animate;
+pub mod ascii;
+pub mod bbchallenge;
+pub mod bench;
+pub mod busy_beaver;
+pub mod cache;
+pub mod compile;
+pub mod complexity;
+pub mod compose;
+pub mod coverage;
+pub mod cycler;
+pub mod debugger;
+pub mod diff;
+mod dispatch;
+pub mod dot;
+pub mod dsl;
+pub mod edit;
+pub mod encoding;
+pub mod equivalence;
 pub mod error;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod grid;
+pub mod halting;
+pub mod invert;
+#[cfg(feature = "jflap")]
+pub mod jflap;
+#[cfg(feature = "serde")]
+pub mod json;
+pub mod library;
+#[cfg(feature = "lsp")]
+pub mod lsp;
 pub mod machine;
+pub mod macro_machine;
+pub mod minimize;
+pub mod models;
+pub mod multihead;
+pub mod mutate;
+pub mod nondeterministic;
+pub mod normalize;
+pub mod observer;
+pub mod parser;
 pub mod program;
+pub mod recorder;
+pub mod reduce;
+pub mod remap;
+pub mod renumber;
+pub mod search;
+pub mod table;
 pub mod tape;
+pub mod testing;
+pub mod trace;
+#[cfg(feature = "tui")]
+pub mod tui;
+pub mod utm;
+pub mod validation;
+#[cfg(feature = "yaml")]
+pub mod yaml;
 
 #[cfg(test)]
 mod tests {
-    use std::str::FromStr;
+    use std::{collections::BTreeSet, str::FromStr};
 
     use crate::*;
 
@@ -57,4 +112,1330 @@ mod tests {
             VecTape::from_str("_111111_111111").unwrap().inner
         );
     }
+
+    #[test]
+    fn test_swap() {
+        let program = Program::from_str(include_str!("../examples/swap.tng")).unwrap();
+        let tape = VecTape::from_str("_abba_").unwrap();
+        let mut machine = TuringMachine::from_tape(tape);
+        machine.execute(&program).unwrap();
+        assert_eq!(machine.tape().to_string(), "_baab_");
+    }
+
+    #[test]
+    fn test_multi_character_symbol() {
+        assert!(matches!(
+            Program::from_str("+0\n-1\n0,1,ab,b,r"),
+            Err(ParseError {
+                kind: InvalidProgram::MultiCharacterSymbol,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_aligned_transitions() {
+        let aligned = Program::from_str(
+            "+0\n-1\n0 , 0 , a , b , r\n0\t,\t1\t,\t_\t,\t=\t,\tn  # done\n  0,0,b,=,r*",
+        )
+        .unwrap();
+        let compact = Program::from_str("+0\n-1\n0,0,a,b,r\n0,1,_,=,n # done\n0,0,b,=,r*").unwrap();
+        assert_eq!(aligned.to_string(), compact.to_string());
+
+        // Whitespace on its own is still an empty segment
+        for source in ["+0\n-1\n0,1, ,x,n", "+0\n-1\n0 , 1 ,   , x , n"] {
+            let program = Program::from_str(source).unwrap();
+            assert_eq!(program.to_string(), "+0\n-1\n0,1,_,x,n\n", "{source}");
+        }
+
+        // "#" segments in aligned columns are not taken for comments
+        let program =
+            Program::from_str("+0\n-1\n0, 1, #, #, r\n0 ,\t1 , a , # , l\t# done").unwrap();
+        assert_eq!(program.to_string(), "+0\n-1\n0,1,#,#,r\n0,1,a,#,l # done\n");
+        assert_eq!(
+            program.comment(CommentTarget::Transition(State(0), Segment::Symbol('a'))),
+            Some("done")
+        );
+
+        let error = Program::from_str("+0\n-1\n0 , 1x , a , b , r").unwrap_err();
+        assert_eq!(error.kind, InvalidProgram::InvalidState);
+        assert_eq!((error.column, error.text.as_str()), (5, "1x"));
+    }
+
+    #[test]
+    fn test_parse_error_location() {
+        let error = Program::from_str("+0\n# comment\n0,1a,_,_,n").unwrap_err();
+        assert_eq!(
+            error,
+            ParseError {
+                kind: InvalidProgram::InvalidState,
+                line: 3,
+                column: 3,
+                text: String::from("1a"),
+                line_text: String::from("0,1a,_,_,n"),
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "invalid state, expected a number or a name (line 3, column 3)\n  |\n3 | 0,1a,_,_,n\n  |   ^^"
+        );
+
+        let error = Program::from_str("+0\n0,1,_,_").unwrap_err();
+        assert_eq!(error.kind, InvalidProgram::MissingAction);
+        assert_eq!((error.line, error.column, error.text.as_str()), (2, 8, ""));
+
+        let error = Program::from_str("+x!").unwrap_err();
+        assert_eq!(
+            (error.line, error.column, error.text.as_str()),
+            (1, 2, "x!")
+        );
+
+        let error = Program::from_str("-1\n0,1,_,_,n").unwrap_err();
+        assert_eq!(error.kind, InvalidProgram::MissingInitialState);
+        assert_eq!(error.to_string(), "program is missing an initial state");
+    }
+
+    #[test]
+    fn test_duplicate_transition() {
+        let error = Program::from_str("+0\n-1\n0,1,a,b,r\n0,0,a,a,l").unwrap_err();
+        assert_eq!(error.kind, InvalidProgram::DuplicateTransition);
+        assert_eq!((error.line, error.text.as_str()), (4, "0,0,a,a,l"));
+
+        let error = Program::from_str("+0\n0,0,*,=,r\n0,1,*,_,n").unwrap_err();
+        assert_eq!(error.kind, InvalidProgram::DuplicateTransition);
+        assert_eq!(error.line, 3);
+
+        assert!(Program::from_str("+0\n-1\n-1\n0,1,a,b,r\n0,1,*,b,r").is_ok());
+        assert!(nondeterministic::NdProgram::from_str("+0\n0,1,a,b,r\n0,0,a,a,l").is_ok());
+    }
+
+    #[test]
+    fn test_conflicting_state_role() {
+        for (program, state, line) in [
+            ("+0\n-1\n!1\n0,1,*,=,n", 1, 3),
+            ("+0\n!1\n-R1\n0,1,*,=,n", 1, 3),
+            ("!0\n+0\n-1\n0,1,*,=,n", 0, 2),
+            ("+start\n-1\n!start\nstart,1,*,=,n", 2, 3),
+        ] {
+            let error = Program::from_str(program).unwrap_err();
+            assert_eq!(
+                error.kind,
+                InvalidProgram::ConflictingStateRole(State(state)),
+                "{program}"
+            );
+            assert_eq!(error.line, line, "{program}");
+        }
+
+        assert_eq!(
+            InvalidProgram::ConflictingStateRole(State(3)).to_string(),
+            "state 3 is an error state and the initial or a final state"
+        );
+        assert!(Program::from_str("+0\n-0\n!1\n0,1,*,=,n").is_ok());
+    }
+
+    #[test]
+    fn test_includes() {
+        let read = |path: &str| match path {
+            "increment.tng" => Some(include_str!("../examples/next_integer.tng").to_owned()),
+            "error.tng" => Some(String::from("+0\n!1\n0,1,*,=,n")),
+            "self.tng" => Some(String::from("+0\n@include \"self.tng\" 0 1")),
+            _ => None,
+        };
+
+        let program = Program::<Segment>::parse_with_includes(
+            "+0\n-1\n@include \"increment.tng\" 0 2\n@include \"error.tng\" 2 1",
+            read,
+        )
+        .unwrap();
+        let mut machine = TuringMachine::from_tape(VecTape::from_str("_1011_").unwrap());
+        assert!(matches!(
+            machine.execute(&program),
+            Err(ExecutionError::ReachedError(_))
+        ));
+
+        let error = Program::from_str("+0\n@include \"increment.tng\" 0 1").unwrap_err();
+        assert_eq!((error.kind, error.line), (InvalidProgram::IncludeFailed, 2));
+
+        let error = Program::<Segment>::parse_with_includes("+0\n@include increment.tng 0 1", read)
+            .unwrap_err();
+        assert_eq!(
+            (error.kind, error.line),
+            (InvalidProgram::InvalidInclude, 2)
+        );
+
+        let error = Program::<Segment>::parse_with_includes("+0\n@include \"self.tng\" 0 1", read)
+            .unwrap_err();
+        assert_eq!((error.kind, error.line), (InvalidProgram::IncludeFailed, 2));
+
+        let error = Program::<Segment>::parse_with_includes(
+            "+0\n-1\n0,1,_,_,n\n@include \"increment.tng\" 0 1",
+            read,
+        )
+        .unwrap_err();
+        assert_eq!(
+            (error.kind, error.line),
+            (InvalidProgram::DuplicateTransition, 4)
+        );
+    }
+
+    #[test]
+    fn test_seek() {
+        let program = Program::from_str("+0\n-2\n0,1,_,_,r*\n1,2,_,x,l*").unwrap();
+        assert_eq!(
+            program.to_string(),
+            "+0\n-2\n0,1,_,_,n\n1,2,_,x,n\n0,0,*,=,r\n1,1,*,=,l\n"
+        );
+
+        let mut machine = TuringMachine::from_tape(VecTape::from_str("_ab_").unwrap());
+        machine.execute(&program).unwrap();
+        assert_eq!(machine.tape().to_string(), "_abx");
+
+        let error = Program::from_str("+0\n0,1,*,_,r*").unwrap_err();
+        assert_eq!(
+            (error.kind, error.text.as_str()),
+            (InvalidProgram::WildcardSeek, "*")
+        );
+        let error = Program::from_str("+0\n0,1,_,_,n*").unwrap_err();
+        assert_eq!(
+            (error.kind, error.text.as_str()),
+            (InvalidProgram::InvalidAction, "n*")
+        );
+        let error = Program::from_str("+0\n0,0,*,=,r\n0,1,_,_,r*").unwrap_err();
+        assert_eq!(
+            (error.kind, error.line),
+            (InvalidProgram::DuplicateTransition, 3)
+        );
+    }
+
+    #[test]
+    fn test_step() {
+        let program = Program::from_str(include_str!("../examples/append.tng")).unwrap();
+        let tape = VecTape::from_str("_1_").unwrap();
+        let mut machine = TuringMachine::from_tape(tape);
+
+        let outcome = machine.step(&program, program.initial_state()).unwrap();
+        assert_eq!(outcome.written, Segment::Symbol('1'));
+        assert_eq!(outcome.action, Direction::Right);
+        assert!(!outcome.halted);
+
+        let outcome = machine.step(&program, outcome.state).unwrap();
+        assert_eq!(outcome.written, Segment::Symbol('0'));
+        assert!(!outcome.halted);
+
+        let outcome = machine.step(&program, outcome.state).unwrap();
+        assert_eq!(outcome.action, Direction::Stay);
+        assert!(outcome.halted);
+        assert_eq!(machine.tape().to_string(), "_101");
+    }
+
+    #[test]
+    fn test_execute_bounded() {
+        let program = Program::from_str(include_str!("../examples/copy.tng")).unwrap();
+        let mut bounded = TuringMachine::from_tape(VecTape::from_str("_111_").unwrap());
+        let mut unbounded = TuringMachine::from_tape(VecTape::from_str("_111_").unwrap());
+
+        assert_eq!(
+            bounded.execute_bounded(&program, 1000),
+            unbounded.execute(&program)
+        );
+        assert_eq!(bounded.tape(), unbounded.tape());
+
+        let program = Program::from_str("+0\n-1\n0,0,_,_,n").unwrap();
+        let mut machine = TuringMachine::from_tape(VecTape::from_str("_").unwrap());
+        assert_eq!(
+            machine.execute_bounded(&program, 1000),
+            Err(ExecutionError::StepLimitExceeded(1000))
+        );
+        assert_eq!(
+            machine.execute_with_limit(&program, 10),
+            Err(ExecutionError::StepLimitExceeded(10))
+        );
+    }
+
+    #[test]
+    fn test_execute_batch() {
+        let program = Program::from_str(include_str!("../examples/next_integer.tng")).unwrap();
+        let inputs: Vec<_> = (1..=100)
+            .map(|n| VecTape::from_str(&format!("_{n:b}_")).unwrap())
+            .collect();
+        let config = ExecutionConfig {
+            max_steps: Some(1000),
+            ..ExecutionConfig::default()
+        };
+
+        let results = TuringMachine::execute_batch(&program, &inputs, &config);
+
+        assert_eq!(results.len(), inputs.len());
+        for (n, (result, tape)) in (1..=100).zip(results) {
+            assert!(result.is_ok());
+            assert_eq!(tape.to_string().trim_matches('_'), format!("{:b}", n + 1));
+        }
+
+        let program = Program::from_str("+0\n-1\n0,0,*,=,r").unwrap();
+        let results = TuringMachine::execute_batch(&program, &inputs[..1], &config);
+        assert_eq!(results[0].0, Err(ExecutionError::StepLimitExceeded(1000)));
+        assert!(TuringMachine::execute_batch(&program, &[] as &[VecTape], &config).is_empty());
+    }
+
+    #[test]
+    fn test_step_back() {
+        let program = Program::from_str(include_str!("../examples/next_integer.tng")).unwrap();
+        let mut machine = TuringMachine::from_tape(VecTape::from_str("_1011_").unwrap());
+        machine.set_history_limit(100);
+        let mut states = vec![program.initial_state()];
+        let mut tapes = vec![machine.tape().clone()];
+
+        let mut run = machine.run(&program);
+        while let Some(outcome) = run.next() {
+            states.push(outcome.unwrap().state);
+            tapes.push(run.tape().clone());
+        }
+
+        states.pop();
+        tapes.pop();
+        while let Some(state) = machine.step_back() {
+            assert_eq!(Some(state), states.pop());
+            assert_eq!(machine.tape(), &tapes.pop().unwrap());
+        }
+        assert!(states.is_empty());
+
+        machine.set_history_limit(0);
+        machine.execute(&program).unwrap();
+        assert_eq!(machine.step_back(), None);
+    }
+
+    #[test]
+    fn test_snapshot_restore() {
+        let program = Program::from_str(include_str!("../examples/copy.tng")).unwrap();
+        let mut machine = TuringMachine::from_tape(VecTape::from_str("_111_").unwrap());
+        let total = machine.run(&program).count() as u64;
+        let expected = machine.tape().to_string();
+
+        let mut machine = TuringMachine::from_tape(VecTape::from_str("_111_").unwrap());
+        let mut run = machine.run(&program);
+        run.by_ref().take(10).for_each(drop);
+        let snapshot = run.snapshot().unwrap();
+        assert_eq!(snapshot.steps, 10);
+        assert_eq!(Some(snapshot.state), run.state());
+
+        let mut machine = TuringMachine::from_tape(VecTape::from_str("_").unwrap());
+        let mut run = machine.restore(&program, snapshot);
+        assert!(run.by_ref().all(|result| result.is_ok()));
+        assert_eq!(run.steps(), total);
+        assert!(run.snapshot().is_none());
+        assert_eq!(machine.tape().to_string(), expected);
+    }
+
+    #[test]
+    fn test_tape_diff() {
+        let program = Program::from_str(include_str!("../examples/copy.tng")).unwrap();
+        let mut machine = TuringMachine::from_tape(SparseTape::from_str("_111_").unwrap());
+        let mut tapes = vec![machine.tape().clone()];
+        let mut run = machine.run(&program);
+        while let Some(result) = run.next() {
+            result.unwrap();
+            tapes.push(run.tape().clone());
+        }
+
+        // Replaying the diffs of every step restores every tape
+        let mut tape = tapes[0].clone();
+        for pair in tapes.windows(2) {
+            let diff = TapeDiff::between(&pair[0], &pair[1]);
+            assert!(diff.changes().len() <= 1);
+            diff.apply(&mut tape);
+            assert_eq!(tape.to_string(), pair[1].to_string());
+            assert_eq!(tape.position(), pair[1].position());
+        }
+
+        // Tapes of different kinds can be compared as well
+        let diff = TapeDiff::between(
+            &VecTape::from_str("_111_").unwrap(),
+            &tapes[tapes.len() - 1],
+        );
+        assert_eq!(diff.changes().len(), 3);
+        assert!(TapeDiff::between(&tape, &tape).is_empty());
+    }
+
+    #[test]
+    fn test_execute_with_report() {
+        let program = Program::from_str(include_str!("../examples/next_integer.tng")).unwrap();
+        let mut machine = TuringMachine::from_tape(VecTape::from_str("_1011_").unwrap());
+        let config = ExecutionConfig {
+            record_trace: true,
+            ..ExecutionConfig::default()
+        };
+        let mut report = ExecutionReport::default();
+        let state = machine
+            .execute_with(&program, &config, &mut report)
+            .unwrap();
+
+        let mut machine = TuringMachine::from_tape(VecTape::from_str("_1011_").unwrap());
+        let mut trace = trace::Trace::new();
+        assert_eq!(machine.execute_traced(&program, &mut trace), Ok(state));
+        assert_eq!(report.trace, trace);
+
+        assert_eq!(report.steps, trace.len() as u64);
+        assert_eq!(
+            report.writes,
+            trace
+                .steps()
+                .iter()
+                .filter(|step| step.read != step.written)
+                .count() as u64
+        );
+        assert_eq!(report.leftmost, -1);
+        assert_eq!(report.rightmost, 4);
+        assert_eq!(report.cells_visited(), 6);
+        assert_eq!(report.state_visits.values().sum::<u64>(), report.steps);
+        assert_eq!(report.state_visits[&program.initial_state()], 5);
+
+        let mut machine = TuringMachine::from_tape(VecTape::from_str("_1011_").unwrap());
+        let mut report = ExecutionReport::default();
+        machine
+            .execute_with(&program, &ExecutionConfig::default(), &mut report)
+            .unwrap();
+        assert!(report.trace.is_empty());
+    }
+
+    #[test]
+    fn test_execute_with_tape_limit() {
+        let program = Program::from_str("+0\n-1\n0,0,_,1,l").unwrap();
+        let config = ExecutionConfig {
+            max_tape_len: Some(10),
+            ..ExecutionConfig::default()
+        };
+        let mut machine = TuringMachine::from_tape(VecTape::from_str("_").unwrap());
+        let mut report = ExecutionReport::default();
+
+        assert_eq!(
+            machine.execute_with(&program, &config, &mut report),
+            Err(ExecutionError::TapeLimitExceeded(10))
+        );
+        assert_eq!(report.steps, 10);
+        assert_eq!(machine.tape().to_string(), "_1111111111");
+
+        let program = Program::from_str(include_str!("../examples/next_integer.tng")).unwrap();
+        let mut machine = TuringMachine::from_tape(VecTape::from_str("_111_").unwrap());
+        assert!(machine
+            .execute_with(&program, &config, &mut ExecutionReport::default())
+            .is_ok());
+    }
+
+    #[test]
+    fn test_execute_with_loop_detection() {
+        let program = Program::from_str("+0\n-2\n0,1,_,_,r\n1,0,_,_,l\n0,2,1,1,n").unwrap();
+        let config = ExecutionConfig {
+            max_steps: Some(1000),
+            detect_loops: true,
+            ..ExecutionConfig::default()
+        };
+
+        let mut machine = TuringMachine::from_tape(VecTape::from_str("__").unwrap());
+        assert_eq!(
+            machine.execute_with(&program, &config, &mut ExecutionReport::default()),
+            Err(ExecutionError::InfiniteLoopDetected(
+                State::from_str("1").unwrap()
+            ))
+        );
+
+        let mut machine = TuringMachine::from_tape(VecTape::from_str("__").unwrap());
+        assert_eq!(
+            machine.execute_with(
+                &program,
+                &ExecutionConfig {
+                    detect_loops: false,
+                    ..config
+                },
+                &mut ExecutionReport::default()
+            ),
+            Err(ExecutionError::StepLimitExceeded(1000))
+        );
+
+        let mut machine = TuringMachine::from_tape(VecTape::from_str("_1").unwrap());
+        assert_eq!(
+            machine.execute_with(&program, &config, &mut ExecutionReport::default()),
+            Ok(State::from_str("2").unwrap())
+        );
+    }
+
+    #[test]
+    fn test_logical_positions() {
+        fn check<T>(mut tape: T)
+        where
+            T: Tape<Symbol = Segment> + std::fmt::Display,
+        {
+            assert_eq!(tape.position(), 0);
+            assert_eq!(tape.get(0), Segment::Symbol('1'));
+            assert_eq!(tape.get(1), Segment::Symbol('0'));
+            assert_eq!(tape.get(-7), Segment::Empty);
+            assert_eq!(tape.get(5000), Segment::Empty);
+
+            tape.seek(-5);
+            assert_eq!(tape.position(), -5);
+            tape.put(Segment::Symbol('x'));
+            assert_eq!(tape.get(-5), Segment::Symbol('x'));
+            assert_eq!(tape.get(0), Segment::Symbol('1'));
+
+            tape.seek(2);
+            assert_eq!(tape.position(), 2);
+            assert_eq!(*tape.current(), Segment::Symbol('1'));
+            assert_eq!(tape.to_string(), "x____101");
+        }
+
+        check(VecTape::from_str("_101").unwrap());
+        check(VecDequeTape::from_str("_101").unwrap());
+        check(SparseTape::from_str("_101").unwrap());
+        check(PagedTape::from_str("_101").unwrap());
+        check(PackedTape::from_str("_101").unwrap());
+    }
+
+    #[test]
+    fn test_program_builder() {
+        let (zero, one, empty) = (Segment::Symbol('0'), Segment::Symbol('1'), Segment::Empty);
+        let program = ProgramBuilder::new()
+            .initial(0)
+            .finals([3])
+            .transition(0, 0, zero, zero, Direction::Right)
+            .transition(0, 0, one, one, Direction::Right)
+            .transition(0, 1, empty, empty, Direction::Left)
+            .transition(1, 2, zero, one, Direction::Left)
+            .transition(1, 1, one, zero, Direction::Left)
+            .transition(1, 3, empty, one, Direction::Stay)
+            .transition(2, 2, zero, zero, Direction::Left)
+            .transition(2, 2, one, one, Direction::Left)
+            .transition(2, 3, empty, empty, Direction::Right)
+            .build()
+            .unwrap();
+        let parsed = Program::from_str(include_str!("../examples/next_integer.tng")).unwrap();
+
+        for input in ["_0_", "_1011_", "_111_"] {
+            let mut built = TuringMachine::from_tape(VecTape::from_str(input).unwrap());
+            let mut expected = TuringMachine::from_tape(VecTape::from_str(input).unwrap());
+            assert_eq!(built.execute(&program), expected.execute(&parsed));
+            assert_eq!(built.tape(), expected.tape());
+        }
+
+        assert_eq!(
+            ProgramBuilder::<Segment>::new()
+                .finals([1])
+                .build()
+                .unwrap_err(),
+            InvalidProgram::MissingInitialState
+        );
+    }
+
+    #[test]
+    fn test_program_display() {
+        let program = Program::from_str("+0\n-2\n!1\n-3\n0,2,1,1,n\n0,1,_, ,l\n0,0,0,1,r").unwrap();
+        assert_eq!(
+            program.to_tng_string(),
+            "+0\n-2\n-3\n!1\n0,1,_,_,l\n0,0,0,1,r\n0,2,1,1,n\n"
+        );
+
+        for code in [
+            include_str!("../examples/next_integer.tng"),
+            include_str!("../examples/palindrome.tng"),
+            include_str!("../examples/copy.tng"),
+        ] {
+            let program = Program::from_str(code).unwrap();
+            let printed = program.to_string();
+            assert_eq!(Program::from_str(&printed).unwrap().to_string(), printed);
+        }
+    }
+
+    #[test]
+    fn test_named_states() {
+        let code = "+scan\n-done\nscan,scan,1,1,r\nscan,carry,_,_,l\ncarry,carry,1,0,l\ncarry,4,0,1,n\ncarry,4,_,1,n\n4,done,1,1,n\n";
+        let program = Program::from_str(code).unwrap();
+
+        assert_eq!(program.state("scan"), Some(State::from_str("5").unwrap()));
+        assert_eq!(program.state("done"), Some(State::from_str("6").unwrap()));
+        assert_eq!(program.state("carry"), Some(State::from_str("7").unwrap()));
+        assert_eq!(program.state("missing"), None);
+        assert_eq!(program.state_name(State::from_str("4").unwrap()), None);
+        assert_eq!(program.state_name(program.initial_state()), Some("scan"));
+
+        let mut machine = TuringMachine::from_tape(VecTape::from_str("_11_").unwrap());
+        assert_eq!(
+            machine.execute(&program),
+            Ok(program.state("done").unwrap())
+        );
+        assert_eq!(machine.tape().to_string(), "100_");
+
+        assert_eq!(
+            program.to_string(),
+            "+scan\n-done\n4,done,1,1,n\nscan,carry,_,_,l\nscan,scan,1,1,r\ncarry,4,_,1,n\ncarry,4,0,1,n\ncarry,carry,1,0,l\n"
+        );
+
+        assert_eq!(
+            Program::from_str("+0\n0,1a,_,_,n").unwrap_err().kind,
+            InvalidProgram::InvalidState
+        );
+    }
+
+    #[test]
+    fn test_wildcards() {
+        // Moves right over any symbol until the first blank and appends an "x".
+        let program = Program::from_str("+0\n-1\n0,1,_,x,n\n0,0,*,=,r").unwrap();
+        let mut machine = TuringMachine::from_tape(VecTape::from_str("_ab1c_").unwrap());
+        machine.execute(&program).unwrap();
+        assert_eq!(machine.tape().to_string(), "_ab1cx");
+        assert_eq!(program.to_string(), "+0\n-1\n0,1,_,x,n\n0,0,*,=,r\n");
+
+        let program = Program::from_str("+0\n-1\n0,0,a,=,r\n0,0,*,b,r\n0,1,_,_,n").unwrap();
+        let mut machine = TuringMachine::from_tape(VecTape::from_str("_acab_").unwrap());
+        machine.execute(&program).unwrap();
+        assert_eq!(machine.tape().to_string(), "_abab_");
+
+        let program =
+            nondeterministic::NdProgram::from_str("+0\n-1\n0,0,*,=,r\n0,1,*,=,n").unwrap();
+        let machine =
+            nondeterministic::NdTuringMachine::from_tape(VecTape::from_str("_ab_").unwrap());
+        assert!(matches!(
+            machine.execute(&program, 10),
+            nondeterministic::NdOutcome::Accepted { steps: 1, .. }
+        ));
+    }
+
+    /// Example programs with an input and the known part of a tape after
+    /// running them, which is the same on every kind of tape.
+    const TAPE_CASES: [(&str, &str, &str); 5] = [
+        (
+            include_str!("../examples/next_integer.tng"),
+            "_111_",
+            "1000_",
+        ),
+        (include_str!("../examples/append.tng"), "_111_", "_11101"),
+        (
+            include_str!("../examples/copy.tng"),
+            "_111111_",
+            "_111111_111111",
+        ),
+        (include_str!("../examples/swap.tng"), "_abba_", "_baab_"),
+        (
+            include_str!("../examples/palindrome.tng"),
+            "_110000011_",
+            "___________",
+        ),
+    ];
+
+    #[test]
+    fn test_tape_backends() {
+        fn run<T>(program: &Program, input: &str) -> (Result<State, ExecutionError>, i64, String)
+        where
+            T: Tape<Symbol = Segment> + FromStr<Err = InvalidProgram>,
+        {
+            let mut machine = TuringMachine::from_tape(T::from_str(input).unwrap());
+            let result = machine.execute(program);
+            let tape = machine.tape();
+
+            (
+                result,
+                tape.position(),
+                tape.iter().map(char::from).collect(),
+            )
+        }
+
+        for (code, input, expected) in TAPE_CASES {
+            let program = Program::from_str(code).unwrap();
+            let result = run::<VecTape>(&program, input);
+            assert!(result.0.is_ok(), "{input}");
+            assert_eq!(result.2, expected, "{input}");
+
+            assert_eq!(run::<VecDequeTape>(&program, input), result, "{input}");
+            assert_eq!(run::<SparseTape>(&program, input), result, "{input}");
+            assert_eq!(run::<PagedTape>(&program, input), result, "{input}");
+            assert_eq!(run::<PackedTape>(&program, input), result, "{input}");
+            assert_eq!(run::<RleTape>(&program, input), result, "{input}");
+        }
+    }
+
+    #[test]
+    fn test_tape_views() {
+        fn known<T: Tape<Symbol = Segment>>(mut tape: T) -> (i64, String, Option<String>) {
+            tape.left();
+            tape.left();
+            tape.put(Segment::Symbol('1'));
+            tape.seek(3);
+            assert_eq!(tape.len(), tape.iter().count());
+            let slice = tape
+                .as_slice()
+                .map(|slice| slice.iter().copied().map(char::from).collect());
+
+            (tape.start(), tape.iter().map(char::from).collect(), slice)
+        }
+
+        let expected = (-2, "1_ab__".to_owned(), None);
+        assert_eq!(
+            known(VecTape::from_str("_ab_").unwrap()),
+            (-2, "1_ab__".to_owned(), Some("1_ab__".to_owned()))
+        );
+        assert_eq!(known(SparseTape::from_str("_ab_").unwrap()), expected);
+        assert_eq!(known(PagedTape::from_str("_ab_").unwrap()), expected);
+        assert_eq!(known(PackedTape::from_str("_ab_").unwrap()), expected);
+        assert_eq!(known(RleTape::from_str("_ab_").unwrap()), expected);
+
+        let tape = CircularTape::from_str("_ab_").unwrap();
+        assert_eq!((tape.start(), Tape::len(&tape)), (-1, 4));
+        assert_eq!(tape.as_slice(), Some(&tape.inner[..]));
+    }
+
+    #[test]
+    fn test_sparse_tape_left_heavy() {
+        // Keeps writing ones while moving left, which would shift a `VecTape`
+        // on every single step.
+        let program = Program::from_str("+0\n-1\n0,0,_,1,l").unwrap();
+        let mut machine = TuringMachine::from_tape(SparseTape::from_str("_").unwrap());
+
+        assert_eq!(
+            machine.execute_bounded(&program, 200_000),
+            Err(ExecutionError::StepLimitExceeded(200_000))
+        );
+
+        let tape = machine.tape();
+        assert_eq!(tape.position(), -200_000);
+        assert_eq!(tape.min_index(), -200_000);
+        assert_eq!(tape.max_index(), 0);
+        assert_eq!(tape.cells_written(), 200_000);
+    }
+
+    #[test]
+    fn test_paged_tape_page_boundaries() {
+        let program = Program::from_str("+0\n-1\n0,0,_,1,l").unwrap();
+        let mut machine = TuringMachine::from_tape(PagedTape::from_str("_").unwrap());
+
+        assert_eq!(
+            machine.execute_bounded(&program, 10_000),
+            Err(ExecutionError::StepLimitExceeded(10_000))
+        );
+
+        let tape = machine.tape();
+        assert_eq!(tape.position(), -10_000);
+        assert_eq!(tape.min_index(), -10_000);
+        assert_eq!(tape.pages(), 4);
+        assert_eq!(tape.to_string(), format!("_{}", "1".repeat(10_000)));
+
+        let program = Program::from_str("+0\n-1\n0,0,_,1,r\n0,1,1,1,n").unwrap();
+        let mut machine = TuringMachine::from_tape(machine.tape().clone());
+        machine.execute(&program).unwrap();
+        assert_eq!(machine.tape().position(), -9_999);
+        assert_eq!(machine.tape().to_string(), "1".repeat(10_001));
+    }
+
+    #[test]
+    fn test_rle_tape() {
+        for (code, input, _) in TAPE_CASES {
+            let program = Program::from_str(code).unwrap();
+
+            let mut expected = TuringMachine::from_tape(VecTape::from_str(input).unwrap());
+            let steps = expected.run(&program).count() as u64;
+
+            let mut machine = TuringMachine::from_tape(RleTape::from_str(input).unwrap());
+            let (_, accelerated) = machine.execute_accelerated(&program, None).unwrap();
+            assert_eq!(accelerated, steps);
+            assert_eq!(machine.tape().to_string(), expected.tape().to_string());
+            assert_eq!(machine.tape().position(), expected.tape().position());
+        }
+
+        let program = Program::from_str("+0\n-1\n0,0,_,1,l").unwrap();
+        let mut machine = TuringMachine::from_tape(RleTape::from_str("_").unwrap());
+        assert_eq!(
+            machine.execute_bounded(&program, 1000),
+            Err(ExecutionError::StepLimitExceeded(1000))
+        );
+        assert_eq!(machine.tape().position(), -1000);
+        assert_eq!(machine.tape().runs(), 2);
+        assert_eq!(machine.tape().get(-500), Segment::Symbol('1'));
+    }
+
+    #[test]
+    fn test_execute_accelerated() {
+        // Adds two unary numbers separated by "+"
+        let program =
+            Program::from_str("+0\n-3\n0,0,1,1,r\n0,1,+,1,r\n1,1,1,1,r\n1,2,_,_,l\n2,3,1,_,n")
+                .unwrap();
+        let input = format!("_{}+{}_", "1".repeat(100_000), "1".repeat(50_000));
+
+        let mut expected = TuringMachine::from_tape(VecTape::from_str(&input).unwrap());
+        let steps = expected.run(&program).count() as u64;
+
+        let mut machine = TuringMachine::from_tape(RleTape::from_str(&input).unwrap());
+        let (state, accelerated) = machine.execute_accelerated(&program, None).unwrap();
+        assert_eq!(state, State::from_str("3").unwrap());
+        assert_eq!(accelerated, steps);
+        assert_eq!(machine.tape().to_string(), expected.tape().to_string());
+        assert_eq!(machine.tape().runs(), 3);
+
+        let mut machine = TuringMachine::from_tape(RleTape::from_str(&input).unwrap());
+        assert_eq!(
+            machine.execute_accelerated(&program, Some(1000)),
+            Err(ExecutionError::StepLimitExceeded(1000))
+        );
+        assert_eq!(machine.tape().position(), 1000);
+
+        let program = Program::from_str("+0\n-1\n0,0,*,=,r").unwrap();
+        let mut machine = TuringMachine::from_tape(RleTape::from_str("_11_").unwrap());
+        assert_eq!(
+            machine.execute_accelerated(&program, None),
+            Err(ExecutionError::InfiniteLoopDetected(
+                program.initial_state()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_packed_tape() {
+        let program = Program::from_str("+0\n-1\n0,0,_,1,l").unwrap();
+        let mut machine = TuringMachine::from_tape(PackedTape::from_str("_").unwrap());
+        assert_eq!(
+            machine.execute_bounded(&program, 1000),
+            Err(ExecutionError::StepLimitExceeded(1000))
+        );
+        assert_eq!(machine.tape().position(), -1000);
+        assert_eq!(machine.tape().bytes(), 251);
+        assert_eq!(machine.tape().to_string(), format!("_{}", "1".repeat(1000)));
+
+        assert_eq!(
+            PackedTape::from_str("_abcd_"),
+            Err(InvalidProgram::TooManySymbols)
+        );
+    }
+
+    #[test]
+    fn test_custom_alphabet() {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        enum Mark {
+            Blank,
+            A,
+            B,
+            C,
+        }
+
+        impl Symbol for Mark {
+            const BLANK: Self = Self::Blank;
+        }
+
+        impl FromStr for Mark {
+            type Err = InvalidProgram;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match s {
+                    "_" => Ok(Self::Blank),
+                    "a" => Ok(Self::A),
+                    "b" => Ok(Self::B),
+                    "c" => Ok(Self::C),
+                    _ => Err(InvalidProgram::InvalidSegment),
+                }
+            }
+        }
+
+        // Rotate a -> b -> c -> a until the first blank.
+        let program =
+            Program::<Mark>::parse("+0\n-1\n0,0,a,b,r\n0,0,b,c,r\n0,0,c,a,r\n0,1,_,_,n").unwrap();
+        let tape = VecTape::new(vec![Mark::A, Mark::B, Mark::C], 0);
+        let mut machine = TuringMachine::from_tape(tape);
+        machine.execute(&program).unwrap();
+
+        assert_eq!(
+            machine.tape().inner,
+            [Mark::B, Mark::C, Mark::A, Mark::Blank]
+        );
+    }
+
+    #[test]
+    fn test_run() {
+        let program = Program::from_str(include_str!("../examples/next_integer.tng")).unwrap();
+        let mut machine = TuringMachine::from_tape(VecTape::from_str("_1011_").unwrap());
+
+        let steps: Vec<_> = machine.run(&program).collect::<Result<_, _>>().unwrap();
+        assert!(steps.last().unwrap().halted);
+        assert!(steps[..steps.len() - 1].iter().all(|step| !step.halted));
+        assert_eq!(machine.tape().to_string(), "_1100_");
+
+        let program = Program::from_str(include_str!("../examples/palindrome.tng")).unwrap();
+        let mut machine = TuringMachine::from_tape(VecTape::from_str("_10_").unwrap());
+        let mut run = machine.run(&program);
+
+        assert!(run.by_ref().last().unwrap().is_err());
+        assert_eq!(run.next(), None);
+        assert_eq!(run.state(), None);
+    }
+
+    #[test]
+    fn test_execute_async() {
+        use std::{
+            future::Future,
+            pin::pin,
+            task::{Context, Poll, Waker},
+        };
+
+        let mut cx = Context::from_waker(Waker::noop());
+
+        let program = Program::from_str(include_str!("../examples/next_integer.tng")).unwrap();
+        let mut machine = TuringMachine::from_tape(VecTape::from_str("_1011_").unwrap());
+        let mut future = pin!(machine.execute_async(&program, CancellationToken::new()));
+        assert!(matches!(future.as_mut().poll(&mut cx), Poll::Ready(Ok(_))));
+
+        // A machine that never halts yields until it is cancelled
+        let program = Program::from_str("+0\n-1\n0,0,_,1,r").unwrap();
+        let mut machine = TuringMachine::from_tape(VecTape::from_str("_").unwrap());
+        let cancel = CancellationToken::new();
+        {
+            let mut future = pin!(machine.execute_async(&program, cancel.clone()));
+
+            for _ in 0..3 {
+                assert!(future.as_mut().poll(&mut cx).is_pending());
+            }
+            cancel.cancel();
+            assert_eq!(
+                future.as_mut().poll(&mut cx),
+                Poll::Ready(Err(ExecutionError::Cancelled(State(0))))
+            );
+        }
+        assert_eq!(machine.tape().to_string().len(), 3 * 4096 + 1);
+    }
+
+    #[test]
+    fn test_execute_for() {
+        use std::time::Duration;
+
+        let program = Program::from_str(include_str!("../examples/next_integer.tng")).unwrap();
+        let mut machine = TuringMachine::from_tape(VecTape::from_str("_1011_").unwrap());
+
+        let progress = machine.execute_for(&program, Duration::ZERO).unwrap();
+        assert_eq!(
+            progress,
+            Progress::Paused {
+                state: program.initial_state(),
+                steps: 0
+            }
+        );
+        assert!(matches!(
+            machine.resume_for(&program, program.initial_state(), Duration::from_mins(1)),
+            Ok(Progress::Halted(_))
+        ));
+        assert_eq!(machine.tape().to_string(), "_1100_");
+
+        // A machine that never halts is paused after its budget
+        let program = Program::from_str("+0\n-1\n0,0,_,1,r").unwrap();
+        let mut machine = TuringMachine::from_tape(VecTape::from_str("_").unwrap());
+        let Ok(Progress::Paused { state, steps }) =
+            machine.execute_for(&program, Duration::from_millis(10))
+        else {
+            panic!("machine halted");
+        };
+        assert_eq!(state, program.initial_state());
+        assert!(steps > 0 && steps % 4096 == 0);
+    }
+
+    #[test]
+    fn test_entry_points() {
+        let source = "+0\n-done\n@entry clear wipe\n@entry set 0\n0,done,_,1,n\nwipe,done,*,_,n";
+        let program = Program::from_str(source).unwrap();
+
+        assert_eq!(program.entry_point("set"), Some(program.initial_state()));
+        assert_eq!(
+            program
+                .entry_points()
+                .map(|(name, _)| name)
+                .collect::<Vec<_>>(),
+            ["clear", "set"]
+        );
+        assert_eq!(
+            Program::from_str(&program.to_string()).unwrap().to_string(),
+            program.to_string()
+        );
+
+        let wipe = program.entry_point("clear").unwrap();
+        let mut machine = TuringMachine::from_tape(VecTape::from_str("_1").unwrap());
+        machine.execute_from(&program, wipe).unwrap();
+        assert_eq!(machine.tape().to_string(), "__");
+
+        // States that are only reachable from an entry point are kept
+        let mut pruned = program.clone();
+        assert!(pruned.prune_unreachable().is_empty());
+        assert!(program.validate().is_empty());
+
+        for (source, kind, line) in [
+            (
+                "+0\n@entry a 0\n@entry a 1",
+                InvalidProgram::DuplicateEntry,
+                3,
+            ),
+            ("+0\n@entry a", InvalidProgram::InvalidEntry, 2),
+            ("+0\n@entry 1 1", InvalidProgram::InvalidEntry, 2),
+        ] {
+            let error = Program::from_str(source).unwrap_err();
+            assert_eq!((error.kind, error.line), (kind, line));
+        }
+    }
+
+    #[test]
+    fn test_introspection() {
+        let program =
+            Program::from_str("+0\n-2\n-R3\n!4\n1,2,a,b,l\n0,1,a,a,r\n0,3,_,_,n\n1,4,*,=,n")
+                .unwrap();
+        let state = |s| State::from_str(s).unwrap();
+
+        assert_eq!(program.initial_state(), state("0"));
+        assert_eq!(
+            program.final_states(),
+            BTreeSet::from([state("2"), state("3")])
+        );
+        assert_eq!(program.error_states(), BTreeSet::from([state("4")]));
+        assert_eq!(
+            program.states(),
+            BTreeSet::from(["0", "1", "2", "3", "4"].map(state))
+        );
+
+        let transitions: Vec<_> = program
+            .transitions()
+            .map(|transition| {
+                (
+                    transition.from(),
+                    transition.condition(),
+                    transition.write(),
+                    transition.action(),
+                    transition.to(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            transitions,
+            [
+                (
+                    state("0"),
+                    Segment::Empty,
+                    Segment::Empty,
+                    Direction::Stay,
+                    state("3")
+                ),
+                (
+                    state("0"),
+                    Segment::Symbol('a'),
+                    Segment::Symbol('a'),
+                    Direction::Right,
+                    state("1")
+                ),
+                (
+                    state("1"),
+                    Segment::Symbol('a'),
+                    Segment::Symbol('b'),
+                    Direction::Left,
+                    state("2")
+                ),
+            ]
+        );
+
+        let wildcards: Vec<_> = program
+            .wildcards()
+            .map(|wildcard| (wildcard.from(), wildcard.write(), wildcard.to()))
+            .collect();
+        assert_eq!(wildcards, [(state("1"), None, state("4"))]);
+    }
+
+    #[test]
+    fn test_direction() {
+        for direction in [Direction::Left, Direction::Right, Direction::Stay] {
+            assert_eq!(Direction::from_str(&direction.to_string()), Ok(direction));
+            assert_eq!(direction.opposite().offset(), -direction.offset());
+        }
+        assert_eq!(Direction::from_str("R"), Ok(Direction::Right));
+        assert_eq!(Direction::from_str("_"), Ok(Direction::Stay));
+        assert_eq!(Direction::from_str("x"), Err(InvalidProgram::InvalidAction));
+    }
+
+    #[test]
+    fn test_verdicts() {
+        // Accepts inputs with an even number of "1"s
+        let program = Program::from_str(
+            "+even\n-A3\n-R odd_end\neven,odd,1,1,r\nodd,even,1,1,r\neven,3,_,_,n\n\
+             odd,odd_end,_,_,n",
+        )
+        .unwrap();
+
+        for (input, verdict) in [
+            ("_", Verdict::Accepted),
+            ("_1", Verdict::Rejected),
+            ("_11", Verdict::Accepted),
+            ("_111", Verdict::Rejected),
+        ] {
+            let mut machine = TuringMachine::from_tape(VecTape::from_str(input).unwrap());
+            assert_eq!(machine.decide(&program).unwrap().1, verdict, "{input}");
+        }
+
+        assert_eq!(
+            program.to_string(),
+            "+even\n-3\n-R odd_end\neven,3,_,_,n\neven,odd,1,1,r\nodd,odd_end,_,_,n\n\
+             odd,even,1,1,r\n"
+        );
+        assert_eq!(
+            Program::from_str("+0\n-R1\n0,1,_,_,n").unwrap().to_string(),
+            "+0\n-R1\n0,1,_,_,n\n"
+        );
+
+        // Final states named like the markers keep working
+        let program = Program::from_str("+0\n-Reject\n-A\n0,Reject,_,_,n").unwrap();
+        let reject = program.state("Reject").unwrap();
+        assert_eq!(program.verdict(reject), Some(Verdict::Accepted));
+        assert_eq!(
+            program.verdict(program.state("A").unwrap()),
+            Some(Verdict::Accepted)
+        );
+
+        // Named final states keep their verdicts when printed and parsed again
+        let program = Program::from_str(
+            "+0\n-Reject\n-A\n-R Rx\n-A R_1\n0,Reject,a,a,n\n0,A,b,b,n\n0,Rx,c,c,n\n\
+             0,R_1,d,d,n",
+        )
+        .unwrap();
+        let printed = Program::from_str(&program.to_string()).unwrap();
+        assert_eq!(printed.to_string(), program.to_string());
+        for (name, verdict) in [
+            ("Reject", Verdict::Accepted),
+            ("A", Verdict::Accepted),
+            ("Rx", Verdict::Rejected),
+            ("R_1", Verdict::Accepted),
+        ] {
+            let state = printed.state(name).unwrap();
+            assert_eq!(printed.verdict(state), Some(verdict), "{name}");
+            assert_eq!(program.verdict(program.state(name).unwrap()), Some(verdict));
+        }
+
+        // Names that collide with the markers are rejected
+        assert_eq!(
+            Program::from_str("+0\n-R1\n0,R1,_,_,n").unwrap_err().kind,
+            InvalidProgram::InvalidState
+        );
+        assert_eq!(
+            Program::from_str("+0\n-A 2\n0,A2,_,_,n").unwrap_err().kind,
+            InvalidProgram::InvalidState
+        );
+    }
+
+    #[test]
+    fn test_output() {
+        assert!(VecTape::from_str("____").unwrap().trim().is_empty());
+        assert_eq!(
+            VecTape::from_str("_a").unwrap().trim(),
+            [Segment::Symbol('a')]
+        );
+
+        let program = Program::from_str(include_str!("../examples/next_integer.tng")).unwrap();
+        let mut machine = TuringMachine::from_tape(RleTape::from_str("_111_").unwrap());
+        machine.execute(&program).unwrap();
+        assert_eq!(machine.tape().to_string(), "1000_");
+        assert_eq!(machine.output(), "1000");
+
+        let machine = TuringMachine::from_tape(SparseTape::from_str("__").unwrap());
+        assert_eq!(machine.output(), "");
+    }
+
+    #[test]
+    fn test_integer_codecs() {
+        assert_eq!(encode_binary(11).to_string(), "_1011_");
+        assert_eq!(encode_unary(3).to_string(), "_111_");
+        assert_eq!(encode_unary_args(&[2, 0, 1]).to_string(), "_11__1_");
+        assert_eq!(encode_binary_args(&[5, 0]).to_string(), "_101_0_");
+        assert_eq!(*encode_unary(2).current(), Segment::Symbol('1'));
+
+        for n in [0, 1, 2, 7, 100] {
+            assert_eq!(decode_unary(&encode_unary(n)), Some(n));
+        }
+        for n in [0, 1, 2, 255, u64::MAX] {
+            assert_eq!(decode_binary(&encode_binary(n)), Some(n));
+        }
+        assert_eq!(
+            decode_unary_args(&encode_unary_args(&[0, 3, 0, 1, 0])),
+            Some(vec![3, 0, 1])
+        );
+        assert_eq!(
+            decode_binary_args(&encode_binary_args(&[6, 0, 9])),
+            Some(vec![6, 0, 9])
+        );
+
+        for tape in ["_12_", "_1_1_", "_"] {
+            assert_eq!(decode_binary(&VecTape::from_str(tape).unwrap()), None);
+        }
+        assert_eq!(decode_unary(&VecTape::from_str("_101_").unwrap()), None);
+        assert_eq!(
+            decode_binary(&VecTape::from_str(&format!("_1{}_", "0".repeat(64))).unwrap()),
+            None
+        );
+
+        // Arithmetic programs can be checked against real numbers
+        let program = Program::from_str(include_str!("../examples/next_integer.tng")).unwrap();
+        for n in 0..64 {
+            let mut machine = TuringMachine::from_tape(encode_binary(n));
+            machine.execute(&program).unwrap();
+            assert_eq!(decode_binary(machine.tape()), Some(n + 1));
+        }
+    }
+
+    #[test]
+    fn test_render_window() {
+        let mut tape = VecTape::from_str("_0110").unwrap();
+        tape.right();
+        tape.right();
+        assert_eq!(tape.render_window(1), "…1 [1] 0");
+        assert_eq!(tape.render_window(0), "…[1]…");
+        assert_eq!(tape.render_window(3), "_ 0 1 [1] 0 _ _");
+
+        tape.seek(-3);
+        assert_eq!(tape.render_window(1), "_ [_] _…");
+        assert_eq!(VecTape::from_str("__").unwrap().render_window(1), "_ [_] _");
+    }
+
+    #[test]
+    fn test_execute_detailed() {
+        let program = Program::from_str("+0\n-1\n!9\n0,0,a,b,r\n0,9,_,_,n").unwrap();
+        let mut machine = TuringMachine::from_tape(VecTape::from_str("_aaa").unwrap());
+
+        let failure = machine.execute_detailed(&program).unwrap_err();
+        assert_eq!(failure.error, ExecutionError::ReachedError(State(9)));
+        assert_eq!(failure.steps, 3);
+        assert_eq!(failure.position, 3);
+        assert_eq!(
+            failure.window.len(),
+            2 * ExecutionFailure::<Segment>::RADIUS + 1
+        );
+        assert_eq!(
+            failure.to_string(),
+            "reached error state 9 (after 3 steps, at position 3)\n  _ _ b b b [_] _ _ _ _ _"
+        );
+
+        let program = Program::from_str("+0\n-1\n0,1,*,=,n").unwrap();
+        assert_eq!(machine.execute_detailed(&program), Ok(State(1)));
+
+        for (error, message) in [
+            (
+                ExecutionError::UndefinedBehavior(State(7), Segment::Empty),
+                "no transition for \"_\" in state 7",
+            ),
+            (
+                ExecutionError::StepLimitExceeded(10),
+                "did not halt within 10 steps",
+            ),
+            (
+                ExecutionError::InfiniteLoopDetected(State(2)),
+                "entered an infinite loop in state 2",
+            ),
+            (
+                ExecutionError::TapeLimitExceeded(5),
+                "visited more than 5 cells",
+            ),
+            (ExecutionError::Cancelled(State(0)), "cancelled in state 0"),
+            (
+                ExecutionError::FellOffTape(State(3)),
+                "moved left of the start of the tape in state 3",
+            ),
+        ] {
+            assert_eq!(error.to_string(), message);
+        }
+    }
+
+    #[test]
+    fn test_execute_from_unknown_state() {
+        let program = Program::from_str("+0\n-1\n0,1,*,x,r").unwrap();
+        let mut machine = TuringMachine::from_tape(VecTape::from_str("_a").unwrap());
+
+        assert_eq!(
+            machine.execute_from(&program, State(7)),
+            Err(ExecutionError::UndefinedBehavior(
+                State(7),
+                Segment::Symbol('a')
+            ))
+        );
+        assert_eq!(machine.execute(&program), Ok(State(1)));
+        assert_eq!(machine.tape().to_string(), "_x_");
+
+        let mut run = machine.run_from(&program, State(1));
+        assert!(run.next().unwrap().is_err());
+        assert_eq!(run.state(), None);
+    }
+
+    #[test]
+    fn test_trailing_comments() {
+        let code = "+0 # start\n-R1 # reject\n!2\n0,1,#,#,r # hash\n0,2,*,=,n  #  anything else ";
+        let program = Program::from_str(code).unwrap();
+        assert_eq!(
+            program.to_string(),
+            "+0 # start\n-R1 # reject\n!2\n0,1,#,#,r # hash\n0,2,*,=,n # anything else\n"
+        );
+        assert_eq!(
+            Program::from_str(&program.to_string()).unwrap().to_string(),
+            program.to_string()
+        );
+        assert_eq!(
+            program.comment(CommentTarget::Wildcard(State(0))),
+            Some("anything else")
+        );
+        assert_eq!(program.comment(CommentTarget::Error(State(2))), None);
+
+        // Duplicates are dropped together with their comments
+        let error = Program::from_str("+0\n0,0,a,a,r # first\n0,1,a,a,r # second").unwrap_err();
+        assert_eq!(error.kind, InvalidProgram::DuplicateTransition);
+    }
+
+    #[test]
+    fn test_circular_tape() {
+        let mut tape = CircularTape::from_str("_ab").unwrap();
+        assert_eq!((tape.len(), tape.position()), (3, 0));
+
+        tape.left();
+        tape.left();
+        assert_eq!(
+            (tape.position(), *tape.current()),
+            (1, Segment::Symbol('b'))
+        );
+        assert_eq!(tape.get(-2), Segment::Symbol('b'));
+        tape.seek(5);
+        assert_eq!((tape.position(), *tape.current()), (-1, Segment::Empty));
+
+        // Walks right forever, which only loops on a bounded tape
+        let program = Program::from_str("+0\n-1\n0,0,*,1,r").unwrap();
+        let config = ExecutionConfig {
+            max_steps: Some(100),
+            detect_loops: true,
+            ..ExecutionConfig::default()
+        };
+        let mut machine = TuringMachine::from_tape(CircularTape::blank(4));
+        assert_eq!(
+            machine.execute_with(&program, &config, &mut ExecutionReport::default()),
+            Err(ExecutionError::InfiniteLoopDetected(State(0)))
+        );
+        assert_eq!(machine.tape().to_string(), "1111");
+
+        assert_eq!(
+            CircularTape::from_str("").unwrap_err(),
+            InvalidProgram::EmptyTape
+        );
+    }
+
+    #[test]
+    fn test_left_edge() {
+        let program = Program::from_str("+0\n-2\n0,1,*,x,l\n1,2,*,y,l").unwrap();
+        let run = |left_edge| {
+            let mut machine = TuringMachine::from_tape(VecTape::from_str("_a").unwrap());
+            machine.set_left_edge(left_edge);
+            let result = machine.execute(&program);
+            (result, machine.output())
+        };
+
+        assert_eq!(run(LeftEdge::Unbounded), (Ok(State(2)), "yx".to_string()));
+        assert_eq!(run(LeftEdge::Stay), (Ok(State(2)), "y".to_string()));
+        assert_eq!(
+            run(LeftEdge::Fail),
+            (Err(ExecutionError::FellOffTape(State(0))), "a".to_string())
+        );
+
+        // Runs are not skipped past the edge
+        let walker = Program::from_str("+0\n-1\n0,0,*,=,l").unwrap();
+        let mut machine = TuringMachine::from_tape(RleTape::from_str("_1").unwrap());
+        machine.set_left_edge(LeftEdge::Fail);
+        assert_eq!(
+            machine.execute_accelerated(&walker, None),
+            Err(ExecutionError::FellOffTape(State(0)))
+        );
+    }
 }
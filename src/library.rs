@@ -0,0 +1,308 @@
+//! Files holding several named programs.
+//!
+//! A library is a file of programs, each written between a line
+//! `@program name` and a line `@end`, so that collections of routines can be
+//! shipped as a single file. Outside of the programs, only comments and empty
+//! lines are allowed. A program can include the programs defined before it in
+//! the library by their name:
+//!
+//! ```
+//! use std::str::FromStr;
+//!
+//! use turing::{decode_binary, encode_binary, library::ProgramLibrary, TuringMachine};
+//!
+//! let library = ProgramLibrary::from_str(&format!(
+//!     "# Arithmetic on binary numbers\n\
+//!      @program increment\n{}@end\n\
+//!      @program add_two\n\
+//!      +0\n-2\n\
+//!      @include \"increment\" 0 1\n\
+//!      @include \"increment\" 1 2\n\
+//!      @end",
+//!     include_str!("../examples/next_integer.tng"),
+//! ))
+//! .unwrap();
+//!
+//! let mut machine = TuringMachine::from_tape(encode_binary(5));
+//! machine.execute(library.get("add_two").unwrap()).unwrap();
+//! assert_eq!(decode_binary(machine.tape()), Some(7));
+//! ```
+//!
+//! Programs outside of the library can include its programs with
+//! [`Program::parse_with_includes`] and [`ProgramLibrary::source`].
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+use crate::{
+    error::{InvalidProgram, ParseError},
+    program::is_state_name,
+    tape::Symbol,
+    Program, Segment,
+};
+
+/// A collection of named programs, as described in the [module
+/// documentation](self).
+#[derive(Debug, Clone)]
+pub struct ProgramLibrary<S = Segment> {
+    programs: BTreeMap<String, Program<S>>,
+    sources: BTreeMap<String, String>,
+}
+
+impl<S> ProgramLibrary<S> {
+    /// Returns the program with the given name, or [`None`] if there is no
+    /// such program.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&Program<S>> {
+        self.programs.get(name)
+    }
+
+    /// Returns the source of the program with the given name as it was
+    /// written in the library, or [`None`] if there is no such program.
+    ///
+    /// This can be used to include the programs of the library into other
+    /// programs:
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use turing::{library::ProgramLibrary, Program};
+    /// let library = ProgramLibrary::from_str("@program flip\n+0\n-1\n0,1,1,0,n\n@end").unwrap();
+    /// let program: Program = Program::parse_with_includes("+0\n-1\n@include \"flip\" 0 1", |name| {
+    ///     library.source(name).map(str::to_owned)
+    /// })
+    /// .unwrap();
+    ///
+    /// assert_eq!(program.to_string(), "+0\n-1\n0,1,1,0,n\n");
+    /// ```
+    #[must_use]
+    pub fn source(&self, name: &str) -> Option<&str> {
+        self.sources.get(name).map(String::as_str)
+    }
+
+    /// Returns the names of the programs, sorted alphabetically.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.programs.keys().map(String::as_str)
+    }
+
+    /// Returns the programs and their names, sorted alphabetically by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Program<S>)> {
+        self.programs
+            .iter()
+            .map(|(name, program)| (name.as_str(), program))
+    }
+
+    /// Returns the number of programs.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.programs.len()
+    }
+
+    /// Returns whether the library has no programs.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.programs.is_empty()
+    }
+
+    /// Returns the programs by their name.
+    #[must_use]
+    pub fn into_programs(self) -> BTreeMap<String, Program<S>> {
+        self.programs
+    }
+}
+
+impl<S> ProgramLibrary<S>
+where
+    S: Symbol + FromStr<Err = InvalidProgram>,
+{
+    /// Parse a library of programs over a custom alphabet, like
+    /// [`Program::parse`].
+    ///
+    /// Errors are reported with the line numbers of the library.
+    ///
+    /// # Errors
+    ///
+    /// This method will error if a program is invalid, has the same name as
+    /// an earlier one or is not terminated with `@end`, or if there is
+    /// anything other than comments and empty lines outside of the programs.
+    pub fn parse(s: &str) -> Result<Self, ParseError> {
+        let mut library = Self {
+            programs: BTreeMap::new(),
+            sources: BTreeMap::new(),
+        };
+        // The name of the current program and the number of its first line
+        let mut current: Option<(String, usize)> = None;
+        let mut lines = Vec::new();
+
+        for (number, line) in s.lines().enumerate() {
+            let mut tokens = line.split_whitespace();
+            let invalid = || ParseError::whole_line(InvalidProgram::InvalidLibrary, s, number);
+
+            match (tokens.next(), tokens.next(), tokens.next()) {
+                (Some("@program"), Some(name), None) if current.is_none() => {
+                    if !is_state_name(name) {
+                        return Err(invalid());
+                    }
+                    if library.programs.contains_key(name) {
+                        return Err(ParseError::whole_line(
+                            InvalidProgram::DuplicateProgram,
+                            s,
+                            number,
+                        ));
+                    }
+                    current = Some((name.to_owned(), number + 1));
+                }
+                (Some("@program"), ..) => return Err(invalid()),
+                (Some("@end"), None, None) => {
+                    let (name, start) = current.take().ok_or_else(invalid)?;
+                    library.add(s, name, start, &lines.join("\n"))?;
+                    lines.clear();
+                }
+                _ if current.is_some() => lines.push(line),
+                (None, ..) => {}
+                (Some(token), ..) if token.starts_with(['#', '/']) => {}
+                _ => return Err(invalid()),
+            }
+        }
+
+        match current {
+            Some((_, start)) => Err(ParseError::whole_line(
+                InvalidProgram::InvalidLibrary,
+                s,
+                start - 1,
+            )),
+            None => Ok(library),
+        }
+    }
+
+    /// Parse the program `name` from `source`, which starts on line `start`
+    /// of the library `s`, counting from 0.
+    fn add(&mut self, s: &str, name: String, start: usize, source: &str) -> Result<(), ParseError> {
+        let program = Program::parse_with_includes(source, |path| self.sources.get(path).cloned())
+            .map_err(|mut error| {
+                if error.line == 0 {
+                    return ParseError::whole_line(error.kind, s, start - 1);
+                }
+                error.line += start;
+                error
+            })?;
+
+        self.programs.insert(name.clone(), program);
+        self.sources.insert(name, source.to_owned());
+
+        Ok(())
+    }
+}
+
+impl FromStr for ProgramLibrary {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl<S> fmt::Display for ProgramLibrary<S>
+where
+    S: Symbol + fmt::Display,
+{
+    /// Writes the programs sorted by name, separated by empty lines.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, (name, program)) in self.iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "@program {name}\n{program}@end\n")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<S> FromIterator<(String, Program<S>)> for ProgramLibrary<S>
+where
+    S: Symbol + fmt::Display,
+{
+    /// Collect programs into a library, keeping the last program of every
+    /// name.
+    fn from_iter<I: IntoIterator<Item = (String, Program<S>)>>(iter: I) -> Self {
+        let programs: BTreeMap<String, Program<S>> = iter.into_iter().collect();
+        let sources = programs
+            .iter()
+            .map(|(name, program)| (name.clone(), program.to_string()))
+            .collect();
+
+        Self { programs, sources }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::{library::*, *};
+
+    #[test]
+    fn test_library() {
+        let source = "// Routines\n\n@program right\n+0\n-1\n0,1,*,=,r\n@end\n\n\
+                      @program twice\n+0\n-2\n@include \"right\" 0 1\n@include \"right\" 1 2\n@end";
+        let library = ProgramLibrary::from_str(source).unwrap();
+
+        assert_eq!(library.len(), 2);
+        assert_eq!(library.names().collect::<Vec<_>>(), ["right", "twice"]);
+        assert_eq!(
+            library.get("twice").unwrap().to_string(),
+            "+0\n-2\n0,1,*,=,r\n1,2,*,=,r\n"
+        );
+        assert_eq!(library.source("right"), Some("+0\n-1\n0,1,*,=,r"));
+        assert!(library.get("left").is_none());
+
+        // Writing and reading a library keeps its programs
+        let written = library.to_string();
+        assert!(written.starts_with("@program right\n+0\n-1\n0,1,*,=,r\n@end\n\n@program twice\n"));
+        let read = ProgramLibrary::from_str(&written).unwrap();
+        assert_eq!(read.to_string(), written);
+
+        let collected: ProgramLibrary = library.clone().into_programs().into_iter().collect();
+        assert_eq!(collected.to_string(), written);
+    }
+
+    #[test]
+    fn test_library_errors() {
+        for (source, kind, line) in [
+            ("+0\n-1", InvalidProgram::InvalidLibrary, 1),
+            ("@program a\n+0\n-1", InvalidProgram::InvalidLibrary, 1),
+            ("@end", InvalidProgram::InvalidLibrary, 1),
+            ("@program 1a\n+0\n@end", InvalidProgram::InvalidLibrary, 1),
+            (
+                "@program a\n@program b\n@end",
+                InvalidProgram::InvalidLibrary,
+                2,
+            ),
+            (
+                "@program a\n+0\n@end\n@program a\n+0\n@end",
+                InvalidProgram::DuplicateProgram,
+                4,
+            ),
+            (
+                "\n@program a\n+0\n0,1,_,_,q\n@end",
+                InvalidProgram::InvalidAction,
+                4,
+            ),
+            (
+                "\n@program a\n-1\n@end",
+                InvalidProgram::MissingInitialState,
+                2,
+            ),
+            // Only programs defined earlier can be included
+            (
+                "@program a\n+0\n-1\n@include \"b\" 0 1\n@end\n@program b\n+0\n@end",
+                InvalidProgram::IncludeFailed,
+                4,
+            ),
+        ] {
+            let error = ProgramLibrary::from_str(source).unwrap_err();
+            assert_eq!((&error.kind, error.line), (&kind, line), "{source}");
+        }
+
+        assert!(ProgramLibrary::from_str("# Nothing here\n")
+            .unwrap()
+            .is_empty());
+    }
+}
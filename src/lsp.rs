@@ -0,0 +1,728 @@
+//! A language server for `.tng` files.
+//!
+//! The [`Server`] speaks the [Language Server Protocol] over any reader and
+//! writer, and is run on standard input and output by the `turing-lsp`
+//! binary. It supports:
+//!
+//! - diagnostics for every problem found by a [`Parser`], and warnings for
+//!   states that cannot be reached from the initial state or an entry point
+//! - going to the definition of a state, which is its first transition or, if
+//!   it has none, the line marking it as a final or error state
+//! - hovering over a state to see what kind of state it is, and over a
+//!   transition to see what it does
+//!
+//! Documents are synchronized in full on every change.
+//!
+//! [Language Server Protocol]: https://microsoft.github.io/language-server-protocol/
+use std::{
+    collections::HashMap,
+    io::{self, BufRead, Write},
+    ops::Range,
+    str::FromStr,
+};
+
+use serde_json::{json, Value};
+
+use crate::{
+    parser::{Diagnostic, Parser, Severity},
+    program::{split_comment, split_verdict, transition_fields},
+    validation::ValidationWarning,
+    Program, State,
+};
+
+/// The JSON-RPC error code for requests with an unknown method.
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// Read a message of the base protocol, which is a JSON value preceded by a
+/// `Content-Length` header.
+///
+/// Returns [`None`] at the end of `input`.
+///
+/// # Errors
+///
+/// This function will error if reading fails or the message is malformed.
+pub fn read_message(input: &mut impl BufRead) -> io::Result<Option<Value>> {
+    let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_owned());
+    let mut length = None;
+
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                length = Some(
+                    value
+                        .trim()
+                        .parse::<usize>()
+                        .map_err(|_| invalid("invalid Content-Length"))?,
+                );
+            }
+        }
+    }
+
+    let mut content = vec![0; length.ok_or_else(|| invalid("missing Content-Length"))?];
+    input.read_exact(&mut content)?;
+
+    serde_json::from_slice(&content)
+        .map(Some)
+        .map_err(|e| invalid(&e.to_string()))
+}
+
+/// Write a message of the base protocol.
+///
+/// # Errors
+///
+/// This function will error if writing fails.
+pub fn write_message(output: &mut impl Write, message: &Value) -> io::Result<()> {
+    let content = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{content}", content.len())?;
+    output.flush()
+}
+
+/// Returns the [`Diagnostic`]s for the source of a program: every problem
+/// found by a [`Parser`], followed by a warning for every state that cannot be
+/// reached from the initial state or an entry point.
+#[must_use]
+pub fn diagnostics(source: &str) -> Vec<Diagnostic> {
+    let (program, mut diagnostics): (Option<Program>, _) = Parser::new().parse(source);
+    let Some(program) = program else {
+        return diagnostics;
+    };
+
+    let mentions = mentions(source);
+    for warning in program.validate() {
+        let ValidationWarning::UnreachableState(state) = warning else {
+            continue;
+        };
+        let key = state_key(&program, state);
+        let Some(mention) = mentions.iter().find(|mention| mention.key() == key) else {
+            continue;
+        };
+        let line_text = source.lines().nth(mention.line).unwrap_or_default();
+
+        diagnostics.push(Diagnostic {
+            severity: Severity::Warning,
+            message: format!("state {} cannot be reached", mention.token),
+            line: mention.line + 1,
+            column: line_text[..mention.range.start].chars().count() + 1,
+            text: mention.token.to_owned(),
+            line_text: line_text.to_owned(),
+        });
+    }
+
+    diagnostics
+}
+
+/// How a state is used where it is mentioned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Role {
+    Initial,
+    Final,
+    Error,
+    From,
+    To,
+    Include,
+    Entry,
+}
+
+/// A state mentioned in the source of a program.
+#[derive(Debug)]
+struct Mention<'s> {
+    /// The line of the mention, counting from 0.
+    line: usize,
+    /// The byte range of the state in the line.
+    range: Range<usize>,
+    token: &'s str,
+    role: Role,
+}
+
+impl Mention<'_> {
+    /// Returns a key that is equal for all mentions of the same state.
+    fn key(&self) -> String {
+        State::from_str(self.token)
+            .map_or_else(|_| self.token.to_owned(), |state| state.to_string())
+    }
+}
+
+/// Returns the key of `state` in `program`, like [`Mention::key`].
+fn state_key(program: &Program, state: State) -> String {
+    program
+        .state_name(state)
+        .map_or_else(|| state.to_string(), str::to_owned)
+}
+
+/// Returns every mention of a state in `source`, in order.
+fn mentions(source: &str) -> Vec<Mention<'_>> {
+    let mut mentions = Vec::new();
+
+    for (number, line) in source.lines().enumerate() {
+        let mut mention = |start: usize, token: &'_ str, role| {
+            mentions.push(Mention {
+                line: number,
+                range: start..start + token.len(),
+                token: &line[start..start + token.len()],
+                role,
+            });
+        };
+
+        if line.starts_with(['#', '/']) || line.is_empty() {
+            continue;
+        }
+        let (line, _) = split_comment(line);
+
+        let marker = match line.as_bytes().first() {
+            Some(b'+') => Some(Role::Initial),
+            Some(b'-') => Some(Role::Final),
+            Some(b'!') => Some(Role::Error),
+            _ => None,
+        };
+
+        if marker == Some(Role::Final) {
+            let (_, token) = split_verdict(&line[1..]);
+            mention(line.len() - token.len(), token, Role::Final);
+        } else if let Some(role) = marker {
+            mention(1, &line[1..], role);
+        } else if let Some(entry) = line.strip_prefix("@entry") {
+            if let Some(token) = entry.split_whitespace().nth(1) {
+                mention(
+                    token.as_ptr() as usize - line.as_ptr() as usize,
+                    token,
+                    Role::Entry,
+                );
+            }
+        } else if line.starts_with("@include") {
+            let Some(quote) = line.rfind('"') else {
+                continue;
+            };
+            for token in line[quote + 1..].split_whitespace() {
+                mention(
+                    token.as_ptr() as usize - line.as_ptr() as usize,
+                    token,
+                    Role::Include,
+                );
+            }
+        } else {
+            let mut fields = transition_fields(line);
+            if let (Some((from, from_range)), Some((to, to_range))) = (fields.next(), fields.next())
+            {
+                mention(from_range.start, from, Role::From);
+                mention(to_range.start, to, Role::To);
+            }
+        }
+    }
+
+    mentions
+}
+
+/// Returns the number of UTF-16 code units in `text`, which LSP positions
+/// are counted in.
+fn utf16_len(text: &str) -> usize {
+    text.encode_utf16().count()
+}
+
+/// Returns the byte offset in `line` of the UTF-16 offset `character`.
+fn byte_offset(line: &str, character: usize) -> usize {
+    let mut units = 0;
+    for (offset, c) in line.char_indices() {
+        if units >= character {
+            return offset;
+        }
+        units += c.len_utf16();
+    }
+
+    line.len()
+}
+
+/// Returns the LSP range of the bytes `range` on line `line` of `source`.
+fn lsp_range(source: &str, line: usize, range: Range<usize>) -> Value {
+    let text = source.lines().nth(line).unwrap_or_default();
+    json!({
+        "start": {"line": line, "character": utf16_len(&text[..range.start])},
+        "end": {"line": line, "character": utf16_len(&text[..range.end])},
+    })
+}
+
+/// Returns the LSP representation of a [`Diagnostic`].
+fn lsp_diagnostic(diagnostic: &Diagnostic) -> Value {
+    let (line, start) = match diagnostic.line {
+        0 => (0, 0),
+        line => {
+            let before: String = diagnostic
+                .line_text
+                .chars()
+                .take(diagnostic.column - 1)
+                .collect();
+            (line - 1, utf16_len(&before))
+        }
+    };
+    let severity = match diagnostic.severity {
+        Severity::Error => 1,
+        Severity::Warning => 2,
+    };
+
+    json!({
+        "range": {
+            "start": {"line": line, "character": start},
+            "end": {"line": line, "character": start + utf16_len(&diagnostic.text)},
+        },
+        "severity": severity,
+        "source": "turing",
+        "message": diagnostic.message,
+    })
+}
+
+/// Describe the transition written in `line`, if it is one, and return the
+/// range it takes up in the line without its trailing comment.
+fn describe_transition(line: &str) -> Option<(String, Range<usize>)> {
+    let (code, _) = split_comment(line);
+    let fields: Vec<_> = transition_fields(code).collect();
+    let [(from, ref from_range), (to, _), (condition, _), (write, _), (action, ref action_range)] =
+        fields[..]
+    else {
+        return None;
+    };
+
+    let reading = match condition {
+        "*" => String::from("any other segment"),
+        condition => format!("`{condition}`"),
+    };
+    let writing = match write {
+        "=" => String::from("write back the segment"),
+        write if write == condition => String::from("keep the segment"),
+        write => format!("write `{write}`"),
+    };
+    let moving = match action {
+        "l" | "L" => "move left",
+        "r" | "R" => "move right",
+        "n" | "N" | "" | "_" | " " => "stay",
+        "l*" | "L*" => "move left until it is found",
+        "r*" | "R*" => "move right until it is found",
+        _ => return None,
+    };
+
+    Some((
+        format!(
+            "In state `{from}`, when reading {reading}: {writing}, {moving} and go to state \
+             `{to}`."
+        ),
+        from_range.start..action_range.end,
+    ))
+}
+
+/// A language server for `.tng` files.
+#[derive(Debug, Default)]
+pub struct Server {
+    /// The source of every open document, by URI.
+    documents: HashMap<String, String>,
+    shutdown: bool,
+}
+
+impl Server {
+    /// Create a server without open documents.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Handle messages from `input` and write the responses to `output` until
+    /// the client sends the `exit` notification or `input` ends.
+    ///
+    /// Returns whether the client requested a shutdown before exiting, in
+    /// which case the server should exit successfully.
+    ///
+    /// # Errors
+    ///
+    /// This method will error if reading or writing a message fails.
+    pub fn run(&mut self, mut input: impl BufRead, mut output: impl Write) -> io::Result<bool> {
+        while let Some(message) = read_message(&mut input)? {
+            if message["method"] == "exit" {
+                break;
+            }
+
+            for response in self.handle(&message) {
+                write_message(&mut output, &response)?;
+            }
+        }
+
+        Ok(self.shutdown)
+    }
+
+    /// Handle a request or notification, returning the messages to send back
+    /// to the client.
+    pub fn handle(&mut self, message: &Value) -> Vec<Value> {
+        let params = &message["params"];
+        let id = message.get("id").cloned();
+        let respond = |result: Value| {
+            id.as_ref()
+                .map(|id| json!({"jsonrpc": "2.0", "id": id, "result": result}))
+                .into_iter()
+                .collect()
+        };
+
+        match message["method"].as_str().unwrap_or_default() {
+            "initialize" => respond(json!({
+                "capabilities": {
+                    "textDocumentSync": 1,
+                    "definitionProvider": true,
+                    "hoverProvider": true,
+                },
+                "serverInfo": {"name": "turing-lsp"},
+            })),
+            "shutdown" => {
+                self.shutdown = true;
+                respond(Value::Null)
+            }
+            "textDocument/didOpen" => {
+                let document = &params["textDocument"];
+                let uri = document["uri"].as_str().unwrap_or_default();
+                let text = document["text"].as_str().unwrap_or_default();
+                self.documents.insert(uri.to_owned(), text.to_owned());
+                vec![self.publish(uri)]
+            }
+            "textDocument/didChange" => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                let changes = params["contentChanges"].as_array();
+                if let Some(text) = changes
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change["text"].as_str())
+                {
+                    self.documents.insert(uri.to_owned(), text.to_owned());
+                }
+                vec![self.publish(uri)]
+            }
+            "textDocument/didClose" => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                self.documents.remove(uri);
+                vec![json!({
+                    "jsonrpc": "2.0",
+                    "method": "textDocument/publishDiagnostics",
+                    "params": {"uri": uri, "diagnostics": []},
+                })]
+            }
+            "textDocument/definition" => respond(self.definition(params).unwrap_or(Value::Null)),
+            "textDocument/hover" => respond(self.hover(params).unwrap_or(Value::Null)),
+            method => match &id {
+                // Notifications that are not supported are ignored
+                None => Vec::new(),
+                Some(id) => vec![json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "error": {
+                        "code": METHOD_NOT_FOUND,
+                        "message": format!("unsupported method {method}"),
+                    },
+                })],
+            },
+        }
+    }
+
+    /// Returns the notification with the diagnostics of the document at
+    /// `uri`.
+    fn publish(&self, uri: &str) -> Value {
+        let source = self.documents.get(uri).map_or("", String::as_str);
+        let diagnostics: Vec<Value> = diagnostics(source).iter().map(lsp_diagnostic).collect();
+
+        json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/publishDiagnostics",
+            "params": {"uri": uri, "diagnostics": diagnostics},
+        })
+    }
+
+    /// Returns the source of the document and the line and byte offset of
+    /// the position in the parameters of a request.
+    fn position<'s>(&'s self, params: &Value) -> Option<(&'s str, usize, usize)> {
+        let source = self
+            .documents
+            .get(params["textDocument"]["uri"].as_str()?)?;
+        let line = usize::try_from(params["position"]["line"].as_u64()?).ok()?;
+        let character = usize::try_from(params["position"]["character"].as_u64()?).ok()?;
+
+        let text = source.lines().nth(line)?;
+        Some((source, line, byte_offset(text, character)))
+    }
+
+    /// Returns the location of the definition of the state at the position
+    /// in the parameters of a definition request.
+    fn definition(&self, params: &Value) -> Option<Value> {
+        let (source, line, offset) = self.position(params)?;
+        let mentions = mentions(source);
+        let key = mentions
+            .iter()
+            .find(|mention| mention.line == line && mention.range.contains(&offset))?
+            .key();
+
+        let same = || mentions.iter().filter(|mention| mention.key() == key);
+        let definition = same()
+            .find(|mention| mention.role == Role::From)
+            .or_else(|| same().find(|mention| matches!(mention.role, Role::Final | Role::Error)))
+            .or_else(|| same().next())?;
+
+        Some(json!({
+            "uri": params["textDocument"]["uri"],
+            "range": lsp_range(source, definition.line, definition.range.clone()),
+        }))
+    }
+
+    /// Returns the hover information for the state or transition at the
+    /// position in the parameters of a hover request.
+    fn hover(&self, params: &Value) -> Option<Value> {
+        let (source, line, offset) = self.position(params)?;
+        let mentions = mentions(source);
+        let text = source.lines().nth(line)?;
+
+        let Some(mention) = mentions
+            .iter()
+            .find(|mention| mention.line == line && mention.range.contains(&offset))
+        else {
+            let (description, range) = describe_transition(text)?;
+            return Some(json!({
+                "contents": {"kind": "markdown", "value": description},
+                "range": lsp_range(source, line, range),
+            }));
+        };
+
+        let key = mention.key();
+        let same = || mentions.iter().filter(|other| other.key() == key);
+        let mut kinds = Vec::new();
+        for (role, kind) in [
+            (Role::Initial, "initial state"),
+            (Role::Final, "final state"),
+            (Role::Error, "error state"),
+            (Role::Entry, "entry point"),
+        ] {
+            if same().any(|other| other.role == role) {
+                kinds.push(kind);
+            }
+        }
+        if kinds.is_empty() {
+            kinds.push("state");
+        }
+        let transitions = same().filter(|other| other.role == Role::From).count();
+
+        Some(json!({
+            "contents": {
+                "kind": "markdown",
+                "value": format!(
+                    "`{}`: {}, with {transitions} outgoing transition{}",
+                    mention.token,
+                    kinds.join(" and "),
+                    if transitions == 1 { "" } else { "s" }
+                ),
+            },
+            "range": lsp_range(source, line, mention.range.clone()),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::lsp::*;
+
+    const URI: &str = "file:///program.tng";
+
+    fn open(server: &mut Server, text: &str) -> Value {
+        server
+            .handle(&json!({
+                "jsonrpc": "2.0",
+                "method": "textDocument/didOpen",
+                "params": {"textDocument": {"uri": URI, "languageId": "tng", "version": 1, "text": text}},
+            }))
+            .remove(0)
+    }
+
+    fn request(server: &mut Server, method: &str, line: usize, character: usize) -> Value {
+        server
+            .handle(&json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": method,
+                "params": {
+                    "textDocument": {"uri": URI},
+                    "position": {"line": line, "character": character},
+                },
+            }))
+            .remove(0)["result"]
+            .clone()
+    }
+
+    #[test]
+    fn test_lsp_diagnostics() {
+        let mut server = Server::new();
+        let published = open(
+            &mut server,
+            "+start\n-done\nstart,done,_,_,n\nlost,done,_,_,n\nstart,dön e,a,a,r",
+        );
+
+        assert_eq!(published["params"]["uri"], URI);
+        assert_eq!(
+            published["params"]["diagnostics"],
+            json!([
+                {
+                    "range": {"start": {"line": 4, "character": 6}, "end": {"line": 4, "character": 11}},
+                    "severity": 1,
+                    "source": "turing",
+                    "message": "invalid state, expected a number or a name",
+                },
+                {
+                    "range": {"start": {"line": 3, "character": 0}, "end": {"line": 3, "character": 4}},
+                    "severity": 2,
+                    "source": "turing",
+                    "message": "state lost cannot be reached",
+                },
+            ])
+        );
+
+        let published = server.handle(&json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didChange",
+            "params": {
+                "textDocument": {"uri": URI, "version": 2},
+                "contentChanges": [{"text": "+start\n-done\nstart,done,_,_,n"}],
+            },
+        }));
+        assert_eq!(published[0]["params"]["diagnostics"], json!([]));
+
+        // Only the state of a rejecting final state is highlighted
+        let unreachable = &diagnostics("+0\n-1\n-R no\n0,1,_,_,n")[0];
+        assert_eq!((unreachable.column, unreachable.text.as_str()), (4, "no"));
+    }
+
+    #[test]
+    fn test_lsp_definition() {
+        let mut server = Server::new();
+        open(
+            &mut server,
+            "+start\n-done\nstart,loop,_,_,r\nloop,loop,a,a,r\nloop,done,_,_,n",
+        );
+
+        // The "to" state of the first transition
+        assert_eq!(
+            request(&mut server, "textDocument/definition", 2, 7),
+            json!({
+                "uri": URI,
+                "range": {"start": {"line": 3, "character": 0}, "end": {"line": 3, "character": 4}},
+            })
+        );
+        // A final state is defined by its marker
+        assert_eq!(
+            request(&mut server, "textDocument/definition", 4, 5)["range"]["start"],
+            json!({"line": 1, "character": 1})
+        );
+        assert_eq!(
+            request(&mut server, "textDocument/definition", 3, 12),
+            Value::Null
+        );
+    }
+
+    #[test]
+    fn test_lsp_hover() {
+        let mut server = Server::new();
+        open(&mut server, "+0\n-1\n0,0,a,b,r\n0,1,*,=,n");
+
+        assert_eq!(
+            request(&mut server, "textDocument/hover", 2, 0)["contents"]["value"],
+            "`0`: initial state, with 2 outgoing transitions"
+        );
+        assert_eq!(
+            request(&mut server, "textDocument/hover", 2, 6)["contents"]["value"],
+            "In state `0`, when reading `a`: write `b`, move right and go to state `0`."
+        );
+        assert_eq!(
+            request(&mut server, "textDocument/hover", 3, 6)["contents"]["value"],
+            "In state `0`, when reading any other segment: write back the segment, stay and go to \
+             state `1`."
+        );
+
+        open(&mut server, "+0\n-1\n@entry other 2\n0,1,_,_,n\n2,1,_,_,n");
+        assert_eq!(
+            request(&mut server, "textDocument/hover", 2, 13)["contents"]["value"],
+            "`2`: entry point, with 1 outgoing transition"
+        );
+        assert!(diagnostics("+0\n-1\n@entry other 2\n0,1,_,_,n\n2,1,_,_,n").is_empty());
+    }
+
+    #[test]
+    fn test_lsp_aligned_transitions() {
+        let mut server = Server::new();
+        let source = "+0 # start\n-1\n  0 , 1 ,\t#\t, # , r # done\n\t0, 0 , a, b, l\n1 ,0,b,b,n";
+        open(&mut server, source);
+
+        let states: Vec<_> = mentions(source)
+            .iter()
+            .map(|mention| (mention.line, mention.range.clone(), mention.token))
+            .collect();
+        assert_eq!(
+            states,
+            [
+                (0, 1..2, "0"),
+                (1, 1..2, "1"),
+                (2, 2..3, "0"),
+                (2, 6..7, "1"),
+                (3, 1..2, "0"),
+                (3, 4..5, "0"),
+                (4, 0..1, "1"),
+                (4, 3..4, "0"),
+            ]
+        );
+
+        assert_eq!(
+            request(&mut server, "textDocument/hover", 2, 6)["contents"]["value"],
+            "`1`: final state, with 1 outgoing transition"
+        );
+        let hover = request(&mut server, "textDocument/hover", 2, 10);
+        assert_eq!(
+            hover["contents"]["value"],
+            "In state `0`, when reading `#`: keep the segment, move right and go to state `1`."
+        );
+        assert_eq!(
+            hover["range"],
+            json!({"start": {"line": 2, "character": 2}, "end": {"line": 2, "character": 19}})
+        );
+        assert_eq!(
+            request(&mut server, "textDocument/hover", 3, 8)["contents"]["value"],
+            "In state `0`, when reading `a`: write `b`, move left and go to state `0`."
+        );
+        assert_eq!(
+            request(&mut server, "textDocument/definition", 4, 3)["range"]["start"],
+            json!({"line": 2, "character": 2})
+        );
+        assert!(diagnostics(source).is_empty());
+    }
+
+    #[test]
+    fn test_lsp_session() {
+        let messages = [
+            json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {}}),
+            json!({"jsonrpc": "2.0", "method": "initialized", "params": {}}),
+            json!({"jsonrpc": "2.0", "id": 2, "method": "workspace/symbol", "params": {}}),
+            json!({"jsonrpc": "2.0", "id": 3, "method": "shutdown"}),
+            json!({"jsonrpc": "2.0", "method": "exit"}),
+        ];
+        let mut input = Vec::new();
+        for message in &messages {
+            write_message(&mut input, message).unwrap();
+        }
+
+        let mut output = Vec::new();
+        assert!(Server::new().run(input.as_slice(), &mut output).unwrap());
+
+        let mut output = output.as_slice();
+        let initialized = read_message(&mut output).unwrap().unwrap();
+        assert_eq!(initialized["result"]["capabilities"]["hoverProvider"], true);
+        let unsupported = read_message(&mut output).unwrap().unwrap();
+        assert_eq!(unsupported["error"]["code"], METHOD_NOT_FOUND);
+        let shutdown = read_message(&mut output).unwrap().unwrap();
+        assert_eq!(shutdown, json!({"jsonrpc": "2.0", "id": 3, "result": null}));
+        assert_eq!(read_message(&mut output).unwrap(), None);
+    }
+}
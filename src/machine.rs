@@ -1,9 +1,315 @@
-use crate::{tape::Tape, ExecutionError, Move, Program, State};
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt,
+    future::Future,
+    iter::FusedIterator,
+    num::NonZeroUsize,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    dispatch::StateTable,
+    observer::{Observer, Observers},
+    program::Transition,
+    tape::{RleTape, Symbol, Tape},
+    trace::{Trace, TraceStep},
+    Direction, ExecutionError, ExecutionFailure, Program, ReplayError, Segment, State, Verdict,
+};
+
+/// The outcome of a single [`TuringMachine::step`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StepOutcome<S = Segment> {
+    /// The state the machine is in after the step.
+    pub state: State,
+    /// The segment that was written to the tape.
+    pub written: S,
+    /// The movement that was performed after writing.
+    pub action: Direction,
+    /// Whether [`StepOutcome::state`] is a final state of the program.
+    pub halted: bool,
+}
+
+/// The result of a single step of a [`Run`].
+pub type StepResult<S = Segment> = Result<StepOutcome<S>, ExecutionError<S>>;
+
+/// The result and final tape of one input of
+/// [`TuringMachine::execute_batch`].
+pub type BatchResult<T> = (Result<State, ExecutionError<<T as Tape>::Symbol>>, T);
+
+/// The result of following a transition of a [`StateTable`], together with
+/// the index of the state it leads to.
+type Indexed<R, S> = Result<(usize, R), ExecutionError<S>>;
+
+/// An iterator over the steps of a [`Program`], created with
+/// [`TuringMachine::run`].
+///
+/// The iterator ends after the step that reached a final state or after the
+/// first error.
+#[derive(Debug)]
+pub struct Run<'a, T>
+where
+    T: Tape,
+{
+    machine: &'a mut TuringMachine<T>,
+    table: StateTable<T::Symbol>,
+    /// The index of the current state in `table`.
+    index: Option<usize>,
+    steps: u64,
+}
+
+impl<T> Run<'_, T>
+where
+    T: Tape,
+{
+    /// Returns a reference to the [`Tape`] of the running machine.
+    #[must_use]
+    pub fn tape(&self) -> &T {
+        self.machine.tape()
+    }
+
+    /// Returns the state the machine is in, or [`None`] if the run is over.
+    #[must_use]
+    pub fn state(&self) -> Option<State> {
+        self.index.map(|index| self.table.state(index))
+    }
+
+    /// Returns the number of steps performed so far, including those before
+    /// the snapshot the run was restored from.
+    #[must_use]
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    /// Capture the tape, state and step count of the run, or [`None`] if the
+    /// run is over.
+    ///
+    /// The run can be continued later with [`TuringMachine::restore`].
+    #[must_use]
+    pub fn snapshot(&self) -> Option<MachineSnapshot<T>>
+    where
+        T: Clone,
+    {
+        Some(MachineSnapshot {
+            tape: self.machine.tape.clone(),
+            state: self.state()?,
+            steps: self.steps,
+        })
+    }
+}
+
+impl<T> Iterator for Run<'_, T>
+where
+    T: Tape,
+{
+    type Item = StepResult<T::Symbol>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let result = self.machine.step_indexed(&self.table, self.index?);
+        self.steps += 1;
+
+        self.index = match &result {
+            Ok((index, outcome)) if !outcome.halted => Some(*index),
+            _ => None,
+        };
+
+        Some(result.map(|(_, outcome)| outcome))
+    }
+}
+
+impl<T> FusedIterator for Run<'_, T> where T: Tape {}
+
+/// A snapshot of an execution in progress, which can be used to resume it
+/// later, for example after storing it on disk.
+///
+/// Snapshots are taken with [`Run::snapshot`] and resumed with
+/// [`TuringMachine::restore`]. A [`TuringMachine`] only owns its tape, while
+/// the current state and the step count belong to the [`Run`] of a program,
+/// so the run is snapshotted instead of the machine. For the same reason,
+/// restoring needs the program again and continues as a new [`Run`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MachineSnapshot<T> {
+    /// The tape, including the cursor position.
+    pub tape: T,
+    /// The state the machine is in.
+    pub state: State,
+    /// The number of steps performed so far.
+    pub steps: u64,
+}
+
+/// The number of steps [`TuringMachine::execute_async`] and
+/// [`TuringMachine::execute_for`] perform before they check whether to stop.
+const STEPS_PER_CHECK: u64 = 4096;
+
+/// The outcome of a [`TuringMachine::execute_for`] that did not fail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Progress {
+    /// The program reached the given final state.
+    Halted(State),
+    /// The time budget expired before the program halted. Execution can be
+    /// continued with [`TuringMachine::resume_for`].
+    Paused {
+        /// The state the machine is in.
+        state: State,
+        /// The number of steps performed within the budget.
+        steps: u64,
+    },
+}
+
+/// A flag for stopping a [`TuringMachine::execute_async`] from another thread
+/// or task.
+///
+/// Clones share the flag, so one clone can be passed to the execution while
+/// another one is kept to cancel it.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// Create a token that is not cancelled yet.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request the cancellation of every execution using this token.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns whether [`CancellationToken::cancel`] was called on this token
+    /// or one of its clones.
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// A future that is pending once, to let other tasks run.
+struct YieldNow(bool);
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            Poll::Ready(())
+        } else {
+            self.0 = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+/// Options for [`TuringMachine::execute_with`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExecutionConfig {
+    /// The maximum number of steps to perform, or [`None`] to run until the
+    /// program halts.
+    pub max_steps: Option<u64>,
+    /// The maximum number of cells the cursor may visit, or [`None`] to let
+    /// the tape grow without bounds.
+    ///
+    /// This protects the host from running out of memory when running
+    /// untrusted programs. Cells are counted from the leftmost to the
+    /// rightmost position the cursor was at.
+    pub max_tape_len: Option<u64>,
+    /// Whether to stop with [`ExecutionError::InfiniteLoopDetected`] once the
+    /// machine repeats a configuration of state, cursor position and tape
+    /// contents.
+    ///
+    /// Loops are found with Brent's cycle detection, which keeps a single
+    /// copy of the tape around and reports a loop at most about twice as many
+    /// steps after it was entered as it takes to go around it once.
+    pub detect_loops: bool,
+    /// Whether to record every step in [`ExecutionReport::trace`].
+    pub record_trace: bool,
+}
+
+/// Statistics about an execution, filled by [`TuringMachine::execute_with`].
+///
+/// Positions are relative to the cursor position at the start of the run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecutionReport<S = Segment> {
+    /// The number of steps performed.
+    pub steps: u64,
+    /// The number of steps that replaced the segment under the cursor with a
+    /// different one.
+    pub writes: u64,
+    /// The leftmost position the cursor was at.
+    pub leftmost: i64,
+    /// The rightmost position the cursor was at.
+    pub rightmost: i64,
+    /// The number of steps performed in each state.
+    pub state_visits: HashMap<State, u64>,
+    /// Every step performed, if [`ExecutionConfig::record_trace`] is set.
+    pub trace: Trace<S>,
+}
+
+impl<S> ExecutionReport<S> {
+    /// Returns the number of cells the cursor was on at least once.
+    #[must_use]
+    pub fn cells_visited(&self) -> u64 {
+        self.rightmost.abs_diff(self.leftmost) + 1
+    }
+}
+
+impl<S> Default for ExecutionReport<S> {
+    fn default() -> Self {
+        Self {
+            steps: 0,
+            writes: 0,
+            leftmost: 0,
+            rightmost: 0,
+            state_visits: HashMap::new(),
+            trace: Trace::new(),
+        }
+    }
+}
+
+/// What happens when a transition moves the cursor left of position 0.
+///
+/// Bounding the tape on the left gives the semi-infinite tape of many
+/// textbooks, where the input starts in the first cell. Position 0 is where
+/// the cursor starts on tapes created with [`crate::VecTape::new`] or parsed
+/// from a string.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LeftEdge {
+    /// The tape extends to the left without bounds.
+    #[default]
+    Unbounded,
+    /// The cursor stays at position 0, as if the transition did not move.
+    Stay,
+    /// Execution fails with [`ExecutionError::FellOffTape`].
+    Fail,
+}
+
+/// The information needed to undo a step.
+#[derive(Debug, Clone, Copy)]
+struct Undo<S> {
+    from: State,
+    read: S,
+    action: Direction,
+}
 
 /// The actual turing machine that can execute [`Program`]s.
 #[derive(Debug)]
-pub struct TuringMachine<T> {
+pub struct TuringMachine<T>
+where
+    T: Tape,
+{
     tape: T,
+    history: VecDeque<Undo<T::Symbol>>,
+    history_limit: usize,
+    left_edge: LeftEdge,
+    observers: Observers<T::Symbol>,
 }
 
 impl<T> TuringMachine<T>
@@ -13,7 +319,13 @@ where
     /// Create a new [`TuringMachine`] from a [`Tape`].
     #[must_use]
     pub fn from_tape(tape: T) -> Self {
-        Self { tape }
+        Self {
+            tape,
+            history: VecDeque::new(),
+            history_limit: 0,
+            left_edge: LeftEdge::Unbounded,
+            observers: Observers(Vec::new()),
+        }
     }
 
     /// Returns a reference to the internal [`Tape`] used by the machine.
@@ -29,42 +341,985 @@ where
         &mut self.tape
     }
 
+    /// Returns the contents of the tape from the leftmost to the rightmost
+    /// non-empty segment, as they are displayed.
+    ///
+    /// This is the usual place for a program to leave its result:
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use turing::{Program, TuringMachine, VecTape};
+    /// let program = Program::from_str(include_str!("../examples/next_integer.tng")).unwrap();
+    /// let mut machine = TuringMachine::from_tape(VecTape::from_str("_1011_").unwrap());
+    /// machine.execute(&program).unwrap();
+    ///
+    /// assert_eq!(machine.output(), "1100");
+    /// ```
+    #[must_use]
+    pub fn output(&self) -> String
+    where
+        T: fmt::Display,
+        T::Symbol: fmt::Display,
+    {
+        let blank = T::Symbol::BLANK.to_string();
+
+        self.tape
+            .to_string()
+            .trim_start_matches(blank.as_str())
+            .trim_end_matches(blank.as_str())
+            .to_owned()
+    }
+
+    /// Remember the last `limit` steps, so they can be undone with
+    /// [`TuringMachine::step_back`].
+    ///
+    /// The history is disabled by default. Lowering the limit forgets the
+    /// oldest steps.
+    pub fn set_history_limit(&mut self, limit: usize) {
+        self.history_limit = limit;
+
+        if let Some(excess) = self.history.len().checked_sub(limit) {
+            self.history.drain(..excess);
+        }
+    }
+
+    /// Choose what happens when a transition moves the cursor left of
+    /// position 0.
+    ///
+    /// The tape is unbounded by default. Recorded steps of a transition that
+    /// stays at the edge have [`Direction::Stay`] as their action.
+    pub fn set_left_edge(&mut self, left_edge: LeftEdge) {
+        self.left_edge = left_edge;
+    }
+
+    /// Register an [`Observer`] that is told about every step this machine
+    /// performs from now on.
+    pub fn add_observer(&mut self, observer: impl Observer<T::Symbol> + 'static) {
+        self.observers.0.push(Box::new(observer));
+    }
+
+    /// Remove all registered [`Observer`]s.
+    pub fn clear_observers(&mut self) {
+        self.observers.0.clear();
+    }
+
+    /// Undo the last step that is still in the history, restoring the tape
+    /// and cursor position from before it.
+    ///
+    /// Returns the state the machine was in before the step, or [`None`] if
+    /// the history is empty.
+    pub fn step_back(&mut self) -> Option<State> {
+        let undo = self.history.pop_back()?;
+
+        match undo.action {
+            Direction::Left => self.tape.right(),
+            Direction::Right => self.tape.left(),
+            Direction::Stay => {}
+        }
+        self.tape.put(undo.read);
+
+        Some(undo.from)
+    }
+
+    /// Perform a single transition of a [`Program`], starting in `state`.
+    ///
+    /// The returned [`StepOutcome`] describes what the transition did and the
+    /// state the machine ended up in. A step is always performed, even if
+    /// `state` is a final state.
+    ///
+    /// Driving a program one step at a time, printing the tape in between:
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use turing::{Program, TuringMachine, VecTape};
+    /// let program = Program::from_str("+0\n-1\n0,0,1,0,r\n0,1,_,_,n").unwrap();
+    /// let mut machine = TuringMachine::from_tape(VecTape::from_str("_11").unwrap());
+    /// let mut state = program.initial_state();
+    ///
+    /// loop {
+    ///     let outcome = machine.step(&program, state).unwrap();
+    ///     println!("{:?} -> {}", outcome.state, machine.tape());
+    ///     state = outcome.state;
+    ///
+    ///     if outcome.halted {
+    ///         break;
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(machine.tape().to_string(), "_00_");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method will error if no transition is defined for `state` and the
+    /// current segment, or if the transition leads to an error state.
+    pub fn step(
+        &mut self,
+        program: &Program<T::Symbol>,
+        state: State,
+    ) -> Result<StepOutcome<T::Symbol>, ExecutionError<T::Symbol>> {
+        let transition = self.apply(program, state)?;
+        let halted = self.halts_in(program, transition.to)?;
+
+        Ok(StepOutcome {
+            state: transition.to,
+            written: transition.write,
+            action: transition.action,
+            halted,
+        })
+    }
+
+    /// Perform a single step like [`TuringMachine::step`], starting in the
+    /// state at `index` in `table` and returning the index of the state it
+    /// leads to as well.
+    pub(crate) fn step_indexed(
+        &mut self,
+        table: &StateTable<T::Symbol>,
+        index: usize,
+    ) -> Indexed<StepOutcome<T::Symbol>, T::Symbol> {
+        let (to, transition) = self.apply_indexed(table, index)?;
+        let halted = self.halts_in_indexed(table, to)?;
+
+        Ok((
+            to,
+            StepOutcome {
+                state: transition.to,
+                written: transition.write,
+                action: transition.action,
+                halted,
+            },
+        ))
+    }
+
+    /// Perform the transition for `state` and the current segment, without
+    /// looking at the state it leads to.
+    fn apply(
+        &mut self,
+        program: &Program<T::Symbol>,
+        state: State,
+    ) -> Result<Transition<T::Symbol>, ExecutionError<T::Symbol>> {
+        let current = *self.tape.current();
+        let Some(transition) = program.transition(state, current) else {
+            return Err(self.undefined(state, current));
+        };
+        let transition = self.bounded(transition)?;
+
+        self.perform(current, transition);
+        Ok(transition)
+    }
+
+    /// Perform the transition for the state at `index` in `table` and the
+    /// current segment like [`TuringMachine::apply`], returning the index of
+    /// the state it leads to as well.
+    fn apply_indexed(
+        &mut self,
+        table: &StateTable<T::Symbol>,
+        index: usize,
+    ) -> Indexed<Transition<T::Symbol>, T::Symbol> {
+        let current = *self.tape.current();
+        let Some((to, transition)) = table.transition(index, current) else {
+            return Err(self.undefined(table.state(index), current));
+        };
+        let transition = self.bounded(transition)?;
+
+        self.perform(current, transition);
+        Ok((to, transition))
+    }
+
+    /// Returns the error for a missing transition and tells the observers
+    /// about it.
+    fn undefined(&mut self, state: State, current: T::Symbol) -> ExecutionError<T::Symbol> {
+        let error = ExecutionError::UndefinedBehavior(state, current);
+        self.observers
+            .notify(|observer| observer.on_halt(&Err(error.clone())));
+        error
+    }
+
+    /// Returns `transition` adjusted to the left edge of the tape, or the
+    /// error for moving off it, which the observers are told about.
+    fn bounded(
+        &mut self,
+        transition: Transition<T::Symbol>,
+    ) -> Result<Transition<T::Symbol>, ExecutionError<T::Symbol>> {
+        if transition.action != Direction::Left || self.tape.position() > 0 {
+            return Ok(transition);
+        }
+
+        match self.left_edge {
+            LeftEdge::Unbounded => Ok(transition),
+            LeftEdge::Stay => Ok(Transition {
+                action: Direction::Stay,
+                ..transition
+            }),
+            LeftEdge::Fail => {
+                let error = ExecutionError::FellOffTape(transition.from);
+                self.observers
+                    .notify(|observer| observer.on_halt(&Err(error.clone())));
+                Err(error)
+            }
+        }
+    }
+
+    /// Write, move and record `transition`, which was taken for the segment
+    /// `current`.
+    fn perform(&mut self, current: T::Symbol, transition: Transition<T::Symbol>) {
+        let state = transition.from;
+        let position = self.tape.position();
+
+        self.tape.put(transition.write);
+
+        match transition.action {
+            Direction::Left => self.tape.left(),
+            Direction::Right => self.tape.right(),
+            Direction::Stay => {}
+        }
+
+        if self.history_limit > 0 {
+            if self.history.len() == self.history_limit {
+                self.history.pop_front();
+            }
+
+            self.history.push_back(Undo {
+                from: state,
+                read: current,
+                action: transition.action,
+            });
+        }
+
+        if !self.observers.0.is_empty() {
+            let step = TraceStep {
+                from: state,
+                to: transition.to,
+                position,
+                read: current,
+                written: transition.write,
+                action: transition.action,
+            };
+            self.observers.notify(|observer| {
+                observer.on_step(&step);
+                if step.written != step.read {
+                    observer.on_write(step.position, step.read, step.written);
+                }
+                if step.to != step.from {
+                    observer.on_state_change(step.from, step.to);
+                }
+            });
+        }
+    }
+
+    /// Returns whether execution halts in `state` like [`Program::halts_in`],
+    /// and tells the observers if it does.
+    fn halts_in(
+        &mut self,
+        program: &Program<T::Symbol>,
+        state: State,
+    ) -> Result<bool, ExecutionError<T::Symbol>> {
+        self.notify_halt(state, program.halts_in(state))
+    }
+
+    /// Returns whether execution halts in the state at `index` in `table`
+    /// like [`TuringMachine::halts_in`].
+    fn halts_in_indexed(
+        &mut self,
+        table: &StateTable<T::Symbol>,
+        index: usize,
+    ) -> Result<bool, ExecutionError<T::Symbol>> {
+        self.notify_halt(table.state(index), table.halts_in(index))
+    }
+
+    /// Tell the observers if execution `halted` in `state`.
+    fn notify_halt(
+        &mut self,
+        state: State,
+        halted: Result<bool, ExecutionError<T::Symbol>>,
+    ) -> Result<bool, ExecutionError<T::Symbol>> {
+        match &halted {
+            Ok(false) => {}
+            Ok(true) => self
+                .observers
+                .notify(|observer| observer.on_halt(&Ok(state))),
+            Err(error) => self
+                .observers
+                .notify(|observer| observer.on_halt(&Err(error.clone()))),
+        }
+
+        halted
+    }
+
+    /// Returns an iterator that runs a [`Program`] one step at a time.
+    ///
+    /// This allows using iterator adapters to bound or inspect the execution:
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use turing::{Program, TuringMachine, VecTape};
+    /// let program = Program::from_str("+0\n-1\n0,0,_,1,r").unwrap();
+    /// let mut machine = TuringMachine::from_tape(VecTape::from_str("_").unwrap());
+    ///
+    /// assert_eq!(machine.run(&program).take(3).count(), 3);
+    /// assert_eq!(machine.tape().to_string(), "111_");
+    /// ```
+    pub fn run<'a>(&'a mut self, program: &'a Program<T::Symbol>) -> Run<'a, T> {
+        self.run_from(program, program.initial_state)
+    }
+
+    /// Returns an iterator that runs a [`Program`] one step at a time like
+    /// [`TuringMachine::run`], starting in `state` instead of the initial
+    /// state.
+    pub fn run_from<'a>(&'a mut self, program: &'a Program<T::Symbol>, state: State) -> Run<'a, T> {
+        let (table, index) = StateTable::new(program, state);
+
+        Run {
+            machine: self,
+            table,
+            index: Some(index),
+            steps: 0,
+        }
+    }
+
+    /// Continue a run from a [`MachineSnapshot`], replacing the tape of this
+    /// machine with the one of the snapshot.
+    ///
+    /// Checkpointing a long computation and resuming it:
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use turing::{Program, TuringMachine, VecTape};
+    /// let program = Program::from_str("+0\n-1\n0,0,1,1,r\n0,1,_,_,n").unwrap();
+    /// let mut machine = TuringMachine::from_tape(VecTape::from_str("_111").unwrap());
+    ///
+    /// let mut run = machine.run(&program);
+    /// run.next();
+    /// let snapshot = run.snapshot().unwrap();
+    ///
+    /// let mut machine = TuringMachine::from_tape(VecTape::from_str("_").unwrap());
+    /// let mut run = machine.restore(&program, snapshot);
+    /// assert_eq!(run.by_ref().count(), 3);
+    /// assert_eq!(run.steps(), 4);
+    /// ```
+    pub fn restore<'a>(
+        &'a mut self,
+        program: &'a Program<T::Symbol>,
+        snapshot: MachineSnapshot<T>,
+    ) -> Run<'a, T> {
+        self.tape = snapshot.tape;
+        let (table, index) = StateTable::new(program, snapshot.state);
+
+        Run {
+            machine: self,
+            table,
+            index: Some(index),
+            steps: snapshot.steps,
+        }
+    }
+
     /// Run a [`Program`] with this turing machine.
     ///
     /// # Errors
     ///
     /// This method will error if it encounters undefined behaviour or reaches
     /// an error state.
-    pub fn execute(&mut self, program: &Program) -> Result<State, ExecutionError> {
+    pub fn execute(
+        &mut self,
+        program: &Program<T::Symbol>,
+    ) -> Result<State, ExecutionError<T::Symbol>> {
+        self.execute_from(program, program.initial_state)
+    }
+
+    /// Run a [`Program`] that decides a problem with this turing machine,
+    /// returning the final state it halted in and whether that state accepts
+    /// or rejects the input.
+    ///
+    /// This is separate from [`TuringMachine::execute`], which keeps
+    /// returning only the state, so that programs that do not decide
+    /// anything and existing callers are not affected.
+    ///
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use turing::{Program, TuringMachine, VecTape, Verdict};
+    /// // Accepts inputs that start with a "1"
+    /// let program = Program::from_str("+0\n-A1\n-R2\n0,1,1,1,n\n0,2,*,=,n").unwrap();
+    /// let mut machine = TuringMachine::from_tape(VecTape::from_str("_01").unwrap());
+    ///
+    /// assert_eq!(machine.decide(&program).unwrap().1, Verdict::Rejected);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method will error if it encounters undefined behaviour or reaches
+    /// an error state.
+    pub fn decide(
+        &mut self,
+        program: &Program<T::Symbol>,
+    ) -> Result<(State, Verdict), ExecutionError<T::Symbol>> {
+        let state = self.execute(program)?;
+
+        // Execution only halts in final states
+        Ok((state, program.final_verdict(state)))
+    }
+
+    /// Run a [`Program`] with this turing machine, starting in `state`
+    /// instead of the initial state, usually one of its
+    /// [entry points](Program::entry_point).
+    ///
+    /// # Errors
+    ///
+    /// This method will error if it encounters undefined behaviour or reaches
+    /// an error state.
+    pub fn execute_from(
+        &mut self,
+        program: &Program<T::Symbol>,
+        state: State,
+    ) -> Result<State, ExecutionError<T::Symbol>> {
+        let (table, mut index) = StateTable::new(program, state);
+
+        loop {
+            let (to, outcome) = self.step_indexed(&table, index)?;
+            index = to;
+
+            if outcome.halted {
+                return Ok(outcome.state);
+            }
+        }
+    }
+
+    /// Run a [`Program`] with this turing machine without blocking the
+    /// executor, stopping once `cancel` is cancelled.
+    ///
+    /// The execution checks for cancellation and yields to other tasks every
+    /// few thousand steps. It does not need a particular async runtime.
+    ///
+    /// Stopping a machine that never halts from another thread:
+    /// ```
+    /// # use std::{str::FromStr, thread, time::Duration};
+    /// # use turing::{CancellationToken, ExecutionError, Program, TuringMachine, VecTape};
+    /// let program = Program::from_str("+0\n-1\n0,0,_,_,r").unwrap();
+    /// let mut machine = TuringMachine::from_tape(VecTape::from_str("_").unwrap());
+    /// let cancel = CancellationToken::new();
+    ///
+    /// let token = cancel.clone();
+    /// thread::spawn(move || {
+    ///     thread::sleep(Duration::from_millis(10));
+    ///     token.cancel();
+    /// });
+    ///
+    /// # fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    /// #     let mut future = std::pin::pin!(future);
+    /// #     let mut cx = std::task::Context::from_waker(std::task::Waker::noop());
+    /// #     loop {
+    /// #         if let std::task::Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+    /// #             return output;
+    /// #         }
+    /// #     }
+    /// # }
+    /// let result = block_on(machine.execute_async(&program, cancel));
+    /// assert!(matches!(result, Err(ExecutionError::Cancelled(_))));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method will error if it encounters undefined behaviour, reaches
+    /// an error state or is cancelled.
+    pub async fn execute_async(
+        &mut self,
+        program: &Program<T::Symbol>,
+        cancel: CancellationToken,
+    ) -> Result<State, ExecutionError<T::Symbol>> {
+        let (table, mut index) = StateTable::new(program, program.initial_state);
+        let mut steps = 0_u64;
+
+        loop {
+            if steps.is_multiple_of(STEPS_PER_CHECK) {
+                if steps != 0 {
+                    YieldNow(false).await;
+                }
+
+                if cancel.is_cancelled() {
+                    return Err(ExecutionError::Cancelled(table.state(index)));
+                }
+            }
+
+            let (to, outcome) = self.step_indexed(&table, index)?;
+            index = to;
+            steps += 1;
+
+            if outcome.halted {
+                return Ok(outcome.state);
+            }
+        }
+    }
+
+    /// Run a [`Program`] with this turing machine until it halts or `budget`
+    /// has passed, whichever comes first.
+    ///
+    /// The clock is checked every few thousand steps, so the budget may be
+    /// exceeded slightly. If it expires, the tape is left as it is and the
+    /// returned [`Progress::Paused`] holds the state to continue from:
+    /// ```
+    /// # use std::{str::FromStr, time::Duration};
+    /// # use turing::{Progress, Program, TuringMachine, VecTape};
+    /// let program = Program::from_str("+0\n-1\n0,0,1,0,r\n0,1,_,_,n").unwrap();
+    /// let mut machine = TuringMachine::from_tape(VecTape::from_str("_111").unwrap());
+    ///
+    /// let mut progress = machine.execute_for(&program, Duration::ZERO).unwrap();
+    /// while let Progress::Paused { state, .. } = progress {
+    ///     // Keep the user interface responsive in between
+    ///     progress = machine
+    ///         .resume_for(&program, state, Duration::from_millis(16))
+    ///         .unwrap();
+    /// }
+    ///
+    /// assert!(matches!(progress, Progress::Halted(_)));
+    /// assert_eq!(machine.tape().to_string(), "_000_");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method will error if it encounters undefined behaviour or reaches
+    /// an error state.
+    pub fn execute_for(
+        &mut self,
+        program: &Program<T::Symbol>,
+        budget: Duration,
+    ) -> Result<Progress, ExecutionError<T::Symbol>> {
+        self.resume_for(program, program.initial_state, budget)
+    }
+
+    /// Continue running a [`Program`] in `state` like
+    /// [`TuringMachine::execute_for`], usually with the state of a
+    /// [`Progress::Paused`].
+    ///
+    /// # Errors
+    ///
+    /// This method will error if it encounters undefined behaviour or reaches
+    /// an error state.
+    pub fn resume_for(
+        &mut self,
+        program: &Program<T::Symbol>,
+        state: State,
+        budget: Duration,
+    ) -> Result<Progress, ExecutionError<T::Symbol>> {
+        let start = Instant::now();
+        let (table, mut index) = StateTable::new(program, state);
+        let mut steps = 0_u64;
+
+        loop {
+            if steps.is_multiple_of(STEPS_PER_CHECK) && start.elapsed() >= budget {
+                return Ok(Progress::Paused {
+                    state: table.state(index),
+                    steps,
+                });
+            }
+
+            let (to, outcome) = self.step_indexed(&table, index)?;
+            index = to;
+            steps += 1;
+
+            if outcome.halted {
+                return Ok(Progress::Halted(outcome.state));
+            }
+        }
+    }
+
+    /// Run a [`Program`] with this turing machine for at most `max_steps`
+    /// steps.
+    ///
+    /// If the program halts within the limit, the result is the same as the
+    /// one of [`TuringMachine::execute`].
+    ///
+    /// # Errors
+    ///
+    /// This method will error if it encounters undefined behaviour, reaches
+    /// an error state or does not halt within `max_steps` steps.
+    pub fn execute_bounded(
+        &mut self,
+        program: &Program<T::Symbol>,
+        max_steps: u64,
+    ) -> Result<State, ExecutionError<T::Symbol>> {
+        let (table, mut index) = StateTable::new(program, program.initial_state);
+
+        for _ in 0..max_steps {
+            let (to, outcome) = self.step_indexed(&table, index)?;
+            index = to;
+
+            if outcome.halted {
+                return Ok(outcome.state);
+            }
+        }
+
+        Err(ExecutionError::StepLimitExceeded(max_steps))
+    }
+
+    /// Run a [`Program`] with this turing machine for at most `max_steps`
+    /// steps, so that untrusted programs cannot run forever.
+    ///
+    /// This is the same as [`TuringMachine::execute_bounded`].
+    ///
+    /// # Errors
+    ///
+    /// This method will error if it encounters undefined behaviour, reaches
+    /// an error state or does not halt within `max_steps` steps, with
+    /// [`ExecutionError::StepLimitExceeded`].
+    pub fn execute_with_limit(
+        &mut self,
+        program: &Program<T::Symbol>,
+        max_steps: u64,
+    ) -> Result<State, ExecutionError<T::Symbol>> {
+        self.execute_bounded(program, max_steps)
+    }
+
+    /// Run a [`Program`] with this turing machine like
+    /// [`TuringMachine::execute`], but report errors together with the number
+    /// of steps performed before them and the tape around the cursor.
+    ///
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use turing::{ExecutionError, Program, State, TuringMachine, VecTape};
+    /// let program = Program::from_str("+0\n-1\n0,0,0,0,r\n0,1,_,_,n").unwrap();
+    /// let mut machine = TuringMachine::from_tape(VecTape::from_str("_001").unwrap());
+    ///
+    /// let failure = machine.execute_detailed(&program).unwrap_err();
+    /// assert_eq!(failure.steps, 2);
+    /// assert_eq!(
+    ///     failure.to_string(),
+    ///     "no transition for \"1\" in state 0 (after 2 steps, at position 2)\n  \
+    ///      _ _ _ 0 0 [1] _ _ _ _ _"
+    /// );
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method will error if it encounters undefined behaviour or reaches
+    /// an error state.
+    pub fn execute_detailed(
+        &mut self,
+        program: &Program<T::Symbol>,
+    ) -> Result<State, ExecutionFailure<T::Symbol>> {
+        let (table, mut index) = StateTable::new(program, program.initial_state);
+        let mut steps = 0;
+
+        loop {
+            let (to, outcome) = self
+                .step_indexed(&table, index)
+                .map_err(|error| ExecutionFailure::new(error, steps, &self.tape))?;
+            index = to;
+            steps += 1;
+
+            if outcome.halted {
+                return Ok(outcome.state);
+            }
+        }
+    }
+
+    /// Run a [`Program`] with this turing machine, using the options in
+    /// `config`, and collect statistics about the execution in `report`.
+    ///
+    /// Loop detection only finds machines that return to the exact same
+    /// configuration. Machines that move further away from the start forever
+    /// are not detected and need a step limit instead, although
+    /// [`Program::translated_cycle`] can find the ones that repeat the same
+    /// pattern.
+    ///
+    /// Like a [`Trace`], the report is filled even if execution fails, up to
+    /// and including the step that failed, if it performed a transition.
+    ///
+    /// # Errors
+    ///
+    /// This method will error if it encounters undefined behaviour, reaches
+    /// an error state, exceeds the step or tape length limit or runs into a
+    /// loop.
+    pub fn execute_with(
+        &mut self,
+        program: &Program<T::Symbol>,
+        config: &ExecutionConfig,
+        report: &mut ExecutionReport<T::Symbol>,
+    ) -> Result<State, ExecutionError<T::Symbol>>
+    where
+        T: Clone + PartialEq,
+    {
+        let (table, mut index) = StateTable::new(program, program.initial_state);
         let mut state = program.initial_state;
+        let mut position = 0;
+        let mut leftmost = 0;
+        let mut rightmost = 0;
+        let mut steps = 0;
+        let mut checkpoint = config.detect_loops.then(|| (state, self.tape.clone()));
+        let mut power = 1_u64;
+        let mut length = 0_u64;
 
-        // Find the next transition
         loop {
-            let current = self.tape.current();
-            let transition = program
-                .transitions
-                .get(&(state, *current))
-                .ok_or(ExecutionError::UndefinedBehavior(state, *current))?;
+            if config.max_steps == Some(steps) {
+                return Err(ExecutionError::StepLimitExceeded(steps));
+            }
+
+            let read = *self.tape.current();
+            let (to, transition) = self.apply_indexed(&table, index)?;
+            steps += 1;
 
-            self.tape.put(transition.write);
+            report.steps += 1;
+            if transition.write != read {
+                report.writes += 1;
+            }
+            *report.state_visits.entry(state).or_default() += 1;
 
-            match transition.action {
-                Move::Left => self.tape.left(),
-                Move::Right => self.tape.right(),
-                Move::Nothing => {}
+            if config.record_trace {
+                report.trace.steps.push(TraceStep {
+                    from: state,
+                    to: transition.to,
+                    position,
+                    read,
+                    written: transition.write,
+                    action: transition.action,
+                });
             }
 
+            position += transition.action.offset();
+            leftmost = leftmost.min(position);
+            rightmost = rightmost.max(position);
+            report.leftmost = report.leftmost.min(position);
+            report.rightmost = report.rightmost.max(position);
+            index = to;
             state = transition.to;
 
-            if program.final_states.contains(&state) {
-                break;
+            if let Some(max_tape_len) = config.max_tape_len {
+                if rightmost.abs_diff(leftmost) >= max_tape_len {
+                    return Err(ExecutionError::TapeLimitExceeded(max_tape_len));
+                }
+            }
+
+            if self.halts_in_indexed(&table, index)? {
+                return Ok(state);
             }
 
-            if program.error_states.contains(&state) {
-                return Err(ExecutionError::ReachedError(state));
+            if let Some((checkpoint_state, checkpoint_tape)) = &mut checkpoint {
+                if *checkpoint_state == state && *checkpoint_tape == self.tape {
+                    return Err(ExecutionError::InfiniteLoopDetected(state));
+                }
+
+                length += 1;
+                if length == power {
+                    *checkpoint_state = state;
+                    checkpoint_tape.clone_from(&self.tape);
+                    power *= 2;
+                    length = 0;
+                }
             }
         }
+    }
+
+    /// Run a [`Program`] on each of the `inputs` with the options in
+    /// `config`, spreading the inputs over all available threads.
+    ///
+    /// Returns the result and the final tape of every input, in the order of
+    /// the inputs. Setting a step limit in `config` ensures that the batch
+    /// finishes even if the program does not halt on some input.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if running the program on any of the inputs
+    /// panics, for example because a [`crate::PackedTape`] ran out of
+    /// symbols.
+    #[must_use]
+    pub fn execute_batch(
+        program: &Program<T::Symbol>,
+        inputs: &[T],
+        config: &ExecutionConfig,
+    ) -> Vec<BatchResult<T>>
+    where
+        T: Clone + PartialEq + Send + Sync,
+        T::Symbol: Send + Sync,
+    {
+        let threads = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+        let chunk_size = inputs.len().div_ceil(threads).max(1);
+
+        thread::scope(|scope| {
+            let handles: Vec<_> = inputs
+                .chunks(chunk_size)
+                .map(|chunk| {
+                    scope.spawn(move || {
+                        chunk
+                            .iter()
+                            .map(|tape| {
+                                let mut machine = Self::from_tape(tape.clone());
+                                let result = machine.execute_with(
+                                    program,
+                                    config,
+                                    &mut ExecutionReport::default(),
+                                );
+                                (result, machine.tape)
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().expect("running a program panicked"))
+                .collect()
+        })
+    }
+
+    /// Run a [`Program`] with this turing machine and record every step in
+    /// `trace`.
+    ///
+    /// Positions in the trace are relative to the cursor position at the
+    /// start of the run. The trace is filled even if execution fails, up to
+    /// and including the step that failed, if it performed a transition.
+    ///
+    /// # Errors
+    ///
+    /// This method will error if it encounters undefined behaviour or reaches
+    /// an error state.
+    pub fn execute_traced(
+        &mut self,
+        program: &Program<T::Symbol>,
+        trace: &mut Trace<T::Symbol>,
+    ) -> Result<State, ExecutionError<T::Symbol>> {
+        let (table, mut index) = StateTable::new(program, program.initial_state);
+        let mut position = 0;
+
+        loop {
+            let read = *self.tape.current();
+            let (to, transition) = self.apply_indexed(&table, index)?;
+
+            trace.steps.push(TraceStep {
+                from: transition.from,
+                to: transition.to,
+                position,
+                read,
+                written: transition.write,
+                action: transition.action,
+            });
+
+            position += transition.action.offset();
+            index = to;
+
+            if self.halts_in_indexed(&table, index)? {
+                return Ok(transition.to);
+            }
+        }
+    }
+
+    /// Run a [`Program`] with this turing machine along a recorded `trace`,
+    /// checking that every step the program performs matches the recorded
+    /// one.
+    ///
+    /// Positions in the trace are relative to the cursor position at the
+    /// start of the run, like the ones recorded by
+    /// [`TuringMachine::execute_traced`]. The trace may end before the
+    /// program halts, in which case the replay stops there. Returns the state
+    /// after the last step of the trace.
+    ///
+    /// # Errors
+    ///
+    /// This method will error at the first step that does not match the
+    /// program, leaving the tape as it was after the last matching step.
+    pub fn replay(
+        &mut self,
+        program: &Program<T::Symbol>,
+        trace: &Trace<T::Symbol>,
+    ) -> Result<State, ReplayError<T::Symbol>> {
+        let mut state = program.initial_state;
+        let mut position = 0;
+
+        for (step, expected) in trace.steps().iter().enumerate() {
+            if step != 0 && !matches!(program.halts_in(state), Ok(false)) {
+                return Err(ReplayError::Halted { step });
+            }
+
+            let read = *self.tape.current();
+            let transition = program.transition(state, read).ok_or(ReplayError::Failed {
+                step,
+                error: ExecutionError::UndefinedBehavior(state, read),
+            })?;
+            let actual = TraceStep {
+                from: state,
+                to: transition.to,
+                position,
+                read,
+                written: transition.write,
+                action: transition.action,
+            };
+
+            if actual != *expected {
+                return Err(ReplayError::Diverged {
+                    step,
+                    expected: *expected,
+                    actual,
+                });
+            }
+
+            self.apply(program, state)
+                .map_err(|error| ReplayError::Failed { step, error })?;
+            position += transition.action.offset();
+            state = transition.to;
+        }
 
         Ok(state)
     }
 }
+
+impl<S> TuringMachine<RleTape<S>>
+where
+    S: Symbol,
+{
+    /// Run a [`Program`] with this turing machine, moving over a whole run of
+    /// equal segments at once whenever a transition loops back to its own
+    /// state.
+    ///
+    /// Returns the final state and the number of steps, which is the same as
+    /// if every step had been performed on its own. This makes programs that
+    /// move back and forth over long runs of symbols, such as most unary
+    /// arithmetic, run in time proportional to the number of runs instead of
+    /// the number of steps.
+    ///
+    /// A looping transition that moves into the blank end of the tape never
+    /// stops, which is reported as [`ExecutionError::InfiniteLoopDetected`]
+    /// right away.
+    ///
+    /// # Errors
+    ///
+    /// This method will error if it encounters undefined behaviour, reaches
+    /// an error state, does not halt within `max_steps` steps or runs off the
+    /// end of the tape forever.
+    pub fn execute_accelerated(
+        &mut self,
+        program: &Program<S>,
+        max_steps: Option<u64>,
+    ) -> Result<(State, u64), ExecutionError<S>> {
+        let (table, mut index) = StateTable::new(program, program.initial_state);
+        let mut steps = 0;
+
+        loop {
+            if max_steps == Some(steps) {
+                return Err(ExecutionError::StepLimitExceeded(steps));
+            }
+
+            let state = table.state(index);
+            let read = *self.tape.current();
+            let (to, transition) = table
+                .transition(index, read)
+                .ok_or(ExecutionError::UndefinedBehavior(state, read))?;
+
+            // Runs to the left may cross the edge of the tape
+            if to == index
+                && (transition.action == Direction::Right
+                    || (transition.action == Direction::Left
+                        && self.left_edge == LeftEdge::Unbounded))
+                && !table.is_halting(index)
+            {
+                let limit = max_steps.map_or(u64::MAX, |max_steps| max_steps - steps);
+                steps += self
+                    .tape
+                    .skip_run(transition.write, transition.action, limit)
+                    .ok_or(ExecutionError::InfiniteLoopDetected(state))?;
+                continue;
+            }
+
+            self.apply_indexed(&table, index)?;
+            steps += 1;
+            index = to;
+
+            if self.halts_in_indexed(&table, index)? {
+                return Ok((transition.to, steps));
+            }
+        }
+    }
+}
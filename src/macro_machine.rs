@@ -0,0 +1,497 @@
+//! Simulation of programs as macro machines.
+//!
+//! A macro machine groups the tape into blocks of a fixed number of cells
+//! and keeps runs of equal blocks together with their length. A macro step
+//! runs the program inside a single block, from entering it on one side
+//! until it leaves it on either side, and is cached for every state, side
+//! and block. When a macro step leaves a block on the opposite side in the
+//! same state it entered in, it does the same for every block of the run, so
+//! the whole run is handled at once.
+//!
+//! Machines that sweep back and forth over long regular parts of the tape,
+//! like most busy beaver champions, run in far fewer macro steps than steps:
+//!
+//! ```
+//! use std::str::FromStr;
+//!
+//! use turing::{busy_beaver::BB4, macro_machine::MacroMachine, VecTape};
+//!
+//! let mut machine = MacroMachine::new(&VecTape::from_str("_").unwrap(), 2);
+//! let (_, steps) = machine.execute(&BB4.program(), None).unwrap();
+//!
+//! assert_eq!(steps, BB4.steps);
+//! assert_eq!(machine.to_tape().to_string().matches('1').count(), BB4.ones);
+//! ```
+//!
+//! Which block size works best depends on the program, and blocks that are
+//! too large make the cache less effective.
+use std::collections::HashMap;
+
+use crate::{
+    dispatch::StateTable, tape::Symbol, Direction, ExecutionError, Program, Segment, State, Tape,
+    VecTape,
+};
+
+/// A stack of runs of blocks, with the run closest to the cursor on top.
+type Runs<S> = Vec<(Vec<S>, u64)>;
+
+/// The result of running a program inside a single block.
+#[derive(Debug, Clone)]
+enum MacroStep<S> {
+    /// The cursor left the block on the side of `action`.
+    Exit {
+        block: Vec<S>,
+        to: usize,
+        action: Direction,
+        steps: u64,
+    },
+    /// The program halted with the cursor at `offset` from the start of the
+    /// block, which is just outside of it if the last step left the block.
+    Halt {
+        block: Vec<S>,
+        to: usize,
+        offset: i64,
+        steps: u64,
+    },
+    /// The program failed or loops inside the block forever.
+    Fail {
+        block: Vec<S>,
+        offset: i64,
+        error: ExecutionError<S>,
+    },
+}
+
+/// A tape split into blocks, which runs programs one block at a time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MacroMachine<S = Segment> {
+    block_size: usize,
+    /// The runs left of the boundary the cursor is at.
+    left: Runs<S>,
+    /// The runs right of the boundary the cursor is at.
+    right: Runs<S>,
+    /// The position of the first cell right of the boundary.
+    boundary: i64,
+    /// The side of the boundary the cursor is on.
+    facing: Direction,
+    /// The cursor position.
+    cursor: i64,
+}
+
+impl<S> MacroMachine<S>
+where
+    S: Symbol,
+{
+    /// Create a macro machine that splits `tape` into blocks of `block_size`
+    /// cells, with a boundary between two blocks at the cursor.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `block_size` is zero.
+    #[must_use]
+    pub fn new(tape: &VecTape<S>, block_size: usize) -> Self {
+        assert!(block_size > 0, "blocks must have at least one cell");
+
+        let cursor = tape.position();
+        let leftmost = cursor - i64::try_from(tape.position).expect("tape position exceeds i64");
+        let rightmost = leftmost + i64::try_from(tape.inner.len()).expect("tape exceeds i64") - 1;
+        let size = i64::try_from(block_size).expect("block size exceeds i64");
+
+        let mut machine = Self {
+            block_size,
+            left: Vec::new(),
+            right: Vec::new(),
+            boundary: cursor,
+            facing: Direction::Right,
+            cursor,
+        };
+
+        // Push the blocks furthest away from the cursor first
+        let blocks = |start: i64| {
+            (start..start + size)
+                .map(|position| tape.get(position))
+                .collect::<Vec<S>>()
+        };
+        let mut start = cursor + (rightmost - cursor).max(0) / size * size;
+        while start >= cursor {
+            machine.push(Direction::Right, blocks(start), 1);
+            start -= size;
+        }
+        let mut start = cursor - ((cursor - leftmost).max(0) + size - 1) / size * size;
+        while start < cursor {
+            machine.push(Direction::Left, blocks(start), 1);
+            start += size;
+        }
+
+        machine
+    }
+
+    /// Run `program` from its initial state for at most `max_steps` steps,
+    /// or until it halts if there is no limit.
+    ///
+    /// Returns the final state and the number of steps, which is the same as
+    /// if every step had been performed on its own. Runs that move into the
+    /// blank end of the tape forever are reported as
+    /// [`ExecutionError::InfiniteLoopDetected`], as are programs that loop
+    /// inside a single block.
+    ///
+    /// # Errors
+    ///
+    /// This method will error if it encounters undefined behaviour, reaches
+    /// an error state, does not halt within `max_steps` steps or is found to
+    /// loop forever.
+    pub fn execute(
+        &mut self,
+        program: &Program<S>,
+        max_steps: Option<u64>,
+    ) -> Result<(State, u64), ExecutionError<S>> {
+        let (table, mut index) = StateTable::new(program, program.initial_state);
+        let mut cache: HashMap<(usize, bool, Vec<S>), MacroStep<S>> = HashMap::new();
+        let mut steps = 0_u64;
+        let limit = max_steps.unwrap_or(u64::MAX);
+        let exceeded = || ExecutionError::StepLimitExceeded(limit);
+
+        loop {
+            let facing = self.facing;
+            let (block, count) = self.pop(facing);
+            let step = cache
+                .entry((index, facing == Direction::Right, block.clone()))
+                .or_insert_with(|| run_block(&table, index, facing, block.clone()))
+                .clone();
+
+            match step {
+                MacroStep::Exit {
+                    block: written,
+                    to,
+                    action,
+                    steps: taken,
+                } if to == index && action == facing => {
+                    // Every block of the run is passed the same way
+                    let count =
+                        count.ok_or(ExecutionError::InfiniteLoopDetected(table.state(to)))?;
+                    let Some(total) = taken
+                        .checked_mul(count)
+                        .and_then(|taken| steps.checked_add(taken))
+                        .filter(|steps| *steps <= limit)
+                    else {
+                        self.restore(facing, block, Some(count));
+                        return Err(exceeded());
+                    };
+                    steps = total;
+
+                    self.push(facing.opposite(), written, count);
+                    self.shift(facing, count);
+                }
+                MacroStep::Exit {
+                    block: written,
+                    to,
+                    action,
+                    steps: taken,
+                } => {
+                    let Some(total) = steps.checked_add(taken).filter(|steps| *steps <= limit)
+                    else {
+                        self.restore(facing, block, count);
+                        return Err(exceeded());
+                    };
+                    steps = total;
+
+                    self.put_back(facing, block, count);
+                    if action == facing {
+                        self.push(facing.opposite(), written, 1);
+                        self.shift(facing, 1);
+                    } else {
+                        self.push(facing, written, 1);
+                    }
+                    self.facing = action;
+                    self.cursor = self.edge();
+                    index = to;
+                }
+                MacroStep::Halt {
+                    block: written,
+                    to,
+                    offset,
+                    steps: taken,
+                } => {
+                    let Some(total) = steps.checked_add(taken).filter(|steps| *steps <= limit)
+                    else {
+                        self.restore(facing, block, count);
+                        return Err(exceeded());
+                    };
+                    steps = total;
+
+                    self.put_back(facing, block, count);
+                    self.push(facing, written, 1);
+                    self.cursor = self.start(facing) + offset;
+                    return Ok((table.state(to), steps));
+                }
+                MacroStep::Fail {
+                    block: written,
+                    offset,
+                    error,
+                } => {
+                    self.put_back(facing, block, count);
+                    self.push(facing, written, 1);
+                    self.cursor = self.start(facing) + offset;
+                    return Err(error);
+                }
+            }
+        }
+    }
+
+    /// Returns the tape with every block written out, with the cursor at the
+    /// same position.
+    ///
+    /// This needs memory for every cell between the outermost non-blank
+    /// blocks, which can be far more than the macro machine itself uses.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the written out tape does not fit into memory.
+    #[must_use]
+    pub fn to_tape(&self) -> VecTape<S> {
+        let size = i64::try_from(self.block_size).expect("block size exceeds i64");
+        let cells: u64 = self.left.iter().map(|(_, count)| *count).sum();
+        let mut leftmost = self.boundary - i64::try_from(cells).expect("tape exceeds i64") * size;
+
+        let mut inner = Vec::new();
+        for (block, count) in self.left.iter().chain(self.right.iter().rev()) {
+            for _ in 0..*count {
+                inner.extend_from_slice(block);
+            }
+        }
+
+        // The cursor may be on a blank cell outside of the blocks
+        if self.cursor < leftmost {
+            let padding = usize::try_from(leftmost - self.cursor).expect("tape exceeds usize");
+            inner.splice(0..0, std::iter::repeat_n(S::BLANK, padding));
+            leftmost = self.cursor;
+        }
+        let index = usize::try_from(self.cursor - leftmost).expect("tape exceeds usize");
+        if index >= inner.len() {
+            inner.resize(index + 1, S::BLANK);
+        }
+
+        VecTape::new(inner, index)
+    }
+
+    /// Take one block of the run next to the cursor on the side of `facing`,
+    /// together with the length of the run, which is [`None`] for the blank
+    /// end of the tape.
+    fn pop(&mut self, facing: Direction) -> (Vec<S>, Option<u64>) {
+        self.runs(facing).pop().map_or_else(
+            || (vec![S::BLANK; self.block_size], None),
+            |(block, count)| (block, Some(count)),
+        )
+    }
+
+    /// Return the rest of a run that one block was taken from by
+    /// [`MacroMachine::pop`].
+    fn put_back(&mut self, facing: Direction, block: Vec<S>, count: Option<u64>) {
+        if let Some(count) = count.filter(|count| *count > 1) {
+            self.push(facing, block, count - 1);
+        }
+    }
+
+    /// Return a whole run taken by [`MacroMachine::pop`].
+    fn restore(&mut self, facing: Direction, block: Vec<S>, count: Option<u64>) {
+        if let Some(count) = count {
+            self.push(facing, block, count);
+        }
+    }
+
+    /// Add `count` copies of `block` next to the boundary on the side of
+    /// `side`.
+    fn push(&mut self, side: Direction, block: Vec<S>, count: u64) {
+        let runs = self.runs(side);
+
+        match runs.last_mut() {
+            Some((top, top_count)) if *top == block => *top_count += count,
+            // Blank blocks at the end of the tape are implied
+            None if block.iter().all(|segment| *segment == S::BLANK) => {}
+            _ => runs.push((block, count)),
+        }
+    }
+
+    /// Move the boundary by `count` blocks in the direction of `facing`.
+    fn shift(&mut self, facing: Direction, count: u64) {
+        let cells = u64::try_from(self.block_size)
+            .ok()
+            .and_then(|size| size.checked_mul(count))
+            .and_then(|cells| i64::try_from(cells).ok())
+            .expect("tape exceeds i64");
+        self.boundary += facing.offset() * cells;
+        self.cursor = self.edge();
+    }
+
+    /// Returns the position of the first cell of the block next to the
+    /// boundary on the side of `facing`.
+    fn start(&self, facing: Direction) -> i64 {
+        match facing {
+            Direction::Left => self.boundary - i64::try_from(self.block_size).unwrap_or(i64::MAX),
+            _ => self.boundary,
+        }
+    }
+
+    /// Returns the position of the cell next to the boundary the cursor is
+    /// facing.
+    fn edge(&self) -> i64 {
+        match self.facing {
+            Direction::Left => self.boundary - 1,
+            _ => self.boundary,
+        }
+    }
+
+    /// Returns the runs on the side of `side`.
+    fn runs(&mut self, side: Direction) -> &mut Runs<S> {
+        match side {
+            Direction::Left => &mut self.left,
+            _ => &mut self.right,
+        }
+    }
+}
+
+/// Run the program in `table` from the state at `index` on `block`, entering
+/// it on the opposite side of `facing`, until it leaves the block or halts.
+fn run_block<S>(
+    table: &StateTable<S>,
+    mut index: usize,
+    facing: Direction,
+    mut block: Vec<S>,
+) -> MacroStep<S>
+where
+    S: Symbol,
+{
+    let size = i64::try_from(block.len()).expect("block size exceeds i64");
+    let mut offset = if facing == Direction::Left {
+        size - 1
+    } else {
+        0
+    };
+    let mut steps = 0;
+
+    // Brent's cycle detection, only a few configurations fit into a block
+    let mut checkpoint = (index, offset, block.clone());
+    let mut power = 1_u64;
+    let mut length = 0_u64;
+
+    loop {
+        let cell = usize::try_from(offset).expect("offset is inside the block");
+        let read = block[cell];
+        let Some((to, transition)) = table.transition(index, read) else {
+            return MacroStep::Fail {
+                block,
+                offset,
+                error: ExecutionError::UndefinedBehavior(table.state(index), read),
+            };
+        };
+
+        block[cell] = transition.write;
+        offset += transition.action.offset();
+        steps += 1;
+        index = to;
+
+        match table.halts_in(index) {
+            Ok(false) => {}
+            Ok(true) => {
+                return MacroStep::Halt {
+                    block,
+                    to,
+                    offset,
+                    steps,
+                }
+            }
+            Err(error) => {
+                return MacroStep::Fail {
+                    block,
+                    offset,
+                    error,
+                }
+            }
+        }
+
+        if !(0..size).contains(&offset) {
+            return MacroStep::Exit {
+                block,
+                to,
+                action: transition.action,
+                steps,
+            };
+        }
+
+        if checkpoint.0 == index && checkpoint.1 == offset && checkpoint.2 == block {
+            return MacroStep::Fail {
+                block,
+                offset,
+                error: ExecutionError::InfiniteLoopDetected(table.state(index)),
+            };
+        }
+        length += 1;
+        if length == power {
+            checkpoint = (index, offset, block.clone());
+            power *= 2;
+            length = 0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::{busy_beaver::*, macro_machine::*, *};
+
+    #[test]
+    fn test_macro_machine_matches_steps() {
+        for input in ["_1_", "_1011_", "_111_", "_0_"] {
+            let program = Program::from_str(include_str!("../examples/next_integer.tng")).unwrap();
+            let tape = VecTape::from_str(input).unwrap();
+
+            let mut machine = TuringMachine::from_tape(tape.clone());
+            let steps = machine.run(&program).count() as u64;
+
+            for block_size in 1..4 {
+                let mut macro_machine = MacroMachine::new(&tape, block_size);
+                assert_eq!(
+                    macro_machine.execute(&program, None).unwrap(),
+                    (State(3), steps)
+                );
+
+                let result = macro_machine.to_tape();
+                assert_eq!(result.trim(), machine.tape().trim());
+                assert_eq!(
+                    result.get(result.position()),
+                    machine.tape().get(machine.tape().position())
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_macro_machine_runs() {
+        for champion in [BB2, BB3, BB4] {
+            let mut machine = MacroMachine::new(&VecTape::from_str("_").unwrap(), 3);
+            let (_, steps) = machine.execute(&champion.program(), None).unwrap();
+
+            assert_eq!(steps, champion.steps);
+            assert_eq!(
+                machine.to_tape().to_string().matches('1').count(),
+                champion.ones
+            );
+        }
+
+        // Walks right forever
+        let program = Program::from_str("+0\n-1\n0,0,*,1,r").unwrap();
+        let mut machine = MacroMachine::new(&VecTape::from_str("_111").unwrap(), 2);
+        assert_eq!(
+            machine.execute(&program, None),
+            Err(ExecutionError::InfiniteLoopDetected(State(0)))
+        );
+
+        // The tape is left as it was before the last macro step
+        let mut machine = MacroMachine::new(&VecTape::from_str("_").unwrap(), 2);
+        assert_eq!(
+            machine.execute(&BB4.program(), Some(100)),
+            Err(ExecutionError::StepLimitExceeded(100))
+        );
+        assert!(machine.to_tape().to_string().matches('1').count() < BB4.ones);
+    }
+}
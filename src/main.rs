@@ -1,48 +1,151 @@
-use std::{env::args, fs::read_to_string, process::exit, str::FromStr, time::Instant};
+use std::{env::args, fs::read_to_string, path::Path, process::exit, str::FromStr, time::Instant};
 
-use turing::{Program, TuringMachine, VecTape};
+use turing::{ExecutionError, ExecutionFailure, Program, TuringMachine, VecTape};
 
-fn main() {
+const USAGE: &str = "Usage: turing [--max-steps <n>] [--entry <name>] [--trace] <program> <tape>
+
+Runs a .tng program on the given tape and prints the final tape, the final
+state and the number of steps.
+
+Options:
+    --max-steps <n>  Stop after at most n steps
+    --entry <name>   Start at the entry point with the given name
+    --trace          Print the state and tape after every step
+    --help           Print this message";
+
+struct Options {
+    program_path: String,
+    tape: String,
+    max_steps: Option<u64>,
+    entry: Option<String>,
+    trace: bool,
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("{message}");
+    exit(1);
+}
+
+fn parse_options() -> Options {
     let mut args = args();
     // Skip the executable
     args.next();
 
-    let program_path = args.next().unwrap_or_else(|| {
-        eprintln!("Expected a program path.");
-        exit(1);
-    });
-    let tape = args.next().unwrap_or_else(|| {
-        eprintln!("Expected a tape buffer.");
-        exit(1);
-    });
+    let mut positional = Vec::with_capacity(2);
+    let mut max_steps = None;
+    let mut entry = None;
+    let mut trace = false;
 
-    let tape = VecTape::from_str(&tape).unwrap_or_else(|_| {
-        eprintln!("Tape buffer is invalid.");
-        exit(1);
-    });
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--max-steps" => {
+                let value = args
+                    .next()
+                    .unwrap_or_else(|| fail("Expected a number after --max-steps."));
+                max_steps = Some(
+                    value
+                        .parse()
+                        .unwrap_or_else(|_| fail("Step limit is not a valid number.")),
+                );
+            }
+            "--entry" => {
+                entry = Some(
+                    args.next()
+                        .unwrap_or_else(|| fail("Expected a name after --entry.")),
+                );
+            }
+            "--trace" => trace = true,
+            "--help" | "-h" => {
+                println!("{USAGE}");
+                exit(0);
+            }
+            _ if arg.starts_with("--") => fail(&format!("Unknown option {arg}.\n\n{USAGE}")),
+            _ => positional.push(arg),
+        }
+    }
 
-    let code = read_to_string(program_path).unwrap_or_else(|e| {
-        eprintln!("Could not read program: {e}.");
-        exit(1);
-    });
+    let mut positional = positional.into_iter();
+    let program_path = positional
+        .next()
+        .unwrap_or_else(|| fail(&format!("Expected a program path.\n\n{USAGE}")));
+    let tape = positional
+        .next()
+        .unwrap_or_else(|| fail(&format!("Expected a tape buffer.\n\n{USAGE}")));
 
-    let program = Program::from_str(&code).unwrap_or_else(|e| {
-        eprintln!("Failed to parse program: {e:?}");
-        exit(1);
+    if positional.next().is_some() {
+        fail(&format!("Too many arguments.\n\n{USAGE}"));
+    }
+
+    Options {
+        program_path,
+        tape,
+        max_steps,
+        entry,
+        trace,
+    }
+}
+
+fn main() {
+    let options = parse_options();
+
+    let tape = VecTape::from_str(&options.tape).unwrap_or_else(|_| fail("Tape buffer is invalid."));
+
+    let code = read_to_string(&options.program_path)
+        .unwrap_or_else(|e| fail(&format!("Could not read program: {e}.")));
+
+    // Included programs are looked up relative to the program
+    let directory = Path::new(&options.program_path)
+        .parent()
+        .unwrap_or_else(|| Path::new("."));
+    let program =
+        Program::parse_with_includes(&code, |path| read_to_string(directory.join(path)).ok())
+            .unwrap_or_else(|e| fail(&format!("Failed to parse program: {e}")));
+
+    let state = options.entry.map_or(program.initial_state(), |name| {
+        program
+            .entry_point(&name)
+            .unwrap_or_else(|| fail(&format!("Program has no entry point {name}.")))
     });
 
     let mut machine = TuringMachine::from_tape(tape);
+    let mut run = machine.run_from(&program, state);
+    let mut steps = 0;
 
     let start = Instant::now();
-    let result = machine.execute(&program);
+    let result = loop {
+        if options.max_steps == Some(steps) {
+            break Err(ExecutionError::StepLimitExceeded(steps));
+        }
+
+        match run.next() {
+            Some(Ok(outcome)) => {
+                steps += 1;
+
+                if options.trace {
+                    println!("{steps:>8} {:>6} {}", outcome.state, run.tape());
+                }
+
+                if outcome.halted {
+                    break Ok(outcome.state);
+                }
+            }
+            Some(Err(e)) => break Err(e),
+            None => unreachable!("a run only ends after halting or failing"),
+        }
+    };
     let taken = start.elapsed();
 
     let tape = machine.tape();
 
     match result {
         Ok(s) => println!(
-            "Program finished successfully in {taken:?}. Final tape: {tape}. Final state: {s:?}"
+            "Program finished successfully in {taken:?} after {steps} steps. Final tape: {tape}. \
+             Final state: {s}"
         ),
-        Err(e) => println!("Program failed to run in {taken:?}. Final tape: {tape}. Error: {e:?}"),
+        Err(e) => {
+            let failure = ExecutionFailure::new(e, steps, tape);
+            println!("Program failed to run in {taken:?}. Final tape: {tape}. Error: {failure}");
+            exit(2);
+        }
     }
 }
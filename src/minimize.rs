@@ -0,0 +1,319 @@
+//! Simplification of [`Program`]s without changing what they compute.
+//!
+//! [`Program::prune_unreachable`] removes the states that execution can never
+//! reach. Two states are equivalent if they write the same segments, perform
+//! the same moves and lead to equivalent states for every segment they may
+//! read. [`Program::minimize`] finds them by partition refinement: it starts
+//! with all ordinary states in one block and splits blocks until the states in
+//! each block behave the same.
+use std::collections::{BTreeSet, HashMap};
+
+use crate::{tape::Symbol, Program, State};
+
+/// The behaviour of a state for one segment: the segment it writes, the
+/// offset of its move and the block of the state it leads to.
+type Behaviour<S> = Option<(Option<S>, i64, usize)>;
+
+impl<S> Program<S>
+where
+    S: Symbol,
+{
+    /// Returns every state that occurs in the program, as the initial state,
+    /// an entry point, a final or error state or in a transition.
+    #[must_use]
+    pub fn states(&self) -> BTreeSet<State> {
+        let mut states = BTreeSet::from([self.initial_state]);
+        states.extend(self.entry_points.values().copied());
+        states.extend(self.final_states.iter().copied());
+        states.extend(self.error_states.iter().copied());
+        for transition in self.transitions.values() {
+            states.insert(transition.from);
+            states.insert(transition.to);
+        }
+        for wildcard in self.wildcards.values() {
+            states.insert(wildcard.from);
+            states.insert(wildcard.to);
+        }
+
+        states
+    }
+
+    /// Returns every segment the transitions of the program read or write.
+    fn alphabet(&self) -> BTreeSet<S> {
+        let mut alphabet = BTreeSet::new();
+        for transition in self.transitions.values() {
+            alphabet.insert(transition.condition);
+            alphabet.insert(transition.write);
+        }
+        alphabet.extend(
+            self.wildcards
+                .values()
+                .filter_map(|wildcard| wildcard.write),
+        );
+
+        alphabet
+    }
+
+    /// Remove every state that cannot be reached from the initial state or an
+    /// entry point, together with its transitions, and return the removed
+    /// states.
+    ///
+    /// Unreachable final and error states are removed as well, and so are the
+    /// transitions of final and error states and the names of removed states.
+    ///
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use turing::{Program, State};
+    /// let mut program = Program::from_str("+0\n-1\n0,1,_,_,n\n2,1,_,_,n\n2,3,a,a,n").unwrap();
+    /// let removed = program.prune_unreachable();
+    ///
+    /// assert_eq!(
+    ///     removed.into_iter().collect::<Vec<_>>(),
+    ///     [State::from_str("2").unwrap(), State::from_str("3").unwrap()]
+    /// );
+    /// assert_eq!(program.to_string(), "+0\n-1\n0,1,_,_,n\n");
+    /// ```
+    pub fn prune_unreachable(&mut self) -> BTreeSet<State> {
+        let reachable = self.reachable_states();
+        let removed: BTreeSet<State> = self
+            .states()
+            .into_iter()
+            .filter(|state| !reachable.contains(state))
+            .collect();
+
+        self.final_states.retain(|state| reachable.contains(state));
+        self.rejecting_states
+            .retain(|state| reachable.contains(state));
+        self.error_states.retain(|state| reachable.contains(state));
+        // Execution halts in final and error states, so their transitions are
+        // never taken either
+        let live = |state: &State| {
+            reachable.contains(state)
+                && !self.final_states.contains(state)
+                && !self.error_states.contains(state)
+        };
+        self.transitions.retain(|(from, _), _| live(from));
+        self.wildcards.retain(|from, _| live(from));
+        self.names.retain(|state, _| reachable.contains(state));
+        self.entry_points
+            .retain(|_, state| reachable.contains(state));
+
+        removed
+    }
+
+    /// Returns an equivalent program in which equivalent states are merged,
+    /// together with the state of the new program for every old state.
+    ///
+    /// Final and error states are never merged, since they are the result of
+    /// running the program. Merged states are replaced by the smallest state
+    /// among them, which keeps its name.
+    ///
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use turing::{Program, State};
+    /// // States 0 and 1 both skip over "a"s
+    /// let program = Program::from_str("+0\n-2\n0,1,a,a,r\n1,0,a,a,r\n0,2,_,_,n\n1,2,_,_,n").unwrap();
+    /// let (minimized, mapping) = program.minimize();
+    ///
+    /// assert_eq!(
+    ///     mapping[&State::from_str("1").unwrap()],
+    ///     State::from_str("0").unwrap()
+    /// );
+    /// assert_eq!(minimized.to_string(), "+0\n-2\n0,2,_,_,n\n0,0,a,a,r\n");
+    /// ```
+    #[must_use]
+    pub fn minimize(&self) -> (Self, HashMap<State, State>) {
+        let states = self.states();
+        let alphabet = self.alphabet();
+
+        // Every final and error state starts in a block of its own, and all
+        // other states start in block 0
+        let mut blocks: HashMap<State, usize> = states
+            .iter()
+            .filter(|state| self.final_states.contains(state) || self.error_states.contains(state))
+            .enumerate()
+            .map(|(index, state)| (*state, index + 1))
+            .collect();
+        for state in &states {
+            blocks.entry(*state).or_insert(0);
+        }
+        let mut count = blocks.values().collect::<BTreeSet<_>>().len();
+
+        loop {
+            let mut signatures: HashMap<(usize, Vec<Behaviour<S>>), usize> = HashMap::new();
+            let mut refined = HashMap::with_capacity(blocks.len());
+
+            for state in &states {
+                let mut behaviour: Vec<Behaviour<S>> = alphabet
+                    .iter()
+                    .map(|read| {
+                        self.transition(*state, *read).map(|transition| {
+                            (
+                                Some(transition.write),
+                                transition.action.offset(),
+                                blocks[&transition.to],
+                            )
+                        })
+                    })
+                    .collect();
+                // Segments outside of the alphabet are only matched by the
+                // wildcard, which may write back what it read
+                behaviour.push(self.wildcards.get(state).map(|wildcard| {
+                    (
+                        wildcard.write,
+                        wildcard.action.offset(),
+                        blocks[&wildcard.to],
+                    )
+                }));
+
+                let next = signatures.len();
+                let block = *signatures.entry((blocks[state], behaviour)).or_insert(next);
+                refined.insert(*state, block);
+            }
+
+            blocks = refined;
+            if signatures.len() == count {
+                break;
+            }
+            count = signatures.len();
+        }
+
+        let mut representatives: HashMap<usize, State> = HashMap::with_capacity(count);
+        for state in &states {
+            representatives.entry(blocks[state]).or_insert(*state);
+        }
+        let mapping: HashMap<State, State> = states
+            .iter()
+            .map(|state| (*state, representatives[&blocks[state]]))
+            .collect();
+
+        let program = Self {
+            initial_state: mapping[&self.initial_state],
+            final_states: self.final_states.clone(),
+            rejecting_states: self.rejecting_states.clone(),
+            error_states: self.error_states.clone(),
+            transitions: self
+                .transitions
+                .values()
+                .filter(|transition| mapping[&transition.from] == transition.from)
+                .map(|transition| {
+                    let mut transition = *transition;
+                    transition.to = mapping[&transition.to];
+                    ((transition.from, transition.condition), transition)
+                })
+                .collect(),
+            wildcards: self
+                .wildcards
+                .values()
+                .filter(|wildcard| mapping[&wildcard.from] == wildcard.from)
+                .map(|wildcard| {
+                    let mut wildcard = *wildcard;
+                    wildcard.to = mapping[&wildcard.to];
+                    (wildcard.from, wildcard)
+                })
+                .collect(),
+            names: self
+                .names
+                .iter()
+                .filter(|(state, _)| mapping.get(state) == Some(state))
+                .map(|(state, name)| (*state, name.clone()))
+                .collect(),
+            entry_points: self
+                .entry_points
+                .iter()
+                .map(|(name, state)| (name.clone(), mapping[state]))
+                .collect(),
+            comments: self.comments.clone(),
+        };
+
+        (program, mapping)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::*;
+
+    #[test]
+    fn test_minimize() {
+        // next_integer.tng, with the state that moves right split in two
+        let program = Program::from_str(
+            "+0\n-3\n0,4,0,0,r\n0,0,1,1,r\n0,1,_,_,l\n4,0,0,0,r\n4,4,1,1,r\n4,1,_,_,l\n\
+             1,2,0,1,l\n1,1,1,0,l\n1,3,_,1,n\n2,2,0,0,l\n2,2,1,1,l\n2,3,_,_,r",
+        )
+        .unwrap();
+        let (minimized, mapping) = program.minimize();
+
+        assert_eq!(mapping.len(), 5);
+        assert_eq!(mapping[&State(4)], State(0));
+        assert_eq!(mapping[&State(2)], State(2));
+        assert_eq!(minimized.transitions.len(), 9);
+
+        for input in ["_0_", "_1011_", "_111_"] {
+            let mut expected = TuringMachine::from_tape(VecTape::from_str(input).unwrap());
+            let mut machine = TuringMachine::from_tape(VecTape::from_str(input).unwrap());
+            assert_eq!(
+                machine.execute(&minimized),
+                expected.execute(&program).map(|state| mapping[&state])
+            );
+            assert_eq!(machine.tape(), expected.tape());
+        }
+    }
+
+    #[test]
+    fn test_minimize_keeps_results() {
+        // The final states and the error states are equivalent, but they are
+        // different results
+        let program =
+            Program::from_str("+0\n-1\n-2\n!3\n!4\n0,1,a,a,n\n0,2,b,b,n\n0,3,c,c,n\n0,4,*,=,n")
+                .unwrap();
+        let (minimized, mapping) = program.minimize();
+
+        assert!(mapping.iter().all(|(old, new)| old == new));
+        assert_eq!(minimized.to_string(), program.to_string());
+    }
+
+    #[test]
+    fn test_minimize_wildcards() {
+        let program = Program::from_str(
+            "+start\n-done\nstart,skip,_,_,r\nskip,again,*,=,r\nagain,skip,*,=,r\nskip,done,_,_,n\n\
+             again,done,_,_,n",
+        )
+        .unwrap();
+        let (minimized, mapping) = program.minimize();
+        let skip = program.state("skip").unwrap();
+        let again = program.state("again").unwrap();
+
+        assert_eq!(mapping[&again], skip);
+        assert_eq!(minimized.state("skip"), Some(skip));
+        assert_eq!(minimized.state("again"), None);
+        assert_eq!(minimized.wildcards.len(), 1);
+        assert_eq!(minimized.transitions.len(), 2);
+
+        let (again, _) = minimized.minimize();
+        assert_eq!(again.to_string(), minimized.to_string());
+    }
+
+    #[test]
+    fn test_prune_unreachable() {
+        let mut program = Program::from_str(
+            "+start\n-done\n-never\n!oops\nstart,done,_,_,n\nstart,start,*,=,r\n\
+             orphan,never,_,_,n\norphan,oops,*,=,n\ndone,never,_,_,n",
+        )
+        .unwrap();
+        let orphan = program.state("orphan").unwrap();
+        let never = program.state("never").unwrap();
+        let oops = program.state("oops").unwrap();
+
+        let removed = program.prune_unreachable();
+
+        assert_eq!(removed, [orphan, never, oops].into_iter().collect());
+        assert_eq!(program.state("orphan"), None);
+        assert_eq!(program.transitions.len(), 1);
+        assert_eq!(program.wildcards.len(), 1);
+        assert!(program.validate().is_empty());
+        assert!(program.prune_unreachable().is_empty());
+    }
+}
@@ -0,0 +1,41 @@
+//! Other models of computation that are equivalent to turing machines.
+//!
+//! [`tag_system`] simulates tag systems, which repeatedly delete symbols
+//! from the front of a word and append a word depending on the first one,
+//! and [`counter_machine`] simulates machines with a fixed number of
+//! counters that can only be incremented, decremented and tested for zero.
+//! Both can be converted into [`crate::Program`]s that do the same on a
+//! tape, and programs can be converted into counter machines, which shows
+//! how the models simulate each other:
+//!
+//! ```
+//! use turing::{
+//!     models::counter_machine::{self, CounterMachine, Instruction},
+//!     TuringMachine,
+//! };
+//!
+//! // Adds the first counter to the second one
+//! let machine = CounterMachine::new(
+//!     2,
+//!     vec![
+//!         Instruction::Decrement(0, 1, 2),
+//!         Instruction::Increment(1, 0),
+//!         Instruction::Halt,
+//!     ],
+//! )
+//! .unwrap();
+//!
+//! let mut counters = [3, 4];
+//! machine.run(&mut counters, 100).unwrap();
+//! assert_eq!(counters, [0, 7]);
+//!
+//! let program = machine.to_program();
+//! let mut turing_machine = TuringMachine::from_tape(counter_machine::encode_counters(&[3, 4]));
+//! turing_machine.execute(&program).unwrap();
+//! assert_eq!(
+//!     counter_machine::decode_counters(turing_machine.tape(), 2),
+//!     Some(vec![0, 7])
+//! );
+//! ```
+pub mod counter_machine;
+pub mod tag_system;
@@ -0,0 +1,763 @@
+//! Counter machines.
+//!
+//! A counter machine has a fixed number of counters holding natural numbers
+//! and a list of [`Instruction`]s, starting with the first one. Each
+//! instruction increments a counter, or decrements it if it is not zero and
+//! branches on whether it was, until the machine reaches a halting
+//! instruction.
+//!
+//! Counter machines and turing machines can simulate each other:
+//! - [`CounterMachine::to_program`] returns a program that keeps the counters
+//!   on the tape, as written by [`encode_counters`].
+//! - [`CounterMachine::from_program`] returns a machine with three counters
+//!   that keeps the tape left and right of the cursor as two numbers, as
+//!   written by [`encode_tape`], and uses the third one for arithmetic.
+//! - [`CounterMachine::to_two_counters`] returns a machine with only two
+//!   counters that keeps all counters of the original machine as the exponents
+//!   of prime factors of the first one, as written by [`encode_godel`].
+//!
+//! ```
+//! use std::str::FromStr;
+//!
+//! use turing::{
+//!     decode_binary, encode_binary,
+//!     models::counter_machine::{self, CounterMachine},
+//!     Program,
+//! };
+//!
+//! let program = Program::from_str(include_str!("../../examples/next_integer.tng")).unwrap();
+//! let (machine, alphabet) = CounterMachine::from_program(&program);
+//!
+//! let mut counters = counter_machine::encode_tape(&encode_binary(5), &alphabet).unwrap();
+//! machine.run(&mut counters, 100_000).unwrap();
+//! let tape = counter_machine::decode_tape(&counters, &alphabet);
+//! assert_eq!(decode_binary(&tape), Some(6));
+//! ```
+//!
+//! The simulations are exponentially slower than the machines they simulate,
+//! so they are only useful for small inputs.
+use std::collections::{hash_map::Entry, BTreeSet, HashMap, VecDeque};
+
+use crate::{
+    error::InvalidModel, Direction, Program, ProgramBuilder, Segment, State, Tape, VecTape,
+};
+
+/// The largest number of counters a [`CounterMachine`] can have, one for
+/// every lowercase letter that represents it on a tape.
+const MAX_COUNTERS: usize = 26;
+
+/// An instruction of a [`CounterMachine`], referring to counters and other
+/// instructions by their index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instruction {
+    /// Increment the counter and continue with the instruction.
+    Increment(usize, usize),
+    /// Decrement the counter and continue with the first instruction if it
+    /// is not zero, or continue with the second instruction if it is.
+    Decrement(usize, usize, usize),
+    /// Halt.
+    Halt,
+}
+
+/// A counter machine, as described in the [module
+/// documentation](self).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CounterMachine {
+    counters: usize,
+    instructions: Vec<Instruction>,
+}
+
+impl CounterMachine {
+    /// Create a counter machine with `counters` counters that starts with
+    /// the first of `instructions`.
+    ///
+    /// # Errors
+    ///
+    /// This method will error if the machine has more than 26 counters or
+    /// no instructions, or if an instruction refers to a counter or
+    /// instruction that does not exist.
+    pub fn new(counters: usize, instructions: Vec<Instruction>) -> Result<Self, InvalidModel> {
+        if counters > MAX_COUNTERS {
+            return Err(InvalidModel::TooManyCounters);
+        }
+        if instructions.is_empty() {
+            return Err(InvalidModel::UndefinedInstruction(0));
+        }
+
+        for instruction in &instructions {
+            let (counter, targets) = match *instruction {
+                Instruction::Increment(counter, next) => (counter, [next, next]),
+                Instruction::Decrement(counter, next, zero) => (counter, [next, zero]),
+                Instruction::Halt => continue,
+            };
+
+            if counter >= counters {
+                return Err(InvalidModel::UndefinedCounter(counter));
+            }
+            if let Some(target) = targets
+                .into_iter()
+                .find(|target| *target >= instructions.len())
+            {
+                return Err(InvalidModel::UndefinedInstruction(target));
+            }
+        }
+
+        Ok(Self {
+            counters,
+            instructions,
+        })
+    }
+
+    /// Returns the number of counters.
+    #[must_use]
+    pub fn counters(&self) -> usize {
+        self.counters
+    }
+
+    /// Returns the instructions.
+    #[must_use]
+    pub fn instructions(&self) -> &[Instruction] {
+        &self.instructions
+    }
+
+    /// Run the machine on `counters` for at most `max_steps` steps and return
+    /// the number of steps it took to halt, or [`None`] if it did not halt.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `counters` does not have one value for every
+    /// counter of the machine.
+    pub fn run(&self, counters: &mut [u64], max_steps: u64) -> Option<u64> {
+        assert_eq!(counters.len(), self.counters, "wrong number of counters");
+
+        let mut index = 0;
+        for step in 0..max_steps {
+            index = match self.instructions[index] {
+                Instruction::Increment(counter, next) => {
+                    counters[counter] += 1;
+                    next
+                }
+                Instruction::Decrement(counter, _, zero) if counters[counter] == 0 => zero,
+                Instruction::Decrement(counter, next, _) => {
+                    counters[counter] -= 1;
+                    next
+                }
+                Instruction::Halt => return Some(step),
+            };
+        }
+
+        None
+    }
+
+    /// Returns a program that runs the machine on the counters on the tape,
+    /// starting with the cursor on the "#" written by [`encode_counters`].
+    ///
+    /// The tape holds every counter as the number of times its letter
+    /// occurs after the "#", in any order. Incrementing a counter appends
+    /// its letter, and decrementing it replaces one of its letters with the
+    /// last letter. The program halts with the cursor on the "#" once the
+    /// machine halts.
+    ///
+    /// # Panics
+    ///
+    /// This method never panics, the program always has an initial state.
+    #[must_use]
+    pub fn to_program(&self) -> Program {
+        const MARK: Segment = Segment::Symbol('#');
+        const HOLE: Segment = Segment::Symbol('$');
+        let letter =
+            |counter: usize| Segment::Symbol(char::from(b'a' + u8::try_from(counter).unwrap_or(0)));
+
+        // The first states start the instructions with the same index
+        let mut next = self.instructions.len();
+        let mut state = || {
+            next += 1;
+            next - 1
+        };
+        let mut builder = ProgramBuilder::new().initial(0);
+
+        // States that move the cursor back to the "#" and continue with an
+        // instruction
+        let mut homes: HashMap<usize, usize> = HashMap::new();
+        let mut home =
+            |builder: ProgramBuilder, target: usize, state: &mut dyn FnMut() -> usize| {
+                if let Some(home) = homes.get(&target) {
+                    return (builder, *home);
+                }
+
+                let home = state();
+                homes.insert(target, home);
+                let builder = builder
+                    .wildcard(home, home, None, Direction::Left)
+                    .transition(home, target, MARK, MARK, Direction::Stay);
+                (builder, home)
+            };
+
+        for (index, instruction) in self.instructions.iter().enumerate() {
+            match *instruction {
+                Instruction::Increment(counter, next) => {
+                    let (with_home, home) = home(builder, next, &mut state);
+                    builder = with_home
+                        .wildcard(index, index, None, Direction::Right)
+                        .transition(
+                            index,
+                            home,
+                            Segment::Empty,
+                            letter(counter),
+                            Direction::Stay,
+                        );
+                }
+                Instruction::Decrement(counter, next, zero) => {
+                    let (with_home, zero) = home(builder, zero, &mut state);
+                    let (with_home, home) = home(with_home, next, &mut state);
+                    let (end, last) = (state(), state());
+                    builder = with_home
+                        // Find a letter of the counter and replace it with a hole
+                        .wildcard(index, index, None, Direction::Right)
+                        .transition(index, end, letter(counter), HOLE, Direction::Right)
+                        .transition(index, zero, Segment::Empty, Segment::Empty, Direction::Left)
+                        // Go to the last letter
+                        .wildcard(end, end, None, Direction::Right)
+                        .transition(end, last, Segment::Empty, Segment::Empty, Direction::Left)
+                        .transition(last, home, HOLE, Segment::Empty, Direction::Left);
+
+                    // Move the last letter into the hole
+                    for other in 0..self.counters {
+                        let carry = state();
+                        builder = builder
+                            .transition(last, carry, letter(other), Segment::Empty, Direction::Left)
+                            .wildcard(carry, carry, None, Direction::Left)
+                            .transition(carry, home, HOLE, letter(other), Direction::Stay);
+                    }
+                }
+                Instruction::Halt => builder = builder.finals([index]),
+            }
+        }
+
+        builder.build().expect("program has an initial state")
+    }
+
+    /// Returns a machine with three counters that runs `program` on the tape
+    /// written by [`encode_tape`], together with the alphabet to encode it
+    /// with.
+    ///
+    /// The first counter holds the segments left of the cursor and the second
+    /// one the segment under the cursor and the segments right of it, as the
+    /// digits of numbers whose base is the size of the alphabet, with the
+    /// least significant digits next to the cursor. The alphabet starts with
+    /// the blank, so that the infinitely many blanks are leading zeros. The
+    /// third counter is used to multiply and divide the others.
+    ///
+    /// The machine halts when the program halts, fails or reaches an
+    /// undefined transition.
+    #[must_use]
+    pub fn from_program(program: &Program) -> (Self, Vec<Segment>) {
+        const LEFT: usize = 0;
+        const RIGHT: usize = 1;
+        const TEMP: usize = 2;
+
+        let mut alphabet = BTreeSet::from([Segment::Empty]);
+        for transition in program.transitions.values() {
+            alphabet.extend([transition.condition, transition.write]);
+        }
+        alphabet.extend(
+            program
+                .wildcards
+                .values()
+                .filter_map(|wildcard| wildcard.write),
+        );
+        let alphabet: Vec<Segment> = alphabet.into_iter().collect();
+        let digit = |segment: Segment| alphabet.iter().position(|other| *other == segment);
+
+        let mut assembler = Assembler::default();
+        let halt = assembler.push(Instruction::Halt);
+        let states: Vec<State> = std::iter::once(program.initial_state)
+            .chain(program.states())
+            .collect();
+        let mut entries: HashMap<State, usize> = HashMap::new();
+        for state in &states {
+            entries.entry(*state).or_insert_with(|| assembler.reserve());
+        }
+
+        for state in program.states() {
+            if program.final_states.contains(&state) || program.error_states.contains(&state) {
+                assembler.bind(entries[&state], halt);
+                continue;
+            }
+
+            let mut handlers = Vec::with_capacity(alphabet.len());
+            for (read, segment) in alphabet.iter().enumerate() {
+                let Some(transition) = program.transition(state, *segment) else {
+                    // Put the segment back and halt
+                    handlers.push(assembler.shift_in(RIGHT, TEMP, alphabet.len(), read, halt));
+                    continue;
+                };
+
+                let write = digit(transition.write).unwrap_or_default();
+                let to = entries[&transition.to];
+                handlers.push(match transition.action {
+                    Direction::Right => assembler.shift_in(LEFT, TEMP, alphabet.len(), write, to),
+                    Direction::Stay => assembler.shift_in(RIGHT, TEMP, alphabet.len(), write, to),
+                    Direction::Left => {
+                        // Move the segment left of the cursor under it
+                        let moves: Vec<usize> = (0..alphabet.len())
+                            .map(|left| assembler.shift_in(RIGHT, TEMP, alphabet.len(), left, to))
+                            .collect();
+                        let pop = assembler.shift_out(LEFT, TEMP, &moves);
+                        assembler.shift_in(RIGHT, TEMP, alphabet.len(), write, pop)
+                    }
+                });
+            }
+
+            let start = assembler.shift_out(RIGHT, TEMP, &handlers);
+            assembler.bind(entries[&state], start);
+        }
+
+        let machine = assembler.finish(entries[&program.initial_state], 3);
+        (machine, alphabet)
+    }
+
+    /// Returns a machine with two counters that does the same as this one
+    /// on the counters encoded by [`encode_godel`].
+    ///
+    /// The first counter holds the product of the `i`-th prime to the power
+    /// of the `i`-th counter of this machine, and the second one is used to
+    /// multiply and divide it.
+    #[must_use]
+    pub fn to_two_counters(&self) -> Self {
+        const NUMBER: usize = 0;
+        const TEMP: usize = 1;
+
+        let primes = primes(self.counters);
+        let mut assembler = Assembler::default();
+        let halt = assembler.push(Instruction::Halt);
+        let entries: Vec<usize> = self
+            .instructions
+            .iter()
+            .map(|_| assembler.reserve())
+            .collect();
+
+        for (index, instruction) in self.instructions.iter().enumerate() {
+            let start = match *instruction {
+                Instruction::Increment(counter, next) => {
+                    let back = assembler.transfer(TEMP, NUMBER, 1, entries[next]);
+                    assembler.transfer(NUMBER, TEMP, primes[counter], back)
+                }
+                Instruction::Decrement(counter, next, zero) => {
+                    // Divisible by the prime if and only if the counter is not
+                    // zero, otherwise undo the division
+                    let remainders: Vec<usize> = (0..primes[counter])
+                        .map(|remainder| match remainder {
+                            0 => entries[next],
+                            remainder => assembler.shift_in(
+                                NUMBER,
+                                TEMP,
+                                primes[counter],
+                                remainder,
+                                entries[zero],
+                            ),
+                        })
+                        .collect();
+                    assembler.shift_out(NUMBER, TEMP, &remainders)
+                }
+                Instruction::Halt => halt,
+            };
+            assembler.bind(entries[index], start);
+        }
+
+        assembler.finish(entries[0], 2)
+    }
+}
+
+/// The instructions of a [`CounterMachine`] under construction.
+///
+/// Instructions can be reserved before the instruction they stand for is
+/// generated and bound to it later, which is needed for loops.
+#[derive(Debug, Default)]
+struct Assembler {
+    instructions: Vec<Instruction>,
+    aliases: HashMap<usize, usize>,
+}
+
+impl Assembler {
+    /// Add `instruction` and return its index.
+    fn push(&mut self, instruction: Instruction) -> usize {
+        self.instructions.push(instruction);
+        self.instructions.len() - 1
+    }
+
+    /// Returns the index of an instruction that is bound or overwritten
+    /// later.
+    fn reserve(&mut self) -> usize {
+        self.push(Instruction::Halt)
+    }
+
+    /// Make the reserved instruction `slot` continue like `target`.
+    fn bind(&mut self, slot: usize, target: usize) {
+        self.aliases.insert(slot, target);
+    }
+
+    /// Add `amount` to `counter` and continue with `next`.
+    fn add(&mut self, counter: usize, amount: usize, next: usize) -> usize {
+        (0..amount).fold(next, |next, _| {
+            self.push(Instruction::Increment(counter, next))
+        })
+    }
+
+    /// Add `factor` times the value of `from` to `to`, set `from` to zero and
+    /// continue with `next`.
+    fn transfer(&mut self, from: usize, to: usize, factor: usize, next: usize) -> usize {
+        let start = self.reserve();
+        let body = self.add(to, factor, start);
+        self.instructions[start] = Instruction::Decrement(from, body, next);
+        start
+    }
+
+    /// Multiply `counter` by `base`, add `digit` and continue with `next`,
+    /// using `temp`, which must be zero.
+    fn shift_in(
+        &mut self,
+        counter: usize,
+        temp: usize,
+        base: usize,
+        digit: usize,
+        next: usize,
+    ) -> usize {
+        let add = self.add(counter, digit, next);
+        let back = self.transfer(temp, counter, base, add);
+        self.transfer(counter, temp, 1, back)
+    }
+
+    /// Divide `counter` by the length of `next` and continue with the entry
+    /// of `next` for the remainder, using `temp`, which must be zero.
+    fn shift_out(&mut self, counter: usize, temp: usize, next: &[usize]) -> usize {
+        let steps: Vec<usize> = next.iter().map(|_| self.reserve()).collect();
+
+        for (remainder, target) in next.iter().enumerate() {
+            let exit = self.transfer(temp, counter, 1, *target);
+            let more = match steps.get(remainder + 1) {
+                Some(step) => *step,
+                None => self.push(Instruction::Increment(temp, steps[0])),
+            };
+            self.instructions[steps[remainder]] = Instruction::Decrement(counter, more, exit);
+        }
+
+        steps[0]
+    }
+
+    /// Returns the machine starting with `start`, with the bound instructions
+    /// resolved and the unreachable ones removed.
+    fn finish(self, start: usize, counters: usize) -> CounterMachine {
+        let resolve = |mut index: usize| {
+            while let Some(target) = self.aliases.get(&index) {
+                index = *target;
+            }
+            index
+        };
+
+        // Number the instructions in the order they are reached
+        let mut order = vec![resolve(start)];
+        let mut numbers = HashMap::from([(order[0], 0)]);
+        let mut pending = VecDeque::from([order[0]]);
+        while let Some(index) = pending.pop_front() {
+            let targets = match self.instructions[index] {
+                Instruction::Increment(_, next) => vec![next],
+                Instruction::Decrement(_, next, zero) => vec![next, zero],
+                Instruction::Halt => Vec::new(),
+            };
+
+            for target in targets.into_iter().map(resolve) {
+                if let Entry::Vacant(entry) = numbers.entry(target) {
+                    entry.insert(order.len());
+                    order.push(target);
+                    pending.push_back(target);
+                }
+            }
+        }
+
+        let number = |index: usize| numbers[&resolve(index)];
+        let instructions = order
+            .into_iter()
+            .map(|index| match self.instructions[index] {
+                Instruction::Increment(counter, next) => {
+                    Instruction::Increment(counter, number(next))
+                }
+                Instruction::Decrement(counter, next, zero) => {
+                    Instruction::Decrement(counter, number(next), number(zero))
+                }
+                Instruction::Halt => Instruction::Halt,
+            })
+            .collect();
+
+        CounterMachine {
+            counters,
+            instructions,
+        }
+    }
+}
+
+/// Returns the first `count` primes.
+fn primes(count: usize) -> Vec<usize> {
+    let mut primes: Vec<usize> = Vec::with_capacity(count);
+
+    for candidate in 2_usize.. {
+        if primes.len() == count {
+            break;
+        }
+        if primes.iter().all(|prime| !candidate.is_multiple_of(*prime)) {
+            primes.push(candidate);
+        }
+    }
+
+    primes
+}
+
+/// Returns a tape holding `counters` for [`CounterMachine::to_program`]: a
+/// "#" followed by the letter of every counter as often as its value, with
+/// the cursor on the "#".
+///
+/// # Panics
+///
+/// This function panics if a counter does not fit into a [`usize`].
+#[must_use]
+pub fn encode_counters(counters: &[u64]) -> VecTape {
+    let mut inner = vec![Segment::Symbol('#')];
+    for (counter, value) in counters.iter().enumerate() {
+        let letter = Segment::Symbol(char::from(b'a' + u8::try_from(counter).unwrap_or(0)));
+        let value = usize::try_from(*value).expect("counter fits into a usize");
+        inner.extend(std::iter::repeat_n(letter, value));
+    }
+    inner.push(Segment::Empty);
+
+    VecTape::new(inner, 0)
+}
+
+/// Returns the values of the first `count` counters on a tape written by
+/// [`encode_counters`], or [`None`] if the tape holds anything else.
+#[must_use]
+pub fn decode_counters(tape: &VecTape, count: usize) -> Option<Vec<u64>> {
+    let mut counters = vec![0; count];
+
+    for segment in tape.trim().iter().skip(1) {
+        let Segment::Symbol(letter @ 'a'..='z') = segment else {
+            return None;
+        };
+        let counter = usize::from(u8::try_from(*letter).ok()? - b'a');
+        *counters.get_mut(counter)? += 1;
+    }
+
+    (tape.trim().first() == Some(&Segment::Symbol('#'))).then_some(counters)
+}
+
+/// Returns the counters for a machine returned by
+/// [`CounterMachine::from_program`] to run on `tape`, or [`None`] if the tape
+/// holds segments outside of `alphabet` or is too long to fit into the
+/// counters.
+#[must_use]
+pub fn encode_tape(tape: &VecTape, alphabet: &[Segment]) -> Option<Vec<u64>> {
+    let base = u64::try_from(alphabet.len()).ok()?;
+    let position = tape.position();
+    let origin = i64::try_from(tape.origin).ok()?;
+    let first = (-origin).min(position);
+    let last = (i64::try_from(tape.inner.len()).ok()? - origin - 1).max(position);
+
+    // The most significant digits are furthest from the cursor
+    let number = |positions: Vec<i64>| {
+        positions.into_iter().try_fold(0_u64, |number, position| {
+            let digit = alphabet
+                .iter()
+                .position(|segment| *segment == tape.get(position))?;
+            number
+                .checked_mul(base)?
+                .checked_add(u64::try_from(digit).ok()?)
+        })
+    };
+    let left = number((first..position).collect())?;
+    let right = number((position..=last).rev().collect())?;
+
+    Some(vec![left, right, 0])
+}
+
+/// Returns the tape held by the counters of a machine returned by
+/// [`CounterMachine::from_program`], with the cursor at the same position
+/// as in [`encode_tape`].
+///
+/// # Panics
+///
+/// This function panics if there are fewer than two counters or a digit is
+/// outside of `alphabet`.
+#[must_use]
+pub fn decode_tape(counters: &[u64], alphabet: &[Segment]) -> VecTape {
+    let base = u64::try_from(alphabet.len()).unwrap_or(u64::MAX);
+    let digits = |mut number: u64| {
+        let mut digits = Vec::new();
+        while number > 0 && base > 1 {
+            let digit = usize::try_from(number % base).unwrap_or(usize::MAX);
+            digits.push(alphabet[digit]);
+            number /= base;
+        }
+        digits
+    };
+
+    let mut inner = digits(counters[0]);
+    inner.reverse();
+    let position = inner.len();
+    inner.extend(digits(counters[1]));
+    inner.push(Segment::Empty);
+
+    VecTape::new(inner, position)
+}
+
+/// Returns the first counter for a machine returned by
+/// [`CounterMachine::to_two_counters`] to run on `counters`, or [`None`] if
+/// it does not fit into a [`u64`].
+#[must_use]
+pub fn encode_godel(counters: &[u64]) -> Option<u64> {
+    primes(counters.len())
+        .into_iter()
+        .zip(counters)
+        .try_fold(1_u64, |number, (prime, exponent)| {
+            let power = u64::try_from(prime)
+                .ok()?
+                .checked_pow(u32::try_from(*exponent).ok()?)?;
+            number.checked_mul(power)
+        })
+}
+
+/// Returns the `count` counters held by the first counter of a machine
+/// returned by [`CounterMachine::to_two_counters`].
+#[must_use]
+pub fn decode_godel(mut number: u64, count: usize) -> Vec<u64> {
+    primes(count)
+        .into_iter()
+        .map(|prime| {
+            let prime = u64::try_from(prime).unwrap_or(u64::MAX);
+            let mut exponent = 0;
+            while number > 0 && number.is_multiple_of(prime) {
+                number /= prime;
+                exponent += 1;
+            }
+            exponent
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::{
+        models::counter_machine::{self, *},
+        *,
+    };
+
+    /// Multiplies the first two counters into the third one, using the
+    /// fourth one to restore the second counter.
+    fn multiply() -> CounterMachine {
+        CounterMachine::new(
+            4,
+            vec![
+                Instruction::Decrement(0, 1, 7),
+                Instruction::Decrement(1, 2, 4),
+                Instruction::Increment(2, 3),
+                Instruction::Increment(3, 1),
+                Instruction::Decrement(3, 5, 0),
+                Instruction::Increment(1, 4),
+                Instruction::Halt,
+                Instruction::Halt,
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_run() {
+        let machine = multiply();
+        let mut counters = [3, 4, 0, 0];
+        assert!(machine.run(&mut counters, 1000).is_some());
+        assert_eq!(counters, [0, 4, 12, 0]);
+
+        assert_eq!(machine.run(&mut [5, 5, 0, 0], 10), None);
+
+        for (counters, instructions, error) in [
+            (27, vec![Instruction::Halt], InvalidModel::TooManyCounters),
+            (1, vec![], InvalidModel::UndefinedInstruction(0)),
+            (
+                1,
+                vec![Instruction::Increment(1, 0)],
+                InvalidModel::UndefinedCounter(1),
+            ),
+            (
+                1,
+                vec![Instruction::Decrement(0, 0, 1)],
+                InvalidModel::UndefinedInstruction(1),
+            ),
+        ] {
+            assert_eq!(CounterMachine::new(counters, instructions), Err(error));
+        }
+        assert_eq!(
+            InvalidModel::UndefinedCounter(1).to_string(),
+            "instruction uses undefined counter 1"
+        );
+    }
+
+    #[test]
+    fn test_to_program() {
+        let machine = multiply();
+        let program = machine.to_program();
+
+        for (a, b) in [(0, 0), (0, 3), (2, 0), (2, 3), (3, 3)] {
+            let mut counters = [a, b, 0, 0];
+            machine.run(&mut counters, 10_000).unwrap();
+
+            let mut turing_machine =
+                TuringMachine::from_tape(counter_machine::encode_counters(&[a, b, 0, 0]));
+            turing_machine.execute(&program).unwrap();
+            assert_eq!(
+                counter_machine::decode_counters(turing_machine.tape(), 4),
+                Some(counters.to_vec())
+            );
+        }
+    }
+
+    #[test]
+    fn test_from_program() {
+        let program =
+            Program::from_str("+0\n-2\n0,0,a,b,l\n0,1,_,x,r\n1,1,*,=,r\n1,2,_,_,n").unwrap();
+        let (machine, alphabet) = CounterMachine::from_program(&program);
+        assert_eq!(machine.counters(), 3);
+
+        for input in ["_aaa", "_a", "_"] {
+            let tape = VecTape::from_str(input).unwrap();
+            let mut counters = counter_machine::encode_tape(&tape, &alphabet).unwrap();
+            assert_eq!(
+                counter_machine::decode_tape(&counters, &alphabet).trim(),
+                tape.trim()
+            );
+            machine.run(&mut counters, 1_000_000).unwrap();
+
+            let mut turing_machine = TuringMachine::from_tape(tape);
+            turing_machine.execute(&program).unwrap();
+            let expected = turing_machine.tape();
+
+            let tape = counter_machine::decode_tape(&counters, &alphabet);
+            assert_eq!(tape.trim(), expected.trim(), "{input}");
+            assert_eq!(tape.get(tape.position()), expected.get(expected.position()));
+        }
+
+        // Segments outside of the alphabet cannot be encoded
+        let tape = VecTape::from_str("_c").unwrap();
+        assert_eq!(counter_machine::encode_tape(&tape, &alphabet), None);
+    }
+
+    #[test]
+    fn test_to_two_counters() {
+        let machine = multiply().to_two_counters();
+        assert_eq!(machine.counters(), 2);
+
+        let mut counters = [counter_machine::encode_godel(&[2, 2, 0, 0]).unwrap(), 0];
+        assert_eq!(counters[0], 2 * 2 * 3 * 3);
+        machine.run(&mut counters, 10_000_000).unwrap();
+        assert_eq!(counter_machine::decode_godel(counters[0], 4), [0, 2, 4, 0]);
+    }
+}
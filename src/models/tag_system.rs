@@ -0,0 +1,309 @@
+//! Tag systems.
+//!
+//! A tag system with deletion number `m` works on a word of symbols. In
+//! every step, it looks at the first symbol, deletes the first `m` symbols
+//! and appends the production of the first symbol to the end of the word. It
+//! halts when the word is shorter than `m` or the first symbol has no
+//! production.
+//!
+//! [`TagSystem::to_program`] converts a tag system into a [`Program`] that
+//! runs it on a tape holding the word:
+//!
+//! ```
+//! use turing::{
+//!     models::tag_system::{self, TagSystem},
+//!     TuringMachine,
+//! };
+//!
+//! // Follows the Collatz sequence of the length of a word of "a"s until it
+//! // reaches 1
+//! let system = TagSystem::new(2, [('a', "bc"), ('b', "a"), ('c', "aaa")]).unwrap();
+//! assert_eq!(system.run("aaa", 1000), Some(String::from("a")));
+//!
+//! let mut machine = TuringMachine::from_tape(tag_system::encode_word("aaa"));
+//! machine.execute(&system.to_program()).unwrap();
+//! assert_eq!(
+//!     tag_system::decode_word(machine.tape()).as_deref(),
+//!     Some("a")
+//! );
+//! ```
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+use crate::{error::InvalidModel, Direction, Program, ProgramBuilder, Segment, Tape, VecTape};
+
+/// A tag system, as described in the [module documentation](self).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagSystem {
+    deletion: usize,
+    productions: BTreeMap<char, String>,
+}
+
+impl TagSystem {
+    /// Create a tag system that deletes `deletion` symbols in every step and
+    /// appends the word of the first symbol in `productions`.
+    ///
+    /// # Errors
+    ///
+    /// This method will error if `deletion` is zero or a symbol is "_" or
+    /// " ".
+    pub fn new<'a>(
+        deletion: usize,
+        productions: impl IntoIterator<Item = (char, &'a str)>,
+    ) -> Result<Self, InvalidModel> {
+        if deletion == 0 {
+            return Err(InvalidModel::DeletionNumber);
+        }
+
+        let productions: BTreeMap<char, String> = productions
+            .into_iter()
+            .map(|(symbol, production)| (symbol, production.to_owned()))
+            .collect();
+        let blank = productions
+            .iter()
+            .flat_map(|(symbol, production)| std::iter::once(*symbol).chain(production.chars()))
+            .any(|symbol| Segment::from(symbol) == Segment::Empty);
+        if blank {
+            return Err(InvalidModel::BlankSymbol);
+        }
+
+        Ok(Self {
+            deletion,
+            productions,
+        })
+    }
+
+    /// Returns the number of symbols deleted in every step.
+    #[must_use]
+    pub fn deletion(&self) -> usize {
+        self.deletion
+    }
+
+    /// Returns the word appended when `symbol` is the first symbol, or
+    /// [`None`] if the tag system halts on it.
+    #[must_use]
+    pub fn production(&self, symbol: char) -> Option<&str> {
+        self.productions.get(&symbol).map(String::as_str)
+    }
+
+    /// Perform a single step on `word` and return whether the tag system
+    /// continued, or `false` if it halted and left `word` unchanged.
+    pub fn step(&self, word: &mut VecDeque<char>) -> bool {
+        let Some(production) = word.front().and_then(|symbol| self.production(*symbol)) else {
+            return false;
+        };
+        if word.len() < self.deletion {
+            return false;
+        }
+
+        word.drain(..self.deletion);
+        word.extend(production.chars());
+        true
+    }
+
+    /// Run the tag system on `word` for at most `max_steps` steps and return
+    /// the word it halted with, or [`None`] if it did not halt.
+    #[must_use]
+    pub fn run(&self, word: &str, max_steps: u64) -> Option<String> {
+        let mut word: VecDeque<char> = word.chars().collect();
+
+        for _ in 0..max_steps {
+            if !self.step(&mut word) {
+                return Some(word.into_iter().collect());
+            }
+        }
+
+        None
+    }
+
+    /// Returns a program that runs the tag system on the word on the tape,
+    /// starting with the cursor on its first symbol, as written by
+    /// [`encode_word`].
+    ///
+    /// Every step of the tag system checks that the word is long enough,
+    /// appends the production to its end and then deletes the symbols from
+    /// its start. The program halts with the cursor on the first symbol of
+    /// the word the tag system halts with.
+    ///
+    /// # Panics
+    ///
+    /// This method never panics, the program always has an initial state.
+    #[must_use]
+    pub fn to_program(&self) -> Program {
+        const START: usize = 0;
+        const HALT: usize = 1;
+        const BACK: usize = 2;
+        const REWIND: usize = 3;
+        let erase = |index: usize| 4 + index;
+        let mut next = erase(self.deletion);
+        let mut state = || {
+            next += 1;
+            next - 1
+        };
+
+        let mut builder = ProgramBuilder::new()
+            .initial(START)
+            .finals([HALT])
+            .transition(START, HALT, Segment::Empty, Segment::Empty, Direction::Stay)
+            // The word is too short, go back to its start
+            .wildcard(BACK, BACK, None, Direction::Left)
+            .transition(BACK, HALT, Segment::Empty, Segment::Empty, Direction::Right)
+            // Go back to the start of the word and delete its first symbols
+            .wildcard(REWIND, REWIND, None, Direction::Left)
+            .transition(
+                REWIND,
+                erase(0),
+                Segment::Empty,
+                Segment::Empty,
+                Direction::Right,
+            );
+        for index in 0..self.deletion {
+            let to = if index + 1 == self.deletion {
+                START
+            } else {
+                erase(index + 1)
+            };
+            builder = builder.wildcard(erase(index), to, Some(Segment::Empty), Direction::Right);
+        }
+
+        let symbols: BTreeSet<char> = self
+            .productions
+            .iter()
+            .flat_map(|(symbol, production)| std::iter::once(*symbol).chain(production.chars()))
+            .collect();
+        for symbol in symbols {
+            let segment = Segment::Symbol(symbol);
+            let Some(production) = self.production(symbol) else {
+                builder = builder.transition(START, HALT, segment, segment, Direction::Stay);
+                continue;
+            };
+
+            // Check that the word has enough symbols
+            let mut current = state();
+            builder = builder.transition(START, current, segment, segment, Direction::Right);
+            for _ in 1..self.deletion {
+                let next = state();
+                builder = builder
+                    .transition(
+                        current,
+                        BACK,
+                        Segment::Empty,
+                        Segment::Empty,
+                        Direction::Left,
+                    )
+                    .wildcard(current, next, None, Direction::Right);
+                current = next;
+            }
+
+            // Append the production to the end of the word
+            let append = current;
+            builder = builder.wildcard(append, append, None, Direction::Right);
+            if production.is_empty() {
+                builder = builder.transition(
+                    append,
+                    REWIND,
+                    Segment::Empty,
+                    Segment::Empty,
+                    Direction::Left,
+                );
+            }
+            let (mut from, mut condition) = (append, Some(Segment::Empty));
+            for (index, write) in production.chars().map(Segment::Symbol).enumerate() {
+                let (to, action) = if index + 1 == production.chars().count() {
+                    (REWIND, Direction::Left)
+                } else {
+                    (state(), Direction::Right)
+                };
+                builder = match condition {
+                    Some(condition) => builder.transition(from, to, condition, write, action),
+                    None => builder.wildcard(from, to, Some(write), action),
+                };
+                (from, condition) = (to, None);
+            }
+        }
+
+        builder.build().expect("program has an initial state")
+    }
+}
+
+/// Returns a tape holding `word`, surrounded by blanks, with the cursor on
+/// its first symbol.
+#[must_use]
+pub fn encode_word(word: &str) -> VecTape {
+    let mut inner: Vec<Segment> = word.chars().map(Segment::from).collect();
+    inner.push(Segment::Empty);
+
+    VecTape::new(inner, 0)
+}
+
+/// Returns the word starting under the cursor, or [`None`] if there are any
+/// other symbols on the tape.
+#[must_use]
+pub fn decode_word(tape: &VecTape) -> Option<String> {
+    let word: String = (tape.position()..)
+        .map(|position| tape.get(position))
+        .take_while(|segment| *segment != Segment::Empty)
+        .map(char::from)
+        .collect();
+
+    let symbols = tape
+        .trim()
+        .iter()
+        .filter(|segment| **segment != Segment::Empty);
+    (symbols.count() == word.chars().count()).then_some(word)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        models::tag_system::{self, *},
+        *,
+    };
+
+    #[test]
+    fn test_tag_system() {
+        let collatz = TagSystem::new(2, [('a', "bc"), ('b', "a"), ('c', "aaa")]).unwrap();
+        let halting = TagSystem::new(3, [('a', "ba"), ('b', ""), ('c', "x")]).unwrap();
+        let single = TagSystem::new(1, [('a', "bb"), ('b', "")]).unwrap();
+
+        for (system, word) in [
+            (&collatz, "aaaaaaa"),
+            (&collatz, ""),
+            (&halting, "aaaa"),
+            (&halting, "ab"),
+            (&halting, "cbaa"),
+            (&halting, "bbb"),
+            (&single, "aab"),
+        ] {
+            let expected = system.run(word, 10_000).unwrap();
+
+            let mut machine = TuringMachine::from_tape(tag_system::encode_word(word));
+            machine.execute(&system.to_program()).unwrap();
+            assert_eq!(
+                tag_system::decode_word(machine.tape()),
+                Some(expected),
+                "{word}"
+            );
+        }
+
+        assert_eq!(collatz.run("aaaaaaa", 10_000).as_deref(), Some("a"));
+        assert_eq!(halting.run("ab", 10), Some(String::from("ab")));
+        assert_eq!(halting.run("cbaa", 10), Some(String::from("ax")));
+
+        // Appends a word longer than the one it deletes forever
+        let growing = TagSystem::new(1, [('a', "aa")]).unwrap();
+        assert_eq!(growing.run("a", 100), None);
+
+        assert_eq!(
+            TagSystem::new(0, [('a', "a")]),
+            Err(InvalidModel::DeletionNumber)
+        );
+        assert_eq!(
+            TagSystem::new(2, [('a', "a_")]),
+            Err(InvalidModel::BlankSymbol)
+        );
+        assert_eq!(
+            InvalidModel::BlankSymbol.to_string(),
+            "tag system uses \"_\" or \" \" as a symbol"
+        );
+    }
+}
@@ -0,0 +1,523 @@
+//! Turing machines with several heads on a single tape.
+//!
+//! A [`MultiHeadProgram`] is written in the same format as a
+//! [`crate::Program`], except that a transition has a condition, a segment to
+//! write and an action for every head, one after the other:
+//!
+//! ```text
+//! from,to,condition 1,write 1,action 1,condition 2,write 2,action 2
+//! ```
+//!
+//! The number of heads is given by the first transition. Every head reads
+//! the segment under it, then the heads write in order, so that a later head
+//! overwrites an earlier one on the same cell, and finally all heads move.
+//! With two heads, deciding whether a word is a number of "a"s followed by
+//! the same number of "b"s takes a single pass:
+//!
+//! ```
+//! use std::str::FromStr;
+//!
+//! use turing::{
+//!     multihead::{MultiHeadMachine, MultiHeadProgram, MultiHeadTape},
+//!     VecTape,
+//! };
+//!
+//! // The second head skips the "a"s, then both heads walk in lockstep
+//! let program = MultiHeadProgram::from_str(
+//!     "+0\n-2\n0,0,a,=,n,a,=,r\n0,1,a,=,n,b,=,n\n1,1,a,=,r,b,=,r\n1,2,b,=,n,_,=,n",
+//! )
+//! .unwrap();
+//!
+//! let decide = |input| {
+//!     let tape = MultiHeadTape::new(VecTape::from_str(input).unwrap(), 2);
+//!     MultiHeadMachine::from_tape(tape).execute(&program).is_ok()
+//! };
+//! assert!(decide("_aabb"));
+//! assert!(!decide("_aab"));
+//! ```
+//!
+//! Wildcards, seeking actions and includes are not supported.
+use std::{
+    collections::{HashMap, HashSet},
+    ops::Range,
+    str::FromStr,
+};
+
+use crate::{
+    error::{ExecutionError, InvalidProgram, ParseError},
+    program::{Line, StateNames},
+    tape::{Segment, Symbol},
+    Direction, State, Tape,
+};
+
+/// A [`Tape`] with several heads on it.
+///
+/// The heads are kept as positions on the underlying tape, which is moved to
+/// a head whenever it reads or writes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultiHeadTape<T> {
+    tape: T,
+    heads: Vec<i64>,
+}
+
+impl<T> MultiHeadTape<T>
+where
+    T: Tape,
+{
+    /// Create a tape with `heads` heads, all at the cursor position of
+    /// `tape`.
+    #[must_use]
+    pub fn new(tape: T, heads: usize) -> Self {
+        let position = tape.position();
+        Self::with_positions(tape, vec![position; heads])
+    }
+
+    /// Create a tape with a head at each of `positions`.
+    #[must_use]
+    pub fn with_positions(tape: T, positions: Vec<i64>) -> Self {
+        Self {
+            tape,
+            heads: positions,
+        }
+    }
+
+    /// Returns the number of heads.
+    #[must_use]
+    pub fn heads(&self) -> usize {
+        self.heads.len()
+    }
+
+    /// Returns the position of every head.
+    #[must_use]
+    pub fn positions(&self) -> &[i64] {
+        &self.heads
+    }
+
+    /// Returns the segment under `head`.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if there is no such head.
+    #[must_use]
+    pub fn read(&self, head: usize) -> T::Symbol {
+        self.tape.get(self.heads[head])
+    }
+
+    /// Write `segment` to the cell under `head`.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if there is no such head.
+    pub fn write(&mut self, head: usize, segment: T::Symbol) {
+        self.tape.seek(self.heads[head]);
+        self.tape.put(segment);
+    }
+
+    /// Move `head` by `action`.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if there is no such head.
+    pub fn shift(&mut self, head: usize, action: Direction) {
+        self.heads[head] += action.offset();
+    }
+
+    /// Returns a reference to the underlying tape.
+    #[must_use]
+    pub fn tape(&self) -> &T {
+        &self.tape
+    }
+
+    /// Returns the underlying tape.
+    #[must_use]
+    pub fn into_inner(self) -> T {
+        self.tape
+    }
+}
+
+/// What a [`MultiHeadProgram`] does with a single head in a transition.
+#[derive(Debug, Clone, Copy)]
+struct HeadAction<S> {
+    /// The segment to write, or [`None`] to write back the segment that was
+    /// read.
+    write: Option<S>,
+    action: Direction,
+}
+
+/// A transition of a [`MultiHeadProgram`], keyed by its "from" state and
+/// the conditions of all heads.
+#[derive(Debug, Clone)]
+struct MultiHeadTransition<S> {
+    to: State,
+    heads: Vec<HeadAction<S>>,
+}
+
+/// A program for the [`MultiHeadMachine`].
+///
+/// The format is the same as the one of a [`crate::Program`], with a
+/// condition, write and action for each head in every transition.
+#[derive(Debug, Clone)]
+pub struct MultiHeadProgram<S = Segment> {
+    initial_state: State,
+    final_states: HashSet<State>,
+    error_states: HashSet<State>,
+    heads: usize,
+    transitions: HashMap<(State, Vec<S>), MultiHeadTransition<S>>,
+    names: StateNames,
+}
+
+impl<S> MultiHeadProgram<S>
+where
+    S: Symbol,
+{
+    /// Returns the state execution starts in.
+    #[must_use]
+    pub fn initial_state(&self) -> State {
+        self.initial_state
+    }
+
+    /// Returns the number of heads the transitions are written for, which is
+    /// 0 if there are no transitions.
+    #[must_use]
+    pub fn heads(&self) -> usize {
+        self.heads
+    }
+
+    /// Returns the state with the given name, if the program has one.
+    #[must_use]
+    pub fn state(&self, name: &str) -> Option<State> {
+        self.names
+            .iter()
+            .find(|(_, other)| *other == name)
+            .map(|(state, _)| *state)
+    }
+}
+
+impl<S> MultiHeadProgram<S>
+where
+    S: Symbol + FromStr<Err = InvalidProgram>,
+{
+    /// Parse a program over a custom alphabet, using the [`FromStr`]
+    /// implementation of the symbols for the segments of each transition.
+    ///
+    /// # Errors
+    ///
+    /// This method will error if the program is invalid or its transitions
+    /// are not all written for the same number of heads.
+    pub fn parse(s: &str) -> Result<Self, ParseError> {
+        // The fields of the heads are parsed here and removed, so that the
+        // states of each line can be parsed like the line of a single-head
+        // program
+        let mut parsed = Vec::new();
+        let mut lines = Vec::new();
+        let mut heads = None;
+
+        for (number, line) in s.lines().enumerate() {
+            let is_transition =
+                !line.starts_with(['#', '/', '+', '-', '!', '@']) && !line.is_empty();
+            let fields: Vec<&str> = line.split(',').collect();
+
+            if let (true, [from, to, rest @ ..]) = (is_transition, &fields[..]) {
+                let count = rest.len() / 3;
+                if count == 0 || count * 3 != rest.len() || *heads.get_or_insert(count) != count {
+                    return Err(ParseError::whole_line(InvalidProgram::HeadCount, s, number));
+                }
+
+                parsed.push(Some(parse_heads::<S>(
+                    line,
+                    from.len() + to.len() + 2,
+                    rest,
+                    number,
+                )?));
+                lines.push(format!("{from},{to},_,_,n"));
+            } else {
+                parsed.push(None);
+                lines.push(line.to_owned());
+            }
+        }
+
+        let (lines, names) = Line::<Segment>::parse_all(&lines.join("\n"))?;
+        let mut initial_state = None;
+        let mut final_states = HashSet::with_capacity(1);
+        let mut error_states = HashSet::new();
+        let mut transitions = HashMap::new();
+
+        for (number, line) in lines {
+            match line {
+                Line::Initial(state) => initial_state = Some(state),
+                Line::Final(state, _) => {
+                    final_states.insert(state);
+                }
+                Line::Error(state) => {
+                    error_states.insert(state);
+                }
+                Line::Transition(transition) => {
+                    let Some((conditions, heads)) = parsed[number].take() else {
+                        unreachable!("transitions were parsed above");
+                    };
+                    let value = MultiHeadTransition {
+                        to: transition.to,
+                        heads,
+                    };
+
+                    if transitions
+                        .insert((transition.from, conditions), value)
+                        .is_some()
+                    {
+                        return Err(ParseError::duplicate_transition(s, number));
+                    }
+                }
+                Line::Include(_) => {
+                    return Err(ParseError::unsupported_include(s, number));
+                }
+                // Execution always starts in the initial state
+                Line::Entry(..) => {}
+                Line::Wildcard(_) | Line::Seek(..) => {
+                    unreachable!("transitions were replaced with \"_\" and \"n\"")
+                }
+            }
+        }
+
+        Ok(Self {
+            initial_state: initial_state
+                .ok_or_else(|| ParseError::from(InvalidProgram::MissingInitialState))?,
+            final_states,
+            error_states,
+            heads: heads.unwrap_or(0),
+            transitions,
+            names,
+        })
+    }
+}
+
+/// Parse the condition, write and action `fields` of every head of the
+/// transition on `line`, whose first field starts at byte `start`.
+fn parse_heads<S>(
+    line: &str,
+    mut start: usize,
+    fields: &[&str],
+    number: usize,
+) -> Result<(Vec<S>, Vec<HeadAction<S>>), ParseError>
+where
+    S: Symbol + FromStr<Err = InvalidProgram>,
+{
+    let mut ranges = fields.iter().map(|field| {
+        let range = start..start + field.len();
+        start = range.end + 1;
+        range
+    });
+    let error = |kind, range: Range<usize>| ParseError {
+        kind,
+        line: number + 1,
+        column: line[..range.start].chars().count() + 1,
+        text: line[range].to_owned(),
+        line_text: line.to_owned(),
+    };
+
+    let mut conditions = Vec::with_capacity(fields.len() / 3);
+    let mut heads = Vec::with_capacity(fields.len() / 3);
+    for head in fields.chunks(3) {
+        let &[condition, write, action] = head else {
+            unreachable!("the number of fields is a multiple of 3");
+        };
+        let (condition_range, write_range, action_range) = (
+            ranges.next().unwrap_or_default(),
+            ranges.next().unwrap_or_default(),
+            ranges.next().unwrap_or_default(),
+        );
+
+        conditions.push(S::from_str(condition).map_err(|kind| error(kind, condition_range))?);
+        let write = match write {
+            "=" => None,
+            write => Some(S::from_str(write).map_err(|kind| error(kind, write_range))?),
+        };
+        let action = Direction::from_str(action).map_err(|kind| error(kind, action_range))?;
+        heads.push(HeadAction { write, action });
+    }
+
+    Ok((conditions, heads))
+}
+
+impl FromStr for MultiHeadProgram {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+/// A turing machine that can execute [`MultiHeadProgram`]s on a
+/// [`MultiHeadTape`].
+#[derive(Debug)]
+pub struct MultiHeadMachine<T> {
+    tape: MultiHeadTape<T>,
+}
+
+impl<T> MultiHeadMachine<T>
+where
+    T: Tape,
+{
+    /// Create a new [`MultiHeadMachine`] from a [`MultiHeadTape`].
+    #[must_use]
+    pub fn from_tape(tape: MultiHeadTape<T>) -> Self {
+        Self { tape }
+    }
+
+    /// Returns a reference to the [`MultiHeadTape`] of the machine.
+    #[must_use]
+    pub fn tape(&self) -> &MultiHeadTape<T> {
+        &self.tape
+    }
+
+    /// Perform the transition for `state` and the segments under the heads,
+    /// and return the state it leads to.
+    ///
+    /// # Errors
+    ///
+    /// This method will error if no transition is defined, which is always
+    /// the case if the program is written for a different number of heads.
+    /// The error contains the segment under the first head.
+    pub fn step(
+        &mut self,
+        program: &MultiHeadProgram<T::Symbol>,
+        state: State,
+    ) -> Result<State, ExecutionError<T::Symbol>> {
+        let read: Vec<_> = (0..self.tape.heads())
+            .map(|head| self.tape.read(head))
+            .collect();
+        let Some(transition) = program.transitions.get(&(state, read)) else {
+            let first = (self.tape.heads() > 0).then(|| self.tape.read(0));
+            return Err(ExecutionError::UndefinedBehavior(
+                state,
+                first.unwrap_or(T::Symbol::BLANK),
+            ));
+        };
+
+        for (head, action) in transition.heads.iter().enumerate() {
+            if let Some(write) = action.write {
+                self.tape.write(head, write);
+            }
+        }
+        for (head, action) in transition.heads.iter().enumerate() {
+            self.tape.shift(head, action.action);
+        }
+
+        Ok(transition.to)
+    }
+
+    /// Run a [`MultiHeadProgram`] with this turing machine until it halts.
+    ///
+    /// # Errors
+    ///
+    /// This method will error if it encounters undefined behaviour or reaches
+    /// an error state.
+    pub fn execute(
+        &mut self,
+        program: &MultiHeadProgram<T::Symbol>,
+    ) -> Result<State, ExecutionError<T::Symbol>> {
+        self.execute_inner(program, None)
+    }
+
+    /// Run a [`MultiHeadProgram`] with this turing machine for at most
+    /// `max_steps` steps.
+    ///
+    /// # Errors
+    ///
+    /// This method will error if it encounters undefined behaviour, reaches
+    /// an error state or does not halt within `max_steps` steps.
+    pub fn execute_bounded(
+        &mut self,
+        program: &MultiHeadProgram<T::Symbol>,
+        max_steps: u64,
+    ) -> Result<State, ExecutionError<T::Symbol>> {
+        self.execute_inner(program, Some(max_steps))
+    }
+
+    fn execute_inner(
+        &mut self,
+        program: &MultiHeadProgram<T::Symbol>,
+        max_steps: Option<u64>,
+    ) -> Result<State, ExecutionError<T::Symbol>> {
+        let mut state = program.initial_state;
+        let mut steps = 0;
+
+        loop {
+            if max_steps == Some(steps) {
+                return Err(ExecutionError::StepLimitExceeded(steps));
+            }
+
+            state = self.step(program, state)?;
+            steps += 1;
+
+            if program.final_states.contains(&state) {
+                return Ok(state);
+            } else if program.error_states.contains(&state) {
+                return Err(ExecutionError::ReachedError(state));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::{multihead::*, *};
+
+    #[test]
+    fn test_multihead_tape() {
+        let mut tape = MultiHeadTape::new(VecTape::from_str("_abc").unwrap(), 2);
+        tape.shift(1, Direction::Right);
+        tape.shift(1, Direction::Right);
+        assert_eq!(tape.positions(), [0, 2]);
+        assert_eq!(
+            (tape.read(0), tape.read(1)),
+            (Segment::Symbol('a'), Segment::Symbol('c'))
+        );
+
+        tape.write(0, Segment::Symbol('x'));
+        tape.shift(0, Direction::Left);
+        tape.write(0, Segment::Symbol('y'));
+        assert_eq!(tape.into_inner().to_string(), "yxbc");
+    }
+
+    #[test]
+    fn test_multihead_program() {
+        // Copies the input to the right of it, with the second head writing
+        let program = MultiHeadProgram::from_str(
+            "+0\n-done\n0,0,1,=,n,1,=,r\n0,1,1,=,n,_,=,r\n1,1,1,=,r,_,1,r\n1,done,_,=,n,_,=,n",
+        )
+        .unwrap();
+        assert_eq!(program.heads(), 2);
+
+        let tape = MultiHeadTape::new(VecTape::from_str("_111").unwrap(), 2);
+        let mut machine = MultiHeadMachine::from_tape(tape);
+        assert_eq!(
+            machine.execute(&program),
+            Ok(program.state("done").unwrap())
+        );
+        assert_eq!(machine.tape().positions(), [3, 7]);
+        assert_eq!(machine.tape().tape().to_string(), "_111_111");
+
+        // A program for two heads on a tape with one
+        let tape = MultiHeadTape::new(VecTape::from_str("_1").unwrap(), 1);
+        assert_eq!(
+            MultiHeadMachine::from_tape(tape).execute(&program),
+            Err(ExecutionError::UndefinedBehavior(
+                State(0),
+                Segment::Symbol('1')
+            ))
+        );
+
+        let error = MultiHeadProgram::from_str("+0\n0,0,a,a,r\n0,1,b,b,r,b,b,r").unwrap_err();
+        assert_eq!((error.kind, error.line), (InvalidProgram::HeadCount, 3));
+        let error = MultiHeadProgram::from_str("+0\n0,0,a,a,r,b,b,q").unwrap_err();
+        assert_eq!(error.kind, InvalidProgram::InvalidAction);
+        assert_eq!((error.line, error.column), (2, 15));
+        let error = MultiHeadProgram::from_str("+0\n0,0,a,a,r,b,b,r\n0,1,a,b,l,b,a,l").unwrap_err();
+        assert_eq!(
+            (error.kind, error.line),
+            (InvalidProgram::DuplicateTransition, 3)
+        );
+    }
+}
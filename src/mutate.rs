@@ -0,0 +1,263 @@
+//! Mutation testing of programs.
+//!
+//! A mutant is a copy of a program with a single edit to one of its
+//! transitions: it writes a different segment, moves in a different
+//! direction or leads to a different state. A suite of test tapes kills a
+//! mutant if the mutant behaves differently from the original program on at
+//! least one of them, as decided by [`Program::equivalent_on`]. Mutants that
+//! survive point at behaviour the suite does not check, which makes them
+//! useful for grading the test tapes written for an exercise:
+//!
+//! ```
+//! use std::str::FromStr;
+//!
+//! use turing::{mutate, Program, VecTape};
+//!
+//! let program = Program::from_str(include_str!("../examples/next_integer.tng")).unwrap();
+//!
+//! // Never carries into a new digit
+//! let weak = [VecTape::from_str("_10_").unwrap()];
+//! let strong = ["_10_", "_1_", "_1011_"].map(|tape| VecTape::from_str(tape).unwrap());
+//!
+//! let survivors = mutate::surviving(&program, &weak, 1000);
+//! assert!(survivors.len() > mutate::surviving(&program, &strong, 1000).len());
+//! ```
+//!
+//! Wildcard transitions are not mutated. Some mutants are equivalent to the
+//! original program, for example when they retarget a transition that is
+//! never taken, so no suite kills every mutant.
+use std::{collections::BTreeSet, fmt};
+
+use crate::{tape::Symbol, Direction, Program, Segment, State, VecTape};
+
+/// A single edit to a transition of a program.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mutation<S = Segment> {
+    /// The transition writes another segment.
+    Write {
+        /// The state the transition leaves.
+        from: State,
+        /// The segment the transition reads.
+        condition: S,
+        /// The segment written instead.
+        write: S,
+    },
+    /// The transition moves the cursor in another direction.
+    Action {
+        /// The state the transition leaves.
+        from: State,
+        /// The segment the transition reads.
+        condition: S,
+        /// The move performed instead.
+        action: Direction,
+    },
+    /// The transition leads to another state.
+    Retarget {
+        /// The state the transition leaves.
+        from: State,
+        /// The segment the transition reads.
+        condition: S,
+        /// The state the transition leads to instead.
+        to: State,
+    },
+}
+
+impl<S> fmt::Display for Mutation<S>
+where
+    S: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Write {
+                from,
+                condition,
+                write,
+            } => write!(f, "state {from} writes {write} on {condition}"),
+            Self::Action {
+                from,
+                condition,
+                action,
+            } => write!(f, "state {from} moves {action} on {condition}"),
+            Self::Retarget {
+                from,
+                condition,
+                to,
+            } => write!(f, "state {from} leads to {to} on {condition}"),
+        }
+    }
+}
+
+/// A program with a single [`Mutation`].
+#[derive(Debug, Clone)]
+pub struct Mutant<S = Segment> {
+    /// The edit that was applied.
+    pub mutation: Mutation<S>,
+    /// The mutated program.
+    pub program: Program<S>,
+}
+
+impl<S> Program<S>
+where
+    S: Symbol,
+{
+    /// Returns every program that differs from this one by a single
+    /// [`Mutation`], in order of the transitions they edit.
+    ///
+    /// Writes are replaced with every other segment the program reads or
+    /// writes, including the blank, and transitions are retargeted to every
+    /// other state of the program.
+    #[must_use]
+    pub fn mutants(&self) -> Vec<Mutant<S>> {
+        let mut transitions: Vec<_> = self.transitions.values().copied().collect();
+        transitions.sort_by_key(|transition| (transition.from, transition.condition));
+
+        let mut alphabet = BTreeSet::from([S::BLANK]);
+        alphabet.extend(
+            transitions
+                .iter()
+                .flat_map(|transition| [transition.condition, transition.write]),
+        );
+        alphabet.extend(
+            self.wildcards
+                .values()
+                .filter_map(|wildcard| wildcard.write),
+        );
+        let states = self.states();
+
+        let mut mutants = Vec::new();
+        for transition in transitions {
+            let (from, condition) = (transition.from, transition.condition);
+
+            let writes = alphabet
+                .iter()
+                .filter(|write| **write != transition.write)
+                .map(|write| Mutation::Write {
+                    from,
+                    condition,
+                    write: *write,
+                });
+            let actions = [Direction::Left, Direction::Right, Direction::Stay]
+                .into_iter()
+                .filter(|action| *action != transition.action)
+                .map(|action| Mutation::Action {
+                    from,
+                    condition,
+                    action,
+                });
+            let targets =
+                states
+                    .iter()
+                    .filter(|to| **to != transition.to)
+                    .map(|to| Mutation::Retarget {
+                        from,
+                        condition,
+                        to: *to,
+                    });
+
+            for mutation in writes.chain(actions).chain(targets) {
+                mutants.push(Mutant {
+                    mutation,
+                    program: self.mutated(mutation),
+                });
+            }
+        }
+
+        mutants
+    }
+
+    /// Returns the program with `mutation` applied.
+    fn mutated(&self, mutation: Mutation<S>) -> Self {
+        let mut program = self.clone();
+
+        let (from, condition) = match mutation {
+            Mutation::Write {
+                from, condition, ..
+            }
+            | Mutation::Action {
+                from, condition, ..
+            }
+            | Mutation::Retarget {
+                from, condition, ..
+            } => (from, condition),
+        };
+
+        if let Some(transition) = program.transitions.get_mut(&(from, condition)) {
+            match mutation {
+                Mutation::Write { write, .. } => transition.write = write,
+                Mutation::Action { action, .. } => transition.action = action,
+                Mutation::Retarget { to, .. } => transition.to = to,
+            }
+        }
+
+        program
+    }
+}
+
+/// Run every mutant of `program` on `tapes` for at most `max_steps` steps
+/// each and return the mutations of the mutants that behave like `program`
+/// on all of them.
+#[must_use]
+pub fn surviving<S>(program: &Program<S>, tapes: &[VecTape<S>], max_steps: u64) -> Vec<Mutation<S>>
+where
+    S: Symbol,
+{
+    program
+        .mutants()
+        .into_iter()
+        .filter(|mutant| {
+            program
+                .equivalent_on(&mutant.program, tapes.iter().cloned(), max_steps)
+                .is_none()
+        })
+        .map(|mutant| mutant.mutation)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::{mutate::*, *};
+
+    #[test]
+    fn test_mutants() {
+        let program = Program::from_str("+0\n-1\n0,1,a,b,r").unwrap();
+        let mutants = program.mutants();
+
+        // Two other segments, two other moves and one other state
+        assert_eq!(
+            mutants
+                .iter()
+                .map(|mutant| mutant.mutation.to_string())
+                .collect::<Vec<_>>(),
+            [
+                "state 0 writes _ on a",
+                "state 0 writes a on a",
+                "state 0 moves l on a",
+                "state 0 moves n on a",
+                "state 0 leads to 0 on a",
+            ]
+        );
+        assert_eq!(
+            mutants[0].program.to_tng_string(),
+            Program::from_str("+0\n-1\n0,1,a,_,r")
+                .unwrap()
+                .to_tng_string()
+        );
+    }
+
+    #[test]
+    fn test_surviving() {
+        let program = Program::from_str("+0\n-1\n0,1,a,b,r").unwrap();
+
+        // The final tape does not depend on the move
+        let survivors = surviving(&program, &[VecTape::from_str("_a").unwrap()], 10);
+        assert_eq!(survivors.len(), 2);
+        assert!(survivors
+            .iter()
+            .all(|mutation| matches!(mutation, Mutation::Action { .. })));
+
+        // Nothing is checked without tapes
+        assert_eq!(surviving(&program, &[], 10).len(), 5);
+    }
+}
@@ -0,0 +1,354 @@
+//! Non-deterministic turing machines.
+//!
+//! An [`NdProgram`] may define any number of transitions for the same state
+//! and segment. The [`NdTuringMachine`] explores all of them and accepts if
+//! any branch of the computation reaches a final state.
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+};
+
+use crate::{
+    error::{InvalidProgram, ParseError},
+    program::{Line, StateNames, Transition, Wildcard},
+    search::SearchContext,
+    tape::{Segment, Symbol, Tape},
+    Direction, MachineSnapshot, Program, State,
+};
+
+/// A program for the [`NdTuringMachine`].
+///
+/// The format is the same as the one of a [`Program`], except that multiple
+/// transitions may share the same "from" state and condition. Likewise, a
+/// state may have multiple wildcard transitions, which are followed if no
+/// other transition of the state matches.
+#[derive(Debug, Clone)]
+pub struct NdProgram<S = Segment> {
+    pub(crate) initial_state: State,
+    pub(crate) final_states: HashSet<State>,
+    pub(crate) error_states: HashSet<State>,
+    pub(crate) transitions: HashMap<(State, S), Vec<Transition<S>>>,
+    pub(crate) wildcards: HashMap<State, Vec<Wildcard<S>>>,
+    pub(crate) names: StateNames,
+}
+
+impl<S> NdProgram<S>
+where
+    S: Symbol,
+{
+    /// Returns the state execution starts in.
+    #[must_use]
+    pub fn initial_state(&self) -> State {
+        self.initial_state
+    }
+
+    /// Returns the state with the given name, if the program has one.
+    #[must_use]
+    pub fn state(&self, name: &str) -> Option<State> {
+        self.names
+            .iter()
+            .find(|(_, other)| *other == name)
+            .map(|(state, _)| *state)
+    }
+}
+
+impl<S> NdProgram<S>
+where
+    S: Symbol + FromStr<Err = InvalidProgram>,
+{
+    /// Parse a program over a custom alphabet, using the [`FromStr`]
+    /// implementation of the symbols for the segments of each transition.
+    ///
+    /// Programs over [`Segment`]s can be parsed with [`FromStr`] directly.
+    /// Includes are not supported.
+    ///
+    /// # Errors
+    ///
+    /// This method will error if the program is invalid.
+    pub fn parse(s: &str) -> Result<Self, ParseError> {
+        let mut transitions: HashMap<_, Vec<_>> = HashMap::new();
+        let mut wildcards: HashMap<_, Vec<_>> = HashMap::new();
+        let mut initial_state = None;
+        let mut final_states = HashSet::with_capacity(1);
+        let mut error_states = HashSet::new();
+        let (lines, names) = Line::parse_all(s)?;
+
+        for (number, line) in lines {
+            match line {
+                Line::Initial(state) => initial_state = Some(state),
+                Line::Final(state, _) => {
+                    final_states.insert(state);
+                }
+                Line::Error(state) => {
+                    error_states.insert(state);
+                }
+                Line::Transition(transition) => {
+                    transitions
+                        .entry((transition.from, transition.condition))
+                        .or_default()
+                        .push(transition);
+                }
+                Line::Wildcard(wildcard) => {
+                    wildcards.entry(wildcard.from).or_default().push(wildcard);
+                }
+                Line::Seek(transition, wildcard) => {
+                    transitions
+                        .entry((transition.from, transition.condition))
+                        .or_default()
+                        .push(transition);
+                    wildcards.entry(wildcard.from).or_default().push(wildcard);
+                }
+                Line::Include(_) => {
+                    return Err(ParseError::unsupported_include(s, number));
+                }
+                // Execution always starts in the initial state
+                Line::Entry(..) => {}
+            }
+        }
+
+        Ok(Self {
+            initial_state: initial_state
+                .ok_or_else(|| ParseError::from(InvalidProgram::MissingInitialState))?,
+            final_states,
+            error_states,
+            transitions,
+            wildcards,
+            names,
+        })
+    }
+}
+
+impl FromStr for NdProgram {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl<S> From<Program<S>> for NdProgram<S>
+where
+    S: Symbol,
+{
+    fn from(program: Program<S>) -> Self {
+        Self {
+            initial_state: program.initial_state,
+            final_states: program.final_states,
+            error_states: program.error_states,
+            transitions: program
+                .transitions
+                .into_iter()
+                .map(|(key, transition)| (key, vec![transition]))
+                .collect(),
+            wildcards: program
+                .wildcards
+                .into_iter()
+                .map(|(state, wildcard)| (state, vec![wildcard]))
+                .collect(),
+            names: program.names,
+        }
+    }
+}
+
+/// The result of running an [`NdProgram`] with an [`NdTuringMachine`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NdOutcome<T> {
+    /// A branch reached a final state. If multiple branches do, the one with
+    /// the fewest steps is reported.
+    Accepted {
+        /// The final state that was reached.
+        state: State,
+        /// The tape of the accepting branch.
+        tape: T,
+        /// The number of steps the accepting branch took.
+        steps: u64,
+    },
+    /// Every branch halted without reaching a final state, either because it
+    /// reached an error state or because no transition was defined.
+    Rejected,
+    /// No branch reached a final state, but some branches were cut off by the
+    /// depth limit.
+    DepthLimitReached,
+}
+
+/// A non-deterministic turing machine that can execute [`NdProgram`]s.
+#[derive(Debug)]
+pub struct NdTuringMachine<T> {
+    tape: T,
+}
+
+impl<T> NdTuringMachine<T>
+where
+    T: Tape + Clone,
+{
+    /// Create a new [`NdTuringMachine`] from a [`Tape`].
+    #[must_use]
+    pub fn from_tape(tape: T) -> Self {
+        Self { tape }
+    }
+
+    /// Returns a reference to the initial [`Tape`] of every run.
+    #[must_use]
+    pub fn tape(&self) -> &T {
+        &self.tape
+    }
+
+    /// Explore all branches of an [`NdProgram`] breadth-first, following
+    /// each branch for at most `max_depth` steps.
+    ///
+    /// Branches that reach an error state or have no transition to follow are
+    /// discarded.
+    #[must_use]
+    pub fn execute(&self, program: &NdProgram<T::Symbol>, max_depth: u64) -> NdOutcome<T> {
+        self.execute_in(program, max_depth, &mut SearchContext::new())
+    }
+
+    /// Like [`NdTuringMachine::execute`], but taking the tapes of the
+    /// branches from the pool of `context`, which is reset first, and
+    /// returning the tapes of discarded branches to it.
+    pub fn execute_in(
+        &self,
+        program: &NdProgram<T::Symbol>,
+        max_depth: u64,
+        context: &mut SearchContext<T>,
+    ) -> NdOutcome<T> {
+        context.reset();
+        let initial = context.snapshot(&self.tape, program.initial_state, 0);
+        context.branches.push_back(initial);
+        let mut cut_off = false;
+
+        while let Some(branch) = context.branches.pop_front() {
+            if branch.steps == max_depth {
+                cut_off = true;
+                context.recycle_snapshot(branch);
+                continue;
+            }
+
+            let steps = branch.steps + 1;
+            let read = *branch.tape.current();
+            let transitions = program.transitions.get(&(branch.state, read));
+            let wildcards = program
+                .wildcards
+                .get(&branch.state)
+                .filter(|_| transitions.is_none())
+                .into_iter()
+                .flatten()
+                .map(|wildcard| wildcard.resolve(read));
+
+            for transition in transitions.into_iter().flatten().copied().chain(wildcards) {
+                let mut tape = context.tape(&branch.tape);
+                tape.put(transition.write);
+
+                match transition.action {
+                    Direction::Left => tape.left(),
+                    Direction::Right => tape.right(),
+                    Direction::Stay => {}
+                }
+
+                if program.final_states.contains(&transition.to) {
+                    context.recycle_snapshot(branch);
+                    return NdOutcome::Accepted {
+                        state: transition.to,
+                        tape,
+                        steps,
+                    };
+                }
+
+                if program.error_states.contains(&transition.to) {
+                    context.recycle(tape);
+                } else {
+                    context.branches.push_back(MachineSnapshot {
+                        tape,
+                        state: transition.to,
+                        steps,
+                    });
+                }
+            }
+
+            context.recycle_snapshot(branch);
+        }
+
+        if cut_off {
+            NdOutcome::DepthLimitReached
+        } else {
+            NdOutcome::Rejected
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::{nondeterministic::*, search::SearchContext, *};
+
+    /// Accepts words over "a" and "b" that contain "ab", by guessing where the
+    /// "a" is.
+    const CONTAINS_AB: &str = "+0\n-2\n0,0,a,a,r\n0,1,a,a,r\n0,0,b,b,r\n1,2,b,b,r";
+
+    #[test]
+    fn test_nd_accept() {
+        let program = NdProgram::from_str(CONTAINS_AB).unwrap();
+        let machine = NdTuringMachine::from_tape(VecTape::from_str("_bbaab_").unwrap());
+
+        match machine.execute(&program, 100) {
+            NdOutcome::Accepted { state, tape, steps } => {
+                assert_eq!(state, State::from_str("2").unwrap());
+                assert_eq!(tape.to_string(), "_bbaab_");
+                assert_eq!(steps, 5);
+            }
+            outcome => panic!("expected acceptance, got {outcome:?}"),
+        }
+    }
+
+    #[test]
+    fn test_nd_reject() {
+        let program = NdProgram::from_str(CONTAINS_AB).unwrap();
+        let machine = NdTuringMachine::from_tape(VecTape::from_str("_bbaa_").unwrap());
+
+        assert_eq!(machine.execute(&program, 100), NdOutcome::Rejected);
+    }
+
+    #[test]
+    fn test_nd_depth_limit() {
+        let program = NdProgram::from_str("+0\n-1\n0,0,_,_,r\n0,0,_,_,l").unwrap();
+        let machine = NdTuringMachine::from_tape(VecTape::from_str("_").unwrap());
+
+        assert_eq!(machine.execute(&program, 8), NdOutcome::DepthLimitReached);
+    }
+
+    #[test]
+    fn test_nd_deterministic_program() {
+        let program = Program::from_str(include_str!("../examples/palindrome.tng")).unwrap();
+        let program = NdProgram::from(program);
+
+        let machine = NdTuringMachine::from_tape(VecTape::from_str("_101_").unwrap());
+        assert!(matches!(
+            machine.execute(&program, 1000),
+            NdOutcome::Accepted { .. }
+        ));
+
+        let machine = NdTuringMachine::from_tape(VecTape::from_str("_100_").unwrap());
+        assert_eq!(machine.execute(&program, 1000), NdOutcome::Rejected);
+    }
+
+    #[test]
+    fn test_nd_context() {
+        let program = NdProgram::from_str(CONTAINS_AB).unwrap();
+        let machine = NdTuringMachine::from_tape(VecTape::from_str("_bbaab_").unwrap());
+        let mut context = SearchContext::new();
+
+        let outcome = machine.execute_in(&program, 100, &mut context);
+        assert_eq!(outcome, machine.execute(&program, 100));
+        assert!(context.pooled() > 0);
+
+        // Branches left over from the accepting run are reused by the next one
+        let pooled = context.pooled();
+        let machine = NdTuringMachine::from_tape(VecTape::from_str("_bbaa_").unwrap());
+        assert_eq!(
+            machine.execute_in(&program, 100, &mut context),
+            NdOutcome::Rejected
+        );
+        assert!(context.pooled() >= pooled);
+    }
+}
@@ -0,0 +1,196 @@
+//! Removal of transitions that keep the cursor in place.
+//!
+//! Many formal definitions of turing machines only allow moving left or
+//! right, and some tools cannot import transitions with the action "n".
+//! [`Program::normalize`] rewrites them into transitions that move, without
+//! changing what the program computes:
+//!
+//! ```
+//! use std::str::FromStr;
+//!
+//! use turing::{testing, Program};
+//!
+//! let program = Program::from_str(include_str!("../examples/next_integer.tng")).unwrap();
+//! assert!(!program.stay_put_transitions().is_empty());
+//!
+//! let normalized = program.normalize();
+//! assert!(normalized.stay_put_transitions().is_empty());
+//!
+//! let mut rng = testing::SplitMix64::new(7);
+//! let inputs = (0..100).map(|_| testing::random_tape(&mut rng, &['0', '1'], 8));
+//! assert_eq!(program.equivalent_on(&normalized, inputs, 1000), None);
+//! ```
+use std::collections::{HashMap, HashSet};
+
+use crate::{program::Wildcard, tape::Symbol, Direction, Program, State};
+
+impl<S> Program<S>
+where
+    S: Symbol,
+{
+    /// Returns the state and condition of every transition that keeps the
+    /// cursor in place, sorted by state and condition. Wildcard transitions
+    /// have [`None`] as their condition.
+    ///
+    /// These are the transitions [`Program::normalize`] removes, so a
+    /// program can be checked before it is exported to a tool that does not
+    /// support them.
+    #[must_use]
+    pub fn stay_put_transitions(&self) -> Vec<(State, Option<S>)> {
+        let mut stays: Vec<_> = self
+            .transitions
+            .values()
+            .filter(|transition| transition.action == Direction::Stay)
+            .map(|transition| (transition.from, Some(transition.condition)))
+            .chain(
+                self.wildcards
+                    .values()
+                    .filter(|wildcard| wildcard.action == Direction::Stay)
+                    .map(|wildcard| (wildcard.from, None)),
+            )
+            .collect();
+        stays.sort();
+
+        stays
+    }
+
+    /// Returns an equivalent program in which every transition moves the
+    /// cursor left or right.
+    ///
+    /// A transition that keeps the cursor in place is merged with the
+    /// transition the program takes next, which is known because the segment
+    /// under the cursor was just written. Where that is not possible, because
+    /// the next state halts, the next transition is undefined or depends on
+    /// a segment a wildcard writes back, the transition moves right instead
+    /// and leads to a new state that moves back left.
+    ///
+    /// The normalized program halts with the same tape contents and in the
+    /// same state, but may take a different number of steps.
+    #[must_use]
+    pub fn normalize(&self) -> Self {
+        let mut program = self.clone();
+
+        let mut next = self.states().last().map_or(0, |state| state.0 + 1);
+        let mut detours = HashMap::new();
+        let mut detour = |to: State| {
+            *detours.entry(to).or_insert_with(|| {
+                next += 1;
+                State(next - 1)
+            })
+        };
+
+        // Sorted, so that the new states are numbered the same every time
+        let mut keys: Vec<_> = program.transitions.keys().copied().collect();
+        keys.sort();
+        for key in keys {
+            let Some(transition) = program.transitions.get_mut(&key) else {
+                continue;
+            };
+            if transition.action != Direction::Stay {
+                continue;
+            }
+
+            if let Some((write, action, to)) = self.successor(transition.to, transition.write) {
+                (transition.write, transition.action, transition.to) = (write, action, to);
+            } else {
+                (transition.action, transition.to) = (Direction::Right, detour(transition.to));
+            }
+        }
+
+        let mut keys: Vec<_> = program.wildcards.keys().copied().collect();
+        keys.sort();
+        for key in keys {
+            let Some(wildcard) = program.wildcards.get_mut(&key) else {
+                continue;
+            };
+            if wildcard.action != Direction::Stay {
+                continue;
+            }
+
+            let successor = wildcard
+                .write
+                .and_then(|write| self.successor(wildcard.to, write));
+            if let Some((write, action, to)) = successor {
+                (wildcard.write, wildcard.action, wildcard.to) = (Some(write), action, to);
+            } else {
+                (wildcard.action, wildcard.to) = (Direction::Right, detour(wildcard.to));
+            }
+        }
+
+        for (to, from) in detours {
+            program.wildcards.insert(
+                from,
+                Wildcard {
+                    from,
+                    to,
+                    write: None,
+                    action: Direction::Left,
+                },
+            );
+        }
+
+        program
+    }
+
+    /// Follow the transitions from `state` reading `read` that keep the
+    /// cursor in place and return the write, action and target of the first
+    /// one that moves.
+    ///
+    /// Returns [`None`] if execution halts, fails or keeps the cursor in place
+    /// forever before that.
+    fn successor(&self, mut state: State, mut read: S) -> Option<(S, Direction, State)> {
+        let mut visited = HashSet::new();
+
+        loop {
+            if self.final_states.contains(&state)
+                || self.error_states.contains(&state)
+                || !visited.insert((state, read))
+            {
+                return None;
+            }
+
+            let transition = self.transition(state, read)?;
+            if transition.action != Direction::Stay {
+                return Some((transition.write, transition.action, transition.to));
+            }
+
+            (state, read) = (transition.to, transition.write);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::*;
+
+    #[test]
+    fn test_normalize() {
+        // Writes "b" in place, then moves right in the next state
+        let program = Program::from_str("+0\n-2\n0,1,a,b,n\n1,2,b,c,r").unwrap();
+        let normalized = program.normalize();
+        assert_eq!(normalized.to_tng_string(), "+0\n-2\n0,2,a,c,r\n1,2,b,c,r\n");
+
+        // Halts right after keeping the cursor in place, or never moves
+        let program = Program::from_str("+0\n-1\n0,1,a,b,n\n0,0,*,=,n").unwrap();
+        let normalized = program.normalize();
+        assert_eq!(normalized.stay_put_transitions(), []);
+        assert_eq!(
+            normalized.to_tng_string(),
+            "+0\n-1\n0,2,a,b,r\n0,3,*,=,r\n2,1,*,=,l\n3,0,*,=,l\n"
+        );
+
+        let inputs = ["_a", "_x", "_"].map(|input| VecTape::from_str(input).unwrap());
+        assert_eq!(program.equivalent_on(&normalized, inputs, 100), None);
+    }
+
+    #[test]
+    fn test_stay_put_transitions() {
+        let program = Program::from_str("+0\n-1\n1,0,a,a,r\n0,1,b,b,n\n0,0,*,x,n").unwrap();
+        assert_eq!(
+            program.stay_put_transitions(),
+            [(State(0), None), (State(0), Some(Segment::Symbol('b')))]
+        );
+    }
+}
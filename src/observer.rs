@@ -0,0 +1,183 @@
+//! Callbacks for the events of an execution.
+//!
+//! An [`Observer`] registered with [`crate::TuringMachine::add_observer`] is
+//! told about every step the machine performs, no matter which method runs
+//! the program. This lets visualizers, loggers and statistics plug into the
+//! execution without reimplementing it:
+//!
+//! ```
+//! use std::{cell::RefCell, rc::Rc, str::FromStr};
+//!
+//! use turing::{observer::Observer, Program, Segment, TuringMachine, VecTape};
+//!
+//! #[derive(Default)]
+//! struct Writes(u64);
+//!
+//! impl Observer<Segment> for Writes {
+//!     fn on_write(&mut self, _: i64, _: Segment, _: Segment) {
+//!         self.0 += 1;
+//!     }
+//! }
+//!
+//! let program = Program::from_str(include_str!("../examples/next_integer.tng")).unwrap();
+//! let writes = Rc::new(RefCell::new(Writes::default()));
+//!
+//! let mut machine = TuringMachine::from_tape(VecTape::from_str("_1011_").unwrap());
+//! machine.add_observer(Rc::clone(&writes));
+//! machine.execute(&program).unwrap();
+//!
+//! assert_eq!(writes.borrow().0, 3);
+//! ```
+use std::{cell::RefCell, fmt, rc::Rc};
+
+use crate::{trace::TraceStep, ExecutionError, State};
+
+/// Callbacks for the events of an execution, which do nothing by default.
+///
+/// Positions are the ones of [`crate::Tape::position`]. Steps that
+/// [`crate::TuringMachine::execute_accelerated`] skips over at once are not
+/// reported.
+pub trait Observer<S> {
+    /// Called after every step, with the position of the cursor before it.
+    fn on_step(&mut self, step: &TraceStep<S>) {
+        let _ = step;
+    }
+
+    /// Called when a step replaces the segment `old` at `position` with a
+    /// different segment `new`.
+    fn on_write(&mut self, position: i64, old: S, new: S) {
+        let _ = (position, old, new);
+    }
+
+    /// Called when a step leads to a different state than the one it started
+    /// in.
+    fn on_state_change(&mut self, from: State, to: State) {
+        let _ = (from, to);
+    }
+
+    /// Called once the program stopped for good, with the final state it
+    /// reached or the error it ran into: an error state or undefined
+    /// behaviour.
+    fn on_halt(&mut self, result: &Result<State, ExecutionError<S>>) {
+        let _ = result;
+    }
+}
+
+/// Shared observers can be inspected while they are registered.
+impl<S, O> Observer<S> for Rc<RefCell<O>>
+where
+    O: Observer<S> + ?Sized,
+{
+    fn on_step(&mut self, step: &TraceStep<S>) {
+        self.borrow_mut().on_step(step);
+    }
+
+    fn on_write(&mut self, position: i64, old: S, new: S) {
+        self.borrow_mut().on_write(position, old, new);
+    }
+
+    fn on_state_change(&mut self, from: State, to: State) {
+        self.borrow_mut().on_state_change(from, to);
+    }
+
+    fn on_halt(&mut self, result: &Result<State, ExecutionError<S>>) {
+        self.borrow_mut().on_halt(result);
+    }
+}
+
+/// The observers registered with a machine.
+pub(crate) struct Observers<S>(pub(crate) Vec<Box<dyn Observer<S>>>);
+
+impl<S> Observers<S> {
+    /// Call `event` for every observer.
+    pub(crate) fn notify(&mut self, mut event: impl FnMut(&mut dyn Observer<S>)) {
+        for observer in &mut self.0 {
+            event(observer.as_mut());
+        }
+    }
+}
+
+impl<S> fmt::Debug for Observers<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} observers", self.0.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc, str::FromStr};
+
+    use crate::{observer::*, *};
+
+    /// Records every event as a line of text.
+    #[derive(Default)]
+    struct Log(Vec<String>);
+
+    impl Observer<Segment> for Log {
+        fn on_step(&mut self, step: &TraceStep) {
+            self.0
+                .push(format!("step {} at {}", step.read, step.position));
+        }
+
+        fn on_write(&mut self, position: i64, old: Segment, new: Segment) {
+            self.0.push(format!("write {old} -> {new} at {position}"));
+        }
+
+        fn on_state_change(&mut self, from: State, to: State) {
+            self.0.push(format!("state {from} -> {to}"));
+        }
+
+        fn on_halt(&mut self, result: &Result<State, ExecutionError>) {
+            self.0.push(format!("halt {result:?}"));
+        }
+    }
+
+    #[test]
+    fn test_observer_events() {
+        let program = Program::from_str("+0\n-1\n0,0,a,b,r\n0,0,b,b,r\n0,1,_,_,n").unwrap();
+        let log = Rc::new(RefCell::new(Log::default()));
+
+        let mut machine = TuringMachine::from_tape(VecTape::from_str("_ab").unwrap());
+        machine.add_observer(Rc::clone(&log));
+        machine.execute(&program).unwrap();
+
+        assert_eq!(
+            log.borrow().0,
+            [
+                "step a at 0",
+                "write a -> b at 0",
+                "step b at 1",
+                "step _ at 2",
+                "state 0 -> 1",
+                "halt Ok(State(1))",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_observer_errors() {
+        let program = Program::from_str("+0\n!1\n0,0,a,a,r\n0,1,b,b,n").unwrap();
+        let log = Rc::new(RefCell::new(Log::default()));
+
+        for (input, expected) in [
+            ("_ab", "halt Err(ReachedError(State(1)))"),
+            ("_a_", "halt Err(UndefinedBehavior(State(0), Empty))"),
+        ] {
+            let mut machine = TuringMachine::from_tape(VecTape::from_str(input).unwrap());
+            machine.add_observer(Rc::clone(&log));
+            assert!(machine.execute(&program).is_err());
+            assert_eq!(log.borrow().0.last().unwrap(), expected);
+
+            machine.clear_observers();
+            let _ = machine.execute_bounded(&program, 10);
+            assert_eq!(log.borrow().0.last().unwrap(), expected);
+        }
+
+        // Running out of steps is not the end of the program
+        let mut machine = TuringMachine::from_tape(VecTape::from_str("_aaa").unwrap());
+        machine.add_observer(Rc::clone(&log));
+        log.borrow_mut().0.clear();
+        assert!(machine.execute_bounded(&program, 2).is_err());
+        assert_eq!(log.borrow().0, ["step a at 0", "step a at 1"]);
+    }
+}
@@ -0,0 +1,330 @@
+//! Parsing that reports every problem in a program instead of only the first.
+//!
+//! [`Program::parse`] stops at the first error, which is all a program runner
+//! needs. Editors want to show all problems at once, so a [`Parser`] skips
+//! invalid lines, keeps going and returns a [`Diagnostic`] for each of them,
+//! together with the program made of the remaining lines:
+//!
+//! ```
+//! use turing::{parser::Parser, Program};
+//!
+//! let (program, diagnostics): (Option<Program>, _) =
+//!     Parser::new().parse("+0\n-1\n0,1,_,_,x\n0,1a,a,a,r\n0,1,b,b,n");
+//!
+//! assert_eq!(program.unwrap().to_string(), "+0\n-1\n0,1,b,b,n\n");
+//! assert_eq!(diagnostics.len(), 2);
+//! assert_eq!((diagnostics[0].line, diagnostics[0].column), (3, 9));
+//! assert_eq!((diagnostics[1].line, diagnostics[1].column), (4, 3));
+//! ```
+use std::{fmt, str::FromStr};
+
+use crate::{
+    error::{write_snippet, InvalidProgram, ParseError},
+    tape::Symbol,
+    Program,
+};
+
+/// How serious a [`Diagnostic`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// The line is invalid and was skipped.
+    Error,
+    /// The program is valid, but probably does not do what was intended.
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Error => "error",
+            Self::Warning => "warning",
+        })
+    }
+}
+
+/// A problem in the source of a program, found by a [`Parser`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// How serious the problem is.
+    pub severity: Severity,
+    /// A description of the problem.
+    pub message: String,
+    /// The line the problem occurred on, counting from 1, or 0 if the problem
+    /// is about the program as a whole.
+    pub line: usize,
+    /// The column the offending text starts at, in characters and counting
+    /// from 1.
+    pub column: usize,
+    /// The offending text, which is empty if something is missing.
+    pub text: String,
+    /// The full line the problem occurred on.
+    pub line_text: String,
+}
+
+impl From<ParseError> for Diagnostic {
+    fn from(error: ParseError) -> Self {
+        Self {
+            severity: Severity::Error,
+            message: error.kind.to_string(),
+            line: error.line,
+            column: error.column,
+            text: error.text,
+            line_text: error.line_text,
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    /// Writes the severity and the problem, followed by the offending line
+    /// like [`ParseError`] does.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.severity, self.message)?;
+
+        if self.line == 0 {
+            return Ok(());
+        }
+
+        writeln!(f, " (line {}, column {})", self.line, self.column)?;
+        write_snippet(f, self.line, self.column, &self.text, &self.line_text)
+    }
+}
+
+/// Options for [`Program::parse_with`] and [`Parser::with_options`].
+///
+/// The default options parse programs leniently, like [`Program::parse`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Whether to reject lines that start with an unknown marker,
+    /// transitions with more than five fields, tabs and whitespace at the
+    /// start or end of a line or next to a comma.
+    ///
+    /// Lenient parsing treats every line starting with "/" as a comment,
+    /// ignores extra fields and whitespace around the values of transitions
+    /// and reads a space as a blank segment. Tools that generate or check
+    /// programs want strict parsing to catch mistakes early, while lenient
+    /// parsing forgives the ones beginners make.
+    pub strict: bool,
+}
+
+/// Reads the source of an included program, given its path.
+type Read<'r> = Box<dyn FnMut(&str) -> Option<String> + 'r>;
+
+/// A parser for [`Program`]s that collects all problems in the source.
+///
+/// Invalid lines are skipped, and transitions that have the same "from" state
+/// and condition as an earlier one are dropped, so the program only consists
+/// of the valid lines. Includes are only resolved if the parser was created
+/// with [`Parser::with_includes`].
+pub struct Parser<'r> {
+    read: Read<'r>,
+    options: ParseOptions,
+}
+
+impl Parser<'_> {
+    /// Create a parser that does not support includes.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_includes(|_| None)
+    }
+}
+
+impl<'r> Parser<'r> {
+    /// Create a parser that reads included programs with `read`, like
+    /// [`Program::parse_with_includes`].
+    #[must_use]
+    pub fn with_includes(read: impl FnMut(&str) -> Option<String> + 'r) -> Self {
+        Self {
+            read: Box::new(read),
+            options: ParseOptions::default(),
+        }
+    }
+
+    /// Parse programs with `options` instead of the default, lenient ones.
+    #[must_use]
+    pub fn with_options(mut self, options: ParseOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Parse a program, returning what is left of it after skipping the
+    /// invalid lines and a [`Diagnostic`] for every problem that was found.
+    ///
+    /// The program is [`None`] if it has no valid initial state. Diagnostics
+    /// are sorted in the order [`Program::parse`] would find them, so the
+    /// first one is the error it would return.
+    pub fn parse<S>(&mut self, s: &str) -> (Option<Program<S>>, Vec<Diagnostic>)
+    where
+        S: Symbol + FromStr<Err = InvalidProgram>,
+    {
+        let mut errors = Vec::new();
+        let program = Program::parse_recovering(s, &mut self.read, 0, self.options, &mut errors);
+
+        (program, errors.into_iter().map(Diagnostic::from).collect())
+    }
+}
+
+impl Default for Parser<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Parser<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Parser").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{parser::*, *};
+
+    #[test]
+    fn test_parser_collects_errors() {
+        let source = "+0\n-1\n0,1,a,b,r\n0,x y,a,a,n\n0,0,a,a,l\n0,1,_,_\n0,1,b,b,q";
+        let (program, diagnostics): (Option<Program>, _) = Parser::new().parse(source);
+
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|diagnostic| (diagnostic.line, diagnostic.text.as_str()))
+                .collect::<Vec<_>>(),
+            [(4, "x y"), (6, ""), (7, "q"), (5, "0,0,a,a,l")]
+        );
+        assert!(diagnostics
+            .iter()
+            .all(|diagnostic| diagnostic.severity == Severity::Error));
+        // The first diagnostic is the error parsing stops at
+        assert_eq!(
+            diagnostics[0],
+            Diagnostic::from(Program::from_str(source).unwrap_err())
+        );
+        assert_eq!(program.unwrap().to_string(), "+0\n-1\n0,1,a,b,r\n");
+    }
+
+    #[test]
+    fn test_parser_without_initial_state() {
+        let (program, diagnostics): (Option<Program>, _) =
+            Parser::new().parse("-1\n0,1,a,b,r\n0,1,a,b,!");
+
+        assert!(program.is_none());
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(
+            diagnostics[1].to_string(),
+            "error: program is missing an initial state"
+        );
+        assert_eq!(
+            diagnostics[0].to_string(),
+            "error: invalid movement action, expected \"l\", \"r\", \"n\", \"l*\" or \"r*\" \
+             (line 3, column 9)\n  |\n3 | 0,1,a,b,!\n  |         ^"
+        );
+    }
+
+    #[test]
+    fn test_strict_parsing() {
+        let strict = ParseOptions { strict: true };
+
+        for (source, kind, text) in [
+            ("+0\n-1\n0,1,a,b,r,x", InvalidProgram::TrailingField, ",x"),
+            (
+                "+0\n-1\n0,1, ,_,r",
+                InvalidProgram::UnexpectedWhitespace,
+                " ",
+            ),
+            (
+                "+0\n-1\n0 ,1,a,b,r",
+                InvalidProgram::UnexpectedWhitespace,
+                " ",
+            ),
+            (
+                "+0\n-1\n0,1,a,b,r  ",
+                InvalidProgram::UnexpectedWhitespace,
+                "  ",
+            ),
+            (
+                "+0\n -1\n0,1,a,b,r",
+                InvalidProgram::UnexpectedWhitespace,
+                " ",
+            ),
+            (
+                "+0\n-1\n0,1,a,b,r\t# tab",
+                InvalidProgram::UnexpectedWhitespace,
+                "\t",
+            ),
+            (
+                "+0\n-1\n/ note\n0,1,a,b,r",
+                InvalidProgram::UnknownMarker,
+                "/",
+            ),
+            (
+                "+0\n-1\n@program x\n0,1,a,b,r",
+                InvalidProgram::UnknownMarker,
+                "@program",
+            ),
+        ] {
+            let error = Program::<Segment>::parse_with(source, strict).unwrap_err();
+            assert_eq!(
+                (&error.kind, error.text.as_str()),
+                (&kind, text),
+                "{source}"
+            );
+        }
+
+        // Lenient parsing accepts these
+        for source in [
+            "+0\n-1\n0,1,a,b,r,x",
+            "+0\n-1\n0,1, ,_,r",
+            "+0\n-1\n/ note\n0,1,a,b,r",
+        ] {
+            assert!(Program::from_str(source).is_ok(), "{source}");
+        }
+
+        let source = "+0\n-1\n// note\n0,1,a,b,r # comment, with a comma\n@entry start 0";
+        assert!(Program::<Segment>::parse_with(source, strict).is_ok());
+        for source in [
+            include_str!("../examples/append.tng"),
+            include_str!("../examples/copy.tng"),
+            include_str!("../examples/next_integer.tng"),
+            include_str!("../examples/palindrome.tng"),
+            include_str!("../examples/swap.tng"),
+        ] {
+            assert!(Program::<Segment>::parse_with(source, strict).is_ok());
+        }
+
+        let (program, diagnostics): (Option<Program>, _) = Parser::new()
+            .with_options(strict)
+            .parse("+0\n-1\n0,1,a,b,r,x\n0,1, ,_,r\n0,1,b,b,r");
+        assert_eq!(program.unwrap().to_string(), "+0\n-1\n0,1,b,b,r\n");
+        assert_eq!(diagnostics.len(), 2);
+    }
+
+    #[test]
+    fn test_parser_includes() {
+        let files = HashMap::from([
+            ("broken.tng", "+0\n-1\n0,1,_,_,?"),
+            ("fine.tng", include_str!("../examples/next_integer.tng")),
+        ]);
+        let mut parser = Parser::with_includes(|path| files.get(path).map(ToString::to_string));
+        let (program, diagnostics): (Option<Program>, _) = parser.parse(
+            "+0\n-3\n@include \"missing.tng\" 0 1\n@include \"broken.tng\" 0 1\n\
+             @include \"fine.tng\" 0 3",
+        );
+
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|diagnostic| (diagnostic.line, diagnostic.message.as_str()))
+                .collect::<Vec<_>>(),
+            [
+                (3, "included program could not be read or includes itself"),
+                (
+                    3,
+                    "invalid movement action, expected \"l\", \"r\", \"n\", \"l*\" or \"r*\""
+                ),
+            ]
+        );
+        assert_eq!(program.unwrap().transitions.len(), 9);
+    }
+}
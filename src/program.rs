@@ -1,51 +1,290 @@
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{btree_map, BTreeMap, BTreeSet, HashMap, HashSet},
+    fmt,
+    ops::Range,
     str::FromStr,
 };
 
-use crate::{error::InvalidProgram, tape::Segment};
+use crate::{
+    error::{ExecutionError, InvalidProgram, ParseError},
+    parser::ParseOptions,
+    tape::{Segment, Symbol},
+};
 
-/// An movement action in a program.
-#[derive(Debug)]
-pub(crate) enum Move {
+/// The direction a transition moves the cursor in after writing.
+///
+/// It is written as "l", "r" or "n" in programs, and parsed from them in
+/// either case, with an empty action or a blank also meaning
+/// [`Direction::Stay`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Direction {
+    /// Move the cursor to the left by one.
     Left,
+    /// Move the cursor to the right by one.
     Right,
-    Nothing,
+    /// Keep the cursor where it is.
+    Stay,
+}
+
+impl Direction {
+    /// Returns the change of the cursor position caused by this direction.
+    #[must_use]
+    pub fn offset(self) -> i64 {
+        match self {
+            Self::Left => -1,
+            Self::Right => 1,
+            Self::Stay => 0,
+        }
+    }
+
+    /// Returns the opposite direction.
+    #[must_use]
+    pub fn opposite(self) -> Self {
+        match self {
+            Self::Left => Self::Right,
+            Self::Right => Self::Left,
+            Self::Stay => Self::Stay,
+        }
+    }
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Left => "l",
+            Self::Right => "r",
+            Self::Stay => "n",
+        })
+    }
+}
+
+/// The outcome of a [`Program`] that decides a problem, given by the final
+/// state it halted in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    /// The program accepted its input.
+    Accepted,
+    /// The program rejected its input.
+    Rejected,
 }
 
 /// A state in a [`Program`].
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
-pub struct State(usize);
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct State(pub(crate) usize);
+
+impl fmt::Display for State {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
 
 /// A transition in a [`Program`].
 ///
 /// If the transition matches the [`crate::TuringMachine`]'s current
-/// state, it will write to the tape and move the cursor.
-#[derive(Debug)]
-pub(crate) struct Transition {
+/// state, it will write to the tape and move the cursor. Transitions are
+/// inspected with [`Program::transitions`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Transition<S = Segment> {
     pub(crate) from: State,
     pub(crate) to: State,
-    pub(crate) condition: Segment,
-    pub(crate) write: Segment,
-    pub(crate) action: Move,
+    pub(crate) condition: S,
+    pub(crate) write: S,
+    pub(crate) action: Direction,
 }
 
+/// A transition in a [`Program`] that matches any segment, written with "*"
+/// as its condition.
+///
+/// It is only taken if no [`Transition`] matches the current segment.
+/// Wildcards are inspected with [`Program::wildcards`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Wildcard<S = Segment> {
+    pub(crate) from: State,
+    pub(crate) to: State,
+    /// The segment to write, or [`None`] to write back the segment that was
+    /// read, written as "=".
+    pub(crate) write: Option<S>,
+    pub(crate) action: Direction,
+}
+
+impl<S> Transition<S>
+where
+    S: Copy,
+{
+    /// Returns the state the transition is taken in.
+    #[must_use]
+    pub fn from(&self) -> State {
+        self.from
+    }
+
+    /// Returns the state the transition leads to.
+    #[must_use]
+    pub fn to(&self) -> State {
+        self.to
+    }
+
+    /// Returns the segment the transition is taken for.
+    #[must_use]
+    pub fn condition(&self) -> S {
+        self.condition
+    }
+
+    /// Returns the segment the transition writes.
+    #[must_use]
+    pub fn write(&self) -> S {
+        self.write
+    }
+
+    /// Returns the direction the transition moves the cursor in after writing.
+    #[must_use]
+    pub fn action(&self) -> Direction {
+        self.action
+    }
+}
+
+impl<S> Wildcard<S>
+where
+    S: Copy,
+{
+    /// Returns the state the wildcard is taken in.
+    #[must_use]
+    pub fn from(&self) -> State {
+        self.from
+    }
+
+    /// Returns the state the wildcard leads to.
+    #[must_use]
+    pub fn to(&self) -> State {
+        self.to
+    }
+
+    /// Returns the segment the wildcard writes, or [`None`] if it writes back
+    /// the segment that was read.
+    #[must_use]
+    pub fn write(&self) -> Option<S> {
+        self.write
+    }
+
+    /// Returns the direction the wildcard moves the cursor in after writing.
+    #[must_use]
+    pub fn action(&self) -> Direction {
+        self.action
+    }
+
+    /// Returns the transition this wildcard performs when reading `read`.
+    pub(crate) fn resolve(&self, read: S) -> Transition<S> {
+        Transition {
+            from: self.from,
+            to: self.to,
+            condition: read,
+            write: self.write.unwrap_or(read),
+            action: self.action,
+        }
+    }
+}
+
+/// The transitions of a [`Program`], keyed by their "from" state and
+/// condition.
+pub(crate) type Transitions<S> = HashMap<(State, S), Transition<S>>;
+
+/// The wildcard transitions of a [`Program`], keyed by their "from" state.
+pub(crate) type Wildcards<S> = HashMap<State, Wildcard<S>>;
+
+/// The names of the named states of a [`Program`].
+pub(crate) type StateNames = HashMap<State, String>;
+
+/// The entry points of a [`Program`], keyed by their name.
+pub(crate) type EntryPoints = BTreeMap<String, State>;
+
+/// A line of a [`Program`] that can carry a trailing comment, written after
+/// " #" at the end of the line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CommentTarget<S = Segment> {
+    /// The declaration of the initial state.
+    Initial,
+    /// The declaration of a final state.
+    Final(State),
+    /// The declaration of an error state.
+    Error(State),
+    /// The transition from a state for a condition.
+    Transition(State, S),
+    /// The wildcard transition from a state.
+    Wildcard(State),
+}
+
+impl<S> CommentTarget<S> {
+    /// Returns the target with its states replaced by `f`.
+    pub(crate) fn map_states(self, f: impl Fn(State) -> State) -> Self {
+        match self {
+            Self::Initial => Self::Initial,
+            Self::Final(state) => Self::Final(f(state)),
+            Self::Error(state) => Self::Error(f(state)),
+            Self::Transition(state, condition) => Self::Transition(f(state), condition),
+            Self::Wildcard(state) => Self::Wildcard(f(state)),
+        }
+    }
+}
+
+/// The trailing comments of a [`Program`], without the "#".
+pub(crate) type Comments<S> = HashMap<CommentTarget<S>, String>;
+
 /// A program for the [`crate::TuringMachine`].
 ///
 /// Each program has:
 ///     - Exactly one initial [`State`], denoted by "+" followed by a state
 ///       number
 ///     - Any amount of final [`State`]s, denoted by any amount of "-" followed
-///       by a state number
+///       by a state number. Final states of programs that decide a problem can
+///       be marked as accepting with "-A" or as rejecting with "-R", followed
+///       by a state number or a space and a state, see [`Program::verdict`].
+///       Unmarked final states accept
 ///     - Any amount of error [`State`]s, denoted by any amount of "+" followed
 ///       by a state number
 ///     - Any amount of comments, which are ignored and start with "#" or "/"
-///     - Any amount of transitions, which have comma-seperated values:
+///     - Trailing comments after " #" at the end of a state declaration or
+///       transition, which are kept with the line, see [`Program::comment`]
+///     - Any amount of includes, written as `@include "path" start end`, which
+///       splice in another program whose initial state becomes "start" and
+///       whose final states become "end", see [`Program::parse_with_includes`]
+///     - Any amount of entry points, written as `@entry name state`, which give
+///       execution other states to start in, see [`Program::entry_point`]
+///     - Any amount of transitions, of which no two may share the same "from"
+///       state and segment to match, which have comma-seperated values:
 ///         - The "from" state
 ///         - The "to" state
-///         - The segment to match
-///         - The segment to write
-///         - The movement action to perform
+///         - The segment to match, or "*" to match any segment that no other
+///           transition of the "from" state matches
+///         - The segment to write, or "=" to write back the segment that was
+///           read
+///         - The movement action to perform, or "l*" and "r*" to seek: move
+///           left or right until the segment to match is found, which expands
+///           to a transition that does not move and a wildcard transition that
+///           skips every other segment
+///
+/// Segments are single characters, where "_" and " " denote an empty segment
+/// and any other character is a symbol of the tape alphabet.
+///
+/// Spaces and tabs around the values of a transition are ignored, so the
+/// columns of a table of transitions can be aligned, like
+/// `0 , 1 , _ , _ , r`. A value that consists only of whitespace is read as
+/// a single space, so it still denotes an empty segment. Trailing comments
+/// start with a "#" after a space or a tab, except for a "#" segment in one of
+/// the first four values, so `0 , 1 , # , # , r # done` still has a "#"
+/// segment and a comment.
+///
+/// States are either numbers or names, which start with a letter followed by
+/// letters, digits and underscores. Names that read as a verdict marker and a
+/// state number, like "R1", are not allowed. Named states are numbered after
+/// the largest numeric state of the program, in the order they first appear,
+/// and can be looked up with [`Program::state`].
 ///
 /// Simple example:
 /// ```tng
@@ -72,26 +311,266 @@ pub(crate) struct Transition {
 /// ## First bit reached again, go to end state 3!
 /// 2,3,_,_,r
 /// ```
-#[derive(Debug)]
-pub struct Program {
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound(
+        serialize = "S: serde::Serialize",
+        deserialize = "S: serde::Deserialize<'de> + Symbol"
+    ))
+)]
+pub struct Program<S = Segment> {
     pub(crate) initial_state: State,
     pub(crate) final_states: HashSet<State>,
+    /// The final states that reject the input.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) rejecting_states: HashSet<State>,
     pub(crate) error_states: HashSet<State>,
-    pub(crate) transitions: HashMap<(State, Segment), Transition>,
+    #[cfg_attr(feature = "serde", serde(with = "transition_list"))]
+    pub(crate) transitions: Transitions<S>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) wildcards: Wildcards<S>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) names: StateNames,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) entry_points: EntryPoints,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) comments: Comments<S>,
 }
 
-impl Program {
-    fn from_parts(
+/// (De)serializes the transitions of a [`Program`] as a list, since most
+/// formats do not support maps with composite keys.
+#[cfg(feature = "serde")]
+mod transition_list {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::{Symbol, Transition, Transitions};
+
+    pub(super) fn serialize<S, Z>(
+        transitions: &Transitions<S>,
+        serializer: Z,
+    ) -> Result<Z::Ok, Z::Error>
+    where
+        S: Serialize,
+        Z: Serializer,
+    {
+        serializer.collect_seq(transitions.values())
+    }
+
+    pub(super) fn deserialize<'de, S, D>(deserializer: D) -> Result<Transitions<S>, D::Error>
+    where
+        S: Deserialize<'de> + Symbol,
+        D: Deserializer<'de>,
+    {
+        let transitions = Vec::<Transition<S>>::deserialize(deserializer)?;
+
+        Ok(transitions
+            .into_iter()
+            .map(|transition| ((transition.from, transition.condition), transition))
+            .collect())
+    }
+}
+
+impl<S> Program<S>
+where
+    S: Symbol,
+{
+    /// Returns the state execution starts in.
+    #[must_use]
+    pub fn initial_state(&self) -> State {
+        self.initial_state
+    }
+
+    /// Returns the state with the given name, if the program has one.
+    #[must_use]
+    pub fn state(&self, name: &str) -> Option<State> {
+        self.names
+            .iter()
+            .find(|(_, other)| *other == name)
+            .map(|(state, _)| *state)
+    }
+
+    /// Returns the name of `state`, if it was given one.
+    #[must_use]
+    pub fn state_name(&self, state: State) -> Option<&str> {
+        self.names.get(&state).map(String::as_str)
+    }
+
+    /// Returns the state of the entry point with the given name, if the
+    /// program has one.
+    ///
+    /// Entry points let a file of shared routines be started at different
+    /// states with [`crate::TuringMachine::execute_from`]:
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use turing::{Program, TuringMachine, VecTape};
+    /// let program =
+    ///     Program::from_str("+set\n-done\n@entry clear clear\nset,done,_,1,n\nclear,done,*,_,n")
+    ///         .unwrap();
+    /// let mut machine = TuringMachine::from_tape(VecTape::from_str("_1").unwrap());
+    ///
+    /// machine
+    ///     .execute_from(&program, program.entry_point("clear").unwrap())
+    ///     .unwrap();
+    /// assert_eq!(machine.tape().to_string(), "__");
+    /// ```
+    #[must_use]
+    pub fn entry_point(&self, name: &str) -> Option<State> {
+        self.entry_points.get(name).copied()
+    }
+
+    /// Returns the names and states of all entry points, sorted by name.
+    pub fn entry_points(&self) -> impl Iterator<Item = (&str, State)> {
+        self.entry_points
+            .iter()
+            .map(|(name, state)| (name.as_str(), *state))
+    }
+
+    /// Returns whether the program accepts or rejects its input when it halts
+    /// in `state`, or [`None`] if `state` is not a final state.
+    ///
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use turing::{Program, State, Verdict};
+    /// let program = Program::from_str("+0\n-A1\n-R2\n-3\n0,1,a,a,n\n0,2,_,_,n").unwrap();
+    /// let state = |s| State::from_str(s).unwrap();
+    ///
+    /// assert_eq!(program.verdict(state("1")), Some(Verdict::Accepted));
+    /// assert_eq!(program.verdict(state("2")), Some(Verdict::Rejected));
+    /// assert_eq!(program.verdict(state("3")), Some(Verdict::Accepted));
+    /// assert_eq!(program.verdict(state("0")), None);
+    /// ```
+    #[must_use]
+    pub fn verdict(&self, state: State) -> Option<Verdict> {
+        self.final_states
+            .contains(&state)
+            .then(|| self.final_verdict(state))
+    }
+
+    /// Returns the verdict of `state`, which has to be a final state.
+    pub(crate) fn final_verdict(&self, state: State) -> Verdict {
+        if self.rejecting_states.contains(&state) {
+            Verdict::Rejected
+        } else {
+            Verdict::Accepted
+        }
+    }
+
+    /// Returns the final states, including the rejecting ones.
+    #[must_use]
+    pub fn final_states(&self) -> BTreeSet<State> {
+        self.final_states.iter().copied().collect()
+    }
+
+    /// Returns the error states.
+    #[must_use]
+    pub fn error_states(&self) -> BTreeSet<State> {
+        self.error_states.iter().copied().collect()
+    }
+
+    /// Returns the transitions of the program, sorted by state and
+    /// condition. Wildcards are returned by [`Program::wildcards`].
+    ///
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use turing::{Direction, Program, Segment};
+    /// let program = Program::from_str("+0\n-1\n0,1,b,_,n\n0,0,a,a,r").unwrap();
+    /// let transitions: Vec<_> = program.transitions().collect();
+    ///
+    /// assert_eq!(transitions.len(), 2);
+    /// assert_eq!(transitions[0].condition(), Segment::Symbol('a'));
+    /// assert_eq!(transitions[0].to(), program.initial_state());
+    /// assert_eq!(transitions[1].write(), Segment::Empty);
+    /// assert_eq!(transitions[1].action(), Direction::Stay);
+    /// ```
+    pub fn transitions(&self) -> impl Iterator<Item = &Transition<S>> {
+        let mut transitions: Vec<_> = self.transitions.iter().collect();
+        transitions.sort_by_key(|(key, _)| *key);
+        transitions.into_iter().map(|(_, transition)| transition)
+    }
+
+    /// Returns the wildcards of the program, sorted by state.
+    pub fn wildcards(&self) -> impl Iterator<Item = &Wildcard<S>> {
+        let mut wildcards: Vec<_> = self.wildcards.values().collect();
+        wildcards.sort_by_key(|wildcard| wildcard.from);
+        wildcards.into_iter()
+    }
+
+    /// Returns the trailing comment of a line, without the "#" and
+    /// surrounding whitespace.
+    ///
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use turing::{CommentTarget, Program, Segment, State};
+    /// let program = Program::from_str("+0 # start here\n-1\n0,1,a,b,r # the only step").unwrap();
+    ///
+    /// assert_eq!(program.comment(CommentTarget::Initial), Some("start here"));
+    /// assert_eq!(
+    ///     program.comment(CommentTarget::Transition(
+    ///         State::from_str("0").unwrap(),
+    ///         Segment::Symbol('a')
+    ///     )),
+    ///     Some("the only step")
+    /// );
+    /// ```
+    #[must_use]
+    pub fn comment(&self, target: CommentTarget<S>) -> Option<&str> {
+        self.comments.get(&target).map(String::as_str)
+    }
+
+    /// Returns the transition to perform in `state` when reading `read`.
+    pub(crate) fn transition(&self, state: State, read: S) -> Option<Transition<S>> {
+        self.transitions.get(&(state, read)).copied().or_else(|| {
+            self.wildcards
+                .get(&state)
+                .map(|wildcard| wildcard.resolve(read))
+        })
+    }
+
+    /// Whether execution halts after entering `state`.
+    ///
+    /// Final states take precedence over error states.
+    pub(crate) fn halts_in(&self, state: State) -> Result<bool, ExecutionError<S>> {
+        if self.final_states.contains(&state) {
+            Ok(true)
+        } else if self.error_states.contains(&state) {
+            Err(ExecutionError::ReachedError(state))
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// Returns the program in the `.tng` text format.
+    ///
+    /// This is the same as the [`fmt::Display`] implementation and can be
+    /// parsed back into an equivalent program.
+    #[must_use]
+    pub fn to_tng_string(&self) -> String
+    where
+        S: fmt::Display,
+    {
+        self.to_string()
+    }
+
+    pub(crate) fn from_parts(
         initial_state: State,
         final_states: HashSet<State>,
         error_states: HashSet<State>,
-        transitions: HashMap<(State, Segment), Transition>,
+        transitions: Transitions<S>,
+        wildcards: Wildcards<S>,
+        names: StateNames,
     ) -> Self {
         Self {
             initial_state,
             final_states,
+            rejecting_states: HashSet::new(),
             error_states,
             transitions,
+            wildcards,
+            names,
+            entry_points: EntryPoints::new(),
+            comments: Comments::new(),
         }
     }
 }
@@ -105,79 +584,1056 @@ impl FromStr for State {
     }
 }
 
-impl FromStr for Move {
+impl FromStr for Direction {
     type Err = InvalidProgram;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "r" | "R" => Ok(Self::Right),
             "l" | "L" => Ok(Self::Left),
-            "n" | "N" | "" | "_" | " " => Ok(Self::Nothing),
+            "n" | "N" | "" | "_" | " " => Ok(Self::Stay),
             _ => Err(InvalidProgram::InvalidAction),
         }
     }
 }
 
-impl FromStr for Transition {
-    type Err = InvalidProgram;
+impl<S> fmt::Display for Program<S>
+where
+    S: Symbol + fmt::Display,
+{
+    /// Writes the initial state, the final states, the error states, the
+    /// entry points and the transitions, each sorted by state, name and
+    /// condition and followed by its trailing comment.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = |state: &State| {
+            self.names
+                .get(state)
+                .map_or_else(|| state.to_string(), Clone::clone)
+        };
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut parts = s.split(',');
-
-        let from = parts.next().ok_or(InvalidProgram::MissingFrom)?;
-        let to = parts.next().ok_or(InvalidProgram::MissingTo)?;
-        let condition = parts.next().ok_or(InvalidProgram::MissingCondition)?;
-        let write = parts.next().ok_or(InvalidProgram::MissingWrite)?;
-        let action = parts.next().ok_or(InvalidProgram::MissingAction)?;
-
-        Ok(Self {
-            from: State::from_str(from)?,
-            to: State::from_str(to)?,
-            condition: Segment::from_str(condition)?,
-            write: Segment::from_str(write)?,
-            action: Move::from_str(action)?,
+        let comment = |target| {
+            self.comments
+                .get(&target)
+                .map_or_else(String::new, |comment| format!(" # {comment}"))
+        };
+
+        writeln!(
+            f,
+            "+{}{}",
+            name(&self.initial_state),
+            comment(CommentTarget::Initial)
+        )?;
+
+        let mut final_states: Vec<_> = self.final_states.iter().collect();
+        final_states.sort();
+        for state in final_states {
+            let comment = comment(CommentTarget::Final(*state));
+            match (self.rejecting_states.contains(state), self.names.get(state)) {
+                (false, _) => writeln!(f, "-{}{comment}", name(state))?,
+                (true, Some(name)) => writeln!(f, "-R {name}{comment}")?,
+                (true, None) => writeln!(f, "-R{state}{comment}")?,
+            }
+        }
+
+        let mut error_states: Vec<_> = self.error_states.iter().collect();
+        error_states.sort();
+        for state in error_states {
+            writeln!(
+                f,
+                "!{}{}",
+                name(state),
+                comment(CommentTarget::Error(*state))
+            )?;
+        }
+
+        for (entry, state) in &self.entry_points {
+            writeln!(f, "@entry {entry} {}", name(state))?;
+        }
+
+        let mut transitions: Vec<_> = self.transitions.iter().collect();
+        transitions.sort_by_key(|(key, _)| *key);
+        for (_, transition) in transitions {
+            writeln!(
+                f,
+                "{},{},{},{},{}{}",
+                name(&transition.from),
+                name(&transition.to),
+                transition.condition,
+                transition.write,
+                transition.action,
+                comment(CommentTarget::Transition(
+                    transition.from,
+                    transition.condition
+                ))
+            )?;
+        }
+
+        let mut wildcards: Vec<_> = self.wildcards.values().collect();
+        wildcards.sort_by_key(|wildcard| wildcard.from);
+        for wildcard in wildcards {
+            let write = wildcard
+                .write
+                .map_or_else(|| String::from("="), |write| write.to_string());
+            writeln!(
+                f,
+                "{},{},*,{write},{}{}",
+                name(&wildcard.from),
+                name(&wildcard.to),
+                wildcard.action,
+                comment(CommentTarget::Wildcard(wildcard.from))
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A line of a program that is not a comment.
+pub(crate) enum Line<S> {
+    Initial(State),
+    Final(State, Verdict),
+    Error(State),
+    Transition(Transition<S>),
+    Wildcard(Wildcard<S>),
+    /// A transition with a seeking action, which expands into the transition
+    /// for the condition and a wildcard that skips any other segment.
+    Seek(Transition<S>, Wildcard<S>),
+    Include(Include),
+    /// An `@entry` directive with its name.
+    Entry(String, State),
+}
+
+/// An `@include` directive of a program.
+pub(crate) struct Include {
+    path: String,
+    start: State,
+    end: State,
+}
+
+/// An error in a single line, with the byte range of the offending text.
+struct LineError {
+    kind: InvalidProgram,
+    start: usize,
+    end: usize,
+}
+
+impl LineError {
+    /// Create an error for the text of `line` in `range`.
+    fn new(kind: InvalidProgram, range: Range<usize>) -> Self {
+        Self {
+            kind,
+            start: range.start,
+            end: range.end,
+        }
+    }
+
+    /// Attach the line the error occurred on, counting from 0.
+    fn locate(self, number: usize, line: &str) -> ParseError {
+        ParseError {
+            kind: self.kind,
+            line: number + 1,
+            column: line[..self.start].chars().count() + 1,
+            text: line[self.start..self.end].to_owned(),
+            line_text: line.to_owned(),
+        }
+    }
+}
+
+impl ParseError {
+    /// The error for an include on line `number` of `s`, counting from 0, in
+    /// a parser that does not support includes.
+    pub(crate) fn unsupported_include(s: &str, number: usize) -> Self {
+        Self::whole_line(InvalidProgram::IncludeFailed, s, number)
+    }
+
+    /// The error for a transition on line `number` of `s`, counting from 0,
+    /// that matches the same state and segment as an earlier one.
+    pub(crate) fn duplicate_transition(s: &str, number: usize) -> Self {
+        Self::whole_line(InvalidProgram::DuplicateTransition, s, number)
+    }
+
+    pub(crate) fn whole_line(kind: InvalidProgram, s: &str, number: usize) -> Self {
+        let line = s.lines().nth(number).unwrap_or_default();
+        LineError::new(kind, 0..line.len()).locate(number, line)
+    }
+}
+
+impl<S> Line<S>
+where
+    S: Copy,
+{
+    /// Returns what a trailing comment on the line is attached to, if it can
+    /// carry one.
+    fn comment_target(&self) -> Option<CommentTarget<S>> {
+        match self {
+            Self::Initial(_) => Some(CommentTarget::Initial),
+            Self::Final(state, _) => Some(CommentTarget::Final(*state)),
+            Self::Error(state) => Some(CommentTarget::Error(*state)),
+            Self::Transition(transition) | Self::Seek(transition, _) => Some(
+                CommentTarget::Transition(transition.from, transition.condition),
+            ),
+            Self::Wildcard(wildcard) => Some(CommentTarget::Wildcard(wildcard.from)),
+            Self::Include(_) | Self::Entry(..) => None,
+        }
+    }
+
+    /// Returns the state the line declares as an error state if it is already
+    /// the initial state or a final state, or the other way around.
+    fn conflicting_role(
+        &self,
+        initial_state: Option<State>,
+        final_states: &HashSet<State>,
+        error_states: &HashSet<State>,
+    ) -> Option<State> {
+        match self {
+            Self::Initial(state) | Self::Final(state, _) => {
+                error_states.contains(state).then_some(*state)
+            }
+            Self::Error(state) => {
+                (initial_state == Some(*state) || final_states.contains(state)).then_some(*state)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl<S> Line<S>
+where
+    S: Copy + FromStr<Err = InvalidProgram>,
+{
+    /// Parse a line, returning [`None`] for comments and empty lines.
+    ///
+    /// States are resolved with `states`.
+    fn parse(
+        line: &str,
+        states: &mut impl FnMut(&str) -> Result<State, InvalidProgram>,
+        options: ParseOptions,
+    ) -> Result<Option<Self>, LineError> {
+        if options.strict {
+            Self::check_strict(line)?;
+        }
+
+        // Skip comments
+        if line.starts_with('#') || line.starts_with('/') || line.is_empty() {
+            return Ok(None);
+        }
+        let (line, _) = split_comment(line);
+
+        if let Some(rest) = line.strip_prefix('-') {
+            let (verdict, token) = split_verdict(rest);
+            let start = line.len() - token.len();
+            let state = states(token).map_err(|kind| LineError::new(kind, start..line.len()))?;
+
+            return Ok(Some(Self::Final(state, verdict)));
+        }
+
+        let mut state = |prefix: char| {
+            line.strip_prefix(prefix)
+                .map(|state| states(state).map_err(|kind| LineError::new(kind, 1..line.len())))
+        };
+
+        let line = if let Some(state) = state('+') {
+            Self::Initial(state?)
+        } else if let Some(state) = state('!') {
+            Self::Error(state?)
+        } else if let Some(include) = line.strip_prefix("@include") {
+            Self::Include(Self::parse_include(line, include, states)?)
+        } else if let Some(entry) = line.strip_prefix("@entry") {
+            Self::parse_entry(line, entry, states)?
+        } else {
+            Self::parse_transition(line, states, options)?
+        };
+
+        Ok(Some(line))
+    }
+
+    /// Check that a line starts with a known marker, a comment or a state and
+    /// has no tabs, no whitespace at its start or end and no whitespace
+    /// around the commas of a transition.
+    fn check_strict(line: &str) -> Result<(), LineError> {
+        let whitespace = |range| Err(LineError::new(InvalidProgram::UnexpectedWhitespace, range));
+
+        if let Some(tab) = line.find('\t') {
+            return whitespace(tab..tab + 1);
+        }
+        let start = line.len() - line.trim_start().len();
+        if start > 0 {
+            return whitespace(0..start);
+        }
+        let end = line.trim_end().len();
+        if end < line.len() {
+            return whitespace(end..line.len());
+        }
+
+        let marker = line.split_whitespace().next().unwrap_or_default();
+        let known = match line.chars().next() {
+            None | Some('#' | '+' | '-' | '!') => true,
+            Some('/') => line.starts_with("//"),
+            Some('@') => matches!(marker, "@include" | "@entry"),
+            Some(c) => c.is_alphanumeric(),
+        };
+        if !known {
+            return Err(LineError::new(
+                InvalidProgram::UnknownMarker,
+                0..marker.len(),
+            ));
+        }
+
+        // Comments may contain anything
+        if line.starts_with(['#', '/']) {
+            return Ok(());
+        }
+        let (code, _) = split_comment(line);
+        let space = code
+            .match_indices(' ')
+            .map(|(space, _)| space)
+            .find(|space| code[..*space].ends_with(',') || code[space + 1..].starts_with(','));
+        if let Some(space) = space {
+            return whitespace(space..space + 1);
+        }
+
+        Ok(())
+    }
+
+    /// Parse the `rest` of an include directive in `line`, resolving its
+    /// states with `states`.
+    fn parse_include(
+        line: &str,
+        rest: &str,
+        states: &mut impl FnMut(&str) -> Result<State, InvalidProgram>,
+    ) -> Result<Include, LineError> {
+        let error = |kind| LineError::new(kind, 0..line.len());
+        let invalid = || error(InvalidProgram::InvalidInclude);
+
+        let (path, rest) = rest
+            .trim_start()
+            .strip_prefix('"')
+            .and_then(|rest| rest.split_once('"'))
+            .ok_or_else(invalid)?;
+        let mut tokens = rest.split_whitespace();
+        let (Some(start), Some(end), None) = (tokens.next(), tokens.next(), tokens.next()) else {
+            return Err(invalid());
+        };
+
+        Ok(Include {
+            path: path.to_owned(),
+            start: states(start).map_err(error)?,
+            end: states(end).map_err(error)?,
         })
     }
+
+    /// Parse the `rest` of an entry point directive in `line`, resolving its
+    /// state with `states`.
+    fn parse_entry(
+        line: &str,
+        rest: &str,
+        states: &mut impl FnMut(&str) -> Result<State, InvalidProgram>,
+    ) -> Result<Self, LineError> {
+        let error = |kind| LineError::new(kind, 0..line.len());
+
+        let mut tokens = rest.split_whitespace();
+        let (Some(name), Some(state), None) = (tokens.next(), tokens.next(), tokens.next()) else {
+            return Err(error(InvalidProgram::InvalidEntry));
+        };
+        if !is_state_name(name) {
+            return Err(error(InvalidProgram::InvalidEntry));
+        }
+
+        Ok(Self::Entry(name.to_owned(), states(state).map_err(error)?))
+    }
+
+    /// Parse a transition, resolving its states with `states`.
+    fn parse_transition(
+        s: &str,
+        states: &mut impl FnMut(&str) -> Result<State, InvalidProgram>,
+        options: ParseOptions,
+    ) -> Result<Self, LineError> {
+        let mut fields = transition_fields(s);
+        let mut field = |missing| {
+            fields
+                .next()
+                .ok_or_else(|| LineError::new(missing, s.len()..s.len()))
+        };
+
+        let (from, from_range) = field(InvalidProgram::MissingFrom)?;
+        let (to, to_range) = field(InvalidProgram::MissingTo)?;
+        let (condition, condition_range) = field(InvalidProgram::MissingCondition)?;
+        let (write, write_range) = field(InvalidProgram::MissingWrite)?;
+        let (action, action_range) = field(InvalidProgram::MissingAction)?;
+
+        if let Some((_, range)) = fields.next().filter(|_| options.strict) {
+            return Err(LineError::new(
+                InvalidProgram::TrailingField,
+                range.start - 1..s.len(),
+            ));
+        }
+
+        let from = states(from).map_err(|kind| LineError::new(kind, from_range))?;
+        let to = states(to).map_err(|kind| LineError::new(kind, to_range))?;
+        let write = match write {
+            "=" => None,
+            write => Some(S::from_str(write).map_err(|kind| LineError::new(kind, write_range))?),
+        };
+        let (action, seek) = match action.strip_suffix('*') {
+            Some(action @ ("l" | "L" | "r" | "R")) => (action, true),
+            _ => (action, false),
+        };
+        let action =
+            Direction::from_str(action).map_err(|kind| LineError::new(kind, action_range))?;
+
+        if seek {
+            if condition == "*" {
+                return Err(LineError::new(
+                    InvalidProgram::WildcardSeek,
+                    condition_range,
+                ));
+            }
+
+            let condition =
+                S::from_str(condition).map_err(|kind| LineError::new(kind, condition_range))?;
+
+            return Ok(Self::Seek(
+                Transition {
+                    from,
+                    to,
+                    condition,
+                    write: write.unwrap_or(condition),
+                    action: Direction::Stay,
+                },
+                Wildcard {
+                    from,
+                    to: from,
+                    write: None,
+                    action,
+                },
+            ));
+        }
+
+        if condition == "*" {
+            return Ok(Self::Wildcard(Wildcard {
+                from,
+                to,
+                write,
+                action,
+            }));
+        }
+
+        let condition =
+            S::from_str(condition).map_err(|kind| LineError::new(kind, condition_range))?;
+
+        Ok(Self::Transition(Transition {
+            from,
+            to,
+            condition,
+            write: write.unwrap_or(condition),
+            action,
+        }))
+    }
+
+    /// Parse all lines of a program, skipping comments and empty lines.
+    ///
+    /// Named states are numbered after the largest numeric state, so the
+    /// whole program is read once to find it before the states are resolved.
+    ///
+    /// Each line is returned with its number, counting from 0.
+    pub(crate) fn parse_all(s: &str) -> Result<(Vec<(usize, Self)>, StateNames), ParseError> {
+        let mut errors = Vec::new();
+        let parsed = Self::parse_all_recovering(s, ParseOptions::default(), &mut errors);
+
+        match errors.into_iter().next() {
+            Some(error) => Err(error),
+            None => Ok(parsed),
+        }
+    }
+
+    /// Parse all lines of a program like [`Line::parse_all`], but skip the
+    /// lines that are invalid and add their errors to `errors`.
+    pub(crate) fn parse_all_recovering(
+        s: &str,
+        options: ParseOptions,
+        errors: &mut Vec<ParseError>,
+    ) -> (Vec<(usize, Self)>, StateNames) {
+        let mut next = 0;
+        for line in s.lines() {
+            // Invalid lines are reported below, when they are parsed again
+            let mut states = |token: &str| match State::from_str(token) {
+                Ok(state) => {
+                    next = next.max(state.0.saturating_add(1));
+                    Ok(state)
+                }
+                Err(_) if is_state_name(token) => Ok(State(0)),
+                Err(e) => Err(e),
+            };
+            let _ = Self::parse(line, &mut states, options);
+        }
+
+        let mut ids = HashMap::new();
+        let mut resolve = |token: &str| {
+            if let Ok(state) = State::from_str(token) {
+                return Ok(state);
+            }
+            if !is_state_name(token) {
+                return Err(InvalidProgram::InvalidState);
+            }
+
+            let state = *ids.entry(token.to_owned()).or_insert_with(|| {
+                next += 1;
+                State(next - 1)
+            });
+
+            Ok(state)
+        };
+
+        let mut lines = Vec::new();
+        for (number, line) in s.lines().enumerate() {
+            match Self::parse(line, &mut resolve, options) {
+                Ok(Some(parsed)) => lines.push((number, parsed)),
+                Ok(None) => {}
+                Err(e) => errors.push(e.locate(number, line)),
+            }
+        }
+
+        (
+            lines,
+            ids.into_iter().map(|(name, state)| (state, name)).collect(),
+        )
+    }
 }
 
-impl FromStr for Program {
-    type Err = InvalidProgram;
+/// Split a transition without its trailing comment into its values, trimmed
+/// like [`trim_field`], together with their ranges in `line`.
+pub(crate) fn transition_fields(line: &str) -> impl Iterator<Item = (&str, Range<usize>)> {
+    let mut start = 0;
+    line.split(',').map(move |field| {
+        let range = start..start + field.len();
+        start = range.end + 1;
+        trim_field(field, range)
+    })
+}
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
+/// Trim the spaces and tabs around a value of a transition at `range` of its
+/// line, returning the trimmed value and its range.
+///
+/// A value that consists only of whitespace is returned as a single space,
+/// which denotes an empty segment.
+fn trim_field(field: &str, range: Range<usize>) -> (&str, Range<usize>) {
+    const WHITESPACE: [char; 2] = [' ', '\t'];
+
+    let trimmed = field.trim_matches(WHITESPACE);
+    if trimmed.is_empty() && !field.is_empty() {
+        return (" ", range);
+    }
+
+    let start = range.start + field.len() - field.trim_start_matches(WHITESPACE).len();
+    (trimmed, start..start + trimmed.len())
+}
+
+/// Split the trailing comment off a line, returning the rest of the line and
+/// the comment without the "#" and surrounding whitespace.
+///
+/// A trailing comment starts at the first "#" after a space or tab, unless
+/// that "#" is a whole value of one of the first four fields of a transition,
+/// so that "#" can still be used as a segment in aligned columns.
+pub(crate) fn split_comment(line: &str) -> (&str, Option<&str>) {
+    const WHITESPACE: [char; 2] = [' ', '\t'];
+
+    let transition = !line.starts_with(['+', '-', '!', '@']);
+    let start = line
+        .match_indices('#')
+        .map(|(start, _)| start)
+        .find(|start| {
+            let before = &line[..*start];
+            let after = line[start + 1..].trim_start_matches(WHITESPACE);
+            let segment = transition && before.matches(',').count() < 4 && after.starts_with(',');
+
+            before.ends_with(WHITESPACE) && !segment
+        });
+
+    match start {
+        Some(start) => (
+            line[..start].trim_end_matches(WHITESPACE),
+            Some(line[start + 1..].trim()),
+        ),
+        None => (line, None),
+    }
+}
+
+/// Split the marker of an accepting or rejecting final state off the text
+/// after the "-" of a final state line.
+///
+/// The marker is "A" or "R" followed by a state number or a space, so that
+/// final states named like "Accept" keep working.
+pub(crate) fn split_verdict(s: &str) -> (Verdict, &str) {
+    let verdict = match s.as_bytes().first() {
+        Some(b'A') => Verdict::Accepted,
+        Some(b'R') => Verdict::Rejected,
+        _ => return (Verdict::Accepted, s),
+    };
+    let state = &s[1..];
+
+    if state.starts_with(' ') || (!state.is_empty() && state.bytes().all(|b| b.is_ascii_digit())) {
+        (verdict, state.trim_start())
+    } else {
+        (Verdict::Accepted, s)
+    }
+}
+
+/// Whether `token` is a valid state name: a letter followed by any amount of
+/// letters, digits and underscores.
+///
+/// Names like "R1" or "A2" are not valid, since "-R1" already marks state 1 as
+/// rejecting.
+pub(crate) fn is_state_name(token: &str) -> bool {
+    let mut chars = token.chars();
+    let verdict = matches!(token.as_bytes().first(), Some(b'A' | b'R'))
+        && token.len() > 1
+        && token.bytes().skip(1).all(|b| b.is_ascii_digit());
+
+    !verdict
+        && chars.next().is_some_and(char::is_alphabetic)
+        && chars.all(|c| c.is_alphanumeric() || c == '_')
+}
+
+impl<S> Program<S>
+where
+    S: Symbol + FromStr<Err = InvalidProgram>,
+{
+    /// Parse a program over a custom alphabet, using the [`FromStr`]
+    /// implementation of the symbols for the segments of each transition.
+    ///
+    /// Programs over [`Segment`]s can be parsed with [`FromStr`] directly.
+    /// Includes are not supported, see [`Program::parse_with_includes`].
+    ///
+    /// # Errors
+    ///
+    /// This method will error if the program is invalid.
+    pub fn parse(s: &str) -> Result<Self, ParseError> {
+        Self::parse_with(s, ParseOptions::default())
+    }
+
+    /// Parse a program like [`Program::parse`], with the given `options`.
+    ///
+    /// Strict parsing rejects programs that lenient parsing accepts, but
+    /// that are probably mistakes or hard to read:
+    /// ```
+    /// # use turing::{parser::ParseOptions, InvalidProgram, Program};
+    /// let strict = ParseOptions { strict: true };
+    /// let program = "+0\n-1\n0,1, ,_,r";
+    ///
+    /// assert!(Program::<turing::Segment>::parse(program).is_ok());
+    /// assert_eq!(
+    ///     Program::<turing::Segment>::parse_with(program, strict)
+    ///         .unwrap_err()
+    ///         .kind,
+    ///     InvalidProgram::UnexpectedWhitespace
+    /// );
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method will error if the program is invalid.
+    pub fn parse_with(s: &str, options: ParseOptions) -> Result<Self, ParseError> {
+        Self::parse_nested(s, &mut |_| None, 0, options)
+    }
+
+    /// Parse a program that may include other programs, reading them with
+    /// `read`.
+    ///
+    /// `read` is called with the path of every `@include` directive, as it is
+    /// written in the program, and returns the source of the included program
+    /// or [`None`] if it cannot be read. The included program is renumbered to
+    /// follow the states of the including program, its initial state is
+    /// replaced by the first state of the directive and its final states by
+    /// the second one. Its error states are kept and its state names and
+    /// entry points are dropped.
+    ///
+    /// Errors in an included program are reported with the line numbers of
+    /// the included program.
+    ///
+    /// ```
+    /// # use std::{collections::HashMap, str::FromStr};
+    /// # use turing::{Program, TuringMachine, VecTape};
+    /// let files = HashMap::from([(
+    ///     "increment.tng",
+    ///     include_str!("../examples/next_integer.tng"),
+    /// )]);
+    /// let program = Program::parse_with_includes(
+    ///     "+start\n-done\n\
+    ///      @include \"increment.tng\" start twice\n\
+    ///      @include \"increment.tng\" twice done",
+    ///     |path| files.get(path).map(|source| source.to_string()),
+    /// )
+    /// .unwrap();
+    ///
+    /// let mut machine = TuringMachine::from_tape(VecTape::from_str("_1011_").unwrap());
+    /// machine.execute(&program).unwrap();
+    /// assert_eq!(machine.output(), "1101");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method will error if the program or an included program is
+    /// invalid, cannot be read or includes itself.
+    pub fn parse_with_includes(
+        s: &str,
+        mut read: impl FnMut(&str) -> Option<String>,
+    ) -> Result<Self, ParseError> {
+        Self::parse_nested(s, &mut read, 0, ParseOptions::default())
+    }
+
+    /// Parse a program that is included `depth` levels deep.
+    fn parse_nested(
+        s: &str,
+        read: &mut dyn FnMut(&str) -> Option<String>,
+        depth: usize,
+        options: ParseOptions,
+    ) -> Result<Self, ParseError> {
+        let mut errors = Vec::new();
+        let program = Self::parse_recovering(s, read, depth, options, &mut errors);
+
+        match (program, errors.into_iter().next()) {
+            (Some(program), None) => Ok(program),
+            (_, Some(error)) => Err(error),
+            (None, None) => Err(ParseError::from(InvalidProgram::MissingInitialState)),
+        }
+    }
+
+    /// Parse a program that is included `depth` levels deep, skipping the
+    /// lines and includes that are invalid and adding their errors to
+    /// `errors`.
+    ///
+    /// Returns [`None`] if no program is left, because it has no initial
+    /// state. The errors are added in the order [`Program::parse`] finds them,
+    /// so the first one is the error it returns.
+    pub(crate) fn parse_recovering(
+        s: &str,
+        read: &mut dyn FnMut(&str) -> Option<String>,
+        depth: usize,
+        options: ParseOptions,
+        errors: &mut Vec<ParseError>,
+    ) -> Option<Self> {
         let mut transitions = HashMap::new();
+        let mut wildcards = HashMap::new();
         let mut initial_state = None;
         let mut final_states = HashSet::with_capacity(1);
+        let mut rejecting_states = HashSet::new();
         let mut error_states = HashSet::new();
+        let mut includes = Vec::new();
+        let mut entry_points = EntryPoints::new();
+        let mut comments = Comments::new();
+        let (lines, names) = Line::parse_all_recovering(s, options, errors);
+        let texts: Vec<&str> = s.lines().collect();
 
-        for line in s.lines() {
-            // Skip comments
-            if line.starts_with('#') || line.starts_with('/') || line.is_empty() {
+        for (number, line) in lines {
+            let target = line.comment_target();
+
+            // States declared as error states and as initial or final states
+            // are only kept in the role declared first
+            if let Some(state) = line.conflicting_role(initial_state, &final_states, &error_states)
+            {
+                let kind = InvalidProgram::ConflictingStateRole(state);
+                errors.push(ParseError::whole_line(kind, s, number));
                 continue;
             }
 
-            // SAFETY: We verified that the line is not empty
-            match line.chars().next().unwrap() {
-                '+' => {
-                    initial_state = Some(State::from_str(&line[1..])?);
+            // Transitions that clash with an earlier one are dropped
+            let duplicate = match line {
+                Line::Initial(state) => {
+                    initial_state = Some(state);
+                    false
+                }
+                Line::Final(state, verdict) => {
+                    final_states.insert(state);
+                    if verdict == Verdict::Rejected {
+                        rejecting_states.insert(state);
+                    }
+                    false
+                }
+                Line::Error(state) => {
+                    error_states.insert(state);
+                    false
+                }
+                Line::Transition(transition) => {
+                    let key = (transition.from, transition.condition);
+                    let duplicate = transitions.contains_key(&key);
+                    transitions.entry(key).or_insert(transition);
+                    duplicate
+                }
+                Line::Wildcard(wildcard) => {
+                    let duplicate = wildcards.contains_key(&wildcard.from);
+                    wildcards.entry(wildcard.from).or_insert(wildcard);
+                    duplicate
                 }
-                '-' => {
-                    final_states.insert(State::from_str(&line[1..])?);
+                Line::Seek(transition, wildcard) => {
+                    let key = (transition.from, transition.condition);
+                    let duplicate =
+                        transitions.contains_key(&key) || wildcards.contains_key(&wildcard.from);
+                    if !duplicate {
+                        transitions.insert(key, transition);
+                        wildcards.insert(wildcard.from, wildcard);
+                    }
+                    duplicate
                 }
-                '!' => {
-                    error_states.insert(State::from_str(&line[1..])?);
+                Line::Include(include) => {
+                    includes.push((number, include));
+                    false
                 }
-                _ => {
-                    let transition = Transition::from_str(line)?;
-                    transitions.insert((transition.from, transition.condition), transition);
+                Line::Entry(name, state) => {
+                    match entry_points.entry(name) {
+                        btree_map::Entry::Vacant(entry) => {
+                            entry.insert(state);
+                        }
+                        btree_map::Entry::Occupied(_) => errors.push(ParseError::whole_line(
+                            InvalidProgram::DuplicateEntry,
+                            s,
+                            number,
+                        )),
+                    }
+                    false
                 }
+            };
+
+            if duplicate {
+                errors.push(ParseError::duplicate_transition(s, number));
+            } else if let (Some(target), (_, Some(comment))) =
+                (target, split_comment(texts[number]))
+            {
+                comments.insert(target, comment.to_owned());
             }
         }
 
-        Ok(Self::from_parts(
-            initial_state.ok_or(InvalidProgram::MissingInitialState)?,
+        let Some(initial_state) = initial_state else {
+            errors.push(ParseError::from(InvalidProgram::MissingInitialState));
+            return None;
+        };
+        let mut program = Self::from_parts(
+            initial_state,
             final_states,
             error_states,
             transitions,
+            wildcards,
+            names,
+        );
+        program.rejecting_states = rejecting_states;
+        program.entry_points = entry_points;
+        program.comments = comments;
+
+        program.include(s, includes, read, depth, options, errors);
+
+        Some(program)
+    }
+
+    /// Add the programs included on the given lines of `s` to this program,
+    /// skipping the ones that are invalid and adding their errors to
+    /// `errors`.
+    fn include(
+        &mut self,
+        s: &str,
+        includes: Vec<(usize, Include)>,
+        read: &mut dyn FnMut(&str) -> Option<String>,
+        depth: usize,
+        options: ParseOptions,
+        errors: &mut Vec<ParseError>,
+    ) {
+        /// Includes nested deeper than this are assumed to be recursive.
+        const MAX_DEPTH: usize = 32;
+
+        let mut bound = includes
+            .iter()
+            .map(|(_, include)| include.start.0.max(include.end.0) + 1)
+            .fold(self.state_bound(), usize::max);
+
+        for (number, include) in includes {
+            let line = s.lines().nth(number).unwrap_or_default();
+            let error = |kind| LineError::new(kind, 0..line.len()).locate(number, line);
+
+            let Some(source) = (depth < MAX_DEPTH).then(|| read(&include.path)).flatten() else {
+                errors.push(error(InvalidProgram::IncludeFailed));
+                continue;
+            };
+            let Some(subroutine) =
+                Self::parse_recovering(&source, read, depth + 1, options, errors)
+            else {
+                continue;
+            };
+
+            let offset = bound;
+            bound += subroutine.state_bound();
+            let subroutine = subroutine.map_states(|state| {
+                if state == subroutine.initial_state {
+                    include.start
+                } else if subroutine.final_states.contains(&state) {
+                    include.end
+                } else {
+                    State(state.0 + offset)
+                }
+            });
+
+            let duplicate = subroutine
+                .transitions
+                .keys()
+                .any(|key| self.transitions.contains_key(key))
+                || subroutine
+                    .wildcards
+                    .keys()
+                    .any(|state| self.wildcards.contains_key(state));
+            if duplicate {
+                errors.push(error(InvalidProgram::DuplicateTransition));
+                continue;
+            }
+
+            self.transitions.extend(subroutine.transitions);
+            self.wildcards.extend(subroutine.wildcards);
+            self.error_states.extend(subroutine.error_states);
+        }
+    }
+}
+
+impl FromStr for Program {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+/// A builder for constructing a [`Program`] in code instead of parsing it.
+///
+/// States are given by their numbers, just like in the text format. If
+/// multiple transitions share the same "from" state and condition, the last
+/// one wins.
+///
+/// ```
+/// # use turing::{Direction, ProgramBuilder, Segment, TuringMachine, VecTape};
+/// # use std::str::FromStr;
+/// let program = ProgramBuilder::new()
+///     .initial(0)
+///     .finals([1])
+///     .transition(
+///         0,
+///         0,
+///         Segment::Symbol('1'),
+///         Segment::Symbol('0'),
+///         Direction::Right,
+///     )
+///     .transition(0, 1, Segment::Empty, Segment::Empty, Direction::Stay)
+///     .build()
+///     .unwrap();
+///
+/// let mut machine = TuringMachine::from_tape(VecTape::from_str("_11").unwrap());
+/// machine.execute(&program).unwrap();
+/// assert_eq!(machine.tape().to_string(), "_00_");
+/// ```
+#[derive(Debug, Clone)]
+pub struct ProgramBuilder<S = Segment> {
+    initial_state: Option<State>,
+    final_states: HashSet<State>,
+    error_states: HashSet<State>,
+    transitions: Transitions<S>,
+    wildcards: Wildcards<S>,
+}
+
+impl<S> ProgramBuilder<S>
+where
+    S: Symbol,
+{
+    /// Create a builder for an empty program without an initial state.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            initial_state: None,
+            final_states: HashSet::new(),
+            error_states: HashSet::new(),
+            transitions: HashMap::new(),
+            wildcards: HashMap::new(),
+        }
+    }
+
+    /// Set the initial state, replacing any previously set one.
+    #[must_use]
+    pub fn initial(mut self, state: usize) -> Self {
+        self.initial_state = Some(State(state));
+        self
+    }
+
+    /// Add final states.
+    #[must_use]
+    pub fn finals(mut self, states: impl IntoIterator<Item = usize>) -> Self {
+        self.final_states.extend(states.into_iter().map(State));
+        self
+    }
+
+    /// Add error states.
+    #[must_use]
+    pub fn errors(mut self, states: impl IntoIterator<Item = usize>) -> Self {
+        self.error_states.extend(states.into_iter().map(State));
+        self
+    }
+
+    /// Add a transition from `from` to `to` that matches `condition`, writes
+    /// `write` and then performs `action`.
+    #[must_use]
+    pub fn transition(
+        mut self,
+        from: usize,
+        to: usize,
+        condition: S,
+        write: S,
+        action: Direction,
+    ) -> Self {
+        let transition = Transition {
+            from: State(from),
+            to: State(to),
+            condition,
+            write,
+            action,
+        };
+        self.transitions
+            .insert((transition.from, transition.condition), transition);
+        self
+    }
+
+    /// Add a transition from `from` to `to` that matches any segment no other
+    /// transition of `from` matches, writes `write` and then performs
+    /// `action`. If `write` is [`None`], the segment that was read is written
+    /// back.
+    #[must_use]
+    pub fn wildcard(mut self, from: usize, to: usize, write: Option<S>, action: Direction) -> Self {
+        self.wildcards.insert(
+            State(from),
+            Wildcard {
+                from: State(from),
+                to: State(to),
+                write,
+                action,
+            },
+        );
+        self
+    }
+
+    /// Build the program.
+    ///
+    /// # Errors
+    ///
+    /// This method will error if no initial state was set.
+    pub fn build(self) -> Result<Program<S>, InvalidProgram> {
+        Ok(Program::from_parts(
+            self.initial_state
+                .ok_or(InvalidProgram::MissingInitialState)?,
+            self.final_states,
+            self.error_states,
+            self.transitions,
+            self.wildcards,
+            HashMap::new(),
         ))
     }
 }
+
+impl<S> Default for ProgramBuilder<S>
+where
+    S: Symbol,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,278 @@
+//! Recording of runs as animations.
+//!
+//! A [`Recorder`] is an [`Observer`] that renders the tape around the cursor
+//! after every step, or every few steps, into [`Frame`]s. The frames can be
+//! exported as plain text, one frame per line, or as an
+//! [asciinema](https://asciinema.org) recording to share runs as
+//! animations:
+//!
+//! ```
+//! use std::{cell::RefCell, rc::Rc, str::FromStr};
+//!
+//! use turing::{recorder::Recorder, Program, TuringMachine, VecTape};
+//!
+//! let program = Program::from_str(include_str!("../examples/next_integer.tng")).unwrap();
+//! let tape = VecTape::from_str("_11_").unwrap();
+//! let recorder = Rc::new(RefCell::new(Recorder::new(tape.clone(), 1)));
+//!
+//! let mut machine = TuringMachine::from_tape(tape);
+//! machine.add_observer(Rc::clone(&recorder));
+//! machine.execute(&program).unwrap();
+//!
+//! assert_eq!(
+//!     recorder.borrow().to_text(),
+//!     "0 0 _ [1] 1\n\
+//!      1 0 1 [1] _\n\
+//!      2 0 …1 [_] _\n\
+//!      3 1 1 [1] _\n\
+//!      4 1 _ [1] 0\n\
+//!      5 1 _ [_] 0…\n\
+//!      6 3 _ [1] 0…\n"
+//! );
+//! ```
+use std::{
+    fmt::{self, Write},
+    time::Duration,
+};
+
+use crate::{
+    observer::Observer, tape::Symbol, trace::TraceStep, ExecutionError, Segment, State, Tape,
+    VecTape,
+};
+
+/// The tape around the cursor at one point of a run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    /// The number of steps performed before the frame was recorded.
+    pub step: u64,
+    /// The state the machine was in.
+    pub state: State,
+    /// The tape around the cursor, as rendered by
+    /// [`VecTape::render_window`].
+    pub tape: String,
+}
+
+/// An [`Observer`] that records the tape as [`Frame`]s.
+///
+/// The recorder follows the run on a copy of the tape the machine started
+/// with, so it has to be created with the same tape and registered before
+/// the first step. The first and last frame of a run are always recorded.
+#[derive(Debug, Clone)]
+pub struct Recorder<S = Segment> {
+    tape: VecTape<S>,
+    radius: usize,
+    interval: u64,
+    steps: u64,
+    state: Option<State>,
+    frames: Vec<Frame>,
+}
+
+impl<S> Recorder<S>
+where
+    S: Symbol + fmt::Display,
+{
+    /// Create a recorder for a run on `tape` that renders `radius` segments
+    /// on either side of the cursor after every step.
+    #[must_use]
+    pub fn new(tape: VecTape<S>, radius: usize) -> Self {
+        Self {
+            tape,
+            radius,
+            interval: 1,
+            steps: 0,
+            state: None,
+            frames: Vec::new(),
+        }
+    }
+
+    /// Only record a frame every `steps` steps, which must be at least 1.
+    pub fn set_interval(&mut self, steps: u64) {
+        self.interval = steps.max(1);
+    }
+
+    /// Returns the frames recorded so far.
+    #[must_use]
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+
+    /// Returns the frames as text, one per line with the step, the state and
+    /// the tape separated by spaces.
+    #[must_use]
+    pub fn to_text(&self) -> String {
+        let width = self
+            .frames
+            .last()
+            .map_or(1, |frame| frame.step.to_string().len());
+        let mut out = String::new();
+
+        for frame in &self.frames {
+            let _ = writeln!(out, "{:>width$} {} {}", frame.step, frame.state, frame.tape);
+        }
+
+        out
+    }
+
+    /// Returns the frames as an
+    /// [asciicast v2](https://docs.asciinema.org/manual/asciicast/v2/)
+    /// recording, which shows every frame for `delay`.
+    #[must_use]
+    pub fn to_asciicast(&self, delay: Duration) -> String {
+        let screens: Vec<String> = self
+            .frames
+            .iter()
+            .map(|frame| {
+                format!(
+                    "Step {}, state {}\r\n{}",
+                    frame.step, frame.state, frame.tape
+                )
+            })
+            .collect();
+        let width = screens
+            .iter()
+            .flat_map(|screen| screen.split("\r\n"))
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0);
+
+        let mut out = format!("{{\"version\": 2, \"width\": {width}, \"height\": 2}}\n");
+        let mut time = Duration::ZERO;
+
+        for screen in screens {
+            let _ = writeln!(
+                out,
+                "[{:.6}, \"o\", {}]",
+                time.as_secs_f64(),
+                json_string(&format!("\x1b[H\x1b[2J{screen}"))
+            );
+            time += delay;
+        }
+
+        out
+    }
+
+    /// Record a frame of the current tape in `state`.
+    fn record(&mut self, state: State) {
+        self.frames.push(Frame {
+            step: self.steps,
+            state,
+            tape: self.tape.render_window(self.radius),
+        });
+    }
+}
+
+impl<S> Observer<S> for Recorder<S>
+where
+    S: Symbol + fmt::Display,
+{
+    fn on_step(&mut self, step: &TraceStep<S>) {
+        if self.frames.is_empty() {
+            self.record(step.from);
+        }
+
+        self.tape.seek(step.position);
+        self.tape.put(step.written);
+        self.tape.seek(step.position + step.action.offset());
+        self.steps += 1;
+        self.state = Some(step.to);
+
+        if self.steps.is_multiple_of(self.interval) {
+            self.record(step.to);
+        }
+    }
+
+    fn on_halt(&mut self, result: &Result<State, ExecutionError<S>>) {
+        let state = match result {
+            Ok(state)
+            | Err(
+                ExecutionError::ReachedError(state) | ExecutionError::UndefinedBehavior(state, _),
+            ) => Some(*state),
+            Err(_) => self.state,
+        };
+
+        let recorded = self
+            .frames
+            .last()
+            .is_some_and(|frame| frame.step == self.steps);
+        if let (Some(state), false) = (state, recorded) {
+            self.record(state);
+        }
+    }
+}
+
+/// Returns `s` as a quoted JSON string.
+fn json_string(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            c if c.is_control() => {
+                let _ = write!(quoted, "\\u{:04x}", u32::from(c));
+            }
+            c => quoted.push(c),
+        }
+    }
+
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc, str::FromStr, time::Duration};
+
+    use crate::{recorder::*, *};
+
+    #[test]
+    fn test_recorder_interval() {
+        let program = Program::from_str("+0\n-1\n0,0,a,b,r\n0,1,_,_,n").unwrap();
+        let tape = VecTape::from_str("_aaa").unwrap();
+        let mut recorder = Recorder::new(tape.clone(), 0);
+        recorder.set_interval(2);
+        let recorder = Rc::new(RefCell::new(recorder));
+
+        let mut machine = TuringMachine::from_tape(tape);
+        machine.add_observer(Rc::clone(&recorder));
+        machine.execute(&program).unwrap();
+
+        let frames = recorder.borrow().frames().to_vec();
+        assert_eq!(
+            frames
+                .iter()
+                .map(|frame| (frame.step, frame.state, frame.tape.as_str()))
+                .collect::<Vec<_>>(),
+            [
+                (0, State(0), "[a]…"),
+                (2, State(0), "…[a]"),
+                (4, State(1), "…[_]"),
+            ]
+        );
+        assert_eq!(
+            recorder.borrow().to_text(),
+            "0 0 [a]…\n2 0 …[a]\n4 1 …[_]\n"
+        );
+    }
+
+    #[test]
+    fn test_recorder_asciicast() {
+        let program = Program::from_str("+0\n!1\n0,1,a,\",n").unwrap();
+        let tape = VecTape::from_str("_a").unwrap();
+        let recorder = Rc::new(RefCell::new(Recorder::new(tape.clone(), 1)));
+
+        let mut machine = TuringMachine::from_tape(tape);
+        machine.add_observer(Rc::clone(&recorder));
+        assert!(machine.execute(&program).is_err());
+
+        assert_eq!(
+            recorder.borrow().to_asciicast(Duration::from_millis(500)),
+            "{\"version\": 2, \"width\": 15, \"height\": 2}\n\
+             [0.000000, \"o\", \"\\u001b[H\\u001b[2JStep 0, state 0\\r\\n_ [a] _\"]\n\
+             [0.500000, \"o\", \"\\u001b[H\\u001b[2JStep 1, state 1\\r\\n_ [\\\"] _\"]\n"
+        );
+    }
+}
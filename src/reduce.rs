@@ -0,0 +1,382 @@
+//! Shrinking of [`Program`]s while preserving some interesting behaviour.
+//!
+//! This is mostly useful for bug reports: given a large program that triggers
+//! a problem and an oracle that recognizes the problem, [`minimize`] removes
+//! states and transitions until nothing more can be removed without losing the
+//! behaviour the oracle looks for.
+use std::time::{Duration, Instant};
+
+use crate::{ExecutionError, Program, Segment, State, TuringMachine, VecTape};
+
+/// Bounds for a reduction with [`minimize`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    /// The maximum number of steps a candidate may take on the input before
+    /// it is considered non-halting and rejected without asking the oracle.
+    pub max_steps: u64,
+    /// The wall-clock budget for the whole reduction. Once it is exhausted,
+    /// the smallest program found so far is returned.
+    pub time_budget: Duration,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_steps: 100_000,
+            time_budget: Duration::from_mins(1),
+        }
+    }
+}
+
+/// An entry in the log of a [`Reduction`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReductionStep {
+    /// The original program was rejected by the oracle or did not halt on the
+    /// input, so nothing was removed.
+    Uninteresting,
+    /// A state was removed together with its outgoing transitions.
+    RemovedState {
+        /// The removed state.
+        state: State,
+        /// The number of transitions that were removed with it.
+        transitions: usize,
+    },
+    /// A chunk of transitions was removed.
+    RemovedTransitions {
+        /// The `(from, condition)` keys of the removed transitions.
+        removed: Vec<(State, Segment)>,
+        /// The states whose wildcard transitions were removed.
+        wildcards: Vec<State>,
+        /// The number of transitions left in the program, including wildcard
+        /// transitions.
+        remaining: usize,
+    },
+    /// The time budget ran out before the program was locally minimal.
+    BudgetExhausted,
+}
+
+/// The result of [`minimize_with_log`].
+#[derive(Debug, Clone)]
+pub struct Reduction {
+    /// The reduced program.
+    pub program: Program,
+    /// Every successful reduction, in the order they were applied.
+    pub log: Vec<ReductionStep>,
+    /// The number of candidates that were tested.
+    pub candidates: usize,
+}
+
+/// Shrink `program` while `oracle` keeps returning `true`.
+///
+/// See [`minimize_with_log`] for details.
+#[must_use]
+pub fn minimize(
+    program: &Program,
+    input: &VecTape,
+    oracle: impl Fn(&Program) -> bool,
+    limits: Limits,
+) -> Program {
+    minimize_with_log(program, input, oracle, limits).program
+}
+
+/// Shrink `program` while `oracle` keeps returning `true` and record every
+/// reduction that was applied.
+///
+/// Candidates are first run on `input`, and only passed to the oracle if they
+/// halt within [`Limits::max_steps`]. States are removed first, followed by a
+/// delta debugging pass over the transitions and wildcard transitions.
+/// Unless the time budget runs out, the result is locally minimal: removing
+/// any single remaining transition or wildcard transition makes the oracle
+/// return `false`.
+///
+/// Candidates are tried in a fixed order, so the result only depends on the
+/// inputs, as long as the time budget is not exhausted.
+pub fn minimize_with_log(
+    program: &Program,
+    input: &VecTape,
+    oracle: impl Fn(&Program) -> bool,
+    limits: Limits,
+) -> Reduction {
+    let mut reducer = Reducer {
+        input,
+        oracle,
+        limits,
+        deadline: Instant::now() + limits.time_budget,
+        reduction: Reduction {
+            program: program.clone(),
+            log: Vec::new(),
+            candidates: 0,
+        },
+    };
+
+    if reducer.test(program) == Some(false) {
+        reducer.reduction.log.push(ReductionStep::Uninteresting);
+    } else if reducer.remove_states().is_none() || reducer.remove_transitions().is_none() {
+        reducer.reduction.log.push(ReductionStep::BudgetExhausted);
+    }
+
+    reducer.reduction
+}
+
+struct Reducer<'a, F> {
+    input: &'a VecTape,
+    oracle: F,
+    limits: Limits,
+    deadline: Instant,
+    reduction: Reduction,
+}
+
+impl<F> Reducer<'_, F>
+where
+    F: Fn(&Program) -> bool,
+{
+    /// Whether a candidate is still interesting, or [`None`] if the time
+    /// budget is exhausted.
+    fn test(&mut self, candidate: &Program) -> Option<bool> {
+        if Instant::now() >= self.deadline {
+            return None;
+        }
+
+        self.reduction.candidates += 1;
+
+        Some(halts_within(candidate, self.input, self.limits.max_steps) && (self.oracle)(candidate))
+    }
+
+    fn remove_states(&mut self) -> Option<()> {
+        let program = &self.reduction.program;
+        let mut states: Vec<State> = program
+            .transitions
+            .keys()
+            .map(|(from, _)| *from)
+            .chain(program.wildcards.keys().copied())
+            .chain(program.final_states.iter().copied())
+            .chain(program.error_states.iter().copied())
+            .filter(|state| *state != program.initial_state)
+            .collect();
+        states.sort();
+        states.dedup();
+
+        for state in states {
+            let mut candidate = self.reduction.program.clone();
+            candidate.final_states.remove(&state);
+            candidate.error_states.remove(&state);
+            candidate.transitions.retain(|(from, _), _| *from != state);
+            candidate.wildcards.remove(&state);
+            let transitions = self.reduction.program.transitions.len()
+                + self.reduction.program.wildcards.len()
+                - candidate.transitions.len()
+                - candidate.wildcards.len();
+
+            if self.test(&candidate)? {
+                self.reduction.program = candidate;
+                self.reduction
+                    .log
+                    .push(ReductionStep::RemovedState { state, transitions });
+            }
+        }
+
+        Some(())
+    }
+
+    fn remove_transitions(&mut self) -> Option<()> {
+        let mut transitions: Vec<_> = self.reduction.program.transitions.keys().copied().collect();
+        transitions.sort();
+        let mut wildcards: Vec<_> = self.reduction.program.wildcards.keys().copied().collect();
+        wildcards.sort();
+        let mut candidates: Vec<Candidate> = transitions
+            .into_iter()
+            .map(|(from, condition)| Candidate::Transition(from, condition))
+            .chain(wildcards.into_iter().map(Candidate::Wildcard))
+            .collect();
+
+        let mut granularity = 2;
+
+        while candidates.len() >= 2 {
+            let chunk_size = candidates.len().div_ceil(granularity);
+            let mut reduced = false;
+
+            for start in (0..candidates.len()).step_by(chunk_size) {
+                let end = (start + chunk_size).min(candidates.len());
+
+                if self.try_remove(&candidates[start..end])? {
+                    candidates.drain(start..end);
+                    granularity = (granularity - 1).max(2);
+                    reduced = true;
+                    break;
+                }
+            }
+
+            if !reduced {
+                if granularity >= candidates.len() {
+                    break;
+                }
+
+                granularity = (granularity * 2).min(candidates.len());
+            }
+        }
+
+        // Delta debugging never tries to remove the very last transition.
+        if candidates.len() == 1 {
+            self.try_remove(&candidates)?;
+        }
+
+        Some(())
+    }
+
+    /// Remove `candidates` from the program if it stays interesting without
+    /// them, or return [`None`] if the time budget is exhausted.
+    fn try_remove(&mut self, candidates: &[Candidate]) -> Option<bool> {
+        let mut candidate = self.reduction.program.clone();
+        let mut removed = Vec::new();
+        let mut wildcards = Vec::new();
+        for remove in candidates {
+            match *remove {
+                Candidate::Transition(from, condition) => {
+                    candidate.transitions.remove(&(from, condition));
+                    removed.push((from, condition));
+                }
+                Candidate::Wildcard(from) => {
+                    candidate.wildcards.remove(&from);
+                    wildcards.push(from);
+                }
+            }
+        }
+
+        if !self.test(&candidate)? {
+            return Some(false);
+        }
+
+        let remaining = candidate.transitions.len() + candidate.wildcards.len();
+        self.reduction.program = candidate;
+        self.reduction.log.push(ReductionStep::RemovedTransitions {
+            removed,
+            wildcards,
+            remaining,
+        });
+
+        Some(true)
+    }
+}
+
+/// A transition that the delta debugging pass of [`minimize_with_log`] can
+/// remove.
+#[derive(Debug, Clone, Copy)]
+enum Candidate {
+    /// The transition with this `(from, condition)` key.
+    Transition(State, Segment),
+    /// The wildcard transition of this state.
+    Wildcard(State),
+}
+
+/// Whether `program` stops on `input` within `max_steps` transitions, either
+/// by halting or by running into an error.
+fn halts_within(program: &Program, input: &VecTape, max_steps: u64) -> bool {
+    let mut machine = TuringMachine::from_tape(input.clone());
+
+    !matches!(
+        machine.execute_bounded(program, max_steps),
+        Err(ExecutionError::StepLimitExceeded(_))
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fmt::Write, str::FromStr};
+
+    use crate::{reduce::*, *};
+
+    /// The palindrome example with its error state renamed to 9 and padded
+    /// with 400 transitions that are never taken.
+    fn padded_palindrome() -> Program {
+        let mut code = include_str!("../examples/palindrome.tng")
+            .replace("!6", "!9")
+            .replace(",6,", ",9,");
+
+        for state in 100..300 {
+            let _ = writeln!(code, "{state},{},0,1,r", state + 1);
+            let _ = writeln!(code, "{state},{state},1,1,l");
+        }
+
+        Program::from_str(&code).unwrap()
+    }
+
+    fn reaches_error_9(program: &Program) -> bool {
+        let mut machine = TuringMachine::from_tape(VecTape::from_str("_10_").unwrap());
+
+        machine.execute(program) == Err(ExecutionError::ReachedError(State::from_str("9").unwrap()))
+    }
+
+    #[test]
+    fn test_minimize_padded_palindrome() {
+        let program = padded_palindrome();
+        let input = VecTape::from_str("_10_").unwrap();
+        assert!(reaches_error_9(&program));
+
+        let reduction = minimize_with_log(&program, &input, reaches_error_9, Limits::default());
+
+        assert!(reaches_error_9(&reduction.program));
+        assert_eq!(reduction.program.transitions.len(), 4);
+        assert!(program.transitions.len() > 400);
+        assert!(!reduction.log.is_empty());
+        assert!(!reduction.log.contains(&ReductionStep::BudgetExhausted));
+
+        // Locally minimal: every remaining transition is needed.
+        for key in reduction.program.transitions.keys() {
+            let mut candidate = reduction.program.clone();
+            candidate.transitions.remove(key);
+            assert!(!reaches_error_9(&candidate));
+        }
+        for state in reduction.program.wildcards.keys() {
+            let mut candidate = reduction.program.clone();
+            candidate.wildcards.remove(state);
+            assert!(!reaches_error_9(&candidate));
+        }
+
+        // Reproducible for the same inputs.
+        let again = minimize(&program, &input, reaches_error_9, Limits::default());
+        let mut keys: Vec<_> = again.transitions.keys().collect();
+        let mut expected: Vec<_> = reduction.program.transitions.keys().collect();
+        keys.sort();
+        expected.sort();
+        assert_eq!(keys, expected);
+    }
+
+    #[test]
+    fn test_minimize_wildcards() {
+        // The wildcard of the initial state is never taken on the input, but
+        // can only be removed on its own
+        let program = Program::from_str("+0\n-1\n0,1,1,1,n\n0,2,*,=,r").unwrap();
+        let input = VecTape::from_str("_1_").unwrap();
+        let halts_in_1 = |program: &Program| {
+            let mut machine = TuringMachine::from_tape(input.clone());
+            machine.execute(program) == Ok(State::from_str("1").unwrap())
+        };
+
+        let reduction = minimize_with_log(&program, &input, halts_in_1, Limits::default());
+
+        assert!(reduction.program.wildcards.is_empty());
+        assert_eq!(reduction.program.transitions.len(), 1);
+        assert_eq!(
+            reduction.log,
+            [ReductionStep::RemovedTransitions {
+                removed: Vec::new(),
+                wildcards: vec![State::from_str("0").unwrap()],
+                remaining: 1,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_minimize_uninteresting() {
+        let program = Program::from_str(include_str!("../examples/palindrome.tng")).unwrap();
+        let input = VecTape::from_str("_10_").unwrap();
+
+        let reduction = minimize_with_log(&program, &input, |_| false, Limits::default());
+
+        assert_eq!(reduction.log, [ReductionStep::Uninteresting]);
+        assert_eq!(
+            reduction.program.transitions.len(),
+            program.transitions.len()
+        );
+    }
+}
@@ -0,0 +1,260 @@
+//! Translation of [`Program`]s and tapes from one alphabet into another.
+//!
+//! [`Program::remap_symbols`] replaces every segment a program reads or writes,
+//! and [`crate::Tape::remap`] does the same for the segments on any kind of
+//! tape. Running the translated program on the translated tape
+//! gives the translated result of running the original program on the
+//! original tape:
+//!
+//! ```
+//! use std::str::FromStr;
+//!
+//! use turing::{Program, Segment, TuringMachine, VecTape};
+//!
+//! let swap = |segment| match segment {
+//!     Segment::Symbol('0') => Segment::Symbol('1'),
+//!     Segment::Symbol('1') => Segment::Symbol('0'),
+//!     segment => segment,
+//! };
+//!
+//! // Increment a binary number where "0" and "1" are swapped
+//! let program = Program::from_str(include_str!("../examples/next_integer.tng")).unwrap();
+//! let program = program.remap_symbols(swap).unwrap();
+//!
+//! let mut machine = TuringMachine::from_tape(VecTape::from_str("_0100_").unwrap());
+//! machine.execute(&program).unwrap();
+//! assert_eq!(machine.tape().to_string(), "_0011_");
+//! ```
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{
+    error::InvalidRemap,
+    program::{CommentTarget, Transition, Wildcard},
+    tape::Symbol,
+    Program,
+};
+
+impl<S> Program<S>
+where
+    S: Symbol,
+{
+    /// Returns the program with every segment replaced by `map(segment)`.
+    ///
+    /// To preserve the behaviour of the program, `map` has to keep the blank
+    /// segment and must not map two segments to the same one. Only the
+    /// segments used by the program are checked, so segments that only occur
+    /// on the tape and are matched by wildcards have to be kept apart by the
+    /// caller.
+    ///
+    /// # Errors
+    ///
+    /// This method will error if `map` does not map the blank segment to the
+    /// blank segment of the new alphabet or maps two segments used by the
+    /// program to the same one.
+    pub fn remap_symbols<T>(&self, map: impl Fn(S) -> T) -> Result<Program<T>, InvalidRemap<S>>
+    where
+        T: Symbol,
+    {
+        if map(S::BLANK) != T::BLANK {
+            return Err(InvalidRemap::BlankChanged);
+        }
+
+        let mut symbols = BTreeSet::from([S::BLANK]);
+        for transition in self.transitions.values() {
+            symbols.insert(transition.condition);
+            symbols.insert(transition.write);
+        }
+        symbols.extend(
+            self.wildcards
+                .values()
+                .filter_map(|wildcard| wildcard.write),
+        );
+
+        let mut images: BTreeMap<T, S> = BTreeMap::new();
+        for symbol in symbols {
+            if let Some(other) = images.insert(map(symbol), symbol) {
+                return Err(InvalidRemap::Collision(other, symbol));
+            }
+        }
+
+        Ok(Program {
+            initial_state: self.initial_state,
+            final_states: self.final_states.clone(),
+            rejecting_states: self.rejecting_states.clone(),
+            error_states: self.error_states.clone(),
+            transitions: self
+                .transitions
+                .values()
+                .map(|transition| {
+                    let transition = Transition {
+                        from: transition.from,
+                        to: transition.to,
+                        condition: map(transition.condition),
+                        write: map(transition.write),
+                        action: transition.action,
+                    };
+                    ((transition.from, transition.condition), transition)
+                })
+                .collect(),
+            wildcards: self
+                .wildcards
+                .iter()
+                .map(|(from, wildcard)| {
+                    let wildcard = Wildcard {
+                        from: wildcard.from,
+                        to: wildcard.to,
+                        write: wildcard.write.map(&map),
+                        action: wildcard.action,
+                    };
+                    (*from, wildcard)
+                })
+                .collect(),
+            names: self.names.clone(),
+            entry_points: self.entry_points.clone(),
+            comments: self
+                .comments
+                .iter()
+                .map(|(target, comment)| {
+                    let target = match *target {
+                        CommentTarget::Transition(from, condition) => {
+                            CommentTarget::Transition(from, map(condition))
+                        }
+                        CommentTarget::Initial => CommentTarget::Initial,
+                        CommentTarget::Final(state) => CommentTarget::Final(state),
+                        CommentTarget::Error(state) => CommentTarget::Error(state),
+                        CommentTarget::Wildcard(state) => CommentTarget::Wildcard(state),
+                    };
+                    (target, comment.clone())
+                })
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    enum Bit {
+        Blank,
+        Zero,
+        One,
+        Other(char),
+    }
+
+    impl Symbol for Bit {
+        const BLANK: Self = Self::Blank;
+    }
+
+    fn to_bit(segment: Segment) -> Bit {
+        match segment {
+            Segment::Empty => Bit::Blank,
+            Segment::Symbol('0') => Bit::Zero,
+            Segment::Symbol('1') => Bit::One,
+            Segment::Symbol(c) => Bit::Other(c),
+        }
+    }
+
+    #[test]
+    fn test_remap_symbols() {
+        let program = Program::from_str(include_str!("../examples/next_integer.tng")).unwrap();
+        let remapped = program.remap_symbols(to_bit).unwrap();
+
+        for input in ["_0_", "_1011_", "_111_"] {
+            let tape = VecTape::from_str(input).unwrap();
+            let mut expected = TuringMachine::from_tape(tape.clone());
+            let mut machine = TuringMachine::from_tape(tape.remap(to_bit));
+
+            assert_eq!(
+                machine.execute(&remapped).unwrap(),
+                expected.execute(&program).unwrap()
+            );
+            assert_eq!(machine.tape(), &expected.tape().remap(to_bit));
+        }
+    }
+
+    #[test]
+    fn test_remap_any_tape() {
+        fn remapped<T: Tape<Symbol = Segment>>(mut tape: T) -> (Vec<Bit>, i64) {
+            tape.left();
+            let mut tape = tape.remap(to_bit);
+            let remapped = (tape.iter().collect(), tape.position());
+
+            // The remapped tape still works like the original one
+            tape.right();
+            tape.put(Bit::Zero);
+            assert_eq!(tape.get(-1), Bit::Blank);
+            assert_eq!(tape.get(0), Bit::Zero);
+            remapped
+        }
+
+        let expected = remapped(VecTape::from_str("_10_").unwrap());
+        assert_eq!(
+            expected,
+            (vec![Bit::Blank, Bit::One, Bit::Zero, Bit::Blank], -1)
+        );
+        assert_eq!(remapped(VecDequeTape::from_str("_10_").unwrap()), expected);
+        assert_eq!(remapped(SparseTape::from_str("_10_").unwrap()), expected);
+        assert_eq!(remapped(PagedTape::from_str("_10_").unwrap()), expected);
+        assert_eq!(remapped(PackedTape::from_str("_10_").unwrap()), expected);
+        assert_eq!(remapped(RleTape::from_str("_10_").unwrap()), expected);
+        assert_eq!(remapped(CircularTape::from_str("_10_").unwrap()), expected);
+
+        // A circular tape still wraps around
+        let mut tape = CircularTape::from_str("_10_").unwrap().remap(to_bit);
+        tape.seek(3);
+        assert_eq!((tape.position(), *tape.current()), (-1, Bit::Blank));
+
+        // Segments that were never written are remapped as well
+        let tape = SparseTape::from_str("_1_")
+            .unwrap()
+            .remap(|segment| match segment {
+                Segment::Empty => Bit::Other('.'),
+                segment @ Segment::Symbol(_) => to_bit(segment),
+            });
+        assert_eq!(tape.get(-1), Bit::Other('.'));
+        assert_eq!(tape.get(2), Bit::Blank);
+    }
+
+    #[test]
+    fn test_remap_symbols_wildcards() {
+        let program = Program::from_str("+0\n-1\n0,0,a,b,r\n0,0,*,=,r\n0,1,_,c,n").unwrap();
+        let remapped = program
+            .remap_symbols(|segment| match segment {
+                Segment::Symbol(c) => Segment::Symbol(c.to_ascii_uppercase()),
+                Segment::Empty => Segment::Empty,
+            })
+            .unwrap();
+
+        let mut machine = TuringMachine::from_tape(VecDequeTape::from_str("_AxA").unwrap());
+        machine.execute(&remapped).unwrap();
+        assert_eq!(machine.tape().to_string(), "_BxBC");
+    }
+
+    #[test]
+    fn test_remap_symbols_invalid() {
+        let program = Program::from_str("+0\n-1\n0,1,a,b,n").unwrap();
+
+        assert_eq!(
+            program
+                .remap_symbols(|segment| match segment {
+                    Segment::Empty => Segment::Symbol('_'),
+                    segment @ Segment::Symbol(_) => segment,
+                })
+                .unwrap_err(),
+            InvalidRemap::BlankChanged
+        );
+        assert_eq!(
+            program
+                .remap_symbols(|segment| match segment {
+                    Segment::Symbol('b') => Segment::Symbol('a'),
+                    segment => segment,
+                })
+                .unwrap_err(),
+            InvalidRemap::Collision(Segment::Symbol('a'), Segment::Symbol('b'))
+        );
+    }
+}
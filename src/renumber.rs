@@ -0,0 +1,122 @@
+//! Compaction of the state numbers of [`Program`]s.
+//!
+//! Editing, minimizing or composing programs leaves gaps between the state
+//! numbers. [`Program::renumber`] numbers the states from 0 without gaps, in
+//! the order a breadth-first search from the initial state visits them:
+//!
+//! ```
+//! use std::str::FromStr;
+//!
+//! use turing::{Program, State};
+//!
+//! let program = Program::from_str("+7\n-2\n7,40,a,a,r\n40,2,_,_,n\n7,2,_,_,n").unwrap();
+//! let (renumbered, mapping) = program.renumber();
+//!
+//! assert_eq!(
+//!     mapping[&State::from_str("40").unwrap()],
+//!     State::from_str("2").unwrap()
+//! );
+//! assert_eq!(
+//!     renumbered.to_string(),
+//!     "+0\n-1\n0,1,_,_,n\n0,2,a,a,r\n2,1,_,_,n\n"
+//! );
+//! ```
+use std::collections::{HashMap, VecDeque};
+
+use crate::{tape::Symbol, Program, State};
+
+impl<S> Program<S>
+where
+    S: Symbol,
+{
+    /// Returns the program with its states numbered from 0 without gaps,
+    /// together with the new number of every state.
+    ///
+    /// The initial state becomes state 0. The other states are numbered in
+    /// the order a breadth-first search from the initial state and then the
+    /// entry points, sorted by name, visits them, following the transitions
+    /// of each state sorted by condition and then its wildcard. States that
+    /// are never visited are numbered last, in ascending order.
+    #[must_use]
+    pub fn renumber(&self) -> (Self, HashMap<State, State>) {
+        // Number `state` next and return whether it had no number yet
+        let visit = |state: State, mapping: &mut HashMap<State, State>| {
+            let next = State(mapping.len());
+            *mapping.entry(state).or_insert(next) == next
+        };
+
+        let mut mapping = HashMap::new();
+        let mut pending = VecDeque::new();
+        let starts = std::iter::once(self.initial_state).chain(self.entry_points.values().copied());
+        for start in starts {
+            if visit(start, &mut mapping) {
+                pending.push_back(start);
+            }
+
+            while let Some(state) = pending.pop_front() {
+                if self.final_states.contains(&state) || self.error_states.contains(&state) {
+                    continue;
+                }
+
+                let mut transitions: Vec<_> = self
+                    .transitions
+                    .values()
+                    .filter(|transition| transition.from == state)
+                    .collect();
+                transitions.sort_by_key(|transition| transition.condition);
+                let targets = transitions
+                    .into_iter()
+                    .map(|transition| transition.to)
+                    .chain(self.wildcards.get(&state).map(|wildcard| wildcard.to));
+
+                for target in targets {
+                    if visit(target, &mut mapping) {
+                        pending.push_back(target);
+                    }
+                }
+            }
+        }
+
+        for state in self.states().into_iter().chain(self.names.keys().copied()) {
+            visit(state, &mut mapping);
+        }
+
+        (self.map_states(|state| mapping[&state]), mapping)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::*;
+
+    #[test]
+    fn test_renumber() {
+        let program = Program::from_str(
+            "+start\n-9\n!5\n@entry other 12\nstart,9,b,b,n\nstart,3,a,a,r\n3,start,*,=,l\n\
+             12,5,_,_,n\n20,9,_,_,n",
+        )
+        .unwrap();
+        let (renumbered, mapping) = program.renumber();
+        let state = |s| State::from_str(s).unwrap();
+
+        assert_eq!(renumbered.initial_state(), state("0"));
+        assert_eq!(renumbered.state("start"), Some(state("0")));
+        assert_eq!(mapping[&state("3")], state("1"));
+        assert_eq!(mapping[&state("9")], state("2"));
+        assert_eq!(mapping[&state("12")], state("3"));
+        assert_eq!(mapping[&state("5")], state("4"));
+        assert_eq!(mapping[&state("20")], state("5"));
+        assert_eq!(
+            renumbered.to_string(),
+            "+start\n-2\n!4\n@entry other 3\nstart,1,a,a,r\nstart,2,b,b,n\n3,4,_,_,n\n5,2,_,_,n\n\
+             1,start,*,=,l\n"
+        );
+
+        // Renumbering is idempotent
+        let (again, mapping) = renumbered.renumber();
+        assert_eq!(again.to_string(), renumbered.to_string());
+        assert!(mapping.iter().all(|(from, to)| from == to));
+    }
+}
@@ -0,0 +1,698 @@
+//! Search for busy beaver champions.
+//!
+//! [`search`] enumerates the machines with a given number of states and
+//! symbols in tree normal form: it starts with a machine without
+//! transitions, runs it on a blank tape and, whenever it reaches a
+//! transition that is not defined yet, branches into one machine for every
+//! way to define it. Machines that only differ by renaming states or
+//! symbols, or by mirroring the tape, are only visited once.
+//!
+//! Defining the missing transition as the halting one finishes a machine
+//! that halts. The other machines are run until they reach the next missing
+//! transition, repeat a configuration exactly, enter a translated cycle as
+//! described in [`crate::cycler`] or run out of steps. Machines that run out
+//! of steps are checked by backward reasoning: if no configuration leads to a
+//! missing transition within a few steps, the machine never halts. The others
+//! are reported as holdouts. Repeated searches can share their allocations
+//! through a [`SearchContext`] with [`search_with`].
+//!
+//! ```
+//! use turing::search::{search, SearchConfig};
+//!
+//! let report = search(&SearchConfig {
+//!     states: 2,
+//!     ..SearchConfig::default()
+//! });
+//!
+//! assert_eq!(report.most_steps.unwrap().steps, 6);
+//! assert_eq!(report.most_ones.unwrap().ones, 4);
+//! assert!(report.holdouts.is_empty());
+//! ```
+use std::collections::{BTreeMap, VecDeque};
+
+use crate::{
+    busy_beaver::letter, cycler::CycleDetector, Direction, MachineSnapshot, Program, State, VecTape,
+};
+
+/// Options for [`search`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchConfig {
+    /// The number of states, not counting the halting state.
+    pub states: usize,
+    /// The number of symbols, including the blank, at most 10.
+    pub symbols: usize,
+    /// The number of steps after which a machine is checked for not
+    /// halting.
+    pub max_steps: u64,
+    /// The number of steps backward reasoning goes back from the missing
+    /// transitions before giving up.
+    pub max_depth: usize,
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        Self {
+            states: 2,
+            symbols: 2,
+            max_steps: 1000,
+            max_depth: 20,
+        }
+    }
+}
+
+/// A machine that halts, found by [`search`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Found {
+    /// The machine in the standard text format of
+    /// [`crate::busy_beaver`], with "---" for transitions it never takes.
+    pub code: String,
+    /// The number of steps it takes to halt on a blank tape, including the
+    /// halting transition.
+    pub steps: u64,
+    /// The number of non-blank symbols on the tape after it halted.
+    pub ones: usize,
+}
+
+impl Found {
+    /// Returns the machine as a [`Program`].
+    ///
+    /// # Panics
+    ///
+    /// This method never panics, the codes of found machines are valid.
+    #[must_use]
+    pub fn program(&self) -> Program {
+        crate::busy_beaver::parse(&self.code).expect("codes of found machines are valid")
+    }
+}
+
+/// The results of [`search`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchReport {
+    /// The number of machines that halt.
+    pub halting: u64,
+    /// The number of machines that were found to repeat a configuration.
+    pub cyclers: u64,
+    /// The number of machines that were found to enter a translated cycle.
+    pub translated: u64,
+    /// The number of machines that were found to never halt by backward
+    /// reasoning.
+    pub backward: u64,
+    /// The machines that could not be decided, in the standard text format.
+    pub holdouts: Vec<String>,
+    /// The halting machine that takes the most steps, the first one found if
+    /// several take the same number of steps.
+    pub most_steps: Option<Found>,
+    /// The halting machine that leaves the most non-blank symbols on the
+    /// tape, the first one found if several leave the same number.
+    pub most_ones: Option<Found>,
+}
+
+impl SearchReport {
+    /// Count a halting machine and keep it if it is a new champion.
+    fn add_halting(&mut self, found: Found) {
+        self.halting += 1;
+
+        if self
+            .most_steps
+            .as_ref()
+            .is_none_or(|best| best.steps < found.steps)
+        {
+            self.most_steps = Some(found.clone());
+        }
+        if self
+            .most_ones
+            .as_ref()
+            .is_none_or(|best| best.ones < found.ones)
+        {
+            self.most_ones = Some(found);
+        }
+    }
+}
+
+/// Allocations that are reused between the branches of a search and between
+/// searches.
+///
+/// Searches go through a huge number of short-lived branches, each of which
+/// would otherwise allocate its own tape or transition table. A context keeps
+/// the tapes, snapshots and machines of finished branches in pools and hands
+/// them out again for new branches, so a search stops allocating once it
+/// reached its widest point. [`search_with`] and
+/// [`crate::nondeterministic::NdTuringMachine::execute_in`] reset the context
+/// before they start, so the same context can be used for any number of
+/// searches:
+///
+/// ```
+/// use turing::search::{search, search_with, SearchConfig, SearchContext};
+///
+/// let config = SearchConfig {
+///     states: 2,
+///     ..SearchConfig::default()
+/// };
+/// let mut context: SearchContext = SearchContext::new();
+///
+/// assert_eq!(search_with(&config, &mut context), search(&config));
+/// assert_eq!(search_with(&config, &mut context), search(&config));
+/// ```
+#[derive(Debug, Clone)]
+pub struct SearchContext<T = VecTape> {
+    /// Tapes that are not used by any branch.
+    tapes: Vec<T>,
+    /// The branches of a non-deterministic execution that are waiting to be
+    /// explored, in order.
+    pub(crate) branches: VecDeque<MachineSnapshot<T>>,
+    /// The machines that are waiting to be simulated by [`search_with`].
+    machines: Vec<Machine>,
+    /// Transition tables that are not used by any machine.
+    tables: Vec<Vec<Option<Entry>>>,
+    /// The tape of the simulated machine and its last checkpoint.
+    scratch: Scratch,
+}
+
+impl<T> SearchContext<T> {
+    /// Create a context without any allocations.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            tapes: Vec::new(),
+            branches: VecDeque::new(),
+            machines: Vec::new(),
+            tables: Vec::new(),
+            scratch: Scratch::default(),
+        }
+    }
+
+    /// Returns a tape to the pool, to be reused by [`SearchContext::tape`].
+    pub fn recycle(&mut self, tape: T) {
+        self.tapes.push(tape);
+    }
+
+    /// Returns the tape of a snapshot to the pool, to be reused by
+    /// [`SearchContext::snapshot`].
+    pub fn recycle_snapshot(&mut self, snapshot: MachineSnapshot<T>) {
+        self.recycle(snapshot.tape);
+    }
+
+    /// Returns the number of tapes in the pool.
+    #[must_use]
+    pub fn pooled(&self) -> usize {
+        self.tapes.len()
+    }
+
+    /// Discard the branches and machines that are left over from the last
+    /// search, returning their allocations to the pools.
+    pub fn reset(&mut self) {
+        let tapes = self.branches.drain(..).map(|branch| branch.tape);
+        self.tapes.extend(tapes);
+        let tables = self.machines.drain(..).map(|machine| machine.table);
+        self.tables.extend(tables);
+    }
+
+    /// Returns a table from the pool, filled with `entries`.
+    fn table(&mut self, entries: impl IntoIterator<Item = Option<Entry>>) -> Vec<Option<Entry>> {
+        let mut table = self.tables.pop().unwrap_or_default();
+        table.clear();
+        table.extend(entries);
+        table
+    }
+}
+
+impl<T> SearchContext<T>
+where
+    T: Clone,
+{
+    /// Returns a copy of `source`, reusing a tape from the pool if there is
+    /// one.
+    pub fn tape(&mut self, source: &T) -> T {
+        match self.tapes.pop() {
+            Some(mut tape) => {
+                tape.clone_from(source);
+                tape
+            }
+            None => source.clone(),
+        }
+    }
+
+    /// Returns a snapshot of a branch in `state` after `steps` steps with a
+    /// copy of `tape`, reusing a tape from the pool if there is one.
+    pub fn snapshot(&mut self, tape: &T, state: State, steps: u64) -> MachineSnapshot<T> {
+        MachineSnapshot {
+            tape: self.tape(tape),
+            state,
+            steps,
+        }
+    }
+}
+
+impl<T> Default for SearchContext<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A defined transition of a [`Machine`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Entry {
+    write: u8,
+    action: Direction,
+    /// The next state, or [`None`] for the halting state.
+    to: Option<u8>,
+}
+
+/// A machine with some of its transitions defined, indexed by state and then
+/// by symbol.
+#[derive(Debug, Clone)]
+struct Machine {
+    states: u8,
+    symbols: u8,
+    table: Vec<Option<Entry>>,
+}
+
+impl Machine {
+    /// Returns the index of the transition of `state` for `symbol`.
+    fn index(&self, state: u8, symbol: u8) -> usize {
+        usize::from(state) * usize::from(self.symbols) + usize::from(symbol)
+    }
+
+    /// Returns the transition of `state` for `symbol`.
+    fn get(&self, state: u8, symbol: u8) -> Option<Entry> {
+        self.table[self.index(state, symbol)]
+    }
+
+    /// Returns every state and symbol in the order of the table.
+    fn cells(&self) -> impl Iterator<Item = (u8, u8)> + '_ {
+        (0..self.states).flat_map(|state| (0..self.symbols).map(move |symbol| (state, symbol)))
+    }
+
+    /// Returns the machine in the standard text format.
+    fn code(&self) -> String {
+        let rows: Vec<String> = self
+            .table
+            .chunks(usize::from(self.symbols))
+            .map(|row| {
+                row.iter()
+                    .map(|entry| match entry {
+                        None => String::from("---"),
+                        Some(entry) => format!(
+                            "{}{}{}",
+                            entry.write,
+                            if entry.action == Direction::Left {
+                                'L'
+                            } else {
+                                'R'
+                            },
+                            entry.to.map_or('Z', |to| letter(usize::from(to)))
+                        ),
+                    })
+                    .collect()
+            })
+            .collect();
+
+        rows.join("_")
+    }
+}
+
+/// The buffers [`simulate`] works in.
+#[derive(Debug, Clone, Default)]
+struct Scratch {
+    tape: Vec<u8>,
+    checkpoint: Vec<u8>,
+}
+
+/// How a run of a [`Machine`] on a blank tape ended.
+enum Outcome {
+    /// The machine reached a missing transition after `steps` steps, with
+    /// `ones` non-blank symbols on the tape.
+    Undefined {
+        state: u8,
+        symbol: u8,
+        steps: u64,
+        ones: usize,
+    },
+    /// The machine repeated a configuration.
+    Cycler,
+    /// The machine entered a translated cycle.
+    Translated,
+    /// The machine did not reach a missing transition within the step limit.
+    Unknown,
+}
+
+/// Run `machine` on a blank tape for at most `max_steps` steps.
+fn simulate(machine: &Machine, max_steps: u64, scratch: &mut Scratch) -> Outcome {
+    let tape = &mut scratch.tape;
+    tape.clear();
+    tape.resize(64, 0);
+    let mut head = tape.len() / 2;
+    let mut state = 0_u8;
+
+    // Brent's cycle detection, comparing the cheap parts first
+    scratch.checkpoint.clone_from(tape);
+    let mut checkpoint = (state, head, &mut scratch.checkpoint);
+    let mut power = 1_u64;
+    let mut length = 0_u64;
+
+    // Positions are relative to the start, which moves when the tape grows
+    let mut origin = head;
+    let mut detector = CycleDetector::new(0, 0, 0, 0);
+
+    for steps in 0..max_steps {
+        let symbol = tape[head];
+        let Some(entry) = machine.get(state, symbol) else {
+            return Outcome::Undefined {
+                state,
+                symbol,
+                steps,
+                ones: tape.iter().filter(|symbol| **symbol != 0).count(),
+            };
+        };
+        // Halting transitions are only added to finished machines
+        let Some(to) = entry.to else {
+            return Outcome::Unknown;
+        };
+
+        tape[head] = entry.write;
+        state = to;
+        if entry.action == Direction::Left {
+            if head == 0 {
+                let grow = tape.len();
+                tape.splice(0..0, std::iter::repeat_n(0, grow));
+                head = grow;
+                origin += grow;
+            }
+            head -= 1;
+        } else {
+            head += 1;
+            if head == tape.len() {
+                tape.resize(tape.len() * 2, 0);
+            }
+        }
+
+        if checkpoint.0 == state && checkpoint.1 == head && checkpoint.2 == tape {
+            return Outcome::Cycler;
+        }
+        length += 1;
+        if length == power {
+            checkpoint.0 = state;
+            checkpoint.1 = head;
+            checkpoint.2.clone_from(tape);
+            power *= 2;
+            length = 0;
+        }
+
+        let position = logical(head, origin);
+        let get = |position: i64| {
+            usize::try_from(position + logical(origin, 0))
+                .ok()
+                .and_then(|index| tape.get(index))
+                .copied()
+                .unwrap_or(0)
+        };
+        if detector.observe(steps + 1, state, position, get).is_some() {
+            return Outcome::Translated;
+        }
+    }
+
+    Outcome::Unknown
+}
+
+/// Returns the position of `index` on a tape that started at `origin`.
+fn logical(index: usize, origin: usize) -> i64 {
+    i64::try_from(index).unwrap_or(i64::MAX) - i64::try_from(origin).unwrap_or(i64::MAX)
+}
+
+/// A configuration that leads to a missing transition, with only the cells
+/// that matter on the way known.
+#[derive(Debug, Clone)]
+struct Partial {
+    state: u8,
+    head: i64,
+    tape: BTreeMap<i64, u8>,
+}
+
+/// Whether backward reasoning shows that `machine` never reaches any of its
+/// missing transitions from a blank tape.
+///
+/// Starting from every missing transition, this goes back one step at a time
+/// through the transitions that could have led to it. If every chain of
+/// predecessors ends within `max_depth` steps because no transition fits
+/// the known cells, no run reaches a missing transition.
+fn never_halts(machine: &Machine, max_depth: usize) -> bool {
+    let mut pending: Vec<(Partial, usize)> = machine
+        .cells()
+        .filter(|(state, symbol)| machine.get(*state, *symbol).is_none())
+        .map(|(state, symbol)| {
+            let partial = Partial {
+                state,
+                head: 0,
+                tape: BTreeMap::from([(0, symbol)]),
+            };
+            (partial, 0)
+        })
+        .collect();
+
+    while let Some((partial, depth)) = pending.pop() {
+        // A run on a blank tape starts in this configuration
+        if partial.state == 0 && partial.tape.values().all(|symbol| *symbol == 0) {
+            return false;
+        }
+        if depth == max_depth {
+            return false;
+        }
+
+        for (from, read) in machine.cells() {
+            let Some(entry) = machine.get(from, read) else {
+                continue;
+            };
+            if entry.to != Some(partial.state) {
+                continue;
+            }
+
+            let head = partial.head - entry.action.offset();
+            if partial
+                .tape
+                .get(&head)
+                .is_some_and(|symbol| *symbol != entry.write)
+            {
+                continue;
+            }
+
+            let mut tape = partial.tape.clone();
+            tape.insert(head, read);
+            pending.push((
+                Partial {
+                    state: from,
+                    head,
+                    tape,
+                },
+                depth + 1,
+            ));
+        }
+    }
+
+    true
+}
+
+/// Enumerate the machines allowed by `config` in tree normal form and report
+/// the champions among the ones that halt.
+///
+/// # Panics
+///
+/// This function panics if `config` allows no states, fewer than two
+/// symbols, more than ten symbols or more than 25 states.
+#[must_use]
+pub fn search(config: &SearchConfig) -> SearchReport {
+    search_with(config, &mut SearchContext::<VecTape>::new())
+}
+
+/// Like [`search`], but reusing the allocations of `context`, which is reset
+/// first.
+///
+/// # Panics
+///
+/// This function panics if `config` allows no states, fewer than two
+/// symbols, more than ten symbols or more than 25 states.
+pub fn search_with<T>(config: &SearchConfig, context: &mut SearchContext<T>) -> SearchReport {
+    assert!(config.states > 0, "a machine needs at least one state");
+    assert!(
+        (2..=10).contains(&config.symbols),
+        "machines need between two and ten symbols"
+    );
+    assert!(config.states <= 25, "machines have at most 25 states");
+    let max_states = u8::try_from(config.states).expect("at most 25 states");
+    let max_symbols = u8::try_from(config.symbols).expect("at most ten symbols");
+
+    context.reset();
+    let mut report = SearchReport::default();
+    let table = context.table(std::iter::repeat_n(None, config.states * config.symbols));
+    context.machines.push(Machine {
+        states: max_states,
+        symbols: max_symbols,
+        table,
+    });
+
+    while let Some(mut machine) = context.machines.pop() {
+        match simulate(&machine, config.max_steps, &mut context.scratch) {
+            Outcome::Undefined {
+                state,
+                symbol,
+                steps,
+                ones,
+            } => expand(
+                &mut machine,
+                (state, symbol),
+                steps,
+                ones,
+                context,
+                &mut report,
+            ),
+            Outcome::Cycler => report.cyclers += 1,
+            Outcome::Translated => report.translated += 1,
+            Outcome::Unknown => {
+                if never_halts(&machine, config.max_depth) {
+                    report.backward += 1;
+                } else {
+                    report.holdouts.push(machine.code());
+                }
+            }
+        }
+
+        context.tables.push(machine.table);
+    }
+
+    report
+}
+
+/// Report `machine` with its missing transition for `state` and `symbol`
+/// defined as the halting one, and queue the machines with every other
+/// definition of it in `context`.
+fn expand<T>(
+    machine: &mut Machine,
+    (state, symbol): (u8, u8),
+    steps: u64,
+    ones: usize,
+    context: &mut SearchContext<T>,
+    report: &mut SearchReport,
+) {
+    let index = machine.index(state, symbol);
+    let defined = machine.table.iter().flatten().count();
+
+    // Halting on the missing transition, writing a non-blank symbol
+    machine.table[index] = Some(Entry {
+        write: 1,
+        action: Direction::Right,
+        to: None,
+    });
+    report.add_halting(Found {
+        code: machine.code(),
+        steps: steps + 1,
+        ones: ones + usize::from(symbol == 0),
+    });
+    machine.table[index] = None;
+
+    // A machine without a halting transition never halts
+    if defined + 1 == machine.table.len() {
+        return;
+    }
+
+    // Only use one state and one symbol that have not been used yet, and
+    // only move right first, since the others are renamings or mirror
+    // images
+    let entries = || machine.table.iter().flatten();
+    let states = entries()
+        .filter_map(|entry| entry.to)
+        .chain([state])
+        .max()
+        .map_or(1, |highest| highest + 2)
+        .min(machine.states);
+    let symbols = entries()
+        .map(|entry| entry.write)
+        .max()
+        .map_or(2, |highest| highest + 2)
+        .min(machine.symbols);
+    let actions: &[Direction] = if defined == 0 {
+        &[Direction::Right]
+    } else {
+        &[Direction::Left, Direction::Right]
+    };
+
+    for to in (0..states).rev() {
+        for action in actions {
+            for write in (0..symbols).rev() {
+                let mut table = context.table(machine.table.iter().copied());
+                table[index] = Some(Entry {
+                    write,
+                    action: *action,
+                    to: Some(to),
+                });
+                context.machines.push(Machine {
+                    states: machine.states,
+                    symbols: machine.symbols,
+                    table,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{busy_beaver::*, search::*};
+
+    #[test]
+    fn test_search_champions() {
+        let report = search(&SearchConfig {
+            states: 3,
+            ..SearchConfig::default()
+        });
+
+        let most_steps = report.most_steps.unwrap();
+        assert_eq!(most_steps.steps, BB3.steps);
+        assert_eq!(encode(&most_steps.program()).unwrap(), most_steps.code);
+        assert_eq!(report.most_ones.unwrap().ones, 6);
+        assert!(report.cyclers > 0 && report.translated > 0 && report.backward > 0);
+    }
+
+    /// Returns the machine with the given code in the standard text format.
+    fn from_code(code: &str) -> Machine {
+        let table: Vec<Option<Entry>> = code
+            .split('_')
+            .flat_map(|row| row.as_bytes().chunks(3))
+            .map(|cell| {
+                (cell != b"---").then(|| Entry {
+                    write: cell[0] - b'0',
+                    action: if cell[1] == b'L' {
+                        Direction::Left
+                    } else {
+                        Direction::Right
+                    },
+                    to: (cell[2] != b'Z').then(|| cell[2] - b'A'),
+                })
+            })
+            .collect();
+
+        Machine {
+            states: u8::try_from(table.len() / 2).unwrap(),
+            symbols: 2,
+            table,
+        }
+    }
+
+    #[test]
+    fn test_backward_reasoning() {
+        // C only enters B on a blank, which B moves left from into C
+        let machine = from_code("0RB---_0LC---_1RB0LC");
+        assert_eq!(machine.code(), "0RB---_0LC---_1RB0LC");
+        assert!(never_halts(&machine, 10));
+
+        // Walks right forever, which backward reasoning can not tell
+        let machine = from_code("0RB---_0RA---");
+        assert!(matches!(
+            simulate(&machine, 100, &mut Scratch::default()),
+            Outcome::Translated
+        ));
+        assert!(!never_halts(&machine, 10));
+
+        // Reaches the missing transition of B for a one after two steps
+        let machine = from_code("1RB---_1LB---");
+        assert!(!never_halts(&machine, 10));
+    }
+}
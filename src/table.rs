@@ -0,0 +1,169 @@
+//! Transition tables of [`Program`]s.
+use std::{
+    collections::BTreeSet,
+    fmt::{self, Write},
+};
+
+use crate::{tape::Symbol, Program, State};
+
+impl<S> Program<S>
+where
+    S: Symbol + fmt::Display,
+{
+    /// Returns the transitions of the program as an aligned table in
+    /// Markdown syntax.
+    ///
+    /// There is one row for every state and one column for every segment the
+    /// program reads, followed by a `*` column if it has wildcard
+    /// transitions. Each cell holds the segment to write, the move and the
+    /// next state of a transition, or nothing if the state has no transition
+    /// for the segment. States are marked like in the `.tng` format, with `+`
+    /// for the initial state, `-` for final states and `!` for error states.
+    ///
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use turing::Program;
+    /// let program = Program::from_str("+0\n-done\n0,0,1,1,r\n0,done,_,1,n").unwrap();
+    ///
+    /// assert_eq!(
+    ///     program.format_table(),
+    ///     "\
+    /// | state | _        | 1     |
+    /// |-------|----------|-------|
+    /// | +0    | 1,n,done | 1,r,0 |
+    /// | -done |          |       |
+    /// "
+    /// );
+    /// ```
+    #[must_use]
+    pub fn format_table(&self) -> String {
+        let symbols: BTreeSet<S> = self
+            .transitions
+            .values()
+            .map(|transition| transition.condition)
+            .collect();
+        let has_wildcards = !self.wildcards.is_empty();
+
+        let mut header = vec![String::from("state")];
+        header.extend(symbols.iter().map(ToString::to_string));
+        if has_wildcards {
+            header.push(String::from("*"));
+        }
+
+        let mut rows = vec![header];
+        for state in self.states() {
+            let marker = if state == self.initial_state {
+                "+"
+            } else if self.final_states.contains(&state) {
+                "-"
+            } else if self.error_states.contains(&state) {
+                "!"
+            } else {
+                ""
+            };
+
+            let mut row = vec![format!("{marker}{}", self.table_name(state))];
+            row.extend(symbols.iter().map(|symbol| {
+                self.transitions
+                    .get(&(state, *symbol))
+                    .map_or_else(String::new, |transition| {
+                        format!(
+                            "{},{},{}",
+                            transition.write,
+                            transition.action,
+                            self.table_name(transition.to)
+                        )
+                    })
+            }));
+            if has_wildcards {
+                row.push(
+                    self.wildcards
+                        .get(&state)
+                        .map_or_else(String::new, |wildcard| {
+                            let write = wildcard
+                                .write
+                                .map_or_else(|| String::from("="), |write| write.to_string());
+                            format!(
+                                "{write},{},{}",
+                                wildcard.action,
+                                self.table_name(wildcard.to)
+                            )
+                        }),
+                );
+            }
+
+            rows.push(row);
+        }
+
+        let widths: Vec<usize> = (0..rows[0].len())
+            .map(|column| {
+                rows.iter()
+                    .map(|row| row[column].chars().count())
+                    .max()
+                    .unwrap_or_default()
+            })
+            .collect();
+
+        let mut out = String::new();
+        for (index, row) in rows.iter().enumerate() {
+            for (cell, width) in row.iter().zip(&widths) {
+                let _ = write!(out, "| {cell:width$} ");
+            }
+            out.push_str("|\n");
+
+            if index == 0 {
+                for width in &widths {
+                    let _ = write!(out, "|{}", "-".repeat(width + 2));
+                }
+                out.push_str("|\n");
+            }
+        }
+
+        out
+    }
+
+    /// Returns the name of `state`, or its number if it has no name.
+    fn table_name(&self, state: State) -> String {
+        self.state_name(state)
+            .map_or_else(|| state.to_string(), ToOwned::to_owned)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::*;
+
+    #[test]
+    fn test_format_table() {
+        let program = Program::from_str(include_str!("../examples/next_integer.tng")).unwrap();
+
+        assert_eq!(
+            program.format_table(),
+            "\
+| state | _     | 0     | 1     |
+|-------|-------|-------|-------|
+| +0    | _,l,1 | 0,r,0 | 1,r,0 |
+| 1     | 1,n,3 | 1,l,2 | 0,l,1 |
+| 2     | _,r,3 | 0,l,2 | 1,l,2 |
+| -3    |       |       |       |
+"
+        );
+    }
+
+    #[test]
+    fn test_format_table_wildcards() {
+        let program = Program::from_str("+0\n!1\n0,0,a,b,r\n0,1,*,=,n").unwrap();
+
+        assert_eq!(
+            program.format_table(),
+            "\
+| state | a     | *     |
+|-------|-------|-------|
+| +0    | b,r,0 | =,n,1 |
+| !1    |       |       |
+"
+        );
+    }
+}
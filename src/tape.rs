@@ -1,37 +1,93 @@
 use std::{
-    collections::VecDeque,
+    collections::{HashMap, VecDeque},
     fmt::{self, Write},
+    hash::Hash,
     str::FromStr,
 };
 
-use crate::error::InvalidProgram;
+use crate::{error::InvalidProgram, Direction};
 
 /// A segment on the infinite [`Tape`].
-#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, PartialOrd, Ord)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "char", into = "char")
+)]
 pub enum Segment {
-    Zero,
-    One,
+    /// A blank segment, written as "_" or " ".
     Empty,
+    /// A segment holding any other character.
+    Symbol(char),
+}
+
+/// A symbol of a tape alphabet.
+///
+/// [`Segment`] is the alphabet used by the `.tng` program format, but tapes,
+/// programs and machines work with any other alphabet as well. Symbols are
+/// ordered so that programs can be inspected and printed deterministically.
+pub trait Symbol: Copy + Ord + Hash + fmt::Debug {
+    /// The symbol of segments that were never written to.
+    const BLANK: Self;
+}
+
+impl Symbol for Segment {
+    const BLANK: Self = Self::Empty;
+}
+
+impl From<char> for Segment {
+    fn from(c: char) -> Self {
+        match c {
+            '_' | ' ' => Self::Empty,
+            c => Self::Symbol(c),
+        }
+    }
+}
+
+impl From<Segment> for char {
+    fn from(segment: Segment) -> Self {
+        match segment {
+            Segment::Empty => '_',
+            Segment::Symbol(c) => c,
+        }
+    }
 }
 
 impl FromStr for Segment {
     type Err = InvalidProgram;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "1" => Ok(Self::One),
-            "0" => Ok(Self::Zero),
-            "_" | " " => Ok(Self::Empty),
-            _ => Err(InvalidProgram::InvalidSegment),
+        let mut chars = s.chars();
+
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Ok(Self::from(c)),
+            (Some(_), Some(_)) => Err(InvalidProgram::MultiCharacterSymbol),
+            (None, _) => Err(InvalidProgram::InvalidSegment),
         }
     }
 }
 
+impl fmt::Display for Segment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_char(char::from(*self))
+    }
+}
+
 /// An infinite working buffer for the [`crate::TuringMachine`].
 ///
 /// Advancing the tape past the known segments will create
 /// empty segments dynamically.
+///
+/// Positions are logical: they are relative to the cursor position the tape
+/// was created with, so growing the tape to the left does not shift them.
 pub trait Tape {
+    /// The alphabet of the tape.
+    type Symbol: Symbol;
+
+    /// The same kind of tape with the alphabet `T`, as returned by
+    /// [`Tape::remap`].
+    type Remapped<T: Symbol>: Tape<Symbol = T>;
+
     /// Advance the cursor to the right by one.
     fn right(&mut self);
 
@@ -39,20 +95,143 @@ pub trait Tape {
     fn left(&mut self);
 
     /// Write to the segment at the cursor position.
-    fn put(&mut self, segment: Segment);
+    fn put(&mut self, segment: Self::Symbol);
 
     /// View the segment at the cursor position.
-    fn current(&self) -> &Segment;
+    fn current(&self) -> &Self::Symbol;
+
+    /// Returns the cursor position.
+    fn position(&self) -> i64;
+
+    /// Returns the segment at `position`, which is empty if the tape was
+    /// never extended that far.
+    fn get(&self, position: i64) -> Self::Symbol;
+
+    /// Returns the position of the leftmost segment of the known part of the
+    /// tape, which holds the initial tape and every segment the cursor
+    /// visited.
+    fn start(&self) -> i64;
+
+    /// Returns the number of segments in the known part of the tape.
+    fn len(&self) -> usize;
+
+    /// Returns whether the known part of the tape has no segments.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the segments of the known part of the tape from left to
+    /// right, starting at [`Tape::start`].
+    ///
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use turing::{SparseTape, Tape};
+    /// let mut tape = SparseTape::from_str("_1_0").unwrap();
+    /// tape.left();
+    /// tape.left();
+    ///
+    /// assert_eq!(tape.start(), -2);
+    /// assert_eq!(tape.iter().map(char::from).collect::<String>(), "__1_0");
+    /// ```
+    fn iter(&self) -> impl Iterator<Item = Self::Symbol> + '_
+    where
+        Self: Sized,
+    {
+        let start = self.start();
+        (0..self.len()).map(move |offset| self.get(start.saturating_add_unsigned(offset as u64)))
+    }
+
+    /// Returns the known part of the tape as a slice, like [`Tape::iter`], or
+    /// [`None`] if the tape does not store it as one.
+    fn as_slice(&self) -> Option<&[Self::Symbol]> {
+        None
+    }
+
+    /// Returns the same kind of tape with every segment of the known part
+    /// replaced by `map(segment)`.
+    ///
+    /// The cursor and the positions of all segments stay the same, so a
+    /// remapped [`CircularTape`] still wraps around at the same positions.
+    ///
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use turing::{Segment, SparseTape, Tape};
+    /// let tape = SparseTape::from_str("_ab_").unwrap();
+    /// let upper: SparseTape = tape.remap(|segment| match segment {
+    ///     Segment::Symbol(c) => Segment::Symbol(c.to_ascii_uppercase()),
+    ///     Segment::Empty => Segment::Empty,
+    /// });
+    ///
+    /// assert_eq!(upper.to_string(), "AB");
+    /// assert_eq!(upper.position(), tape.position());
+    /// ```
+    fn remap<T>(&self, map: impl Fn(Self::Symbol) -> T) -> Self::Remapped<T>
+    where
+        T: Symbol;
+
+    /// Move the cursor to `position`, extending the tape as needed.
+    fn seek(&mut self, position: i64) {
+        while self.position() < position {
+            self.right();
+        }
+
+        while self.position() > position {
+            self.left();
+        }
+    }
+}
+
+/// Returns the logical position of `index` on a tape whose initial cursor
+/// position is at `origin`.
+fn logical(index: usize, origin: usize) -> i64 {
+    if index >= origin {
+        i64::try_from(index - origin).expect("tape position exceeds i64")
+    } else {
+        -i64::try_from(origin - index).expect("tape position exceeds i64")
+    }
+}
+
+/// Returns the index of the logical `position` on a tape whose initial cursor
+/// position is at `origin`, if it is not to the left of the tape.
+fn index(position: i64, origin: usize) -> Option<usize> {
+    let origin = i64::try_from(origin).ok()?;
+    usize::try_from(origin.checked_add(position)?).ok()
 }
 
 /// A [`Tape`] backed by a [`Vec`].
 #[derive(Debug, PartialEq, Eq)]
-pub struct VecTape {
-    pub(crate) inner: Vec<Segment>,
-    position: usize,
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VecTape<S = Segment> {
+    pub(crate) inner: Vec<S>,
+    pub(crate) position: usize,
+    pub(crate) origin: usize,
+}
+
+impl<S> Clone for VecTape<S>
+where
+    S: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            position: self.position,
+            origin: self.origin,
+        }
+    }
+
+    /// Reuses the buffer of this tape, which
+    /// [`crate::search::SearchContext`] relies on.
+    fn clone_from(&mut self, source: &Self) {
+        self.inner.clone_from(&source.inner);
+        self.position = source.position;
+        self.origin = source.origin;
+    }
 }
 
-impl VecTape {
+impl<S> VecTape<S>
+where
+    S: Symbol,
+{
     /// Create a new tape with a known part of the tape and a
     /// specific cursor position.
     ///
@@ -60,36 +239,165 @@ impl VecTape {
     ///
     /// This method will panic if the position is outside of the tape segment.
     #[must_use]
-    pub fn new(inner: Vec<Segment>, position: usize) -> Self {
+    pub fn new(inner: Vec<S>, position: usize) -> Self {
         assert!(position < inner.len());
-        Self { inner, position }
+        Self {
+            inner,
+            position,
+            origin: position,
+        }
+    }
+
+    /// Returns the segments from the leftmost to the rightmost non-empty
+    /// segment, which is empty if the whole tape is blank.
+    ///
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use turing::{Segment, VecTape};
+    /// let tape = VecTape::from_str("__1_0__").unwrap();
+    ///
+    /// assert_eq!(
+    ///     tape.trim(),
+    ///     [Segment::Symbol('1'), Segment::Empty, Segment::Symbol('0')]
+    /// );
+    /// ```
+    #[must_use]
+    pub fn trim(&self) -> &[S] {
+        let start = self
+            .inner
+            .iter()
+            .position(|segment| *segment != S::BLANK)
+            .unwrap_or(self.inner.len());
+        let end = self
+            .inner
+            .iter()
+            .rposition(|segment| *segment != S::BLANK)
+            .map_or(start, |end| end + 1);
+
+        &self.inner[start..end]
+    }
+}
+
+impl<S> VecTape<S>
+where
+    S: Symbol + fmt::Display,
+{
+    /// Returns the `radius` segments on either side of the cursor and the
+    /// segment under it in brackets, separated by spaces.
+    ///
+    /// An ellipsis on either end means that there are more non-empty segments
+    /// outside of the window.
+    ///
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use turing::VecTape;
+    /// let tape = VecTape::from_str("_10110").unwrap();
+    ///
+    /// assert_eq!(tape.render_window(2), "_ _ [1] 0 1…");
+    /// assert_eq!(tape.render_window(5), "_ _ _ _ _ [1] 0 1 1 0 _");
+    /// ```
+    #[must_use]
+    pub fn render_window(&self, radius: usize) -> String {
+        let position = logical(self.position, self.origin);
+        let radius = i64::try_from(radius).unwrap_or(i64::MAX);
+        let start = position.saturating_sub(radius);
+        let end = position.saturating_add(radius);
+        let mut out = String::new();
+
+        let logical = |index| logical(index, self.origin);
+        let leftmost = self.inner.iter().position(|segment| *segment != S::BLANK);
+        let rightmost = self.inner.iter().rposition(|segment| *segment != S::BLANK);
+
+        if leftmost.is_some_and(|index| logical(index) < start) {
+            out.push('…');
+        }
+
+        for segment_position in start..=end {
+            if segment_position != start {
+                out.push(' ');
+            }
+
+            let segment = self.get(segment_position);
+            if segment_position == position {
+                let _ = write!(out, "[{segment}]");
+            } else {
+                let _ = write!(out, "{segment}");
+            }
+        }
+
+        if rightmost.is_some_and(|index| logical(index) > end) {
+            out.push('…');
+        }
+
+        out
     }
 }
 
-impl Tape for VecTape {
+impl<S> Tape for VecTape<S>
+where
+    S: Symbol,
+{
+    type Remapped<T: Symbol> = VecTape<T>;
+    type Symbol = S;
+
     fn right(&mut self) {
         self.position += 1;
 
         if self.position == self.inner.len() {
-            self.inner.push(Segment::Empty);
+            self.inner.push(S::BLANK);
         }
     }
 
     fn left(&mut self) {
         if self.position == 0 {
-            self.inner.insert(0, Segment::Empty);
+            self.inner.insert(0, S::BLANK);
+            self.origin += 1;
         } else {
             self.position -= 1;
         }
     }
 
-    fn put(&mut self, segment: Segment) {
+    fn put(&mut self, segment: S) {
         self.inner[self.position] = segment;
     }
 
-    fn current(&self) -> &Segment {
+    fn current(&self) -> &S {
         &self.inner[self.position]
     }
+
+    fn position(&self) -> i64 {
+        logical(self.position, self.origin)
+    }
+
+    fn start(&self) -> i64 {
+        logical(0, self.origin)
+    }
+
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    fn as_slice(&self) -> Option<&[S]> {
+        Some(&self.inner)
+    }
+
+    fn get(&self, position: i64) -> S {
+        index(position, self.origin)
+            .and_then(|index| self.inner.get(index))
+            .copied()
+            .unwrap_or(S::BLANK)
+    }
+
+    fn remap<T>(&self, map: impl Fn(S) -> T) -> VecTape<T>
+    where
+        T: Symbol,
+    {
+        VecTape {
+            inner: self.inner.iter().map(|segment| map(*segment)).collect(),
+            position: self.position,
+            origin: self.origin,
+        }
+    }
 }
 
 impl FromStr for VecTape {
@@ -100,52 +408,205 @@ impl FromStr for VecTape {
         let mut position = 0;
 
         for (idx, part) in s.chars().enumerate() {
-            match part {
-                '1' => {
-                    inner.push(Segment::One);
+            let segment = Segment::from(part);
+            inner.push(segment);
 
-                    if position == 0 {
-                        position = idx;
-                    }
-                }
-                '0' => {
-                    inner.push(Segment::Zero);
-
-                    if position == 0 {
-                        position = idx;
-                    }
-                }
-                '_' | ' ' => inner.push(Segment::Empty),
-                _ => return Err(InvalidProgram::InvalidSegment),
+            if segment != Segment::Empty && position == 0 {
+                position = idx;
             }
         }
 
-        Ok(Self { inner, position })
+        Ok(Self {
+            inner,
+            position,
+            origin: position,
+        })
     }
 }
 
-impl fmt::Display for VecTape {
+impl<S> fmt::Display for VecTape<S>
+where
+    S: fmt::Display,
+{
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         for segment in &self.inner {
-            match segment {
-                Segment::One => f.write_char('1')?,
-                Segment::Zero => f.write_char('0')?,
-                Segment::Empty => f.write_char('_')?,
-            }
+            segment.fmt(f)?;
         }
 
         Ok(())
     }
 }
 
+/// Returns a tape holding `n` in unary as `n` "1"s, with the cursor on the
+/// first of them.
+///
+/// The number is surrounded by blanks, so a tape for zero is blank.
+///
+/// # Panics
+///
+/// This function panics if `n` does not fit into a [`usize`].
+#[must_use]
+pub fn encode_unary(n: u64) -> VecTape {
+    encode_unary_args(&[n])
+}
+
+/// Returns a tape holding `n` in binary, most significant bit first, with the
+/// cursor on the first bit.
+///
+/// This is the input format of the bundled `next_integer.tng`:
+/// ```
+/// # use std::str::FromStr;
+/// # use turing::{decode_binary, encode_binary, Program, TuringMachine};
+/// let program = Program::from_str(include_str!("../examples/next_integer.tng")).unwrap();
+/// let mut machine = TuringMachine::from_tape(encode_binary(11));
+/// machine.execute(&program).unwrap();
+///
+/// assert_eq!(decode_binary(machine.tape()), Some(12));
+/// ```
+#[must_use]
+pub fn encode_binary(n: u64) -> VecTape {
+    encode_binary_args(&[n])
+}
+
+/// Returns a tape holding `args` in unary like [`encode_unary`], separated
+/// by single blanks, with the cursor on the first segment of the first
+/// argument.
+///
+/// # Panics
+///
+/// This function panics if an argument does not fit into a [`usize`].
+#[must_use]
+pub fn encode_unary_args(args: &[u64]) -> VecTape {
+    encode_args(args, |n| {
+        "1".repeat(usize::try_from(n).expect("argument fits into a usize"))
+    })
+}
+
+/// Returns a tape holding `args` in binary like [`encode_binary`], separated
+/// by single blanks, with the cursor on the first bit of the first argument.
+#[must_use]
+pub fn encode_binary_args(args: &[u64]) -> VecTape {
+    encode_args(args, |n| format!("{n:b}"))
+}
+
+/// Returns a tape holding the encoded `args` separated by single blanks, with
+/// a blank on each side.
+fn encode_args(args: &[u64], encode: impl Fn(u64) -> String) -> VecTape {
+    let mut inner = vec![Segment::Empty];
+    for (index, n) in args.iter().enumerate() {
+        if index > 0 {
+            inner.push(Segment::Empty);
+        }
+        inner.extend(encode(*n).chars().map(Segment::Symbol));
+    }
+    inner.push(Segment::Empty);
+
+    VecTape::new(inner, 1)
+}
+
+/// Returns the number a tape holds in unary, the number of "1"s between the
+/// blanks around it, or [`None`] if it holds anything else.
+#[must_use]
+pub fn decode_unary(tape: &VecTape) -> Option<u64> {
+    match decode_unary_args(tape)?.as_slice() {
+        [] => Some(0),
+        [n] => Some(*n),
+        _ => None,
+    }
+}
+
+/// Returns the number a tape holds in binary, or [`None`] if it holds
+/// anything else or the number does not fit into a [`u64`].
+#[must_use]
+pub fn decode_binary(tape: &VecTape) -> Option<u64> {
+    match decode_binary_args(tape)?.as_slice() {
+        [n] => Some(*n),
+        _ => None,
+    }
+}
+
+/// Returns the numbers a tape holds in unary, separated by single blanks, or
+/// [`None`] if it holds anything else.
+///
+/// Zeros are written as nothing, so zeros before the first and after the
+/// last non-zero argument cannot be told apart from the blanks around them
+/// and are not returned.
+#[must_use]
+pub fn decode_unary_args(tape: &VecTape) -> Option<Vec<u64>> {
+    decode_args(tape, |digits| {
+        digits
+            .iter()
+            .all(|segment| *segment == Segment::Symbol('1'))
+            .then_some(digits.len() as u64)
+    })
+}
+
+/// Returns the numbers a tape holds in binary, separated by single blanks, or
+/// [`None`] if it holds anything else or a number does not fit into a
+/// [`u64`].
+#[must_use]
+pub fn decode_binary_args(tape: &VecTape) -> Option<Vec<u64>> {
+    decode_args(tape, |digits| {
+        let digits: String = digits
+            .iter()
+            .map(|segment| match segment {
+                Segment::Symbol(c @ ('0' | '1')) => Some(*c),
+                _ => None,
+            })
+            .collect::<Option<_>>()?;
+
+        u64::from_str_radix(&digits, 2).ok()
+    })
+}
+
+/// Returns the arguments on a tape, separated by single blanks and decoded
+/// with `decode`.
+fn decode_args(tape: &VecTape, decode: impl Fn(&[Segment]) -> Option<u64>) -> Option<Vec<u64>> {
+    let segments = tape.trim();
+    if segments.is_empty() {
+        return Some(Vec::new());
+    }
+
+    segments
+        .split(|segment| *segment == Segment::Empty)
+        .map(decode)
+        .collect()
+}
+
 /// A [`Tape`] backed by a [`VecDeque`].
 #[derive(Debug, PartialEq, Eq)]
-pub struct VecDequeTape {
-    pub(crate) inner: VecDeque<Segment>,
-    position: usize,
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VecDequeTape<S = Segment> {
+    pub(crate) inner: VecDeque<S>,
+    pub(crate) position: usize,
+    pub(crate) origin: usize,
+}
+
+impl<S> Clone for VecDequeTape<S>
+where
+    S: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            position: self.position,
+            origin: self.origin,
+        }
+    }
+
+    /// Reuses the buffer of this tape, which
+    /// [`crate::search::SearchContext`] relies on.
+    fn clone_from(&mut self, source: &Self) {
+        self.inner.clone_from(&source.inner);
+        self.position = source.position;
+        self.origin = source.origin;
+    }
 }
 
-impl VecDequeTape {
+impl<S> VecDequeTape<S>
+where
+    S: Symbol,
+{
     /// Create a new tape with a known part of the tape and a
     /// specific cursor position.
     ///
@@ -153,36 +614,84 @@ impl VecDequeTape {
     ///
     /// This method will panic if the position is outside of the tape segment.
     #[must_use]
-    pub fn new(inner: VecDeque<Segment>, position: usize) -> Self {
+    pub fn new(inner: VecDeque<S>, position: usize) -> Self {
         assert!(position < inner.len());
-        Self { inner, position }
+        Self {
+            inner,
+            position,
+            origin: position,
+        }
     }
 }
 
-impl Tape for VecDequeTape {
+impl<S> Tape for VecDequeTape<S>
+where
+    S: Symbol,
+{
+    type Remapped<T: Symbol> = VecDequeTape<T>;
+    type Symbol = S;
+
     fn right(&mut self) {
         self.position += 1;
 
         if self.position == self.inner.len() {
-            self.inner.push_back(Segment::Empty);
+            self.inner.push_back(S::BLANK);
         }
     }
 
     fn left(&mut self) {
         if self.position == 0 {
-            self.inner.push_front(Segment::Empty);
+            self.inner.push_front(S::BLANK);
+            self.origin += 1;
         } else {
             self.position -= 1;
         }
     }
 
-    fn put(&mut self, segment: Segment) {
+    fn put(&mut self, segment: S) {
         self.inner[self.position] = segment;
     }
 
-    fn current(&self) -> &Segment {
+    fn current(&self) -> &S {
         &self.inner[self.position]
     }
+
+    fn position(&self) -> i64 {
+        logical(self.position, self.origin)
+    }
+
+    fn start(&self) -> i64 {
+        logical(0, self.origin)
+    }
+
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    fn as_slice(&self) -> Option<&[S]> {
+        match self.inner.as_slices() {
+            (front, []) => Some(front),
+            _ => None,
+        }
+    }
+
+    fn get(&self, position: i64) -> S {
+        index(position, self.origin)
+            .and_then(|index| self.inner.get(index))
+            .copied()
+            .unwrap_or(S::BLANK)
+    }
+
+    fn remap<T>(&self, map: impl Fn(S) -> T) -> VecDequeTape<T>
+    where
+        T: Symbol,
+    {
+        VecDequeTape {
+            inner: self.inner.iter().map(|segment| map(*segment)).collect(),
+            position: self.position,
+            origin: self.origin,
+        }
+    }
 }
 
 impl FromStr for VecDequeTape {
@@ -193,40 +702,1229 @@ impl FromStr for VecDequeTape {
         let mut position = 0;
 
         for (idx, part) in s.chars().enumerate() {
-            match part {
-                '1' => {
-                    inner.push_back(Segment::One);
-
-                    if position == 0 {
-                        position = idx;
-                    }
-                }
-                '0' => {
-                    inner.push_back(Segment::Zero);
+            let segment = Segment::from(part);
+            inner.push_back(segment);
 
-                    if position == 0 {
-                        position = idx;
-                    }
-                }
-                '_' | ' ' => inner.push_back(Segment::Empty),
-                _ => return Err(InvalidProgram::InvalidSegment),
+            if segment != Segment::Empty && position == 0 {
+                position = idx;
             }
         }
 
-        Ok(Self { inner, position })
+        Ok(Self {
+            inner,
+            position,
+            origin: position,
+        })
     }
 }
 
-impl fmt::Display for VecDequeTape {
+impl<S> fmt::Display for VecDequeTape<S>
+where
+    S: fmt::Display,
+{
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         for segment in &self.inner {
-            match segment {
-                Segment::One => f.write_char('1')?,
-                Segment::Zero => f.write_char('0')?,
-                Segment::Empty => f.write_char('_')?,
+            segment.fmt(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A [`Tape`] that only stores segments that were written to, backed by a
+/// [`HashMap`].
+///
+/// Positions are relative to the initial cursor position and become negative
+/// left of it, so moving in either direction never shifts any segments. When
+/// displayed, only the span from the leftmost to the rightmost non-empty
+/// segment is printed.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SparseTape<S = Segment> {
+    cells: HashMap<i64, S>,
+    position: i64,
+    min_index: i64,
+    max_index: i64,
+    blank: S,
+}
+
+impl<S> SparseTape<S>
+where
+    S: Symbol,
+{
+    /// Create a new tape with a known part of the tape and a
+    /// specific cursor position.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if the position is outside of the tape segment.
+    #[must_use]
+    pub fn new(inner: Vec<S>, position: usize) -> Self {
+        assert!(position < inner.len());
+        Self::from_segments(inner, position)
+    }
+
+    fn from_segments(inner: Vec<S>, position: usize) -> Self {
+        let offset = i64::try_from(position).expect("tape position exceeds i64");
+        let mut cells = HashMap::new();
+        let mut max_index = 0;
+
+        for (index, segment) in (-offset..).zip(inner) {
+            if segment != S::BLANK {
+                cells.insert(index, segment);
             }
+
+            max_index = index;
+        }
+
+        Self {
+            cells,
+            position: 0,
+            min_index: -offset,
+            max_index: max_index.max(0),
+            blank: S::BLANK,
+        }
+    }
+
+    /// Returns the position of the leftmost segment that was either part of
+    /// the initial tape or visited by the cursor.
+    #[must_use]
+    pub fn min_index(&self) -> i64 {
+        self.min_index
+    }
+
+    /// Returns the position of the rightmost segment that was either part of
+    /// the initial tape or visited by the cursor.
+    #[must_use]
+    pub fn max_index(&self) -> i64 {
+        self.max_index
+    }
+
+    /// Returns the number of distinct cells that were written to, including
+    /// the non-empty segments of the initial tape.
+    #[must_use]
+    pub fn cells_written(&self) -> usize {
+        self.cells.len()
+    }
+}
+
+impl<S> Tape for SparseTape<S>
+where
+    S: Symbol,
+{
+    type Remapped<T: Symbol> = SparseTape<T>;
+    type Symbol = S;
+
+    fn right(&mut self) {
+        self.position += 1;
+        self.max_index = self.max_index.max(self.position);
+    }
+
+    fn left(&mut self) {
+        self.position -= 1;
+        self.min_index = self.min_index.min(self.position);
+    }
+
+    fn put(&mut self, segment: S) {
+        self.cells.insert(self.position, segment);
+    }
+
+    fn current(&self) -> &S {
+        self.cells.get(&self.position).unwrap_or(&self.blank)
+    }
+
+    fn position(&self) -> i64 {
+        self.position
+    }
+
+    fn start(&self) -> i64 {
+        self.min_index
+    }
+
+    fn len(&self) -> usize {
+        usize::try_from(self.max_index - self.min_index).expect("tape length exceeds usize") + 1
+    }
+
+    fn get(&self, position: i64) -> S {
+        self.cells.get(&position).copied().unwrap_or(S::BLANK)
+    }
+
+    fn seek(&mut self, position: i64) {
+        self.position = position;
+        self.min_index = self.min_index.min(position);
+        self.max_index = self.max_index.max(position);
+    }
+
+    fn remap<T>(&self, map: impl Fn(S) -> T) -> SparseTape<T>
+    where
+        T: Symbol,
+    {
+        // Segments that were never written are only stored once they are no
+        // longer blank
+        let cells = if map(S::BLANK) == T::BLANK {
+            self.cells
+                .iter()
+                .map(|(position, segment)| (*position, map(*segment)))
+                .collect()
+        } else {
+            (self.min_index..=self.max_index)
+                .map(|position| (position, map(self.get(position))))
+                .collect()
+        };
+
+        SparseTape {
+            cells,
+            position: self.position,
+            min_index: self.min_index,
+            max_index: self.max_index,
+            blank: T::BLANK,
+        }
+    }
+}
+
+impl FromStr for SparseTape {
+    type Err = InvalidProgram;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tape = VecTape::from_str(s)?;
+
+        Ok(Self::from_segments(tape.inner, tape.position))
+    }
+}
+
+impl<S> fmt::Display for SparseTape<S>
+where
+    S: Symbol + fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut written = self
+            .cells
+            .iter()
+            .filter(|(_, segment)| **segment != S::BLANK)
+            .map(|(index, _)| *index);
+
+        let Some(first) = written.next() else {
+            return Ok(());
+        };
+        let (start, end) = written.fold((first, first), |(start, end), index| {
+            (start.min(index), end.max(index))
+        });
+
+        for index in start..=end {
+            fmt::Display::fmt(self.cells.get(&index).unwrap_or(&self.blank), f)?;
         }
 
         Ok(())
     }
 }
+
+/// The number of segments in a page of a [`PagedTape`].
+pub const PAGE_SIZE: usize = 4096;
+
+/// A [`Tape`] that stores segments in fixed-size pages.
+///
+/// Growing the tape in either direction only ever allocates a new page, so
+/// segments are never moved or reallocated once written. This keeps long runs
+/// that wander far in both directions cheap, while cells that are close to
+/// each other stay close in memory.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct PagedTape<S = Segment> {
+    pages: VecDeque<Box<[S; PAGE_SIZE]>>,
+    page: usize,
+    offset: usize,
+    position: i64,
+    /// The position of the first segment of the first page.
+    base: i64,
+    min_index: i64,
+    max_index: i64,
+}
+
+impl<S> PagedTape<S>
+where
+    S: Symbol,
+{
+    /// Create a new tape with a known part of the tape and a
+    /// specific cursor position.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if the position is outside of the tape segment.
+    #[must_use]
+    pub fn new(inner: Vec<S>, position: usize) -> Self {
+        assert!(position < inner.len());
+        Self::from_segments(inner, position)
+    }
+
+    fn from_segments(inner: Vec<S>, position: usize) -> Self {
+        let offset = i64::try_from(position).expect("tape position exceeds i64");
+        let mut pages = VecDeque::new();
+        let mut max_index = 0;
+
+        for ((index, segment), logical) in inner.into_iter().enumerate().zip(-offset..) {
+            if index % PAGE_SIZE == 0 {
+                pages.push_back(Box::new([S::BLANK; PAGE_SIZE]));
+            }
+
+            if let Some(page) = pages.back_mut() {
+                page[index % PAGE_SIZE] = segment;
+            }
+
+            max_index = logical;
+        }
+
+        if pages.is_empty() {
+            pages.push_back(Box::new([S::BLANK; PAGE_SIZE]));
+        }
+
+        Self {
+            pages,
+            page: position / PAGE_SIZE,
+            offset: position % PAGE_SIZE,
+            position: 0,
+            base: -offset,
+            min_index: -offset,
+            max_index: max_index.max(0),
+        }
+    }
+
+    /// Returns the position of the leftmost segment that was either part of
+    /// the initial tape or visited by the cursor.
+    #[must_use]
+    pub fn min_index(&self) -> i64 {
+        self.min_index
+    }
+
+    /// Returns the position of the rightmost segment that was either part of
+    /// the initial tape or visited by the cursor.
+    #[must_use]
+    pub fn max_index(&self) -> i64 {
+        self.max_index
+    }
+
+    /// Returns the number of allocated pages.
+    #[must_use]
+    pub fn pages(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// Returns the segment at a position between [`PagedTape::min_index`]
+    /// and [`PagedTape::max_index`].
+    fn segment(&self, index: i64) -> &S {
+        let index = usize::try_from(index - self.base).expect("position is on the tape");
+        &self.pages[index / PAGE_SIZE][index % PAGE_SIZE]
+    }
+}
+
+impl<S> Tape for PagedTape<S>
+where
+    S: Symbol,
+{
+    type Remapped<T: Symbol> = PagedTape<T>;
+    type Symbol = S;
+
+    fn right(&mut self) {
+        self.offset += 1;
+
+        if self.offset == PAGE_SIZE {
+            self.offset = 0;
+            self.page += 1;
+
+            if self.page == self.pages.len() {
+                self.pages.push_back(Box::new([S::BLANK; PAGE_SIZE]));
+            }
+        }
+
+        self.position += 1;
+        self.max_index = self.max_index.max(self.position);
+    }
+
+    fn left(&mut self) {
+        if self.offset == 0 {
+            if self.page == 0 {
+                self.pages.push_front(Box::new([S::BLANK; PAGE_SIZE]));
+                self.base = self.base.wrapping_sub_unsigned(PAGE_SIZE as u64);
+            } else {
+                self.page -= 1;
+            }
+
+            self.offset = PAGE_SIZE - 1;
+        } else {
+            self.offset -= 1;
+        }
+
+        self.position -= 1;
+        self.min_index = self.min_index.min(self.position);
+    }
+
+    fn put(&mut self, segment: S) {
+        self.pages[self.page][self.offset] = segment;
+    }
+
+    fn current(&self) -> &S {
+        &self.pages[self.page][self.offset]
+    }
+
+    fn position(&self) -> i64 {
+        self.position
+    }
+
+    fn start(&self) -> i64 {
+        self.min_index
+    }
+
+    fn len(&self) -> usize {
+        usize::try_from(self.max_index - self.min_index).expect("tape length exceeds usize") + 1
+    }
+
+    fn get(&self, position: i64) -> S {
+        let Ok(index) = usize::try_from(position - self.base) else {
+            return S::BLANK;
+        };
+
+        self.pages
+            .get(index / PAGE_SIZE)
+            .map_or(S::BLANK, |page| page[index % PAGE_SIZE])
+    }
+
+    fn remap<T>(&self, map: impl Fn(S) -> T) -> PagedTape<T>
+    where
+        T: Symbol,
+    {
+        let mut position = self.base;
+        let pages = self
+            .pages
+            .iter()
+            .map(|page| {
+                Box::new(page.map(|segment| {
+                    let known = (self.min_index..=self.max_index).contains(&position);
+                    position += 1;
+                    if known {
+                        map(segment)
+                    } else {
+                        T::BLANK
+                    }
+                }))
+            })
+            .collect();
+
+        PagedTape {
+            pages,
+            page: self.page,
+            offset: self.offset,
+            position: self.position,
+            base: self.base,
+            min_index: self.min_index,
+            max_index: self.max_index,
+        }
+    }
+}
+
+impl FromStr for PagedTape {
+    type Err = InvalidProgram;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tape = VecTape::from_str(s)?;
+
+        Ok(Self::from_segments(tape.inner, tape.position))
+    }
+}
+
+impl<S> fmt::Display for PagedTape<S>
+where
+    S: Symbol + fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for index in self.min_index..=self.max_index {
+            fmt::Display::fmt(self.segment(index), f)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The number of distinct non-empty symbols a [`PackedTape`] can store.
+pub const PACKED_SYMBOLS: usize = 3;
+
+/// A [`Tape`] that stores four segments per byte.
+///
+/// Every segment is stored as a 2-bit code, where code 0 is the blank symbol
+/// and the other codes are assigned to symbols in the order they are first
+/// written. This cuts memory usage by a factor of four or more compared to
+/// the other tapes, but limits the tape to [`PACKED_SYMBOLS`] distinct
+/// non-empty symbols.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct PackedTape<S = Segment> {
+    bytes: VecDeque<u8>,
+    symbols: Vec<S>,
+    cell: usize,
+    position: i64,
+    min_index: i64,
+    max_index: i64,
+}
+
+impl<S> PackedTape<S>
+where
+    S: Symbol,
+{
+    /// Create a new tape with a known part of the tape and a
+    /// specific cursor position.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if the position is outside of the tape segment
+    /// or if the tape contains more than [`PACKED_SYMBOLS`] distinct
+    /// non-empty symbols.
+    #[must_use]
+    pub fn new(inner: Vec<S>, position: usize) -> Self {
+        assert!(position < inner.len());
+        Self::from_segments(inner, position).expect("too many distinct symbols for a packed tape")
+    }
+
+    fn from_segments(inner: Vec<S>, position: usize) -> Result<Self, InvalidProgram> {
+        let offset = i64::try_from(position).expect("tape position exceeds i64");
+        let length = i64::try_from(inner.len()).expect("tape length exceeds i64");
+        let mut tape = Self {
+            bytes: VecDeque::from(vec![0; inner.len().div_ceil(4).max(1)]),
+            symbols: vec![S::BLANK],
+            cell: 0,
+            position: 0,
+            min_index: -offset,
+            max_index: (length - offset - 1).max(0),
+        };
+
+        for (cell, segment) in inner.into_iter().enumerate() {
+            let code = tape.code(segment).ok_or(InvalidProgram::TooManySymbols)?;
+            tape.set(cell, code);
+        }
+
+        tape.cell = position;
+
+        Ok(tape)
+    }
+
+    /// Returns the position of the leftmost segment that was either part of
+    /// the initial tape or visited by the cursor.
+    #[must_use]
+    pub fn min_index(&self) -> i64 {
+        self.min_index
+    }
+
+    /// Returns the position of the rightmost segment that was either part of
+    /// the initial tape or visited by the cursor.
+    #[must_use]
+    pub fn max_index(&self) -> i64 {
+        self.max_index
+    }
+
+    /// Returns the number of bytes used to store the segments.
+    #[must_use]
+    pub fn bytes(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Returns the code of `segment`, assigning a new one if it was never
+    /// stored before, or [`None`] if all codes are taken.
+    fn code(&mut self, segment: S) -> Option<u8> {
+        let code = match self.symbols.iter().position(|symbol| *symbol == segment) {
+            Some(code) => code,
+            None if self.symbols.len() <= PACKED_SYMBOLS => {
+                self.symbols.push(segment);
+                self.symbols.len() - 1
+            }
+            None => return None,
+        };
+
+        u8::try_from(code).ok()
+    }
+
+    fn code_at(&self, cell: usize) -> u8 {
+        (self.bytes[cell / 4] >> (cell % 4 * 2)) & 0b11
+    }
+
+    fn set(&mut self, cell: usize, code: u8) {
+        let shift = cell % 4 * 2;
+        let byte = &mut self.bytes[cell / 4];
+        *byte = *byte & !(0b11 << shift) | code << shift;
+    }
+}
+
+impl<S> Tape for PackedTape<S>
+where
+    S: Symbol,
+{
+    type Remapped<T: Symbol> = PackedTape<T>;
+    type Symbol = S;
+
+    fn right(&mut self) {
+        self.cell += 1;
+
+        if self.cell == self.bytes.len() * 4 {
+            self.bytes.push_back(0);
+        }
+
+        self.position += 1;
+        self.max_index = self.max_index.max(self.position);
+    }
+
+    fn left(&mut self) {
+        if self.cell == 0 {
+            self.bytes.push_front(0);
+            self.cell = 4;
+        }
+
+        self.cell -= 1;
+        self.position -= 1;
+        self.min_index = self.min_index.min(self.position);
+    }
+
+    /// Write a segment at the current cursor position.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if the tape already stores
+    /// [`PACKED_SYMBOLS`] other distinct non-empty symbols.
+    fn put(&mut self, segment: S) {
+        let code = self
+            .code(segment)
+            .expect("too many distinct symbols for a packed tape");
+        self.set(self.cell, code);
+    }
+
+    fn current(&self) -> &S {
+        &self.symbols[usize::from(self.code_at(self.cell))]
+    }
+
+    fn position(&self) -> i64 {
+        self.position
+    }
+
+    fn start(&self) -> i64 {
+        self.min_index
+    }
+
+    fn len(&self) -> usize {
+        usize::try_from(self.max_index - self.min_index).expect("tape length exceeds usize") + 1
+    }
+
+    fn get(&self, position: i64) -> S {
+        index(position - self.position, self.cell)
+            .filter(|cell| *cell < self.bytes.len() * 4)
+            .map_or(S::BLANK, |cell| {
+                self.symbols[usize::from(self.code_at(cell))]
+            })
+    }
+
+    /// Returns the tape with every segment of the known part replaced by
+    /// `map(segment)`.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if the known part of the tape holds more than
+    /// [`PACKED_SYMBOLS`] distinct non-empty symbols after it was remapped.
+    fn remap<T>(&self, map: impl Fn(S) -> T) -> PackedTape<T>
+    where
+        T: Symbol,
+    {
+        let segments = (self.min_index..=self.max_index)
+            .map(|position| map(self.get(position)))
+            .collect();
+        let cursor =
+            usize::try_from(self.position - self.min_index).expect("position is on the tape");
+        let mut tape = PackedTape::from_segments(segments, cursor)
+            .expect("too many distinct symbols for a packed tape");
+
+        tape.position += self.position;
+        tape.min_index += self.position;
+        tape.max_index += self.position;
+
+        tape
+    }
+}
+
+impl FromStr for PackedTape {
+    type Err = InvalidProgram;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tape = VecTape::from_str(s)?;
+
+        Self::from_segments(tape.inner, tape.position)
+    }
+}
+
+impl<S> fmt::Display for PackedTape<S>
+where
+    S: Symbol + fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let behind = usize::try_from(self.position - self.min_index).map_err(|_| fmt::Error)?;
+        let length = usize::try_from(self.max_index - self.min_index).map_err(|_| fmt::Error)?;
+        let first = self.cell - behind;
+
+        for cell in first..=first + length {
+            fmt::Display::fmt(&self.symbols[usize::from(self.code_at(cell))], f)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A [`Tape`] that stores runs of equal segments as a single entry.
+///
+/// Long stretches of the same symbol, as they are common in unary
+/// arithmetic, take constant space. Together with
+/// [`crate::TuringMachine::execute_accelerated`], the cursor can also move
+/// over a whole run in a single step.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct RleTape<S = Segment> {
+    /// The runs of the known part of the tape, each at least one long.
+    runs: Vec<(S, u64)>,
+    /// The position of the first segment of the first run.
+    start: i64,
+    /// The run the cursor is in.
+    run: usize,
+    /// The offset of the cursor in its run.
+    offset: u64,
+    position: i64,
+}
+
+impl<S> RleTape<S>
+where
+    S: Symbol,
+{
+    /// Create a new tape with a known part of the tape and a
+    /// specific cursor position.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if the position is outside of the tape segment.
+    #[must_use]
+    pub fn new(inner: Vec<S>, position: usize) -> Self {
+        assert!(position < inner.len());
+        Self::from_segments(inner, position)
+    }
+
+    fn from_segments(inner: Vec<S>, position: usize) -> Self {
+        let mut runs: Vec<(S, u64)> = Vec::new();
+
+        for segment in inner {
+            match runs.last_mut() {
+                Some((symbol, length)) if *symbol == segment => *length += 1,
+                _ => runs.push((segment, 1)),
+            }
+        }
+        if runs.is_empty() {
+            runs.push((S::BLANK, 1));
+        }
+
+        let mut tape = Self {
+            runs,
+            start: -i64::try_from(position).expect("tape position exceeds i64"),
+            run: 0,
+            offset: 0,
+            position: 0,
+        };
+        tape.locate(0);
+        tape
+    }
+
+    /// Returns the number of runs the known part of the tape consists of.
+    #[must_use]
+    pub fn runs(&self) -> usize {
+        self.runs.len()
+    }
+
+    /// Move the cursor to `position`, extending the known part of the tape
+    /// with blanks if needed.
+    fn locate(&mut self, position: i64) {
+        if position < self.start {
+            let missing = self.start.abs_diff(position);
+            match self.runs.first_mut() {
+                Some((symbol, length)) if *symbol == S::BLANK => *length += missing,
+                _ => self.runs.insert(0, (S::BLANK, missing)),
+            }
+            self.start = position;
+        }
+
+        self.locate_from(0, self.start, position);
+    }
+
+    /// Move the cursor to `position`, searching from the run at index `run`,
+    /// which starts at `run_start`, and extending the known part of the tape
+    /// to the right with blanks if needed.
+    fn locate_from(&mut self, run: usize, mut run_start: i64, position: i64) {
+        for (run, (_, length)) in self.runs.iter().enumerate().skip(run) {
+            let offset = position.abs_diff(run_start);
+            if offset < *length {
+                self.run = run;
+                self.offset = offset;
+                self.position = position;
+                return;
+            }
+            run_start = run_start.saturating_add_unsigned(*length);
+        }
+
+        let missing = position.abs_diff(run_start) + 1;
+        match self.runs.last_mut() {
+            Some((symbol, length)) if *symbol == S::BLANK => *length += missing,
+            _ => self.runs.push((S::BLANK, missing)),
+        }
+        self.run = self.runs.len() - 1;
+        self.offset = self.runs[self.run].1 - 1;
+        self.position = position;
+    }
+
+    /// Write `segment` to the `count` segments of the current run that start
+    /// `first` segments into it, merging the result with neighbouring runs.
+    fn fill(&mut self, first: u64, count: u64, segment: S) {
+        let (symbol, length) = self.runs[self.run];
+        if symbol == segment {
+            return;
+        }
+
+        let mut replacement = Vec::with_capacity(3);
+        if first > 0 {
+            replacement.push((symbol, first));
+        }
+        replacement.push((segment, count));
+        if first + count < length {
+            replacement.push((symbol, length - first - count));
+        }
+
+        // The runs before the previous one stay untouched, so the cursor can
+        // be found again from there
+        let run_start = self.position.saturating_sub_unsigned(self.offset);
+        let (base, base_start) = match self.run.checked_sub(1) {
+            Some(previous) => (
+                previous,
+                run_start.saturating_sub_unsigned(self.runs[previous].1),
+            ),
+            None => (0, run_start),
+        };
+
+        let run = self.run + usize::from(first > 0);
+        self.runs.splice(self.run..=self.run, replacement);
+
+        if run + 1 < self.runs.len() && self.runs[run + 1].0 == segment {
+            self.runs[run].1 += self.runs[run + 1].1;
+            self.runs.remove(run + 1);
+        }
+        if run > 0 && self.runs[run - 1].0 == segment {
+            self.runs[run - 1].1 += self.runs[run].1;
+            self.runs.remove(run);
+        }
+
+        self.locate_from(base, base_start, self.position);
+    }
+
+    /// Write `segment` over the rest of the current run in the direction of
+    /// `action` and move the cursor past it, without moving further than
+    /// `limit` segments.
+    ///
+    /// Returns the number of segments that were moved over, or [`None`] if the
+    /// run is the blank end of the tape, which never ends.
+    pub(crate) fn skip_run(&mut self, segment: S, action: Direction, limit: u64) -> Option<u64> {
+        let (symbol, length) = self.runs[self.run];
+        let last = self.runs.len() - 1;
+
+        let (first, count) = match action {
+            Direction::Right if self.run == last && symbol == S::BLANK => return None,
+            Direction::Left if self.run == 0 && symbol == S::BLANK => return None,
+            Direction::Right => {
+                let count = (length - self.offset).min(limit);
+                (self.offset, count)
+            }
+            Direction::Left => {
+                let count = (self.offset + 1).min(limit);
+                (self.offset + 1 - count, count)
+            }
+            Direction::Stay => return Some(0),
+        };
+
+        let target = match action {
+            Direction::Left => self.position.saturating_sub_unsigned(count),
+            _ => self.position.saturating_add_unsigned(count),
+        };
+
+        self.fill(first, count, segment);
+        self.locate(target);
+
+        Some(count)
+    }
+}
+
+impl<S> Tape for RleTape<S>
+where
+    S: Symbol,
+{
+    type Remapped<T: Symbol> = RleTape<T>;
+    type Symbol = S;
+
+    fn right(&mut self) {
+        self.position += 1;
+
+        if self.offset + 1 < self.runs[self.run].1 {
+            self.offset += 1;
+        } else if self.run + 1 < self.runs.len() {
+            self.run += 1;
+            self.offset = 0;
+        } else if self.runs[self.run].0 == S::BLANK {
+            self.runs[self.run].1 += 1;
+            self.offset += 1;
+        } else {
+            self.runs.push((S::BLANK, 1));
+            self.run += 1;
+            self.offset = 0;
+        }
+    }
+
+    fn left(&mut self) {
+        self.position -= 1;
+
+        if self.offset > 0 {
+            self.offset -= 1;
+        } else if self.run > 0 {
+            self.run -= 1;
+            self.offset = self.runs[self.run].1 - 1;
+        } else {
+            if self.runs[0].0 == S::BLANK {
+                self.runs[0].1 += 1;
+            } else {
+                self.runs.insert(0, (S::BLANK, 1));
+            }
+            self.start -= 1;
+        }
+    }
+
+    fn put(&mut self, segment: S) {
+        self.fill(self.offset, 1, segment);
+    }
+
+    fn current(&self) -> &S {
+        &self.runs[self.run].0
+    }
+
+    fn position(&self) -> i64 {
+        self.position
+    }
+
+    fn start(&self) -> i64 {
+        self.start
+    }
+
+    fn len(&self) -> usize {
+        let len: u64 = self.runs.iter().map(|(_, length)| length).sum();
+        usize::try_from(len).expect("tape length exceeds usize")
+    }
+
+    fn get(&self, position: i64) -> S {
+        let mut run_start = self.start;
+
+        for (symbol, length) in &self.runs {
+            if position >= run_start && position.abs_diff(run_start) < *length {
+                return *symbol;
+            }
+            run_start = run_start.saturating_add_unsigned(*length);
+        }
+
+        S::BLANK
+    }
+
+    fn seek(&mut self, position: i64) {
+        self.locate(position);
+    }
+
+    fn remap<T>(&self, map: impl Fn(S) -> T) -> RleTape<T>
+    where
+        T: Symbol,
+    {
+        let mut runs: Vec<(T, u64)> = Vec::with_capacity(self.runs.len());
+        for (symbol, length) in &self.runs {
+            let symbol = map(*symbol);
+            match runs.last_mut() {
+                Some((last, last_length)) if *last == symbol => *last_length += length,
+                _ => runs.push((symbol, *length)),
+            }
+        }
+
+        let mut tape = RleTape {
+            runs,
+            start: self.start,
+            run: 0,
+            offset: 0,
+            position: 0,
+        };
+        tape.locate(self.position);
+        tape
+    }
+}
+
+impl FromStr for RleTape {
+    type Err = InvalidProgram;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tape = VecTape::from_str(s)?;
+
+        Ok(Self::from_segments(tape.inner, tape.position))
+    }
+}
+
+impl<S> fmt::Display for RleTape<S>
+where
+    S: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (symbol, length) in &self.runs {
+            for _ in 0..*length {
+                symbol.fmt(f)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A [`Tape`] of a fixed number of segments whose ends are joined, so that
+/// moving past either end wraps around to the other one.
+///
+/// This simulates machines that are bounded in space. Positions stay between
+/// the start and the end of the tape as it was created, relative to the
+/// initial cursor position.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CircularTape<S = Segment> {
+    pub(crate) inner: Vec<S>,
+    pub(crate) position: usize,
+    pub(crate) origin: usize,
+}
+
+impl<S> CircularTape<S>
+where
+    S: Symbol,
+{
+    /// Create a new tape of the segments in `inner` and a specific cursor
+    /// position.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if the position is outside of the tape segment.
+    #[must_use]
+    pub fn new(inner: Vec<S>, position: usize) -> Self {
+        assert!(position < inner.len());
+        Self {
+            inner,
+            position,
+            origin: position,
+        }
+    }
+
+    /// Create a tape of `len` blank segments with the cursor on the first
+    /// one.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if `len` is zero.
+    #[must_use]
+    pub fn blank(len: usize) -> Self {
+        Self::new(vec![S::BLANK; len], 0)
+    }
+
+    /// Returns the number of segments on the tape.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Whether the tape has no segments, which is never the case.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Returns the index of the logical `position`, wrapped around the tape.
+    fn wrap(&self, position: i64) -> usize {
+        let len = i64::try_from(self.inner.len()).expect("tape length exceeds i64");
+        let origin = i64::try_from(self.origin).expect("tape position exceeds i64");
+        usize::try_from((origin + position).rem_euclid(len)).expect("index is within the tape")
+    }
+}
+
+impl<S> Tape for CircularTape<S>
+where
+    S: Symbol,
+{
+    type Remapped<T: Symbol> = CircularTape<T>;
+    type Symbol = S;
+
+    fn right(&mut self) {
+        self.position = (self.position + 1) % self.inner.len();
+    }
+
+    fn left(&mut self) {
+        self.position = self.position.checked_sub(1).unwrap_or(self.inner.len() - 1);
+    }
+
+    fn put(&mut self, segment: S) {
+        self.inner[self.position] = segment;
+    }
+
+    fn current(&self) -> &S {
+        &self.inner[self.position]
+    }
+
+    fn position(&self) -> i64 {
+        logical(self.position, self.origin)
+    }
+
+    fn start(&self) -> i64 {
+        logical(0, self.origin)
+    }
+
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    fn as_slice(&self) -> Option<&[S]> {
+        Some(&self.inner)
+    }
+
+    fn get(&self, position: i64) -> S {
+        self.inner[self.wrap(position)]
+    }
+
+    fn seek(&mut self, position: i64) {
+        self.position = self.wrap(position);
+    }
+
+    fn remap<T>(&self, map: impl Fn(S) -> T) -> CircularTape<T>
+    where
+        T: Symbol,
+    {
+        CircularTape {
+            inner: self.inner.iter().map(|segment| map(*segment)).collect(),
+            position: self.position,
+            origin: self.origin,
+        }
+    }
+}
+
+impl FromStr for CircularTape {
+    type Err = InvalidProgram;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tape = VecTape::from_str(s)?;
+
+        if tape.inner.is_empty() {
+            return Err(InvalidProgram::EmptyTape);
+        }
+
+        Ok(Self::new(tape.inner, tape.position))
+    }
+}
+
+impl<S> fmt::Display for CircularTape<S>
+where
+    S: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for segment in &self.inner {
+            segment.fmt(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The segments that differ between two tapes, and the cursor position of the
+/// second one.
+///
+/// Diffs are much smaller than whole tapes when only a few segments changed,
+/// so they can be used to update a view of a tape or to store the history of
+/// an execution:
+///
+/// ```
+/// # use std::str::FromStr;
+/// # use turing::{Segment, Tape, TapeDiff, VecTape};
+/// let before = VecTape::from_str("_101_").unwrap();
+/// let mut after = before.clone();
+/// after.put(Segment::Symbol('0'));
+/// after.left();
+/// after.put(Segment::Symbol('1'));
+///
+/// let diff = TapeDiff::between(&before, &after);
+/// assert_eq!(
+///     diff.changes(),
+///     [(-1, Segment::Symbol('1')), (0, Segment::Symbol('0'))]
+/// );
+///
+/// let mut tape = before.clone();
+/// diff.apply(&mut tape);
+/// assert_eq!(tape, after);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TapeDiff<S = Segment> {
+    changes: Vec<(i64, S)>,
+    position: i64,
+}
+
+impl<S> TapeDiff<S>
+where
+    S: Symbol,
+{
+    /// Returns the segments of `to` that differ from `from` and the cursor
+    /// position of `to`.
+    ///
+    /// Segments are compared over the known parts of both tapes, so segments
+    /// outside of them are blank on both.
+    pub fn between<A, B>(from: &A, to: &B) -> Self
+    where
+        A: Tape<Symbol = S>,
+        B: Tape<Symbol = S>,
+    {
+        let start = from.start().min(to.start());
+        let end = (from.start().saturating_add_unsigned(from.len() as u64))
+            .max(to.start().saturating_add_unsigned(to.len() as u64));
+
+        let changes = (start..end)
+            .filter_map(|position| {
+                let segment = to.get(position);
+                (from.get(position) != segment).then_some((position, segment))
+            })
+            .collect();
+
+        Self {
+            changes,
+            position: to.position(),
+        }
+    }
+
+    /// Returns the positions and new segments of the segments that changed,
+    /// sorted by position.
+    #[must_use]
+    pub fn changes(&self) -> &[(i64, S)] {
+        &self.changes
+    }
+
+    /// Returns the cursor position of the second tape.
+    #[must_use]
+    pub fn position(&self) -> i64 {
+        self.position
+    }
+
+    /// Returns whether no segments changed. The cursor may still have moved.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Write the changed segments to `tape` and move its cursor to the
+    /// position of the second tape, which turns the first tape into the
+    /// second one.
+    pub fn apply(&self, tape: &mut impl Tape<Symbol = S>) {
+        for (position, segment) in &self.changes {
+            tape.seek(*position);
+            tape.put(*segment);
+        }
+
+        tape.seek(self.position);
+    }
+}
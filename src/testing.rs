@@ -0,0 +1,285 @@
+//! Generators of random tapes and programs for property-based tests.
+//!
+//! The generators draw from a small, seedable [`SplitMix64`] random number
+//! generator instead of depending on a particular testing crate. To use them
+//! with proptest or quickcheck, generate a seed with the framework of choice
+//! and pass it to [`SplitMix64::new`].
+//!
+//! Random programs rarely halt, so they should always be run with a step
+//! limit, for example with [`crate::TuringMachine::execute_bounded`], unless
+//! they are generated with [`ProgramConfig::halting`].
+use crate::{program::Transition, Direction, Program, ProgramBuilder, Segment, State, VecTape};
+
+/// The `SplitMix64` pseudo random number generator.
+///
+/// It is fast and good enough for generating test cases, but not suitable
+/// for anything security related.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    /// Create a new generator from a seed. The same seed always produces the
+    /// same sequence of numbers.
+    #[must_use]
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Returns the next number in the sequence.
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number in `0..bound`.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `bound` is zero.
+    pub fn below(&mut self, bound: usize) -> usize {
+        assert!(bound > 0, "bound must not be zero");
+
+        // The modulo bias is negligible for the small bounds used here
+        usize::try_from(self.next_u64() % bound as u64).unwrap_or_default()
+    }
+
+    /// Returns a random element of `items`.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `items` is empty.
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+}
+
+/// Generate a tape with up to `max_len` random symbols from `alphabet`,
+/// surrounded by blanks, with the cursor on the first symbol.
+///
+/// # Panics
+///
+/// This method panics if `alphabet` is empty.
+#[must_use]
+pub fn random_tape(rng: &mut SplitMix64, alphabet: &[char], max_len: usize) -> VecTape {
+    let len = rng.below(max_len + 1);
+    let mut inner = Vec::with_capacity(len + 2);
+
+    inner.push(Segment::Empty);
+    inner.extend((0..len).map(|_| Segment::Symbol(*rng.choose(alphabet))));
+    inner.push(Segment::Empty);
+
+    VecTape::new(inner, 1)
+}
+
+/// Constraints for [`random_program`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProgramConfig {
+    /// The number of states, not counting the single final state.
+    pub states: usize,
+    /// The symbols the program reads and writes, in addition to the blank.
+    pub alphabet: Vec<char>,
+    /// The chance that a state has a transition for a segment, in percent.
+    pub density: u8,
+    /// Whether transitions may keep the cursor in place.
+    pub allow_stay: bool,
+    /// Whether transitions may only lead to states with a higher number, so
+    /// that every run ends after at most [`ProgramConfig::states`] steps.
+    pub halting: bool,
+    /// Whether the final state must be reachable from the initial state by
+    /// following transitions. A run may still never take that path.
+    pub reachable_final: bool,
+}
+
+impl Default for ProgramConfig {
+    fn default() -> Self {
+        Self {
+            states: 4,
+            alphabet: vec!['1'],
+            density: 100,
+            allow_stay: false,
+            halting: false,
+            reachable_final: false,
+        }
+    }
+}
+
+/// Generate a random program with the constraints in `config`.
+///
+/// The program starts in state 0 and its final state is the one numbered
+/// [`ProgramConfig::states`].
+///
+/// # Panics
+///
+/// This method panics if `config` allows no states.
+#[must_use]
+pub fn random_program(rng: &mut SplitMix64, config: &ProgramConfig) -> Program {
+    assert!(config.states > 0, "a program needs at least one state");
+
+    let segments: Vec<Segment> = std::iter::once(Segment::Empty)
+        .chain(config.alphabet.iter().map(|c| Segment::Symbol(*c)))
+        .collect();
+    let actions: &[Direction] = if config.allow_stay {
+        &[Direction::Left, Direction::Right, Direction::Stay]
+    } else {
+        &[Direction::Left, Direction::Right]
+    };
+
+    let mut builder = ProgramBuilder::new().initial(0).finals([config.states]);
+
+    for from in 0..config.states {
+        for condition in &segments {
+            if rng.below(100) >= usize::from(config.density) {
+                continue;
+            }
+
+            let to = if config.halting {
+                from + 1 + rng.below(config.states - from)
+            } else {
+                rng.below(config.states + 1)
+            };
+
+            builder = builder.transition(
+                from,
+                to,
+                *condition,
+                *rng.choose(&segments),
+                *rng.choose(actions),
+            );
+        }
+    }
+
+    let mut program = builder
+        .build()
+        .expect("the builder always has an initial state");
+
+    if config.reachable_final && !program.reachable_states().contains(&State(config.states)) {
+        // Lead a transition of a reachable state to the final state, which
+        // keeps the states ordered if the program has to halt
+        let mut reachable: Vec<State> = program.reachable_states().into_iter().collect();
+        reachable.sort();
+        let from = *rng.choose(&reachable);
+        let condition = *rng.choose(&segments);
+        let (write, action) = (*rng.choose(&segments), *rng.choose(actions));
+
+        program
+            .transitions
+            .entry((from, condition))
+            .and_modify(|transition| transition.to = State(config.states))
+            .or_insert(Transition {
+                from,
+                to: State(config.states),
+                condition,
+                write,
+                action,
+            });
+    }
+
+    program
+}
+
+impl Program {
+    /// Generate a random total program with `states` states besides the
+    /// final one, which has a transition for every state and every segment of
+    /// `alphabet` and the blank.
+    ///
+    /// This is [`random_program`] with the default constraints otherwise. Use
+    /// it with [`ProgramConfig::reachable_final`] to make sure the final state
+    /// can be reached.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `states` is zero.
+    #[must_use]
+    pub fn random(states: usize, alphabet: &[char], rng: &mut SplitMix64) -> Self {
+        random_program(
+            rng,
+            &ProgramConfig {
+                states,
+                alphabet: alphabet.to_vec(),
+                ..ProgramConfig::default()
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{testing::*, *};
+
+    #[test]
+    fn test_split_mix() {
+        let mut rng = SplitMix64::new(1234);
+        let first: Vec<_> = (0..8).map(|_| rng.next_u64()).collect();
+
+        let mut rng = SplitMix64::new(1234);
+        assert!(first.iter().all(|n| *n == rng.next_u64()));
+        assert!((0..1000).all(|_| rng.below(7) < 7));
+    }
+
+    #[test]
+    fn test_random_tape() {
+        let mut rng = SplitMix64::new(0);
+
+        for _ in 0..100 {
+            let tape = random_tape(&mut rng, &['a', 'b'], 5);
+            let contents = tape.to_string();
+
+            assert!(contents.len() <= 7);
+            assert!(contents.chars().all(|c| "ab_".contains(c)));
+        }
+    }
+
+    #[test]
+    fn test_random_program_halting() {
+        let mut rng = SplitMix64::new(42);
+        let config = ProgramConfig {
+            states: 6,
+            density: 80,
+            allow_stay: true,
+            halting: true,
+            ..ProgramConfig::default()
+        };
+
+        for _ in 0..100 {
+            let program = random_program(&mut rng, &config);
+            let tape = random_tape(&mut rng, &config.alphabet, 10);
+            let mut machine = TuringMachine::from_tape(tape);
+
+            assert!(!matches!(
+                machine.execute_bounded(&program, 6),
+                Err(ExecutionError::StepLimitExceeded(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_random_total() {
+        let mut rng = SplitMix64::new(1075);
+        let program = Program::random(3, &['a', 'b'], &mut rng);
+
+        // Three states with a transition for each of the three segments
+        assert_eq!(program.to_tng_string().lines().count(), 2 + 3 * 3);
+    }
+
+    #[test]
+    fn test_random_program_reachable_final() {
+        let mut rng = SplitMix64::new(7);
+        let config = ProgramConfig {
+            states: 5,
+            density: 30,
+            reachable_final: true,
+            ..ProgramConfig::default()
+        };
+
+        for _ in 0..100 {
+            let program = random_program(&mut rng, &config);
+            assert!(program.reachable_states().contains(&State(5)));
+        }
+    }
+}
@@ -0,0 +1,284 @@
+//! Recording of executions, step by step.
+//!
+//! A [`Trace`] is filled by [`crate::TuringMachine::execute_traced`]. Traces
+//! over [`Segment`]s can be written out with [`fmt::Display`] and read back
+//! with [`FromStr`], one step per line:
+//!
+//! ```text
+//! # from,to,position,read,written,action
+//! 0,0,0,1,1,r
+//! 0,1,1,_,0,r
+//! ```
+//!
+//! [`crate::TuringMachine::replay`] checks a trace, for example one written by
+//! another implementation, against a program.
+use std::{fmt, str::FromStr};
+
+use crate::{
+    error::{InvalidProgram, InvalidTrace},
+    Direction, Segment, State,
+};
+
+/// A single step of an execution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceStep<S = Segment> {
+    /// The state the machine was in before the step.
+    pub from: State,
+    /// The state the machine was in after the step.
+    pub to: State,
+    /// The cursor position before the step, relative to the cursor position
+    /// at the start of the execution.
+    pub position: i64,
+    /// The segment that was read.
+    pub read: S,
+    /// The segment that was written.
+    pub written: S,
+    /// The movement that was performed after writing.
+    pub action: Direction,
+}
+
+/// The steps of an execution, in order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trace<S = Segment> {
+    pub(crate) steps: Vec<TraceStep<S>>,
+}
+
+impl<S> Trace<S> {
+    /// Create an empty trace.
+    #[must_use]
+    pub fn new() -> Self {
+        Self { steps: Vec::new() }
+    }
+
+    /// Returns the recorded steps.
+    #[must_use]
+    pub fn steps(&self) -> &[TraceStep<S>] {
+        &self.steps
+    }
+
+    /// Returns the number of recorded steps.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    /// Whether no steps were recorded.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+}
+
+impl<S> Default for Trace<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> fmt::Display for Trace<S>
+where
+    S: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "# from,to,position,read,written,action")?;
+
+        for step in &self.steps {
+            writeln!(f, "{step}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<S> fmt::Display for TraceStep<S>
+where
+    S: fmt::Display,
+{
+    /// Writes the step as a line of a [`Trace`], without the line break.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{},{},{},{},{},{}",
+            self.from, self.to, self.position, self.read, self.written, self.action
+        )
+    }
+}
+
+impl FromStr for TraceStep {
+    type Err = InvalidTrace;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split(',').collect();
+        let [from, to, position, read, written, action] = parts[..] else {
+            return Err(InvalidTrace::FieldCount);
+        };
+
+        Ok(Self {
+            from: State::from_str(from)?,
+            to: State::from_str(to)?,
+            position: position
+                .parse()
+                .map_err(|_| InvalidTrace::InvalidPosition)?,
+            read: Segment::from_str(read)?,
+            written: Segment::from_str(written)?,
+            action: Direction::from_str(action)?,
+        })
+    }
+}
+
+impl FromStr for Trace {
+    type Err = InvalidTrace;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let steps = s
+            .lines()
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(TraceStep::from_str)
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { steps })
+    }
+}
+
+impl From<InvalidProgram> for InvalidTrace {
+    fn from(error: InvalidProgram) -> Self {
+        Self::InvalidStep(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::{trace::*, *};
+
+    #[test]
+    fn test_execute_traced() {
+        let program = Program::from_str(include_str!("../examples/append.tng")).unwrap();
+        let mut machine = TuringMachine::from_tape(VecTape::from_str("_11_").unwrap());
+        let mut trace = Trace::new();
+
+        let state = machine.execute_traced(&program, &mut trace).unwrap();
+
+        assert_eq!(trace.len(), 4);
+        assert_eq!(trace.steps()[3].to, state);
+        assert_eq!(
+            trace
+                .steps()
+                .iter()
+                .map(|step| step.position)
+                .collect::<Vec<_>>(),
+            [0, 1, 2, 3]
+        );
+        assert_eq!(trace.steps()[2].read, Segment::Empty);
+        assert_eq!(trace.steps()[2].written, Segment::Symbol('0'));
+        assert_eq!(trace.steps()[3].action, Direction::Stay);
+    }
+
+    #[test]
+    fn test_trace_error() {
+        let program = Program::from_str(include_str!("../examples/palindrome.tng")).unwrap();
+        let mut machine = TuringMachine::from_tape(VecTape::from_str("_10_").unwrap());
+        let mut trace = Trace::new();
+
+        let result = machine.execute_traced(&program, &mut trace);
+
+        assert_eq!(
+            result,
+            Err(ExecutionError::ReachedError(
+                trace.steps().last().unwrap().to
+            ))
+        );
+    }
+
+    #[test]
+    fn test_trace_roundtrip() {
+        let program = Program::from_str(include_str!("../examples/copy.tng")).unwrap();
+        let mut machine = TuringMachine::from_tape(VecTape::from_str("_11_").unwrap());
+        let mut trace = Trace::new();
+        machine.execute_traced(&program, &mut trace).unwrap();
+
+        assert_eq!(Trace::from_str(&trace.to_string()).unwrap(), trace);
+        assert_eq!(
+            Trace::from_str("0,1,x,_,_,r"),
+            Err(error::InvalidTrace::InvalidPosition)
+        );
+        assert_eq!(
+            Trace::from_str("0,1,0,_,_,x").unwrap_err().to_string(),
+            "invalid step: invalid movement action, expected \"l\", \"r\", \"n\", \"l*\" or \"r*\""
+        );
+    }
+
+    #[test]
+    fn test_replay() {
+        let program = Program::from_str(include_str!("../examples/copy.tng")).unwrap();
+        let machine = || TuringMachine::from_tape(VecTape::from_str("_11_").unwrap());
+        let mut trace = Trace::new();
+        let state = machine().execute_traced(&program, &mut trace).unwrap();
+
+        let mut replayed = machine();
+        assert_eq!(replayed.replay(&program, &trace), Ok(state));
+        assert_eq!(replayed.tape().to_string(), "_11_11");
+
+        let mut partial = trace.clone();
+        partial.steps.truncate(3);
+        assert_eq!(
+            machine().replay(&program, &partial),
+            Ok(partial.steps()[2].to)
+        );
+
+        let mut longer = trace.clone();
+        longer.steps.push(trace.steps()[0]);
+        assert_eq!(
+            machine().replay(&program, &longer),
+            Err(error::ReplayError::Halted { step: trace.len() })
+        );
+        assert_eq!(
+            error::ReplayError::<Segment>::Halted { step: 7 }.to_string(),
+            "halted before step 7 of the trace"
+        );
+    }
+
+    #[test]
+    fn test_replay_divergence() {
+        let program = Program::from_str(include_str!("../examples/next_integer.tng")).unwrap();
+        let trace = Trace::from_str("0,0,0,1,1,r\n0,0,1,_,_,r\n0,1,2,_,_,l").unwrap();
+
+        assert_eq!(
+            TuringMachine::from_tape(VecTape::from_str("_1_").unwrap()).replay(&program, &trace),
+            Err(error::ReplayError::Diverged {
+                step: 1,
+                expected: trace.steps()[1],
+                actual: TraceStep {
+                    from: program.initial_state(),
+                    to: State::from_str("1").unwrap(),
+                    position: 1,
+                    read: Segment::Empty,
+                    written: Segment::Empty,
+                    action: Direction::Left,
+                },
+            })
+        );
+        assert_eq!(
+            TuringMachine::from_tape(VecTape::from_str("_1_").unwrap())
+                .replay(&program, &trace)
+                .unwrap_err()
+                .to_string(),
+            "step 1 does not match the trace: expected 0,0,1,_,_,r, performed 0,1,1,_,_,l"
+        );
+        assert!(matches!(
+            TuringMachine::from_tape(VecTape::from_str("_1x_").unwrap()).replay(&program, &trace),
+            Err(error::ReplayError::Failed {
+                step: 1,
+                error: ExecutionError::UndefinedBehavior(..),
+            })
+        ));
+        assert_eq!(
+            TuringMachine::from_tape(VecTape::from_str("_1x_").unwrap())
+                .replay(&program, &trace)
+                .unwrap_err()
+                .to_string(),
+            "step 1 of the trace failed: no transition for \"x\" in state 0"
+        );
+    }
+}
@@ -0,0 +1,271 @@
+//! A terminal visualizer for stepping through [`Program`]s.
+//!
+//! The visualizer draws the tape around the cursor, the current state and the
+//! transitions of the program with ANSI escape codes, and reads one command
+//! per line from the terminal:
+//!
+//! - `s` or an empty line performs a single step
+//! - `c` continues until the program halts or a breakpoint is hit
+//! - `b <state>` toggles a breakpoint on a state
+//! - `r` resets the machine to the initial tape
+//! - `q` quits
+use std::{
+    collections::HashSet,
+    fmt::{self, Write as _},
+    io::{self, BufRead, Write},
+};
+
+use crate::{
+    debugger::{Debugger, Stop},
+    tape::Tape,
+    ExecutionError, Program, State, TuringMachine,
+};
+
+/// The number of segments shown on each side of the cursor.
+const RADIUS: i64 = 20;
+
+/// The maximum number of steps a single `c` command performs, so programs that
+/// never halt do not hang the visualizer.
+const CONTINUE_LIMIT: u64 = 100_000;
+
+const CLEAR: &str = "\x1b[2J\x1b[H";
+const BOLD: &str = "\x1b[1m";
+const INVERT: &str = "\x1b[7m";
+const GREEN: &str = "\x1b[32m";
+const RESET: &str = "\x1b[0m";
+
+/// An interactive visualizer for a [`Program`] running on a tape.
+#[derive(Debug)]
+pub struct Visualizer<'p, T>
+where
+    T: Tape,
+{
+    program: &'p Program<T::Symbol>,
+    tape: T,
+    debugger: Debugger<'p, T>,
+    breakpoints: HashSet<State>,
+    status: String,
+}
+
+impl<'p, T> Visualizer<'p, T>
+where
+    T: Tape + Clone,
+    T::Symbol: fmt::Display,
+{
+    /// Create a visualizer that runs `program` on `tape`.
+    #[must_use]
+    pub fn new(program: &'p Program<T::Symbol>, tape: T) -> Self {
+        Self {
+            program,
+            debugger: Debugger::new(TuringMachine::from_tape(tape.clone()), program),
+            tape,
+            breakpoints: HashSet::new(),
+            status: String::from("Ready."),
+        }
+    }
+
+    /// Perform a single step.
+    pub fn step(&mut self) {
+        match self.debugger.step() {
+            Ok(None) => self.status = format!("Stepped to state {}.", self.debugger.state()),
+            Ok(Some(stop)) => self.report(Ok(stop)),
+            Err(e) => self.report(Err(e)),
+        }
+    }
+
+    /// Run until the program halts, a breakpoint is hit or
+    /// [`CONTINUE_LIMIT`] steps were performed.
+    pub fn resume(&mut self) {
+        for _ in 0..CONTINUE_LIMIT {
+            match self.debugger.step() {
+                Ok(None) => {}
+                Ok(Some(stop)) => return self.report(Ok(stop)),
+                Err(e) => return self.report(Err(e)),
+            }
+        }
+
+        self.status = format!("Paused after {CONTINUE_LIMIT} steps.");
+    }
+
+    /// Toggle a breakpoint on `state`.
+    pub fn toggle_breakpoint(&mut self, state: State) {
+        if self.breakpoints.remove(&state) {
+            self.debugger.remove_breakpoint(state);
+            self.status = format!("Removed breakpoint on state {state}.");
+        } else {
+            self.breakpoints.insert(state);
+            self.debugger.add_breakpoint(state);
+            self.status = format!("Added breakpoint on state {state}.");
+        }
+    }
+
+    /// Reset the machine to the initial tape, keeping the breakpoints.
+    pub fn reset(&mut self) {
+        self.debugger = Debugger::new(TuringMachine::from_tape(self.tape.clone()), self.program);
+
+        for state in &self.breakpoints {
+            self.debugger.add_breakpoint(*state);
+        }
+
+        self.status = String::from("Reset.");
+    }
+
+    fn report(&mut self, result: Result<Stop<T::Symbol>, ExecutionError<T::Symbol>>) {
+        self.status = match result {
+            Ok(Stop::Halted(state)) => format!("Halted in final state {state}."),
+            Ok(Stop::StateBreakpoint(state)) => format!("Paused at state {state}."),
+            Ok(Stop::PositionBreakpoint(position)) => format!("Paused at position {position}."),
+            Ok(Stop::Watchpoint { position, .. }) => format!("Paused at write to {position}."),
+            Err(ExecutionError::UndefinedBehavior(state, segment)) => {
+                format!("No transition for state {state} and segment {segment}.")
+            }
+            Err(ExecutionError::ReachedError(state)) => format!("Reached error state {state}."),
+            Err(e) => format!("Failed: {e:?}."),
+        };
+    }
+
+    /// Draw the visualizer, without clearing the terminal.
+    #[must_use]
+    pub fn render(&self) -> String {
+        let tape = self.debugger.machine().tape();
+        let position = tape.position();
+        let state = self.debugger.state();
+        let mut out = String::new();
+
+        let _ = writeln!(
+            out,
+            "{BOLD}State {state}{RESET}  step {}  position {position}",
+            self.debugger.steps()
+        );
+        let _ = writeln!(out);
+
+        let mut cursor = String::new();
+        for index in position - RADIUS..=position + RADIUS {
+            let segment = tape.get(index);
+
+            if index == position {
+                let _ = write!(out, "{INVERT}{segment}{RESET}");
+                cursor.push('^');
+                break;
+            }
+
+            let _ = write!(out, "{segment}");
+            cursor.push(' ');
+        }
+        for index in position + 1..=position + RADIUS {
+            let _ = write!(out, "{}", tape.get(index));
+        }
+
+        let _ = writeln!(out);
+        let _ = writeln!(out, "{cursor}");
+
+        let current = tape.get(position).to_string();
+        for line in self.program.to_string().lines() {
+            let fields: Vec<&str> = line.split(',').collect();
+            let active = matches!(
+                fields[..],
+                [from, _, condition, ..]
+                    if from == self.state_label(state) && (condition == current || condition == "*")
+            );
+
+            if active {
+                let _ = writeln!(out, "{GREEN}> {line}{RESET}");
+            } else {
+                let _ = writeln!(out, "  {line}");
+            }
+        }
+
+        let _ = writeln!(out);
+        let _ = writeln!(out, "{}", self.status);
+        let _ = write!(out, "[s]tep [c]ontinue [b]reak <state> [r]eset [q]uit > ");
+
+        out
+    }
+
+    fn state_label(&self, state: State) -> String {
+        self.program
+            .state_name(state)
+            .map_or_else(|| state.to_string(), str::to_owned)
+    }
+
+    /// Run the visualizer on the terminal until the user quits or the input
+    /// ends.
+    ///
+    /// # Errors
+    ///
+    /// This method will error if reading from or writing to the terminal
+    /// fails.
+    pub fn run(&mut self, input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+        let mut lines = input.lines();
+
+        loop {
+            write!(output, "{CLEAR}{}", self.render())?;
+            output.flush()?;
+
+            let Some(line) = lines.next().transpose()? else {
+                return Ok(());
+            };
+            let mut words = line.split_whitespace();
+
+            match (words.next(), words.next()) {
+                (None | Some("s"), _) => self.step(),
+                (Some("c"), _) => self.resume(),
+                (Some("r"), _) => self.reset(),
+                (Some("q"), _) => return Ok(()),
+                (Some("b"), Some(name)) => {
+                    match self.program.state(name).or_else(|| name.parse().ok()) {
+                        Some(state) => self.toggle_breakpoint(state),
+                        None => self.status = format!("Unknown state {name}."),
+                    }
+                }
+                _ => self.status = format!("Unknown command {line:?}."),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::{tui::*, *};
+
+    #[test]
+    fn test_render() {
+        let program = Program::from_str(include_str!("../examples/next_integer.tng")).unwrap();
+        let mut visualizer = Visualizer::new(&program, VecTape::from_str("_11_").unwrap());
+
+        let screen = visualizer.render();
+        assert!(screen.contains(&format!("{}{INVERT}1{RESET}1", "_".repeat(20))));
+        assert!(screen.contains(&format!("{GREEN}> 0,0,1,1,r{RESET}")));
+
+        let state = State::from_str("1").unwrap();
+        visualizer.toggle_breakpoint(state);
+        visualizer.resume();
+        assert!(visualizer.render().contains("Paused at state 1."));
+
+        visualizer.toggle_breakpoint(state);
+        visualizer.resume();
+        assert!(visualizer.render().contains("Halted in final state 3."));
+
+        visualizer.reset();
+        assert!(visualizer.render().contains("State 0"));
+    }
+
+    #[test]
+    fn test_run_commands() {
+        let program = Program::from_str("+0\n-1\n0,0,1,0,r\n0,1,_,_,n").unwrap();
+        let mut visualizer = Visualizer::new(&program, VecTape::from_str("_11").unwrap());
+        let mut output = Vec::new();
+
+        visualizer
+            .run("s\n\nc\nq\ns\n".as_bytes(), &mut output)
+            .unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(output.matches(CLEAR).count(), 4);
+        assert!(output.ends_with(
+            "Halted in final state 1.\n[s]tep [c]ontinue [b]reak <state> [r]eset [q]uit > "
+        ));
+    }
+}
@@ -0,0 +1,310 @@
+//! A universal turing machine.
+//!
+//! [`universal_machine`] is a fixed [`Program`] that runs any other program
+//! over [`Segment`]s, given a description of the program and its input tape
+//! created with [`encode`]:
+//!
+//! ```
+//! use std::str::FromStr;
+//!
+//! use turing::{utm, Program, State, TuringMachine, VecTape};
+//!
+//! let program = Program::from_str(include_str!("../examples/next_integer.tng")).unwrap();
+//! let encoding = utm::encode(&program, &VecTape::from_str("_11_").unwrap());
+//!
+//! let mut machine = TuringMachine::from_tape(encoding.tape().clone());
+//! machine.execute(&utm::universal_machine()).unwrap();
+//!
+//! let (state, tape) = encoding.decode(machine.tape()).unwrap();
+//! assert_eq!(state, State::from_str("3").unwrap());
+//! assert_eq!(tape.to_string(), "100_");
+//! ```
+//!
+//! The description of the program is a list of its transitions, where states
+//! and segments are numbered in binary. The format is documented in
+//! `examples/universal.tng`. Since every simulated step takes many steps of
+//! the universal machine, this is mostly useful as a showcase and as a large
+//! test program for the simulator.
+use std::{collections::BTreeSet, str::FromStr};
+
+use crate::{Direction, Program, Segment, State, VecTape};
+
+const UNIVERSAL: &str = include_str!("../examples/universal.tng");
+
+/// Returns the universal turing machine.
+///
+/// It halts in a final state if the simulated program does, reaches an error
+/// state if the simulated program does and runs into undefined behaviour if
+/// the simulated program does.
+///
+/// # Panics
+///
+/// This function never panics, the universal machine is a valid program.
+#[must_use]
+pub fn universal_machine() -> Program {
+    Program::from_str(UNIVERSAL).expect("the universal machine is valid")
+}
+
+/// The description of a program and its input for the
+/// [`universal_machine`], created with [`encode`].
+#[derive(Debug, Clone)]
+pub struct Encoding {
+    tape: VecTape,
+    states: Vec<State>,
+    symbols: Vec<Segment>,
+}
+
+/// Returns the code of `index` with `width` bits, most significant first.
+fn bits(index: usize, width: usize) -> String {
+    format!("{index:0width$b}")
+}
+
+/// Returns the number of bits needed to number `count` things, but at least
+/// one.
+fn width(count: usize) -> usize {
+    (usize::BITS - count.saturating_sub(1).leading_zeros()).max(1) as usize
+}
+
+/// Describe `program` and its input `tape` for the [`universal_machine`].
+///
+/// Wildcard transitions are expanded for every segment that occurs in the
+/// program or on the tape.
+#[must_use]
+pub fn encode(program: &Program, tape: &VecTape) -> Encoding {
+    let mut symbols = BTreeSet::from([Segment::Empty]);
+    symbols.extend(tape.inner.iter().copied());
+    for transition in program.transitions.values() {
+        symbols.insert(transition.condition);
+        symbols.insert(transition.write);
+    }
+    symbols.extend(
+        program
+            .wildcards
+            .values()
+            .filter_map(|wildcard| wildcard.write),
+    );
+    // The blank segment comes first, so its code is all zeros
+    let symbols: Vec<Segment> = symbols.into_iter().collect();
+
+    let states: Vec<State> = program.states().into_iter().collect();
+
+    let state_width = width(states.len());
+    let symbol_width = width(symbols.len());
+    let state_code = |state: &State| {
+        let index = states.binary_search(state).unwrap_or_default();
+        bits(index, state_width)
+    };
+    let symbol_code = |symbol: &Segment| {
+        let index = symbols.binary_search(symbol).unwrap_or_default();
+        bits(index, symbol_width)
+    };
+
+    let mut description = String::from("[");
+
+    for from in &states {
+        for condition in &symbols {
+            let Some(transition) = program.transition(*from, *condition) else {
+                continue;
+            };
+            let kind = if program.final_states.contains(&transition.to) {
+                'f'
+            } else if program.error_states.contains(&transition.to) {
+                'e'
+            } else {
+                's'
+            };
+            let action = match transition.action {
+                Direction::Left => 'L',
+                Direction::Right => 'R',
+                Direction::Stay => 'N',
+            };
+
+            description.push('#');
+            description.push_str(&state_code(from));
+            description.push_str(&symbol_code(condition));
+            description.push(':');
+            description.push_str(&state_code(&transition.to));
+            description.push_str(&symbol_code(&transition.write));
+            description.push(action);
+            description.push(kind);
+        }
+    }
+
+    // The simulated machine always performs at least one step, so the
+    // initial state is never checked for halting
+    description.push_str("$s");
+    description.push_str(&state_code(&program.initial_state));
+    description.push('%');
+    description.push_str(&bits(0, symbol_width));
+    description.push(']');
+
+    for (index, symbol) in tape.inner.iter().enumerate() {
+        description.push(if index == tape.position { '^' } else { '|' });
+        description.push_str(&symbol_code(symbol));
+    }
+
+    let inner: Vec<Segment> = description.chars().map(Segment::from).collect();
+
+    Encoding {
+        tape: VecTape::new(inner, 0),
+        states,
+        symbols,
+    }
+}
+
+impl Encoding {
+    /// Returns the tape to run the [`universal_machine`] on.
+    #[must_use]
+    pub fn tape(&self) -> &VecTape {
+        &self.tape
+    }
+
+    /// Read the state and tape of the simulated program from the tape of the
+    /// [`universal_machine`].
+    ///
+    /// The cursor of the returned tape is on the cell the simulated head is
+    /// on, and positions are relative to it. Returns [`None`] if the tape is
+    /// not a valid description of the simulated program, for example because
+    /// the universal machine was stopped in the middle of a step.
+    #[must_use]
+    pub fn decode(&self, tape: &VecTape) -> Option<(State, VecTape)> {
+        let text = tape.to_string();
+        // Skip the kind of the state, which the caller knows from the state
+        // the universal machine halted in
+        let (_, register) = text.split_once('$')?;
+        let (state, rest) = register.get(1..)?.split_once('%')?;
+        let (_, cells) = rest.split_once(']')?;
+
+        let state = *self.states.get(usize::from_str_radix(state, 2).ok()?)?;
+
+        let mut inner = Vec::new();
+        let mut position = None;
+
+        for cell in cells.trim_end_matches('_').split_inclusive(['|', '^']) {
+            let (code, marker) = match cell.strip_suffix(['|', '^']) {
+                Some(code) => (code, cell.chars().last()),
+                None => (cell, None),
+            };
+
+            if !code.is_empty() {
+                let index = usize::from_str_radix(code, 2).ok()?;
+                inner.push(*self.symbols.get(index)?);
+            }
+
+            if marker == Some('^') {
+                position = Some(inner.len());
+            }
+        }
+
+        let position = position.filter(|position| *position < inner.len())?;
+
+        Some((state, VecTape::new(inner, position)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::{
+        busy_beaver,
+        testing::{random_program, random_tape, ProgramConfig, SplitMix64},
+        utm::*,
+        ExecutionError, TuringMachine,
+    };
+
+    /// Run `program` on `input` directly and with the universal machine, and
+    /// check that both agree.
+    fn check(program: &Program, input: &VecTape, max_steps: u64) {
+        let mut direct = TuringMachine::from_tape(input.clone());
+        let expected = direct.execute_bounded(program, max_steps);
+
+        let encoding = encode(program, input);
+        let mut universal = TuringMachine::from_tape(encoding.tape().clone());
+        let result = universal.execute(&universal_machine());
+
+        let (state, tape) = encoding.decode(universal.tape()).unwrap();
+        assert_eq!(tape.to_string(), direct.tape().to_string());
+        assert_eq!(tape.position, direct.tape().position);
+
+        match expected {
+            Ok(expected) => {
+                assert!(result.is_ok());
+                assert_eq!(state, expected);
+            }
+            Err(ExecutionError::ReachedError(expected)) => {
+                assert!(matches!(result, Err(ExecutionError::ReachedError(_))));
+                assert_eq!(state, expected);
+            }
+            Err(ExecutionError::UndefinedBehavior(..)) => {
+                assert!(matches!(result, Err(ExecutionError::UndefinedBehavior(..))));
+            }
+            Err(e) => panic!("unexpected error {e:?}"),
+        }
+    }
+
+    #[test]
+    fn test_utm_examples() {
+        let next_integer = include_str!("../examples/next_integer.tng");
+        let palindrome = include_str!("../examples/palindrome.tng");
+        let cases = [
+            (next_integer, "_1011_"),
+            (next_integer, "_111_"),
+            (next_integer, "111"),
+            (include_str!("../examples/append.tng"), "_11_"),
+            (include_str!("../examples/copy.tng"), "_11_"),
+            (include_str!("../examples/swap.tng"), "_ab_"),
+            (palindrome, "_101_"),
+            (palindrome, "_10_"),
+        ];
+
+        for (code, input) in cases {
+            let program = Program::from_str(code).unwrap();
+            check(&program, &VecTape::from_str(input).unwrap(), 1000);
+        }
+    }
+
+    #[test]
+    fn test_utm_busy_beaver() {
+        for champion in [busy_beaver::BB2, busy_beaver::BB3] {
+            check(&champion.program(), &VecTape::from_str("_").unwrap(), 1000);
+        }
+    }
+
+    #[test]
+    fn test_utm_random() {
+        let mut rng = SplitMix64::new(1049);
+        let config = ProgramConfig {
+            states: 4,
+            ..ProgramConfig::default()
+        };
+        let mut checked = 0;
+
+        while checked < 20 {
+            let program = random_program(&mut rng, &config);
+            let input = random_tape(&mut rng, &config.alphabet, 4);
+
+            // Only compare runs that halt quickly, the universal machine
+            // takes thousands of steps for each of their steps
+            let mut machine = TuringMachine::from_tape(input.clone());
+            if let Err(ExecutionError::StepLimitExceeded(_)) = machine.execute_bounded(&program, 30)
+            {
+                continue;
+            }
+
+            check(&program, &input, 30);
+            checked += 1;
+        }
+    }
+
+    #[test]
+    fn test_utm_decode_invalid() {
+        let program = Program::from_str(include_str!("../examples/copy.tng")).unwrap();
+        let encoding = encode(&program, &VecTape::from_str("_1_").unwrap());
+
+        assert!(encoding
+            .decode(&VecTape::from_str("_1_").unwrap())
+            .is_none());
+        assert!(encoding.decode(encoding.tape()).is_some());
+    }
+}
@@ -0,0 +1,186 @@
+//! Static analysis of [`Program`]s.
+//!
+//! Most mistakes in a program only show up at runtime as
+//! [`crate::ExecutionError::UndefinedBehavior`]. [`Program::validate`] finds
+//! common ones without running the program.
+use std::collections::{BTreeSet, HashSet};
+
+use crate::{tape::Symbol, Program, Segment, State};
+
+/// A potential problem in a [`Program`] found by [`Program::validate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidationWarning<S = Segment> {
+    /// The state cannot be reached from the initial state or an entry point.
+    UnreachableState(State),
+    /// The state has no outgoing transitions, but is neither a final nor an
+    /// error state, so execution always fails once it is reached.
+    NoOutgoingTransitions(State),
+    /// A transition leads to a state that has no outgoing transitions and is
+    /// neither a final nor an error state.
+    UndefinedTarget {
+        /// The "from" state of the transition.
+        from: State,
+        /// The condition of the transition.
+        condition: S,
+        /// The undefined state the transition leads to.
+        to: State,
+    },
+    /// The final state has outgoing transitions, which are never taken
+    /// because execution halts once the state is reached.
+    FinalWithTransitions(State),
+}
+
+impl<S> Program<S>
+where
+    S: Symbol,
+{
+    /// Check the program for common mistakes.
+    ///
+    /// The warnings are sorted by the states they concern, so the result is
+    /// the same for equal programs.
+    #[must_use]
+    pub fn validate(&self) -> Vec<ValidationWarning<S>> {
+        let mut warnings = Vec::new();
+        let sources: HashSet<State> = self
+            .transitions
+            .keys()
+            .map(|(from, _)| *from)
+            .chain(self.wildcards.keys().copied())
+            .collect();
+        let is_defined = |state: &State| {
+            sources.contains(state)
+                || self.final_states.contains(state)
+                || self.error_states.contains(state)
+        };
+
+        let mut states = BTreeSet::from([self.initial_state]);
+        states.extend(self.entry_points.values().copied());
+        states.extend(self.final_states.iter().copied());
+        states.extend(self.error_states.iter().copied());
+        for transition in self.transitions.values() {
+            states.insert(transition.from);
+            states.insert(transition.to);
+        }
+        for wildcard in self.wildcards.values() {
+            states.insert(wildcard.from);
+            states.insert(wildcard.to);
+        }
+
+        let reachable = self.reachable_states();
+        for state in &states {
+            if !reachable.contains(state) {
+                warnings.push(ValidationWarning::UnreachableState(*state));
+            }
+        }
+
+        for state in &states {
+            if !is_defined(state) {
+                warnings.push(ValidationWarning::NoOutgoingTransitions(*state));
+            }
+        }
+
+        let mut transitions: Vec<_> = self.transitions.values().collect();
+        transitions.sort_by_key(|transition| (transition.from, transition.condition));
+        for transition in transitions {
+            if !is_defined(&transition.to) {
+                warnings.push(ValidationWarning::UndefinedTarget {
+                    from: transition.from,
+                    condition: transition.condition,
+                    to: transition.to,
+                });
+            }
+        }
+
+        let mut final_states: Vec<_> = self.final_states.intersection(&sources).collect();
+        final_states.sort();
+        for state in final_states {
+            warnings.push(ValidationWarning::FinalWithTransitions(*state));
+        }
+
+        warnings
+    }
+
+    /// Returns all states that can be reached from the initial state or an
+    /// entry point.
+    ///
+    /// Execution halts in final and error states, so their transitions are
+    /// not followed.
+    pub(crate) fn reachable_states(&self) -> HashSet<State> {
+        let mut pending: Vec<State> = std::iter::once(self.initial_state)
+            .chain(self.entry_points.values().copied())
+            .collect();
+        let mut reachable: HashSet<State> = pending.iter().copied().collect();
+
+        while let Some(state) = pending.pop() {
+            if self.final_states.contains(&state) || self.error_states.contains(&state) {
+                continue;
+            }
+
+            let targets = self
+                .transitions
+                .values()
+                .filter(|transition| transition.from == state)
+                .map(|transition| transition.to)
+                .chain(self.wildcards.get(&state).map(|wildcard| wildcard.to));
+
+            for target in targets {
+                if reachable.insert(target) {
+                    pending.push(target);
+                }
+            }
+        }
+
+        reachable
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::{validation::*, *};
+
+    fn state(s: &str) -> State {
+        State::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn test_validate_examples() {
+        for code in [
+            include_str!("../examples/next_integer.tng"),
+            include_str!("../examples/append.tng"),
+            include_str!("../examples/palindrome.tng"),
+            include_str!("../examples/copy.tng"),
+            include_str!("../examples/swap.tng"),
+        ] {
+            assert_eq!(Program::from_str(code).unwrap().validate(), []);
+        }
+    }
+
+    #[test]
+    fn test_validate_warnings() {
+        let program =
+            Program::from_str("+0\n-1\n-4\n0,1,_,_,r\n0,2,1,1,r\n1,0,_,_,n\n3,1,_,_,n\n-5\n+6")
+                .unwrap();
+
+        assert_eq!(
+            program.validate(),
+            [
+                ValidationWarning::UnreachableState(state("0")),
+                ValidationWarning::UnreachableState(state("1")),
+                ValidationWarning::UnreachableState(state("2")),
+                ValidationWarning::UnreachableState(state("3")),
+                ValidationWarning::UnreachableState(state("4")),
+                ValidationWarning::UnreachableState(state("5")),
+                ValidationWarning::NoOutgoingTransitions(state("2")),
+                ValidationWarning::NoOutgoingTransitions(state("6")),
+                ValidationWarning::UndefinedTarget {
+                    from: state("0"),
+                    condition: Segment::Symbol('1'),
+                    to: state("2"),
+                },
+                ValidationWarning::FinalWithTransitions(state("1")),
+            ]
+        );
+    }
+}
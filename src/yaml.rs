@@ -0,0 +1,678 @@
+//! Exchange of [`Program`]s with the [turingmachine.io](https://turingmachine.io)
+//! YAML format.
+//!
+//! The format has no final or error states. Instead, a machine halts once it
+//! is in a state without an instruction for the current symbol. States
+//! without any instructions become final states on import, and final and
+//! error states are written without instructions on export. Error states are
+//! also listed under an extra `error states` key, so that they are imported
+//! as error states again. The simulator cannot keep the cursor in place, so
+//! programs are exported as rewritten by [`Program::normalize`].
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    fmt::Write,
+};
+
+use self::syntax::Value;
+use crate::{
+    error::InvalidYaml,
+    program::{is_state_name, StateNames, Transition},
+    Direction, Program, Segment, State,
+};
+
+impl Program {
+    /// Parse a machine in the turingmachine.io YAML format.
+    ///
+    /// The `blank` symbol becomes [`Segment::Empty`] and the `input` is
+    /// ignored. States are numbered in the order they appear in the table
+    /// and keep their names, if they are valid in the `.tng` format.
+    ///
+    /// # Errors
+    ///
+    /// This method will error if the file uses YAML features outside of the
+    /// subset used by turingmachine.io or does not describe a valid machine.
+    pub fn from_yaml(s: &str) -> Result<Self, InvalidYaml> {
+        let document = syntax::parse(s)?;
+
+        let blank = document
+            .get("blank")
+            .and_then(Value::as_str)
+            .ok_or(InvalidYaml::MissingKey("blank"))?;
+        let blank = single_char(blank)?;
+        let start = document
+            .get("start state")
+            .and_then(Value::as_str)
+            .ok_or(InvalidYaml::MissingKey("start state"))?;
+        let Some(Value::Map(table)) = document.get("table") else {
+            return Err(InvalidYaml::MissingKey("table"));
+        };
+
+        let mut ids = HashMap::new();
+        let mut names = StateNames::new();
+        for (index, (name, _)) in table.iter().enumerate() {
+            let name = name.as_str().ok_or(InvalidYaml::Syntax(0))?;
+            let state = State(index);
+            ids.insert(name, state);
+
+            if is_state_name(name) {
+                names.insert(state, name.to_owned());
+            }
+        }
+        let state = |name: &str| {
+            ids.get(name)
+                .copied()
+                .ok_or_else(|| InvalidYaml::UndefinedState(name.to_owned()))
+        };
+        let segment = |symbol: &Value| {
+            let symbol = single_char(symbol.as_str().unwrap_or_default())?;
+            Ok(if symbol == blank {
+                Segment::Empty
+            } else {
+                Segment::Symbol(symbol)
+            })
+        };
+
+        let mut error_states = HashSet::new();
+        if let Some(errors) = document.get("error states") {
+            let Value::Seq(errors) = errors else {
+                return Err(InvalidYaml::InvalidInstruction(format!("{errors:?}")));
+            };
+            for name in errors {
+                error_states.insert(state(name.as_str().unwrap_or_default())?);
+            }
+        }
+
+        let mut final_states = HashSet::new();
+        let mut transitions = HashMap::new();
+
+        for (name, instructions) in table {
+            let from = state(name.as_str().unwrap_or_default())?;
+            let instructions = match instructions {
+                Value::Map(instructions) if !instructions.is_empty() => instructions,
+                Value::Null | Value::Map(_) if error_states.contains(&from) => continue,
+                Value::Null | Value::Map(_) => {
+                    final_states.insert(from);
+                    continue;
+                }
+                _ => return Err(InvalidYaml::InvalidInstruction(format!("{instructions:?}"))),
+            };
+
+            for (symbols, instruction) in instructions {
+                let symbols = match symbols {
+                    Value::Seq(symbols) => symbols.as_slice(),
+                    symbol => std::slice::from_ref(symbol),
+                };
+                let (action, to, write) = parse_instruction(instruction, state, segment)?;
+
+                for symbol in symbols {
+                    let condition = segment(symbol)?;
+                    let transition = Transition {
+                        from,
+                        to: to.unwrap_or(from),
+                        condition,
+                        write: write.unwrap_or(condition),
+                        action,
+                    };
+
+                    if transitions.insert((from, condition), transition).is_some() {
+                        return Err(InvalidYaml::DuplicateTransition);
+                    }
+                }
+            }
+        }
+
+        Ok(Self::from_parts(
+            state(start)?,
+            final_states,
+            error_states,
+            transitions,
+            HashMap::new(),
+            names,
+        ))
+    }
+
+    /// Returns the program in the turingmachine.io YAML format.
+    ///
+    /// Wildcard transitions are expanded to every symbol that appears in the
+    /// program. Transitions that keep the cursor in place are rewritten as by
+    /// [`Program::normalize`]. States without a name are named "q" followed
+    /// by their number, with underscores appended while another state already
+    /// has that name.
+    #[must_use]
+    pub fn to_yaml(&self) -> String {
+        let program = self.normalize();
+
+        let mut alphabet = BTreeSet::from([Segment::Empty]);
+        for transition in program.transitions.values() {
+            alphabet.extend([transition.condition, transition.write]);
+        }
+        for wildcard in program.wildcards.values() {
+            alphabet.extend(wildcard.write);
+        }
+
+        let states = program.states();
+        let taken: HashSet<&str> = program.names.values().map(String::as_str).collect();
+        let mut names = HashMap::new();
+        for state in &states {
+            let name = program.state_name(*state).map_or_else(
+                || {
+                    let mut name = format!("q{state}");
+                    while taken.contains(name.as_str()) {
+                        name.push('_');
+                    }
+                    name
+                },
+                str::to_owned,
+            );
+            names.insert(*state, name);
+        }
+        let name = |state: &State| names[state].as_str();
+
+        let mut out = String::from("input: ''\nblank: ' '\n");
+        let _ = writeln!(out, "start state: {}", name(&program.initial_state));
+
+        let mut error_states: Vec<_> = program.error_states.iter().map(name).collect();
+        if !error_states.is_empty() {
+            error_states.sort_unstable();
+            let _ = writeln!(out, "error states: [{}]", error_states.join(", "));
+        }
+
+        out.push_str("table:\n");
+        for state in &states {
+            let _ = writeln!(out, "  {}:", name(state));
+
+            if program.final_states.contains(state) || program.error_states.contains(state) {
+                continue;
+            }
+
+            for condition in &alphabet {
+                let Some(transition) = program.transition(*state, *condition) else {
+                    continue;
+                };
+
+                // Normalized programs only move left or right
+                let action = match transition.action {
+                    Direction::Left => "L",
+                    Direction::Right | Direction::Stay => "R",
+                };
+                let to = name(&transition.to);
+
+                let _ = write!(out, "    {}: ", quote(*condition));
+                if transition.write == *condition && transition.to == *state {
+                    let _ = writeln!(out, "{action}");
+                } else if transition.write == *condition {
+                    let _ = writeln!(out, "{{{action}: {to}}}");
+                } else {
+                    let _ = writeln!(
+                        out,
+                        "{{write: {}, {action}: {to}}}",
+                        quote(transition.write)
+                    );
+                }
+            }
+        }
+
+        out
+    }
+}
+
+fn single_char(s: &str) -> Result<char, InvalidYaml> {
+    let mut chars = s.chars();
+
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Ok(c),
+        _ => Err(InvalidYaml::InvalidSymbol(s.to_owned())),
+    }
+}
+
+/// Parse the instruction for a symbol into its move and, if given, the state
+/// it leads to and the segment it writes.
+fn parse_instruction(
+    instruction: &Value,
+    state: impl Fn(&str) -> Result<State, InvalidYaml>,
+    segment: impl Fn(&Value) -> Result<Segment, InvalidYaml>,
+) -> Result<(Direction, Option<State>, Option<Segment>), InvalidYaml> {
+    let invalid = || InvalidYaml::InvalidInstruction(format!("{instruction:?}"));
+
+    match instruction {
+        Value::Scalar(action) => Ok((parse_move(action).ok_or_else(invalid)?, None, None)),
+        Value::Map(entries) => {
+            let mut action = None;
+            let mut to = None;
+            let mut write = None;
+
+            for (key, value) in entries {
+                match (key.as_str(), value) {
+                    (Some("write"), value) => write = Some(segment(value)?),
+                    (Some(key), Value::Null) if action.is_none() => {
+                        action = Some(parse_move(key).ok_or_else(invalid)?);
+                    }
+                    (Some(key), Value::Scalar(target)) if action.is_none() => {
+                        action = Some(parse_move(key).ok_or_else(invalid)?);
+                        to = Some(state(target)?);
+                    }
+                    _ => return Err(invalid()),
+                }
+            }
+
+            Ok((action.ok_or_else(invalid)?, to, write))
+        }
+        _ => Err(invalid()),
+    }
+}
+
+fn parse_move(s: &str) -> Option<Direction> {
+    match s {
+        "L" => Some(Direction::Left),
+        "R" => Some(Direction::Right),
+        _ => None,
+    }
+}
+
+/// Quote a segment as a YAML string, writing the empty segment as a space.
+fn quote(segment: Segment) -> String {
+    match segment {
+        Segment::Empty => String::from("' '"),
+        Segment::Symbol('\'') => String::from("''''"),
+        Segment::Symbol(c) => format!("'{c}'"),
+    }
+}
+
+/// Just enough of a YAML parser to read turingmachine.io files: block
+/// mappings, flow mappings and sequences, and plain and quoted scalars.
+mod syntax {
+    use crate::error::InvalidYaml;
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub(super) enum Value {
+        Null,
+        Scalar(String),
+        Seq(Vec<Value>),
+        Map(Vec<(Value, Value)>),
+    }
+
+    impl Value {
+        pub(super) fn as_str(&self) -> Option<&str> {
+            match self {
+                Self::Scalar(s) => Some(s),
+                _ => None,
+            }
+        }
+
+        pub(super) fn get(&self, key: &str) -> Option<&Self> {
+            match self {
+                Self::Map(entries) => entries
+                    .iter()
+                    .find(|(k, _)| k.as_str() == Some(key))
+                    .map(|(_, value)| value),
+                _ => None,
+            }
+        }
+    }
+
+    /// A line without its comment, with its indentation and number.
+    struct Line<'a> {
+        number: usize,
+        indent: usize,
+        content: &'a str,
+    }
+
+    /// Parse a document consisting of a block mapping.
+    pub(super) fn parse(s: &str) -> Result<Value, InvalidYaml> {
+        let mut lines = Vec::new();
+
+        for (number, line) in s.lines().enumerate() {
+            let content = strip_comment(line).trim_end();
+            let trimmed = content.trim_start();
+
+            if trimmed.is_empty() || trimmed == "---" {
+                continue;
+            }
+
+            lines.push(Line {
+                number: number + 1,
+                indent: content.len() - trimmed.len(),
+                content: trimmed,
+            });
+        }
+
+        let mut position = 0;
+        let value = parse_block(&lines, &mut position, 0)?;
+
+        match lines.get(position) {
+            Some(line) => Err(InvalidYaml::Syntax(line.number)),
+            None => Ok(value),
+        }
+    }
+
+    /// Parse the block mapping whose keys are indented by `indent`.
+    fn parse_block(
+        lines: &[Line],
+        position: &mut usize,
+        indent: usize,
+    ) -> Result<Value, InvalidYaml> {
+        let mut entries = Vec::new();
+
+        while let Some(line) = lines.get(*position) {
+            if line.indent < indent {
+                break;
+            }
+            if line.indent > indent {
+                return Err(InvalidYaml::Syntax(line.number));
+            }
+
+            let syntax = || InvalidYaml::Syntax(line.number);
+            let separator = find_separator(line.content).ok_or_else(syntax)?;
+            let key = parse_flow(line.content[..separator].trim()).ok_or_else(syntax)?;
+            let value = line.content[separator + 1..].trim();
+            *position += 1;
+
+            let value = if !value.is_empty() {
+                parse_flow(value).ok_or_else(syntax)?
+            } else if lines
+                .get(*position)
+                .is_some_and(|next| next.indent > indent)
+            {
+                let indent = lines[*position].indent;
+                parse_block(lines, position, indent)?
+            } else {
+                Value::Null
+            };
+
+            entries.push((key, value));
+        }
+
+        Ok(Value::Map(entries))
+    }
+
+    /// Remove a comment from a line, ignoring "#" in quoted scalars.
+    fn strip_comment(line: &str) -> &str {
+        let mut quote = None;
+
+        for (index, c) in line.char_indices() {
+            match (quote, c) {
+                (None, '\'' | '"') => quote = Some(c),
+                (Some(q), c) if c == q => quote = None,
+                (None, '#') if index == 0 || line[..index].ends_with(char::is_whitespace) => {
+                    return &line[..index];
+                }
+                _ => {}
+            }
+        }
+
+        line
+    }
+
+    /// Find the ":" separating the key from the value of a block mapping
+    /// entry, outside of quotes and brackets.
+    fn find_separator(s: &str) -> Option<usize> {
+        let mut quote = None;
+        let mut depth = 0_usize;
+
+        for (index, c) in s.char_indices() {
+            match (quote, c) {
+                (None, '\'' | '"') => quote = Some(c),
+                (Some(q), c) if c == q => quote = None,
+                (None, '[' | '{') => depth += 1,
+                (None, ']' | '}') => depth = depth.checked_sub(1)?,
+                (None, ':')
+                    if depth == 0
+                        && s[index + 1..]
+                            .chars()
+                            .next()
+                            .is_none_or(char::is_whitespace) =>
+                {
+                    return Some(index);
+                }
+                _ => {}
+            }
+        }
+
+        None
+    }
+
+    /// Parse a flow value that spans the whole string.
+    fn parse_flow(s: &str) -> Option<Value> {
+        let mut chars = s.char_indices().peekable();
+        let value = Flow {
+            s,
+            chars: &mut chars,
+        }
+        .value()?;
+
+        chars.next().is_none().then_some(value)
+    }
+
+    struct Flow<'a, 'c> {
+        s: &'a str,
+        chars: &'c mut std::iter::Peekable<std::str::CharIndices<'a>>,
+    }
+
+    impl Flow<'_, '_> {
+        fn skip_whitespace(&mut self) {
+            while self.chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+        }
+
+        fn value(&mut self) -> Option<Value> {
+            self.skip_whitespace();
+
+            let value = match self.chars.peek()?.1 {
+                '[' => {
+                    self.chars.next();
+                    Value::Seq(self.items(']', Self::value)?)
+                }
+                '{' => {
+                    self.chars.next();
+                    Value::Map(self.items('}', Self::entry)?)
+                }
+                '\'' | '"' => Value::Scalar(self.quoted()?),
+                _ => Value::Scalar(self.plain()),
+            };
+
+            self.skip_whitespace();
+            Some(value)
+        }
+
+        /// Parse comma-separated items until `end`.
+        fn items<T>(
+            &mut self,
+            end: char,
+            mut item: impl FnMut(&mut Self) -> Option<T>,
+        ) -> Option<Vec<T>> {
+            let mut items = Vec::new();
+
+            loop {
+                self.skip_whitespace();
+                if self.chars.next_if(|(_, c)| *c == end).is_some() {
+                    return Some(items);
+                }
+
+                items.push(item(self)?);
+                self.skip_whitespace();
+
+                match self.chars.next()?.1 {
+                    ',' => {}
+                    c if c == end => return Some(items),
+                    _ => return None,
+                }
+            }
+        }
+
+        /// Parse an entry of a flow mapping, where the value may be omitted.
+        fn entry(&mut self) -> Option<(Value, Value)> {
+            let key = self.value()?;
+
+            if self.chars.next_if(|(_, c)| *c == ':').is_some() {
+                Some((key, self.value()?))
+            } else {
+                Some((key, Value::Null))
+            }
+        }
+
+        fn quoted(&mut self) -> Option<String> {
+            let (_, quote) = self.chars.next()?;
+            let mut out = String::new();
+
+            loop {
+                let (_, c) = self.chars.next()?;
+
+                match c {
+                    // A quote is escaped by doubling it in single-quoted scalars
+                    '\'' if quote == '\'' && self.chars.next_if(|(_, c)| *c == '\'').is_some() => {
+                        out.push('\'');
+                    }
+                    '\\' if quote == '"' => out.push(match self.chars.next()?.1 {
+                        'n' => '\n',
+                        't' => '\t',
+                        c => c,
+                    }),
+                    c if c == quote => return Some(out),
+                    c => out.push(c),
+                }
+            }
+        }
+
+        fn plain(&mut self) -> String {
+            let start = self.chars.peek().map_or(self.s.len(), |(index, _)| *index);
+            let mut end = start;
+
+            while let Some((index, c)) = self.chars.peek().copied() {
+                let separator = c == ':'
+                    && self.s[index + 1..]
+                        .chars()
+                        .next()
+                        .is_none_or(|c| c.is_whitespace() || c == ',');
+                if matches!(c, ',' | ']' | '}') || separator {
+                    break;
+                }
+
+                self.chars.next();
+                if !c.is_whitespace() {
+                    end = index + c.len_utf8();
+                }
+            }
+
+            self.s[start..end].to_owned()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::{error::InvalidYaml, *};
+
+    /// The binary increment example of turingmachine.io.
+    const BINARY_INCREMENT: &str = "input: '1011'
+blank: ' '
+start state: right
+table:
+  # scan to the rightmost digit
+  right:
+    [1,0]: R
+    ' '  : {L: carry}
+  # then carry the 1
+  carry:
+    1      : {write: 0, L}
+    [0,' ']: {write: 1, L: done}
+  done:
+";
+
+    #[test]
+    fn test_from_yaml() {
+        let program = Program::from_yaml(BINARY_INCREMENT).unwrap();
+        assert_eq!(program.initial_state(), program.state("right").unwrap());
+
+        for (input, expected) in [("_1011_", "_1100_"), ("_11_", "_100_")] {
+            let mut machine = TuringMachine::from_tape(VecTape::from_str(input).unwrap());
+            assert_eq!(
+                machine.execute(&program),
+                Ok(program.state("done").unwrap())
+            );
+            assert_eq!(machine.tape().to_string(), expected);
+        }
+
+        assert_eq!(
+            Program::from_yaml(&BINARY_INCREMENT.replace("L: done", "L: finished")).unwrap_err(),
+            InvalidYaml::UndefinedState(String::from("finished"))
+        );
+        assert_eq!(
+            Program::from_yaml(&BINARY_INCREMENT.replace("[0,' ']", "[0,1]")).unwrap_err(),
+            InvalidYaml::DuplicateTransition
+        );
+        assert_eq!(
+            Program::from_yaml("blank: ' '\ntable:\n  a:\n").unwrap_err(),
+            InvalidYaml::MissingKey("start state")
+        );
+        assert_eq!(
+            Program::from_yaml("blank: ' '\n  table: {\n").unwrap_err(),
+            InvalidYaml::Syntax(2)
+        );
+    }
+
+    #[test]
+    fn test_yaml_roundtrip() {
+        for (code, inputs) in [
+            (
+                include_str!("../examples/next_integer.tng"),
+                ["_1011_", "_111_"],
+            ),
+            (include_str!("../examples/copy.tng"), ["_1_", "_111_"]),
+        ] {
+            let program = Program::from_str(code).unwrap();
+            let imported = Program::from_yaml(&program.to_yaml()).unwrap();
+
+            for input in inputs {
+                let mut expected = TuringMachine::from_tape(VecTape::from_str(input).unwrap());
+                expected.execute(&program).unwrap();
+
+                let mut machine = TuringMachine::from_tape(VecTape::from_str(input).unwrap());
+                machine.execute(&imported).unwrap();
+
+                assert_eq!(
+                    machine.tape().to_string().trim_matches('_'),
+                    expected.tape().to_string().trim_matches('_')
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_yaml_roundtrip_named_states() {
+        // State 3 has no name, so it must not be exported as "q3"
+        let program = Program::from_str(
+            "+start\n-done\n!fail\nstart,start,0,0,r\nstart,start,1,1,r\nstart,q3,_,_,l\n\
+             start,fail,x,x,n\nq3,q3,1,0,l\nq3,done,0,1,n\nq3,3,_,1,n\n3,done,1,1,n",
+        )
+        .unwrap();
+        let yaml = program.to_yaml();
+        assert!(yaml.contains("error states: [fail]\n"), "{yaml}");
+        assert!(
+            yaml.contains("\n  q3:\n") && yaml.contains("\n  q3_:\n"),
+            "{yaml}"
+        );
+
+        let imported = Program::from_yaml(&yaml).unwrap();
+        assert_eq!(imported.state_name(imported.initial_state()), Some("start"));
+        assert_eq!(imported.stay_put_transitions(), []);
+
+        let name = |program: &Program, result| match result {
+            Ok(state) | Err(ExecutionError::ReachedError(state)) => {
+                program.state_name(state).map(str::to_owned)
+            }
+            Err(e) => panic!("{e}"),
+        };
+        for input in ["_1011_", "_111_", "_", "_1x1_"] {
+            let mut expected = TuringMachine::from_tape(VecTape::from_str(input).unwrap());
+            let expected_state = name(&program, expected.execute(&program));
+
+            let mut machine = TuringMachine::from_tape(VecTape::from_str(input).unwrap());
+            assert_eq!(name(&imported, machine.execute(&imported)), expected_state);
+            assert_eq!(
+                machine.tape().to_string().trim_matches('_'),
+                expected.tape().to_string().trim_matches('_')
+            );
+        }
+    }
+}